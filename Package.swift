@@ -3,18 +3,27 @@ import PackageDescription
 
 let name = "RaptorQ-iOS"
 
+// Checked in so the package resolves by URL; `bindings/build.sh` rebuilds it
+// in place from the Rust crate.
+let framework = "bindings/xcframework/raptorq.xcframework"
+
 let package = Package(
     name: name,
     products: [
         .library(
             name: name,
-            targets: [name]
+            targets: [name, "RaptorQ"]
         ),
     ],
     targets: [
         .binaryTarget(
             name: name,
-            path: "./bindings/xcframework/raptorq.xcframework"
+            path: "./\(framework)"
+        ),
+        .target(
+            name: "RaptorQ",
+            dependencies: [.target(name: name)],
+            path: "./Sources/RaptorQ"
         )
     ]
 )
@@ -0,0 +1,135 @@
+//
+//  RaptorQDecoder.kt
+//  Typed Kotlin/Native API over `raptorq_cinterop.h`, mirroring the Swift
+//  `RaptorQDecoder`
+//
+
+@file:OptIn(ExperimentalForeignApi::class)
+
+package io.novasama.raptorq.kmp
+
+import io.novasama.raptorq.cinterop.*
+import kotlinx.cinterop.*
+
+/**
+ * A status the library reported as a failure, with its description and the
+ * detail from `raptorq_last_error`, if any.
+ */
+class RaptorQException internal constructor(
+    val status: RQStatus,
+    val reason: String? = raptorq_last_error()?.toKString(),
+) : Exception(
+    raptorq_status_message(status)!!.toKString().let { message ->
+        reason?.let { "$message: $it" } ?: message
+    }
+)
+
+/** What happened to a pushed frame. */
+sealed class PushResult {
+    /** The frame was accepted; more are needed. */
+    object Accepted : PushResult()
+
+    /** The frame finished decoding; call [RaptorQDecoder.takeResult]. */
+    object Complete : PushResult()
+
+    /**
+     * The frame was not part of this transfer (a repeated handshake, or
+     * anything pushed after completion) and was skipped.
+     */
+    object Ignored : PushResult()
+
+    /** The frame was rejected, or decoding failed. */
+    data class Failed(val error: RaptorQException) : PushResult()
+}
+
+/**
+ * Owns one decoding context; [close] frees it.
+ *
+ * Not thread-safe: use one decoder from one thread at a time (overlapping
+ * calls fail with `RQ_STATUS_BUSY` rather than corrupt the transfer).
+ */
+class RaptorQDecoder private constructor(private val ctx: CPointer<RQContext>) {
+
+
+    /**
+     * Create a decoder when you already know the total length and the max
+     * payload of each QR frame.
+     */
+    constructor(totalBytes: ULong, maxPayload: UShort) :
+        this(adopt(raptorq_ctx_new(totalBytes, maxPayload)))
+
+    companion object {
+        /** Create a decoder from the 12-byte OTI header embedded in the first QR. */
+        fun fromOti(oti: ByteArray): RaptorQDecoder {
+            raptorq_clear_last_error()
+            if (oti.size != 12) throw RaptorQException(RQ_STATUS_INVALID_ARGUMENT)
+            return RaptorQDecoder(adopt(withBytes(oti) { ptr, _ -> raptorq_ctx_from_oti(ptr) }))
+        }
+
+        /**
+         * Create a decoder from a handshake frame; the recovered object is
+         * checked against the digest it carries.
+         */
+        fun fromHandshake(frame: ByteArray): RaptorQDecoder {
+            raptorq_clear_last_error()
+            return RaptorQDecoder(adopt(withBytes(frame, ::raptorq_ctx_from_handshake)))
+        }
+
+        private fun adopt(ctx: CPointer<RQContext>?): CPointer<RQContext> =
+            ctx ?: throw RaptorQException(RQ_STATUS_INVALID_ARGUMENT)
+
+        /** Call [body] with the address (`null` when empty) and length of [bytes]. */
+        private fun <R> withBytes(bytes: ByteArray, body: (CPointer<UByteVar>?, ULong) -> R): R =
+            bytes.asUByteArray().usePinned { pinned ->
+                body(if (bytes.isEmpty()) null else pinned.addressOf(0), bytes.size.toULong())
+            }
+    }
+
+    /** Push one QR-frame payload. */
+    fun push(frame: ByteArray): PushResult {
+        raptorq_clear_last_error()
+        val status = withBytes(frame) { ptr, len -> raptorq_ctx_push_frame_status(ctx, ptr, len) }
+        return when (status) {
+            RQ_STATUS_OK, RQ_STATUS_PADDING_STRIPPED -> PushResult.Accepted
+            RQ_STATUS_COMPLETE -> PushResult.Complete
+            RQ_STATUS_IGNORED -> PushResult.Ignored
+            else -> PushResult.Failed(RaptorQException(status))
+        }
+    }
+
+    /** `true` once enough packets have been received to reconstruct the object. */
+    val isComplete: Boolean
+        get() = raptorq_ctx_is_complete(ctx) != 0.toUByte()
+
+    /**
+     * Distinct symbols received over the source symbols needed, from 0 to 1,
+     * summed over the blocks; duplicates and each block's overhead do not
+     * count.
+     */
+    val progress: Double
+        get() {
+            if (isComplete) return 1.0
+            var received = 0uL
+            var needed = 0uL
+            for (sbn in 0u until raptorq_ctx_block_count(ctx)) {
+                raptorq_ctx_block_timing(ctx, sbn).useContents {
+                    received += minOf(symbols_received, symbols).toULong()
+                    needed += symbols.toULong()
+                }
+            }
+            return if (needed > 0uL) received.toDouble() / needed.toDouble() else 0.0
+        }
+
+    /**
+     * Copy the reconstructed object out of the library and release its
+     * buffer. Call **once**; subsequent calls return `null`.
+     */
+    fun takeResult(): ByteArray? {
+        val buffer = raptorq_ctx_take_result_buffer(ctx)
+        val bytes = buffer.useContents { ptr?.reinterpret<ByteVar>()?.readBytes(len.toInt()) }
+        raptorq_buffer_free(buffer)
+        return bytes
+    }
+
+    fun close() = raptorq_ctx_free(ctx)
+}
@@ -0,0 +1,56 @@
+//
+//  RQDecoder.h
+//  Objective-C wrapper around the decoding half of the C API
+//
+
+#import <Foundation/Foundation.h>
+#import <raptorq/raptorq_ios.h>
+
+NS_ASSUME_NONNULL_BEGIN
+
+/// Domain of the errors reported by `RQDecoder`; the code is the `RQStatus`.
+extern NSErrorDomain const RQDecoderErrorDomain;
+
+/// Owns one decoding context: frees it on dealloc, takes and returns
+/// `NSData`, and reports failures as `NSError`s carrying the status and the
+/// library's `raptorq_last_error` message.
+///
+/// Like the context it wraps, a decoder must not be used from two threads at
+/// once unless it was created with `threadSafe:YES`.
+@interface RQDecoder : NSObject
+
+/// Decoder for a transfer of `length` bytes sent in frames of at most
+/// `maxPayloadSize` bytes.  `threadSafe` locks the context on every call
+/// (see `raptorq_ctx_new_threadsafe`).
+- (nullable instancetype)initWithTransferLength:(uint64_t)length
+                                 maxPayloadSize:(uint16_t)maxPayloadSize
+                                     threadSafe:(BOOL)threadSafe
+                                          error:(NSError **)error NS_DESIGNATED_INITIALIZER;
+
+/// Decoder for the 12-byte OTI embedded in the first frame.
+- (nullable instancetype)initWithOTI:(NSData *)oti error:(NSError **)error NS_DESIGNATED_INITIALIZER;
+
+/// Decoder for a handshake frame; the recovered object is checked against
+/// its digest.
+- (nullable instancetype)initWithHandshakeFrame:(NSData *)frame error:(NSError **)error NS_DESIGNATED_INITIALIZER;
+
+- (instancetype)init NS_UNAVAILABLE;
+
+/// Push one frame payload and return its status.  Statuses that reject the
+/// frame or the transfer (`RQ_STATUS_MALFORMED`, `RQ_STATUS_DIGEST_MISMATCH`,
+/// `RQ_STATUS_FAILED`, `RQ_STATUS_BUSY` and the like) also fill `error`.
+- (RQStatus)pushFrame:(NSData *)frame error:(NSError **)error;
+
+/// `YES` once the whole object has been recovered.
+@property (nonatomic, readonly, getter=isComplete) BOOL complete;
+
+/// Frames the sender's loop consists of, or `0` when unknown.
+@property (nonatomic, readonly) uint32_t totalFrames;
+
+/// Move the recovered object out of the decoder without copying it.  Returns
+/// `nil` before completion and on every call after the first.
+- (nullable NSData *)takeResultWithError:(NSError **)error;
+
+@end
+
+NS_ASSUME_NONNULL_END
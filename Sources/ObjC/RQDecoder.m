@@ -0,0 +1,127 @@
+//
+//  RQDecoder.m
+//  Objective-C wrapper around the decoding half of the C API
+//
+
+#import "RQDecoder.h"
+
+NSErrorDomain const RQDecoderErrorDomain = @"RQDecoderErrorDomain";
+
+/// `NSError` for `status`, with the thread's last library error as the
+/// failure reason when there is one.
+static NSError *RQErrorFromStatus(RQStatus status) {
+    NSMutableDictionary<NSErrorUserInfoKey, id> *info = [NSMutableDictionary dictionary];
+    info[NSLocalizedDescriptionKey] = @(raptorq_status_message(status));
+    const char *reason = raptorq_last_error();
+    if (reason != NULL) {
+        info[NSLocalizedFailureReasonErrorKey] = @(reason);
+    }
+    return [NSError errorWithDomain:RQDecoderErrorDomain code:status userInfo:info];
+}
+
+/// Whether `status` rejects the frame or the transfer; statuses this
+/// wrapper does not know are treated as failures.
+static BOOL RQStatusIsError(RQStatus status) {
+    switch (status) {
+        case RQ_STATUS_OK:
+        case RQ_STATUS_COMPLETE:
+        case RQ_STATUS_PADDING_STRIPPED:
+        case RQ_STATUS_IGNORED:
+        case RQ_STATUS_READY_TO_FINALIZE:
+        case RQ_STATUS_PENDING:
+            return NO;
+        case RQ_STATUS_MALFORMED:
+        case RQ_STATUS_DIGEST_MISMATCH:
+        case RQ_STATUS_FAILED:
+        case RQ_STATUS_LENGTH_OVERFLOW:
+        case RQ_STATUS_INVALID_HANDLE:
+        case RQ_STATUS_INVALID_ARGUMENT:
+        case RQ_STATUS_BUSY:
+        case RQ_STATUS_CANCELLED:
+            return YES;
+    }
+    return YES;
+}
+
+@implementation RQDecoder {
+    RQContext *_ctx;
+}
+
+/// Take ownership of `ctx`, or report a rejected argument when it is `NULL`.
+- (nullable instancetype)initWithContext:(nullable RQContext *)ctx error:(NSError **)error {
+    if (ctx == NULL) {
+        if (error != NULL) {
+            *error = RQErrorFromStatus(RQ_STATUS_INVALID_ARGUMENT);
+        }
+        return nil;
+    }
+    if ((self = [super init])) {
+        _ctx = ctx;
+    } else {
+        raptorq_ctx_free(ctx);
+    }
+    return self;
+}
+
+- (nullable instancetype)initWithTransferLength:(uint64_t)length
+                                 maxPayloadSize:(uint16_t)maxPayloadSize
+                                     threadSafe:(BOOL)threadSafe
+                                          error:(NSError **)error {
+    raptorq_clear_last_error();
+    RQContext *ctx = threadSafe ? raptorq_ctx_new_threadsafe(length, maxPayloadSize)
+                                : raptorq_ctx_new(length, maxPayloadSize);
+    return [self initWithContext:ctx error:error];
+}
+
+- (nullable instancetype)initWithOTI:(NSData *)oti error:(NSError **)error {
+    raptorq_clear_last_error();
+    RQContext *ctx = oti.length == 12 ? raptorq_ctx_from_oti(oti.bytes) : NULL;
+    return [self initWithContext:ctx error:error];
+}
+
+- (nullable instancetype)initWithHandshakeFrame:(NSData *)frame error:(NSError **)error {
+    raptorq_clear_last_error();
+    RQContext *ctx = raptorq_ctx_from_handshake(frame.bytes, frame.length);
+    return [self initWithContext:ctx error:error];
+}
+
+- (void)dealloc {
+    raptorq_ctx_free(_ctx);
+}
+
+- (RQStatus)pushFrame:(NSData *)frame error:(NSError **)error {
+    raptorq_clear_last_error();
+    RQStatus status = raptorq_ctx_push_frame_status(_ctx, frame.bytes, frame.length);
+    if (RQStatusIsError(status) && error != NULL) {
+        *error = RQErrorFromStatus(status);
+    }
+    return status;
+}
+
+- (BOOL)isComplete {
+    return raptorq_ctx_is_complete(_ctx);
+}
+
+- (uint32_t)totalFrames {
+    return raptorq_ctx_total_frames(_ctx);
+}
+
+- (nullable NSData *)takeResultWithError:(NSError **)error {
+    raptorq_clear_last_error();
+    RQBuffer buffer = raptorq_ctx_take_result_buffer(_ctx);
+    if (buffer.ptr == NULL) {
+        // Not complete yet (or already taken) is not an error; anything the
+        // library logged is.
+        if (raptorq_last_error() != NULL && error != NULL) {
+            *error = RQErrorFromStatus(RQ_STATUS_FAILED);
+        }
+        return nil;
+    }
+    return [[NSData alloc] initWithBytesNoCopy:buffer.ptr
+                                        length:(NSUInteger)buffer.len
+                                   deallocator:^(void *bytes, NSUInteger length) {
+                                       raptorq_buffer_free(buffer);
+                                   }];
+}
+
+@end
@@ -0,0 +1,174 @@
+//
+//  RaptorQDecoder.swift
+//  Typed Swift API over the decoding half of the C API
+//
+
+import raptorq      // <-- module generated by the XCFramework / podspec
+import Foundation
+
+/// A status the library reported as a failure, with its description and the
+/// detail from `raptorq_last_error`, if any.
+public struct RaptorQError: Error, CustomStringConvertible {
+    public let status: RQStatus
+    public let message: String
+    public let reason: String?
+
+    init(_ status: RQStatus) {
+        self.status = status
+        self.message = String(cString: raptorq_status_message(status.rawValue))
+        self.reason = raptorq_last_error().map { String(cString: $0) }
+    }
+
+    public var description: String {
+        reason.map { "\(message): \($0)" } ?? message
+    }
+}
+
+/// What happened to a pushed frame.
+public enum PushResult: Equatable {
+    /// The frame was accepted; more are needed.
+    case accepted
+    /// The frame finished decoding; call `takeResult()`.
+    case complete
+    /// The frame was not part of this transfer (a repeated handshake, or
+    /// anything pushed after completion) and was skipped.
+    case ignored
+    /// The frame was rejected, or decoding failed.
+    case failed(RaptorQError)
+
+    public static func == (lhs: PushResult, rhs: PushResult) -> Bool {
+        switch (lhs, rhs) {
+        case (.accepted, .accepted), (.complete, .complete), (.ignored, .ignored):
+            return true
+        case let (.failed(a), .failed(b)):
+            return a.status == b.status
+        default:
+            return false
+        }
+    }
+}
+
+/// Owns one decoding context and frees it on deinit.
+///
+/// Not thread-safe: use one decoder from one queue at a time (overlapping
+/// calls fail with `RQ_STATUS_BUSY` rather than corrupt the transfer).
+public final class RaptorQDecoder {
+
+    // MARK: Stored properties
+    private let ctx: OpaquePointer     // RQContext *
+
+    // MARK: Initialisation ----------------------------------------------------
+
+    /// Create a decoder when you already know the total length
+    /// and the max payload of each QR frame.
+    public init(transferLength: UInt64, maxPayload: UInt16) throws {
+        raptorq_clear_last_error()
+        guard let raw = raptorq_ctx_new(transferLength, maxPayload) else {
+            throw RaptorQError(RQ_STATUS_INVALID_ARGUMENT)
+        }
+        self.ctx = raw
+    }
+
+    /// Create a decoder from the 12-byte OTI header embedded in the first QR.
+    public convenience init(oti data: Data) throws {
+        raptorq_clear_last_error()
+        let raw = data.count == 12 ? data.withUnsafeBytes { buf in
+            raptorq_ctx_from_oti(buf.bindMemory(to: UInt8.self).baseAddress)
+        } : nil
+        try self.init(wrapping: raw)
+    }
+
+    /// Create a decoder from a handshake frame; the recovered object is
+    /// checked against the digest it carries.
+    public convenience init(handshake frame: Data) throws {
+        raptorq_clear_last_error()
+        let raw = frame.withUnsafeBytes { buf in
+            raptorq_ctx_from_handshake(buf.bindMemory(to: UInt8.self).baseAddress,
+                                       UInt64(buf.count))
+        }
+        try self.init(wrapping: raw)
+    }
+
+    private init(wrapping raw: OpaquePointer?) throws {
+        guard let raw = raw else { throw RaptorQError(RQ_STATUS_INVALID_ARGUMENT) }
+        self.ctx = raw
+    }
+
+    deinit { raptorq_ctx_free(ctx) }
+
+    // MARK: Feeding packets ---------------------------------------------------
+
+    /// Push one QR-frame payload.
+    @discardableResult
+    public func push(_ frame: Data) -> PushResult {
+        raptorq_clear_last_error()
+        let status = frame.withUnsafeBytes { buf in
+            raptorq_ctx_push_frame_status(ctx,
+                                          buf.bindMemory(to: UInt8.self).baseAddress,
+                                          UInt64(buf.count))
+        }
+        switch status {
+        case RQ_STATUS_OK, RQ_STATUS_PADDING_STRIPPED:
+            return .accepted
+        case RQ_STATUS_COMPLETE:
+            return .complete
+        case RQ_STATUS_IGNORED:
+            return .ignored
+        default:
+            return .failed(RaptorQError(status))
+        }
+    }
+
+    /// `true` once enough packets have been received to reconstruct the object.
+    public var isComplete: Bool { raptorq_ctx_is_complete(ctx) }
+
+    /// Distinct symbols received over the source symbols needed, from 0 to
+    /// 1, summed over the blocks; duplicates and each block's overhead do
+    /// not count.
+    public var progress: Double {
+        if isComplete { return 1 }
+        var received: UInt64 = 0
+        var needed: UInt64 = 0
+        for sbn in 0..<raptorq_ctx_block_count(ctx) {
+            let block = raptorq_ctx_block_timing(ctx, sbn)
+            received += UInt64(min(block.symbols_received, block.symbols))
+            needed += UInt64(block.symbols)
+        }
+        return needed > 0 ? Double(received) / Double(needed) : 0
+    }
+
+    // MARK: Taking the result -------------------------------------------------
+
+    /// Move the reconstructed object out of Rust and into a Swift `Data`.
+    /// Call **once**. Subsequent calls return `nil`.
+    public func takeResult() -> Data? {
+        let buffer = raptorq_ctx_take_result_buffer(ctx)
+        guard let ptr = buffer.ptr else { return nil }
+
+        // Wrap without copy; free via raptorq_buffer_free when Data is released.
+        return Data(bytesNoCopy: ptr,
+                    count: Int(buffer.len),
+                    deallocator: .custom { _, _ in raptorq_buffer_free(buffer) })
+    }
+
+    // MARK: Deprecated --------------------------------------------------------
+
+    @available(*, deprecated,
+               message: "use init(transferLength:maxPayload:), which throws a RaptorQError")
+    public convenience init?(totalBytes: UInt64, maxPayload: UInt16) {
+        try? self.init(transferLength: totalBytes, maxPayload: maxPayload)
+    }
+
+    @available(*, deprecated, message: "use init(oti:), which throws a RaptorQError")
+    public convenience init?(oti12 data: Data) {
+        try? self.init(oti: data)
+    }
+
+    /// Push one QR-frame payload.
+    /// - Returns: `true` if decoding completed with this packet.
+    @available(*, deprecated, message: "use push(_:), whose PushResult also reports failures")
+    @discardableResult
+    public func push(frame payload: Data) -> Bool {
+        push(payload) == .complete
+    }
+}
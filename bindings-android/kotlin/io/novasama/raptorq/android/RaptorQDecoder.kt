@@ -0,0 +1,159 @@
+//
+//  RaptorQDecoder.kt
+//  Typed Kotlin API over the JNI shims of `bindings-android`, mirroring the
+//  Swift `RaptorQDecoder`
+//
+
+package io.novasama.raptorq.android
+
+/** The `RQStatus` values the wrapper tells apart. */
+object RQStatus {
+    const val OK = 0
+    const val COMPLETE = 1
+    const val PADDING_STRIPPED = 2
+    const val IGNORED = 3
+    const val INVALID_ARGUMENT = 10
+}
+
+/**
+ * A status the library reported as a failure, with its description and the
+ * detail from `raptorq_last_error`, if any.
+ */
+class RaptorQException internal constructor(
+    val status: Int,
+    val reason: String? = RaptorQNative.lastError(),
+) : Exception(
+    (RaptorQNative.statusMessage(status) ?: "status $status").let { message ->
+        reason?.let { "$message: $it" } ?: message
+    }
+)
+
+/** What happened to a pushed frame. */
+sealed class PushResult {
+    /** The frame was accepted; more are needed. */
+    object Accepted : PushResult()
+
+    /** The frame finished decoding; call [RaptorQDecoder.takeResult]. */
+    object Complete : PushResult()
+
+    /**
+     * The frame was not part of this transfer (a repeated handshake, or
+     * anything pushed after completion) and was skipped.
+     */
+    object Ignored : PushResult()
+
+    /** The frame was rejected, or decoding failed. */
+    data class Failed(val error: RaptorQException) : PushResult()
+}
+
+/**
+ * Owns one decoding context; [close] frees it.
+ *
+ * Not thread-safe: use one decoder from one thread at a time (overlapping
+ * calls fail with `RQ_STATUS_BUSY` rather than corrupt the transfer).
+ * [cancel] is the exception and may be called from any thread.
+ */
+class RaptorQDecoder private constructor(private var ctx: Long) : AutoCloseable {
+
+    private val cancelToken = RaptorQNative.cancelToken(ctx)
+    private var acceptedFrames = 0
+
+    /**
+     * Create a decoder when you already know the total length and the max
+     * payload of each QR frame.
+     */
+    constructor(totalBytes: Long, maxPayload: Int) :
+        this(adopt(RaptorQNative.newContext(totalBytes, maxPayload)))
+
+    companion object {
+        /** Create a decoder from the 12-byte OTI header embedded in the first QR. */
+        fun fromOti(oti: ByteArray): RaptorQDecoder =
+            RaptorQDecoder(adopt(RaptorQNative.fromOti(oti)))
+
+        /**
+         * Create a decoder from a handshake frame; the recovered object is
+         * checked against the digest it carries.
+         */
+        fun fromHandshake(frame: ByteArray): RaptorQDecoder =
+            RaptorQDecoder(adopt(RaptorQNative.fromHandshake(frame)))
+
+        private fun adopt(ctx: Long): Long {
+            if (ctx == 0L) throw RaptorQException(RQStatus.INVALID_ARGUMENT)
+            return ctx
+        }
+    }
+
+    /** Push one QR-frame payload. */
+    fun push(frame: ByteArray): PushResult =
+        when (val status = RaptorQNative.push(ctx, frame)) {
+            RQStatus.OK, RQStatus.PADDING_STRIPPED -> {
+                acceptedFrames += 1
+                PushResult.Accepted
+            }
+            RQStatus.COMPLETE -> {
+                acceptedFrames += 1
+                PushResult.Complete
+            }
+            RQStatus.IGNORED -> PushResult.Ignored
+            else -> PushResult.Failed(RaptorQException(status))
+        }
+
+    /** `true` once enough packets have been received to reconstruct the object. */
+    val isComplete: Boolean
+        get() = RaptorQNative.isComplete(ctx)
+
+    /**
+     * Accepted frames over the frames in the sender's loop, from 0 to 1;
+     * `null` while the loop length is unknown (no handshake frame).
+     */
+    val progress: Double?
+        get() {
+            if (isComplete) return 1.0
+            val total = RaptorQNative.totalFrames(ctx)
+            if (total <= 0) return null
+            return minOf(acceptedFrames.toDouble() / total, 1.0)
+        }
+
+    /**
+     * Move the reconstructed object out of the library. Call **once**;
+     * subsequent calls return `null`.
+     */
+    fun takeResult(): ByteArray? = RaptorQNative.takeResult(ctx)
+
+    /**
+     * Stop any work the context still does in the background; later pushes
+     * fail with `RQ_STATUS_CANCELLED`. Safe from any thread, e.g. when the
+     * scanner screen goes away.
+     */
+    fun cancel() = RaptorQNative.cancel(cancelToken)
+
+    override fun close() {
+        if (ctx != 0L) {
+            RaptorQNative.free(ctx)
+            RaptorQNative.freeCancelToken(cancelToken)
+            ctx = 0
+        }
+    }
+}
+
+/** The JNI shims in `bindings-android/src/lib.rs`. */
+internal object RaptorQNative {
+    init {
+        System.loadLibrary("raptorq_android")
+    }
+
+    @JvmStatic external fun abiVersion(): Int
+    @JvmStatic external fun newContext(transferLength: Long, maxPayloadSize: Int): Long
+    @JvmStatic external fun fromOti(oti: ByteArray): Long
+    @JvmStatic external fun fromHandshake(frame: ByteArray): Long
+    @JvmStatic external fun push(ctx: Long, frame: ByteArray): Int
+    @JvmStatic external fun isComplete(ctx: Long): Boolean
+    @JvmStatic external fun totalFrames(ctx: Long): Int
+    @JvmStatic external fun takeResult(ctx: Long): ByteArray?
+    @JvmStatic external fun free(ctx: Long)
+    @JvmStatic external fun cancelToken(ctx: Long): Long
+    @JvmStatic external fun cancel(token: Long)
+    @JvmStatic external fun freeCancelToken(token: Long)
+    @JvmStatic external fun statusMessage(status: Int): String?
+    @JvmStatic external fun lastError(): String?
+}
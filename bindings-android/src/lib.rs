@@ -0,0 +1,214 @@
+//! JNI entry points for `io.novasama.raptorq.android.RaptorQNative`, so
+//! the Android app decodes with the same Rust core as the iOS framework.
+//!
+//! Every function is a thin shim over the C API of the `bindings` crate:
+//! contexts and cancel tokens cross into Kotlin as `long` handles, statuses
+//! as the `RQStatus` integer, and frames and results as copied `byte[]`s.
+//! `RaptorQDecoder.kt` builds the typed API on top.
+
+use core::ptr;
+
+use jni::objects::{JByteArray, JClass};
+use jni::sys::{jboolean, jbyteArray, jint, jlong, jstring};
+use jni::JNIEnv;
+
+use raptorq_ios::{
+    raptorq_abi_version, raptorq_buffer_free, raptorq_cancel, raptorq_cancel_token_free,
+    raptorq_clear_last_error, raptorq_ctx_cancel_token, raptorq_ctx_free,
+    raptorq_ctx_from_handshake, raptorq_ctx_from_oti, raptorq_ctx_is_complete, raptorq_ctx_new,
+    raptorq_ctx_push_frame_status, raptorq_ctx_take_result_buffer, raptorq_ctx_total_frames,
+    raptorq_last_error, raptorq_status_message, RQCancelToken, RQContext, RQStatus,
+};
+
+fn ctx_from(handle: jlong) -> *mut RQContext {
+    handle as usize as *mut RQContext
+}
+
+fn handle_of<T>(ptr: *const T) -> jlong {
+    ptr as usize as jlong
+}
+
+/// A Java string, or `null` if it cannot be created.
+fn new_string(env: &mut JNIEnv, text: &str) -> jstring {
+    env.new_string(text)
+        .map_or(ptr::null_mut(), |string| string.into_raw())
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_abiVersion(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jint {
+    raptorq_abi_version() as jint
+}
+
+/// `raptorq_ctx_new`; `0` where it returns `NULL`.
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_newContext(
+    _env: JNIEnv,
+    _class: JClass,
+    transfer_length: jlong,
+    max_payload_size: jint,
+) -> jlong {
+    raptorq_clear_last_error();
+    match (
+        u64::try_from(transfer_length),
+        u16::try_from(max_payload_size),
+    ) {
+        (Ok(length), Ok(payload)) => handle_of(raptorq_ctx_new(length, payload)),
+        _ => 0,
+    }
+}
+
+/// `raptorq_ctx_from_oti` on a 12-byte array; `0` for anything else.
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_fromOti(
+    env: JNIEnv,
+    _class: JClass,
+    oti: JByteArray,
+) -> jlong {
+    raptorq_clear_last_error();
+    match env.convert_byte_array(&oti) {
+        Ok(oti) if oti.len() == 12 => handle_of(unsafe { raptorq_ctx_from_oti(oti.as_ptr()) }),
+        _ => 0,
+    }
+}
+
+/// `raptorq_ctx_from_handshake`; `0` where it returns `NULL`.
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_fromHandshake(
+    env: JNIEnv,
+    _class: JClass,
+    frame: JByteArray,
+) -> jlong {
+    raptorq_clear_last_error();
+    match env.convert_byte_array(&frame) {
+        Ok(frame) => {
+            handle_of(unsafe { raptorq_ctx_from_handshake(frame.as_ptr(), frame.len() as u64) })
+        }
+        Err(_) => 0,
+    }
+}
+
+/// `raptorq_ctx_push_frame_status`, returning the status as an integer.
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_push(
+    env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+    frame: JByteArray,
+) -> jint {
+    raptorq_clear_last_error();
+    let status = match env.convert_byte_array(&frame) {
+        Ok(frame) => unsafe {
+            raptorq_ctx_push_frame_status(ctx_from(ctx), frame.as_ptr(), frame.len() as u64)
+        },
+        Err(_) => RQStatus::InvalidArgument,
+    };
+    status as jint
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_isComplete(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) -> jboolean {
+    unsafe { raptorq_ctx_is_complete(ctx_from(ctx)) }.into()
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_totalFrames(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) -> jint {
+    unsafe { raptorq_ctx_total_frames(ctx_from(ctx)) as jint }
+}
+
+/// The recovered object copied into a new `byte[]`, or `null` if there is
+/// none (yet).  The library's buffer is released either way.
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_takeResult(
+    env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) -> jbyteArray {
+    let buf = unsafe { raptorq_ctx_take_result_buffer(ctx_from(ctx)) };
+    if buf.ptr.is_null() {
+        return ptr::null_mut();
+    }
+    let result = unsafe { core::slice::from_raw_parts(buf.ptr, buf.len as usize) };
+    let array = env
+        .byte_array_from_slice(result)
+        .map_or(ptr::null_mut(), |array| array.into_raw());
+    unsafe { raptorq_buffer_free(buf) };
+    array
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_free(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) {
+    unsafe { raptorq_ctx_free(ctx_from(ctx)) };
+}
+
+/// `raptorq_ctx_cancel_token`; `0` for a `0` context.
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_cancelToken(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) -> jlong {
+    handle_of(unsafe { raptorq_ctx_cancel_token(ctx_from(ctx)) })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_cancel(
+    _env: JNIEnv,
+    _class: JClass,
+    token: jlong,
+) {
+    unsafe { raptorq_cancel(token as usize as *const RQCancelToken) };
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_freeCancelToken(
+    _env: JNIEnv,
+    _class: JClass,
+    token: jlong,
+) {
+    unsafe { raptorq_cancel_token_free(token as usize as *const RQCancelToken) };
+}
+
+/// `raptorq_status_message` for the integer `status`; `null` for a value
+/// that is not a status.
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_statusMessage(
+    mut env: JNIEnv,
+    _class: JClass,
+    status: jint,
+) -> jstring {
+    let status = match u8::try_from(status).ok().and_then(RQStatus::from_repr) {
+        Some(status) => status,
+        None => return ptr::null_mut(),
+    };
+    let message = unsafe { std::ffi::CStr::from_ptr(raptorq_status_message(status as u32)) };
+    new_string(&mut env, &message.to_string_lossy())
+}
+
+/// `raptorq_last_error` of the calling thread, or `null`.
+#[no_mangle]
+pub extern "system" fn Java_io_novasama_raptorq_android_RaptorQNative_lastError(
+    mut env: JNIEnv,
+    _class: JClass,
+) -> jstring {
+    let error = raptorq_last_error();
+    if error.is_null() {
+        return ptr::null_mut();
+    }
+    let error = unsafe { std::ffi::CStr::from_ptr(error) };
+    new_string(&mut env, &error.to_string_lossy())
+}
@@ -0,0 +1,262 @@
+//! Node bindings through napi-rs, so the web tooling that renders animated
+//! QR assets encodes with the production encoder rather than a JavaScript
+//! port, and can check its output with the production decoder.
+//!
+//! Like `bindings-py`, every method is a thin shim over the C API of the
+//! `bindings` crate.  Pushes return the `RQStatus` as a number (the module
+//! exports a `STATUS_*` constant for each value); constructors throw where
+//! the C API returns `NULL`.
+
+use std::ffi::CStr;
+
+use napi::bindgen_prelude::*;
+use napi::JsObject;
+use napi_derive::{module_exports, napi};
+
+use raptorq_ios::{
+    raptorq_abi_version, raptorq_buffer_free, raptorq_clear_last_error, raptorq_ctx_dump_buffer,
+    raptorq_ctx_free, raptorq_ctx_from_dump, raptorq_ctx_from_handshake, raptorq_ctx_from_oti,
+    raptorq_ctx_is_complete, raptorq_ctx_new, raptorq_ctx_push_frame_status,
+    raptorq_ctx_take_result_buffer, raptorq_ctx_total_frames, raptorq_enc_frame_buffer,
+    raptorq_enc_frame_count, raptorq_enc_free, raptorq_enc_handshake_frame_buffer, raptorq_enc_new,
+    raptorq_enc_oti_buffer, raptorq_last_error, raptorq_status_message, RQBuffer, RQContext,
+    RQEncoder, RQStatus,
+};
+
+/// Repair packets per source block when the caller does not say.
+const DEFAULT_REPAIR_PACKETS: u32 = 10;
+
+/// The bytes of `buf` copied into a Node `Buffer`, or `None` for a `NULL`
+/// buffer; the library's buffer is released either way.
+fn into_buffer(buf: RQBuffer) -> Option<Buffer> {
+    if buf.ptr.is_null() {
+        return None;
+    }
+    let data = unsafe { std::slice::from_raw_parts(buf.ptr, buf.len as usize) }.to_vec();
+    unsafe { raptorq_buffer_free(buf) };
+    Some(data.into())
+}
+
+/// An `Error` with `raptorq_last_error`, or `what` if there is none.
+fn error(what: &str) -> Error {
+    let reason = raptorq_last_error();
+    if reason.is_null() {
+        return Error::from_reason(what);
+    }
+    let reason = unsafe { CStr::from_ptr(reason) };
+    Error::from_reason(format!("{what}: {}", reason.to_string_lossy()))
+}
+
+fn payload_size(max_payload_size: u32) -> Result<u16> {
+    u16::try_from(max_payload_size)
+        .map_err(|_| Error::from_reason("maxPayloadSize does not fit 16 bits"))
+}
+
+/// `RQStatus::DigestMismatch` as `STATUS_DIGEST_MISMATCH`.
+fn constant_name(status: RQStatus) -> String {
+    let mut name = String::from("STATUS");
+    for c in format!("{status:?}").chars() {
+        if c.is_ascii_uppercase() {
+            name.push('_');
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+    name
+}
+
+#[module_exports]
+fn init(mut exports: JsObject) -> Result<()> {
+    for code in 0..=u8::MAX {
+        if let Some(status) = RQStatus::from_repr(code) {
+            exports.set_named_property(&constant_name(status), code as u32)?;
+        }
+    }
+    Ok(())
+}
+
+#[napi]
+pub fn abi_version() -> u32 {
+    raptorq_abi_version()
+}
+
+/// `raptorq_status_message`; `null` for a value that is not a status.
+#[napi]
+pub fn status_message(status: u32) -> Option<String> {
+    let status = u8::try_from(status).ok().and_then(RQStatus::from_repr)?;
+    let message = unsafe { CStr::from_ptr(raptorq_status_message(status as u32)) };
+    Some(message.to_string_lossy().into_owned())
+}
+
+/// `raptorq_last_error` of the calling thread, or `null`.
+#[napi]
+pub fn last_error() -> Option<String> {
+    let error = raptorq_last_error();
+    if error.is_null() {
+        return None;
+    }
+    let error = unsafe { CStr::from_ptr(error) };
+    Some(error.to_string_lossy().into_owned())
+}
+
+/// Encoder for one object.
+#[napi]
+pub struct Encoder {
+    enc: *mut RQEncoder,
+}
+
+impl Drop for Encoder {
+    fn drop(&mut self) {
+        unsafe { raptorq_enc_free(self.enc) };
+    }
+}
+
+#[napi]
+impl Encoder {
+    #[napi(constructor)]
+    pub fn new(
+        data: Buffer,
+        max_payload_size: u32,
+        repair_packets_per_block: Option<u32>,
+    ) -> Result<Self> {
+        raptorq_clear_last_error();
+        let enc = unsafe {
+            raptorq_enc_new(
+                data.as_ptr(),
+                data.len() as u64,
+                payload_size(max_payload_size)?,
+                repair_packets_per_block.unwrap_or(DEFAULT_REPAIR_PACKETS),
+            )
+        };
+        if enc.is_null() {
+            return Err(error("cannot encode"));
+        }
+        Ok(Encoder { enc })
+    }
+
+    /// The 12-byte OTI a decoder is created from.
+    #[napi]
+    pub fn oti(&self) -> Option<Buffer> {
+        into_buffer(unsafe { raptorq_enc_oti_buffer(self.enc) })
+    }
+
+    #[napi]
+    pub fn handshake_frame(&self, content_type: Option<u32>) -> Result<Option<Buffer>> {
+        let content_type = u16::try_from(content_type.unwrap_or(0))
+            .map_err(|_| Error::from_reason("contentType does not fit 16 bits"))?;
+        Ok(into_buffer(unsafe {
+            raptorq_enc_handshake_frame_buffer(self.enc, content_type)
+        }))
+    }
+
+    #[napi(getter)]
+    pub fn frame_count(&self) -> u32 {
+        unsafe { raptorq_enc_frame_count(self.enc) }
+    }
+
+    #[napi]
+    pub fn frame(&self, index: u32) -> Option<Buffer> {
+        into_buffer(unsafe { raptorq_enc_frame_buffer(self.enc, index) })
+    }
+
+    /// Every frame of one loop, in order, without the handshake.
+    #[napi]
+    pub fn frames(&self) -> Vec<Buffer> {
+        (0..self.frame_count())
+            .filter_map(|index| self.frame(index))
+            .collect()
+    }
+}
+
+/// One decoding context, freed when the object is collected.
+#[napi]
+pub struct Decoder {
+    ctx: *mut RQContext,
+}
+
+impl Drop for Decoder {
+    fn drop(&mut self) {
+        unsafe { raptorq_ctx_free(self.ctx) };
+    }
+}
+
+impl Decoder {
+    fn adopt(ctx: *mut RQContext, what: &str) -> Result<Decoder> {
+        if ctx.is_null() {
+            return Err(error(what));
+        }
+        Ok(Decoder { ctx })
+    }
+}
+
+#[napi]
+impl Decoder {
+    #[napi(constructor)]
+    pub fn new(transfer_length: i64, max_payload_size: u32) -> Result<Self> {
+        raptorq_clear_last_error();
+        let transfer_length = u64::try_from(transfer_length)
+            .map_err(|_| Error::from_reason("transferLength is negative"))?;
+        Decoder::adopt(
+            raptorq_ctx_new(transfer_length, payload_size(max_payload_size)?),
+            "invalid transfer",
+        )
+    }
+
+    #[napi(factory)]
+    pub fn from_oti(oti: Buffer) -> Result<Self> {
+        raptorq_clear_last_error();
+        if oti.len() != 12 {
+            return Err(Error::from_reason("an OTI is 12 bytes"));
+        }
+        Decoder::adopt(unsafe { raptorq_ctx_from_oti(oti.as_ptr()) }, "invalid OTI")
+    }
+
+    #[napi(factory)]
+    pub fn from_handshake(frame: Buffer) -> Result<Self> {
+        raptorq_clear_last_error();
+        Decoder::adopt(
+            unsafe { raptorq_ctx_from_handshake(frame.as_ptr(), frame.len() as u64) },
+            "invalid handshake",
+        )
+    }
+
+    /// Put a decoder back into the state recorded by `raptorq_ctx_dump`.
+    #[napi(factory)]
+    pub fn from_dump(blob: Buffer) -> Result<Self> {
+        raptorq_clear_last_error();
+        Decoder::adopt(
+            unsafe { raptorq_ctx_from_dump(blob.as_ptr(), blob.len() as u64) },
+            "invalid dump",
+        )
+    }
+
+    /// The `RQStatus` of the frame, as a number.
+    #[napi]
+    pub fn push(&mut self, frame: Buffer) -> u32 {
+        raptorq_clear_last_error();
+        let status =
+            unsafe { raptorq_ctx_push_frame_status(self.ctx, frame.as_ptr(), frame.len() as u64) };
+        status as u32
+    }
+
+    #[napi(getter)]
+    pub fn is_complete(&self) -> bool {
+        unsafe { raptorq_ctx_is_complete(self.ctx) }
+    }
+
+    #[napi(getter)]
+    pub fn total_frames(&self) -> u32 {
+        unsafe { raptorq_ctx_total_frames(self.ctx) }
+    }
+
+    /// The recovered object, once; `null` before and after.
+    #[napi]
+    pub fn take_result(&mut self) -> Option<Buffer> {
+        into_buffer(unsafe { raptorq_ctx_take_result_buffer(self.ctx) })
+    }
+
+    /// A `raptorq_ctx_dump` of the state so far, for `fromDump`.
+    #[napi]
+    pub fn dump(&self) -> Option<Buffer> {
+        into_buffer(unsafe { raptorq_ctx_dump_buffer(self.ctx) })
+    }
+}
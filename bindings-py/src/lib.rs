@@ -0,0 +1,256 @@
+//! The `raptorq_py` Python module, for scripting round trips, generating
+//! test corpora and analyzing field dumps against the same Rust core as the
+//! apps.
+//!
+//! Like `bindings-android`, every method is a thin shim over the C API of
+//! the `bindings` crate.  Pushes return the `RQStatus` as an integer (the
+//! module has a `STATUS_*` constant for each value), so a script can tally
+//! what happened to every frame; constructors raise `RaptorQError` where
+//! the C API returns `NULL`.
+
+use std::ffi::CStr;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use raptorq_ios::{
+    raptorq_abi_version, raptorq_buffer_free, raptorq_clear_last_error, raptorq_ctx_dump_buffer,
+    raptorq_ctx_free, raptorq_ctx_from_dump, raptorq_ctx_from_handshake, raptorq_ctx_from_oti,
+    raptorq_ctx_is_complete, raptorq_ctx_new, raptorq_ctx_push_frame_status,
+    raptorq_ctx_take_result_buffer, raptorq_ctx_total_frames, raptorq_enc_frame_buffer,
+    raptorq_enc_frame_count, raptorq_enc_free, raptorq_enc_handshake_frame_buffer, raptorq_enc_new,
+    raptorq_enc_oti_buffer, raptorq_last_error, raptorq_status_message, RQBuffer, RQContext,
+    RQEncoder, RQStatus,
+};
+
+create_exception!(raptorq_py, RaptorQError, PyException);
+
+/// Repair packets per source block when the script does not say.
+const DEFAULT_REPAIR_PACKETS: u32 = 10;
+
+/// The bytes of `buf` as a Python `bytes`, or `None` for a `NULL` buffer;
+/// the library's buffer is released either way.
+fn into_bytes(py: Python<'_>, buf: RQBuffer) -> Option<Py<PyBytes>> {
+    if buf.ptr.is_null() {
+        return None;
+    }
+    let data = unsafe { std::slice::from_raw_parts(buf.ptr, buf.len as usize) };
+    let bytes = PyBytes::new(py, data).unbind();
+    unsafe { raptorq_buffer_free(buf) };
+    Some(bytes)
+}
+
+/// `RaptorQError` with `raptorq_last_error`, or `what` if there is none.
+fn error(what: &str) -> PyErr {
+    let reason = raptorq_last_error();
+    if reason.is_null() {
+        return RaptorQError::new_err(what.to_owned());
+    }
+    let reason = unsafe { CStr::from_ptr(reason) };
+    RaptorQError::new_err(format!("{what}: {}", reason.to_string_lossy()))
+}
+
+/// `RQStatus::DigestMismatch` as `STATUS_DIGEST_MISMATCH`.
+fn constant_name(status: RQStatus) -> String {
+    let mut name = String::from("STATUS");
+    for c in format!("{status:?}").chars() {
+        if c.is_ascii_uppercase() {
+            name.push('_');
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+    name
+}
+
+#[pyfunction]
+fn abi_version() -> u32 {
+    raptorq_abi_version()
+}
+
+/// `raptorq_status_message`; `None` for a value that is not a status.
+#[pyfunction]
+fn status_message(status: u8) -> Option<String> {
+    let status = RQStatus::from_repr(status)?;
+    let message = unsafe { CStr::from_ptr(raptorq_status_message(status as u32)) };
+    Some(message.to_string_lossy().into_owned())
+}
+
+/// `raptorq_last_error` of the calling thread, or `None`.
+#[pyfunction]
+fn last_error() -> Option<String> {
+    let error = raptorq_last_error();
+    if error.is_null() {
+        return None;
+    }
+    let error = unsafe { CStr::from_ptr(error) };
+    Some(error.to_string_lossy().into_owned())
+}
+
+/// Encoder for one object.
+#[pyclass(unsendable)]
+struct Encoder {
+    enc: *mut RQEncoder,
+}
+
+impl Drop for Encoder {
+    fn drop(&mut self) {
+        unsafe { raptorq_enc_free(self.enc) };
+    }
+}
+
+#[pymethods]
+impl Encoder {
+    #[new]
+    #[pyo3(signature = (data, max_payload_size, repair_packets_per_block = DEFAULT_REPAIR_PACKETS))]
+    fn new(data: &[u8], max_payload_size: u16, repair_packets_per_block: u32) -> PyResult<Self> {
+        raptorq_clear_last_error();
+        let enc = unsafe {
+            raptorq_enc_new(
+                data.as_ptr(),
+                data.len() as u64,
+                max_payload_size,
+                repair_packets_per_block,
+            )
+        };
+        if enc.is_null() {
+            return Err(error("cannot encode"));
+        }
+        Ok(Encoder { enc })
+    }
+
+    /// The 12-byte OTI a decoder is created from.
+    fn oti(&self, py: Python<'_>) -> Option<Py<PyBytes>> {
+        into_bytes(py, unsafe { raptorq_enc_oti_buffer(self.enc) })
+    }
+
+    #[pyo3(signature = (content_type = 0))]
+    fn handshake_frame(&self, py: Python<'_>, content_type: u16) -> Option<Py<PyBytes>> {
+        into_bytes(py, unsafe {
+            raptorq_enc_handshake_frame_buffer(self.enc, content_type)
+        })
+    }
+
+    #[getter]
+    fn frame_count(&self) -> u32 {
+        unsafe { raptorq_enc_frame_count(self.enc) }
+    }
+
+    fn frame(&self, py: Python<'_>, index: u32) -> Option<Py<PyBytes>> {
+        into_bytes(py, unsafe { raptorq_enc_frame_buffer(self.enc, index) })
+    }
+
+    /// Every frame of one loop, in order, without the handshake.
+    fn frames(&self, py: Python<'_>) -> Vec<Py<PyBytes>> {
+        (0..self.frame_count())
+            .filter_map(|index| self.frame(py, index))
+            .collect()
+    }
+}
+
+/// One decoding context, freed with the Python object.
+#[pyclass(unsendable)]
+struct Decoder {
+    ctx: *mut RQContext,
+}
+
+impl Drop for Decoder {
+    fn drop(&mut self) {
+        unsafe { raptorq_ctx_free(self.ctx) };
+    }
+}
+
+impl Decoder {
+    fn adopt(ctx: *mut RQContext, what: &str) -> PyResult<Decoder> {
+        if ctx.is_null() {
+            return Err(error(what));
+        }
+        Ok(Decoder { ctx })
+    }
+}
+
+#[pymethods]
+impl Decoder {
+    #[new]
+    fn new(transfer_length: u64, max_payload_size: u16) -> PyResult<Self> {
+        raptorq_clear_last_error();
+        Decoder::adopt(
+            raptorq_ctx_new(transfer_length, max_payload_size),
+            "invalid transfer",
+        )
+    }
+
+    #[staticmethod]
+    fn from_oti(oti: &[u8]) -> PyResult<Self> {
+        raptorq_clear_last_error();
+        if oti.len() != 12 {
+            return Err(RaptorQError::new_err("an OTI is 12 bytes"));
+        }
+        Decoder::adopt(unsafe { raptorq_ctx_from_oti(oti.as_ptr()) }, "invalid OTI")
+    }
+
+    #[staticmethod]
+    fn from_handshake(frame: &[u8]) -> PyResult<Self> {
+        raptorq_clear_last_error();
+        Decoder::adopt(
+            unsafe { raptorq_ctx_from_handshake(frame.as_ptr(), frame.len() as u64) },
+            "invalid handshake",
+        )
+    }
+
+    /// Put a decoder back into the state recorded by `raptorq_ctx_dump`,
+    /// e.g. from a field report.
+    #[staticmethod]
+    fn from_dump(blob: &[u8]) -> PyResult<Self> {
+        raptorq_clear_last_error();
+        Decoder::adopt(
+            unsafe { raptorq_ctx_from_dump(blob.as_ptr(), blob.len() as u64) },
+            "invalid dump",
+        )
+    }
+
+    /// The `RQStatus` of the frame, as an integer.
+    fn push(&mut self, frame: &[u8]) -> u8 {
+        raptorq_clear_last_error();
+        let status =
+            unsafe { raptorq_ctx_push_frame_status(self.ctx, frame.as_ptr(), frame.len() as u64) };
+        status as u8
+    }
+
+    #[getter]
+    fn is_complete(&self) -> bool {
+        unsafe { raptorq_ctx_is_complete(self.ctx) }
+    }
+
+    #[getter]
+    fn total_frames(&self) -> u32 {
+        unsafe { raptorq_ctx_total_frames(self.ctx) }
+    }
+
+    /// The recovered object, once; `None` before and after.
+    fn take_result(&mut self, py: Python<'_>) -> Option<Py<PyBytes>> {
+        into_bytes(py, unsafe { raptorq_ctx_take_result_buffer(self.ctx) })
+    }
+
+    /// A `raptorq_ctx_dump` of the state so far, for `from_dump`.
+    fn dump(&self, py: Python<'_>) -> Option<Py<PyBytes>> {
+        into_bytes(py, unsafe { raptorq_ctx_dump_buffer(self.ctx) })
+    }
+}
+
+#[pymodule]
+fn raptorq_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("RaptorQError", m.py().get_type::<RaptorQError>())?;
+    for code in 0..=u8::MAX {
+        if let Some(status) = RQStatus::from_repr(code) {
+            m.add(constant_name(status).as_str(), code)?;
+        }
+    }
+    m.add_function(wrap_pyfunction!(abi_version, m)?)?;
+    m.add_function(wrap_pyfunction!(status_message, m)?)?;
+    m.add_function(wrap_pyfunction!(last_error, m)?)?;
+    m.add_class::<Encoder>()?;
+    m.add_class::<Decoder>()?;
+    Ok(())
+}
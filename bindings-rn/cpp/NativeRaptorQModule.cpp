@@ -0,0 +1,165 @@
+//
+//  NativeRaptorQModule.cpp
+//
+
+#include "NativeRaptorQModule.h"
+
+#include <algorithm>
+#include <cmath>
+#include <cstdint>
+#include <string>
+#include <utility>
+
+namespace facebook::react {
+
+namespace {
+
+/// A result buffer handed to JavaScript without a copy; the library's
+/// buffer is released when the `ArrayBuffer` is collected.
+class ResultBuffer : public jsi::MutableBuffer {
+ public:
+  explicit ResultBuffer(RQBuffer buffer) : buffer_(buffer) {}
+  ~ResultBuffer() override { raptorq_buffer_free(buffer_); }
+
+  size_t size() const override { return static_cast<size_t>(buffer_.len); }
+  uint8_t *data() override { return buffer_.ptr; }
+
+ private:
+  RQBuffer buffer_;
+};
+
+/// The bytes of an `ArrayBuffer`, or of the window a typed array or
+/// `DataView` has onto one.  Valid while `object` is.
+std::pair<const uint8_t *, size_t> bytesOf(jsi::Runtime &rt, const jsi::Object &object) {
+  if (object.isArrayBuffer(rt)) {
+    auto buffer = object.getArrayBuffer(rt);
+    return {buffer.data(rt), buffer.size(rt)};
+  }
+  auto buffer = object.getProperty(rt, "buffer");
+  if (buffer.isObject() && buffer.getObject(rt).isArrayBuffer(rt)) {
+    auto offset = static_cast<size_t>(object.getProperty(rt, "byteOffset").asNumber());
+    auto length = static_cast<size_t>(object.getProperty(rt, "byteLength").asNumber());
+    auto data = buffer.getObject(rt).getArrayBuffer(rt).data(rt);
+    return {data + offset, length};
+  }
+  throw jsi::JSError(rt, "RaptorQ: expected an ArrayBuffer or a typed array");
+}
+
+} // namespace
+
+NativeRaptorQModule::NativeRaptorQModule(std::shared_ptr<CallInvoker> jsInvoker)
+    : NativeRaptorQCxxSpec(std::move(jsInvoker)) {}
+
+NativeRaptorQModule::~NativeRaptorQModule() {
+  for (auto &[handle, decoder] : decoders_) {
+    raptorq_cancel_token_free(decoder.cancel);
+    raptorq_ctx_free(decoder.ctx);
+  }
+}
+
+int NativeRaptorQModule::adopt(RQContext *ctx) {
+  if (ctx == nullptr) {
+    return 0;
+  }
+  int handle = nextHandle_++;
+  decoders_.emplace(handle, Decoder{ctx, raptorq_ctx_cancel_token(ctx)});
+  return handle;
+}
+
+RQContext *NativeRaptorQModule::contextOf(int handle) const {
+  auto it = decoders_.find(handle);
+  return it == decoders_.end() ? nullptr : it->second.ctx;
+}
+
+int NativeRaptorQModule::abiVersion(jsi::Runtime &) {
+  return static_cast<int>(raptorq_abi_version());
+}
+
+int NativeRaptorQModule::newContext(jsi::Runtime &, double transferLength,
+                                    double maxPayloadSize) {
+  raptorq_clear_last_error();
+  if (!(transferLength >= 0 && transferLength <= 0x1p53) ||
+      std::trunc(transferLength) != transferLength ||
+      !(maxPayloadSize >= 0 && maxPayloadSize <= UINT16_MAX) ||
+      std::trunc(maxPayloadSize) != maxPayloadSize) {
+    return 0;
+  }
+  return adopt(raptorq_ctx_new(static_cast<uint64_t>(transferLength),
+                               static_cast<uint16_t>(maxPayloadSize)));
+}
+
+int NativeRaptorQModule::fromOti(jsi::Runtime &rt, jsi::Object oti) {
+  raptorq_clear_last_error();
+  auto [data, len] = bytesOf(rt, oti);
+  return len == 12 ? adopt(raptorq_ctx_from_oti(data)) : 0;
+}
+
+int NativeRaptorQModule::fromHandshake(jsi::Runtime &rt, jsi::Object frame) {
+  raptorq_clear_last_error();
+  auto [data, len] = bytesOf(rt, frame);
+  return adopt(raptorq_ctx_from_handshake(data, len));
+}
+
+int NativeRaptorQModule::push(jsi::Runtime &rt, int ctx, jsi::Object frame) {
+  raptorq_clear_last_error();
+  auto [data, len] = bytesOf(rt, frame);
+  return raptorq_ctx_push_frame_status(contextOf(ctx), data, len);
+}
+
+bool NativeRaptorQModule::isComplete(jsi::Runtime &, int ctx) {
+  return raptorq_ctx_is_complete(contextOf(ctx));
+}
+
+int NativeRaptorQModule::totalFrames(jsi::Runtime &, int ctx) {
+  return static_cast<int>(raptorq_ctx_total_frames(contextOf(ctx)));
+}
+
+double NativeRaptorQModule::progress(jsi::Runtime &, int ctx) {
+  RQContext *context = contextOf(ctx);
+  uint64_t received = 0;
+  uint64_t needed = 0;
+  for (uint32_t sbn = 0; sbn < raptorq_ctx_block_count(context); ++sbn) {
+    RQBlockTiming block = raptorq_ctx_block_timing(context, sbn);
+    received += std::min(block.symbols_received, block.symbols);
+    needed += block.symbols;
+  }
+  return needed > 0 ? static_cast<double>(received) / static_cast<double>(needed) : 0;
+}
+
+std::optional<jsi::Object> NativeRaptorQModule::takeResult(jsi::Runtime &rt, int ctx) {
+  RQBuffer buffer = raptorq_ctx_take_result_buffer(contextOf(ctx));
+  if (buffer.ptr == nullptr) {
+    return std::nullopt;
+  }
+  return jsi::ArrayBuffer(rt, std::make_shared<ResultBuffer>(buffer));
+}
+
+void NativeRaptorQModule::cancel(jsi::Runtime &, int ctx) {
+  auto it = decoders_.find(ctx);
+  if (it != decoders_.end()) {
+    raptorq_cancel(it->second.cancel);
+  }
+}
+
+void NativeRaptorQModule::free(jsi::Runtime &, int ctx) {
+  auto it = decoders_.find(ctx);
+  if (it != decoders_.end()) {
+    raptorq_cancel_token_free(it->second.cancel);
+    raptorq_ctx_free(it->second.ctx);
+    decoders_.erase(it);
+  }
+}
+
+std::string NativeRaptorQModule::statusMessage(jsi::Runtime &, int status) {
+  return raptorq_status_message(static_cast<uint32_t>(status));
+}
+
+std::optional<std::string> NativeRaptorQModule::lastError(jsi::Runtime &) {
+  const char *error = raptorq_last_error();
+  if (error == nullptr) {
+    return std::nullopt;
+  }
+  return std::string(error);
+}
+
+} // namespace facebook::react
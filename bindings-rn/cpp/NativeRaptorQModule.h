@@ -0,0 +1,66 @@
+//
+//  NativeRaptorQModule.h
+//  C++ TurboModule over the decoding half of the C API, shared by iOS and
+//  Android
+//
+//  Register it where the app provides C++ TurboModules, e.g.
+//
+//      if (name == NativeRaptorQModule::kModuleName) {
+//          return std::make_shared<NativeRaptorQModule>(jsInvoker);
+//      }
+//
+
+#pragma once
+
+#include <RNRaptorQSpecJSI.h>
+
+#include <memory>
+#include <optional>
+#include <string>
+#include <unordered_map>
+
+extern "C" {
+#include "raptorq_ios.h"
+}
+
+namespace facebook::react {
+
+/// Contexts live in the module and cross into JavaScript as integer
+/// handles, so a stale or made-up handle reaches the C API as `NULL` rather
+/// than as a wild pointer.  Contexts still open when the module goes away
+/// (a bundle reload) are freed with it.
+class NativeRaptorQModule : public NativeRaptorQCxxSpec<NativeRaptorQModule> {
+ public:
+  explicit NativeRaptorQModule(std::shared_ptr<CallInvoker> jsInvoker);
+  ~NativeRaptorQModule() override;
+
+  int abiVersion(jsi::Runtime &rt);
+  int newContext(jsi::Runtime &rt, double transferLength, double maxPayloadSize);
+  int fromOti(jsi::Runtime &rt, jsi::Object oti);
+  int fromHandshake(jsi::Runtime &rt, jsi::Object frame);
+  int push(jsi::Runtime &rt, int ctx, jsi::Object frame);
+  bool isComplete(jsi::Runtime &rt, int ctx);
+  int totalFrames(jsi::Runtime &rt, int ctx);
+  double progress(jsi::Runtime &rt, int ctx);
+  std::optional<jsi::Object> takeResult(jsi::Runtime &rt, int ctx);
+  void cancel(jsi::Runtime &rt, int ctx);
+  void free(jsi::Runtime &rt, int ctx);
+  std::string statusMessage(jsi::Runtime &rt, int status);
+  std::optional<std::string> lastError(jsi::Runtime &rt);
+
+ private:
+  struct Decoder {
+    RQContext *ctx;
+    const RQCancelToken *cancel;
+  };
+
+  /// A handle for `ctx`, or `0` for `NULL`.
+  int adopt(RQContext *ctx);
+  /// The context behind `handle`, or `NULL`.
+  RQContext *contextOf(int handle) const;
+
+  std::unordered_map<int, Decoder> decoders_;
+  int nextHandle_ = 1;
+};
+
+} // namespace facebook::react
@@ -0,0 +1,31 @@
+//
+//  NativeRaptorQ.ts
+//  Codegen spec of the `NativeRaptorQ` TurboModule, implemented in C++ by
+//  `cpp/NativeRaptorQModule.cpp`
+//
+
+import type { TurboModule } from 'react-native';
+import { TurboModuleRegistry } from 'react-native';
+
+/**
+ * The decoding half of the C API.  Contexts are integer handles owned by the
+ * module (`0` where the C API returns `NULL`); frames are `ArrayBuffer`s or
+ * typed arrays over one, and results come back as `ArrayBuffer`s.
+ */
+export interface Spec extends TurboModule {
+  abiVersion(): number;
+  newContext(transferLength: number, maxPayloadSize: number): number;
+  fromOti(oti: Object): number;
+  fromHandshake(frame: Object): number;
+  push(ctx: number, frame: Object): number;
+  isComplete(ctx: number): boolean;
+  totalFrames(ctx: number): number;
+  progress(ctx: number): number;
+  takeResult(ctx: number): Object | null;
+  cancel(ctx: number): void;
+  free(ctx: number): void;
+  statusMessage(status: number): string;
+  lastError(): string | null;
+}
+
+export default TurboModuleRegistry.getEnforcing<Spec>('NativeRaptorQ');
@@ -0,0 +1,144 @@
+//
+//  index.ts
+//  Typed TypeScript API over the `NativeRaptorQ` TurboModule, mirroring the
+//  Swift and Kotlin `RaptorQDecoder`
+//
+
+import NativeRaptorQ from './NativeRaptorQ';
+
+/** The `RQStatus` values the wrapper tells apart. */
+export const RQStatus = {
+  OK: 0,
+  COMPLETE: 1,
+  PADDING_STRIPPED: 2,
+  IGNORED: 3,
+  INVALID_ARGUMENT: 10,
+} as const;
+
+/** Frame bytes: an `ArrayBuffer`, or a typed array or `DataView` over one. */
+export type Bytes = ArrayBuffer | ArrayBufferView;
+
+/**
+ * A status the library reported as a failure, with its description and the
+ * detail from `raptorq_last_error`, if any.
+ */
+export class RaptorQError extends Error {
+  readonly status: number;
+  readonly reason: string | null;
+
+  constructor(status: number, reason: string | null = NativeRaptorQ.lastError()) {
+    const message = NativeRaptorQ.statusMessage(status);
+    super(reason === null ? message : `${message}: ${reason}`);
+    this.name = 'RaptorQError';
+    this.status = status;
+    this.reason = reason;
+  }
+}
+
+/** What happened to a pushed frame. */
+export type PushResult =
+  /** The frame was accepted; more are needed. */
+  | { kind: 'accepted' }
+  /** The frame finished decoding; call `takeResult()`. */
+  | { kind: 'complete' }
+  /**
+   * The frame was not part of this transfer (a repeated handshake, or
+   * anything pushed after completion) and was skipped.
+   */
+  | { kind: 'ignored' }
+  /** The frame was rejected, or decoding failed. */
+  | { kind: 'failed'; error: RaptorQError };
+
+function adopt(ctx: number): number {
+  if (ctx === 0) {
+    throw new RaptorQError(RQStatus.INVALID_ARGUMENT);
+  }
+  return ctx;
+}
+
+/**
+ * Owns one decoding context; `close()` frees it.  Every call runs
+ * synchronously on the JavaScript thread, including the final solve in the
+ * push that completes the transfer.
+ */
+export class RaptorQDecoder {
+  private ctx: number;
+
+  private constructor(ctx: number) {
+    this.ctx = ctx;
+  }
+
+  /**
+   * Create a decoder when you already know the total length and the max
+   * payload of each QR frame.
+   */
+  static create(totalBytes: number, maxPayload: number): RaptorQDecoder {
+    return new RaptorQDecoder(adopt(NativeRaptorQ.newContext(totalBytes, maxPayload)));
+  }
+
+  /** Create a decoder from the 12-byte OTI header embedded in the first QR. */
+  static fromOti(oti: Bytes): RaptorQDecoder {
+    return new RaptorQDecoder(adopt(NativeRaptorQ.fromOti(oti)));
+  }
+
+  /**
+   * Create a decoder from a handshake frame; the recovered object is
+   * checked against the digest it carries.
+   */
+  static fromHandshake(frame: Bytes): RaptorQDecoder {
+    return new RaptorQDecoder(adopt(NativeRaptorQ.fromHandshake(frame)));
+  }
+
+  /** Push one QR-frame payload. */
+  push(frame: Bytes): PushResult {
+    const status = NativeRaptorQ.push(this.ctx, frame);
+    switch (status) {
+      case RQStatus.OK:
+      case RQStatus.PADDING_STRIPPED:
+        return { kind: 'accepted' };
+      case RQStatus.COMPLETE:
+        return { kind: 'complete' };
+      case RQStatus.IGNORED:
+        return { kind: 'ignored' };
+      default:
+        return { kind: 'failed', error: new RaptorQError(status) };
+    }
+  }
+
+  /** `true` once enough packets have been received to reconstruct the object. */
+  get isComplete(): boolean {
+    return NativeRaptorQ.isComplete(this.ctx);
+  }
+
+  /**
+   * Distinct symbols received over the source symbols needed, from 0 to 1,
+   * summed over the blocks; duplicates and each block's overhead do not
+   * count.
+   */
+  get progress(): number {
+    if (this.isComplete) {
+      return 1;
+    }
+    return NativeRaptorQ.progress(this.ctx);
+  }
+
+  /**
+   * Move the reconstructed object out of the library, without a copy.
+   * Call **once**; subsequent calls return `null`.
+   */
+  takeResult(): ArrayBuffer | null {
+    return NativeRaptorQ.takeResult(this.ctx) as ArrayBuffer | null;
+  }
+
+  /** Turn away every later push with `RQ_STATUS_CANCELLED`. */
+  cancel(): void {
+    NativeRaptorQ.cancel(this.ctx);
+  }
+
+  close(): void {
+    if (this.ctx !== 0) {
+      NativeRaptorQ.free(this.ctx);
+      this.ctx = 0;
+    }
+  }
+}
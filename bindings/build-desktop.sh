@@ -0,0 +1,73 @@
+#!/bin/bash
+
+# Shared libraries for desktop test harnesses, built from the same sources
+# and with the same features as the mobile libraries.  Takes the Rust
+# targets to build for, the host by default; Windows targets need their
+# linker (MSVC, or MinGW for the -gnu targets).
+
+set -e
+
+# Configuration
+lib_name="raptorq"
+output_dir="./desktop"
+release_dir="../target" # the workspace target directory
+
+# Terminal colors
+RED='\033[1;31m'
+GREEN='\033[0;32m'
+BLUE='\033[1;34m'
+NC='\033[0m' # No Color
+
+log() {
+    local level=$1
+    shift
+    case $level in
+        "ERROR")
+            echo -e "${RED}[ERROR]${NC} $@" >&2
+            ;;
+        "SUCCESS")
+            echo -e "${GREEN}[SUCCESS]${NC} $@"
+            ;;
+        *)
+            echo -e "${BLUE}[INFO]${NC} $@"
+            ;;
+    esac
+}
+
+targets=("$@")
+if [[ ${#targets[@]} -eq 0 ]]; then
+    targets=("$(rustc -vV | sed -n 's/^host: //p')")
+fi
+
+rm -rf $output_dir
+
+for target in "${targets[@]}"; do
+    log "INFO" "Building for $target"
+    cargo build --release --target $target || {
+        log "ERROR" "Failed to build for $target"
+        exit 1
+    }
+
+    # The shared library, plus the import library on Windows
+    case $target in
+        *"-windows-"*)
+            libs=("${lib_name}.dll" "${lib_name}.dll.lib" "lib${lib_name}.dll.a")
+            ;;
+        *"-apple-"*)
+            libs=("lib${lib_name}.dylib")
+            ;;
+        *)
+            libs=("lib${lib_name}.so")
+            ;;
+    esac
+
+    target_dir="$output_dir/$target"
+    mkdir -p "$target_dir"
+    for lib in "${libs[@]}"; do
+        if [[ -f "$release_dir/$target/release/$lib" ]]; then
+            cp "$release_dir/$target/release/$lib" "$target_dir/"
+        fi
+    done
+    cp "./generated/raptorq/raptorq_ios.h" "$target_dir/"
+    log "SUCCESS" "Library for $target in $target_dir"
+done
@@ -1,8 +1,239 @@
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
 use std::path::Path;
 
 extern crate cbindgen;
-use cbindgen::generate;
+use cbindgen::{Builder, Config};
+
+use quote::ToTokens;
+use syn::visit_mut::{self, VisitMut};
+
+/// Replaces the leading `raptorq_` of every exported name with the
+/// `prefixed` feature, unless `RAPTORQ_SYMBOL_PREFIX` says otherwise.
+const DEFAULT_PREFIX: &str = "novarq_";
+
+/// The header, regenerated on every build so that it always matches the
+/// exported functions and types.  `build.sh` copies it to the checked-in
+/// `include/`; builds leave the source tree alone.
+const HEADER: &str = "generated/raptorq/raptorq_ios.h";
+
+/// The same API for Kotlin/Native cinterop, which wants fixed-width types:
+/// every enum is a named `enum` plus a `uint32_t` typedef and every `bool` a
+/// `uint8_t`.
+const CINTEROP_HEADER: &str = "generated/raptorq/raptorq_cinterop.h";
 
 fn main() {
-    generate(Path::new(".")).unwrap().write_to_file(Path::new("./generated/raptorq/raptorq.h"));
-}
\ No newline at end of file
+    println!("cargo::rerun-if-changed=src");
+    println!("cargo::rerun-if-changed=cbindgen.toml");
+    let krate = load_crate();
+    let config = Config::from_file("cbindgen.toml").unwrap();
+    fs::create_dir_all("generated/raptorq").unwrap();
+    write_header(&krate, &config, HEADER);
+    write_header(
+        &fixed_width(&krate),
+        &cinterop_config(config),
+        CINTEROP_HEADER,
+    );
+    #[cfg(feature = "uniffi")]
+    uniffi::generate_scaffolding("src/raptorq.udl").unwrap();
+
+    println!("cargo::rustc-check-cfg=cfg(export_unprefixed)");
+    // `std::time::Instant` panics on `wasm32-unknown-unknown`.
+    println!("cargo::rustc-check-cfg=cfg(no_clock)");
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    if arch == "wasm32" && env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("unknown") {
+        println!("cargo::rustc-cfg=no_clock");
+    }
+    let prefixed = env::var_os("CARGO_FEATURE_PREFIXED").is_some();
+    if env::var_os("CARGO_FEATURE_UNPREFIXED").is_some() || !prefixed {
+        println!("cargo::rustc-cfg=export_unprefixed");
+    }
+    if prefixed {
+        println!("cargo::rerun-if-env-changed=RAPTORQ_SYMBOL_PREFIX");
+        let prefix = env::var("RAPTORQ_SYMBOL_PREFIX").unwrap_or_else(|_| DEFAULT_PREFIX.into());
+        let (wrappers, defines) = prefixed_exports(&krate, &prefix);
+        let out_dir = env::var("OUT_DIR").unwrap();
+        fs::write(Path::new(&out_dir).join("prefixed.rs"), wrappers).unwrap();
+        fs::write("generated/raptorq/raptorq_prefix.h", defines).unwrap();
+    }
+}
+
+/// The whole crate as one file, each `mod name;` replaced by the contents
+/// of `src/name.rs`.
+fn load_crate() -> syn::File {
+    let mut krate = parse(Path::new("src/lib.rs"));
+    for item in &mut krate.items {
+        if let syn::Item::Mod(module) = item {
+            if module.content.is_none() {
+                let path = Path::new("src").join(format!("{}.rs", module.ident));
+                module.content = Some((Default::default(), parse(&path).items));
+                module.semi = None;
+            }
+        }
+    }
+    krate
+}
+
+/// Run cbindgen over the crate.  It reads a copy in which the exports carry
+/// a plain `#[no_mangle]`, the only form it recognizes, and parses that copy
+/// as a lone file so the `raptorq` dependency cannot be mistaken for this
+/// crate.  The output does not depend on the features being built, so the
+/// checked-in copy in `include/` only changes along with the API.
+fn write_header(krate: &syn::File, config: &Config, header: &str) {
+    let mut krate = krate.clone();
+    for_each_export(&mut krate.items, &mut |function| {
+        for attr in &mut function.attrs {
+            if attr.path.is_ident("cfg_attr") {
+                *attr = syn::parse_quote!(#[no_mangle]);
+            }
+        }
+    });
+    let name = Path::new(header).file_stem().unwrap().to_str().unwrap();
+    let source = Path::new(&env::var("OUT_DIR").unwrap()).join(format!("{}.rs", name));
+    fs::write(&source, krate.to_token_stream().to_string()).unwrap();
+
+    Builder::new()
+        .with_config(config.clone())
+        .with_src(source)
+        .generate()
+        .unwrap()
+        .write_to_file(header);
+}
+
+/// The crate with `#[repr(u32)]` enums and `u8` for `bool`, which have the
+/// same ABI on every target the library ships for.
+fn fixed_width(krate: &syn::File) -> syn::File {
+    struct FixedWidth;
+
+    impl VisitMut for FixedWidth {
+        fn visit_item_enum_mut(&mut self, item: &mut syn::ItemEnum) {
+            for attr in &mut item.attrs {
+                if attr.path.is_ident("repr") {
+                    *attr = syn::parse_quote!(#[repr(u32)]);
+                }
+            }
+            visit_mut::visit_item_enum_mut(self, item);
+        }
+
+        fn visit_type_mut(&mut self, ty: &mut syn::Type) {
+            if let syn::Type::Path(path) = ty {
+                if path.qself.is_none() && path.path.is_ident("bool") {
+                    *ty = syn::parse_quote!(u8);
+                    return;
+                }
+            }
+            visit_mut::visit_type_mut(self, ty);
+        }
+    }
+
+    let mut krate = krate.clone();
+    FixedWidth.visit_file_mut(&mut krate);
+    krate
+}
+
+fn cinterop_config(mut config: Config) -> Config {
+    config.include_guard = Some("__RAPTORQ_CINTEROP_H__".into());
+    config.no_includes = true;
+    config.sys_includes = vec!["stdint.h".into()];
+    config
+}
+
+/// Forwarders exporting every `raptorq_*` function under `prefix`, and the
+/// `#define`s mapping the header's names onto them.
+fn prefixed_exports(krate: &syn::File, prefix: &str) -> (String, String) {
+    let mut wrappers = String::new();
+    let mut defines = String::from(
+        "/* THIS FILE WAS AUTOMATICALLY GENERATED. DO NOT EDIT.*/\n\
+         /* Include before raptorq_ios.h to call the prefixed exports. */\n\n",
+    );
+    let mut modules = vec![(String::new(), Vec::new(), &krate.items)];
+    for item in &krate.items {
+        if let syn::Item::Mod(syn::ItemMod {
+            ident,
+            attrs,
+            content: Some((_, items)),
+            ..
+        }) = item
+        {
+            modules.push((ident.to_string(), cfgs(attrs), items));
+        }
+    }
+    for (module, module_cfgs, items) in modules {
+        for item in items {
+            let function = match item {
+                syn::Item::Fn(function) if is_export(function) => function,
+                _ => continue,
+            };
+            let name = function.sig.ident.to_string();
+            let exported = format!("{}{}", prefix, &name["raptorq_".len()..]);
+            let mut sig = function.sig.clone();
+            let mut args = Vec::new();
+            for (i, input) in sig.inputs.iter_mut().enumerate() {
+                if let syn::FnArg::Typed(arg) = input {
+                    let arg_name = format!("arg{}", i);
+                    *arg.pat = syn::parse_str(&arg_name).unwrap();
+                    args.push(arg_name);
+                }
+            }
+            for cfg in module_cfgs.iter().chain(&cfgs(&function.attrs)) {
+                writeln!(wrappers, "{}", cfg).unwrap();
+            }
+            let path = if module.is_empty() {
+                "crate".into()
+            } else {
+                format!("crate::{}", module)
+            };
+            writeln!(
+                wrappers,
+                "#[export_name = \"{}\"]\npub {} {{\n    {}::{}({})\n}}\n",
+                exported,
+                sig.to_token_stream(),
+                path,
+                name,
+                args.join(", ")
+            )
+            .unwrap();
+            writeln!(defines, "#define {} {}", name, exported).unwrap();
+        }
+    }
+    (wrappers, defines)
+}
+
+fn parse(path: &Path) -> syn::File {
+    syn::parse_file(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// Whether `function` is part of the C API: a `raptorq_*` function with
+/// `no_mangle`, possibly behind `cfg_attr`.
+fn is_export(function: &syn::ItemFn) -> bool {
+    function.sig.ident.to_string().starts_with("raptorq_")
+        && function.sig.abi.is_some()
+        && function
+            .attrs
+            .iter()
+            .any(|attr| attr.to_token_stream().to_string().contains("no_mangle"))
+}
+
+/// Call `f` on every export in `items` and the modules among them.
+fn for_each_export(items: &mut [syn::Item], f: &mut impl FnMut(&mut syn::ItemFn)) {
+    for item in items {
+        match item {
+            syn::Item::Fn(function) if is_export(function) => f(function),
+            syn::Item::Mod(syn::ItemMod {
+                content: Some((_, items)),
+                ..
+            }) => for_each_export(items, f),
+            _ => {}
+        }
+    }
+}
+
+/// The `#[cfg(...)]` attributes among `attrs`, as source text.
+fn cfgs(attrs: &[syn::Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("cfg"))
+        .map(|attr| attr.to_token_stream().to_string())
+        .collect()
+}
@@ -5,11 +5,13 @@ set -e
 # Configuration
 lib_name="raptorq"
 output_dir="./xcframework"
-release_dir="./target"
+dylib_dir="./macos" # universal dylib and header for desktop apps
+release_dir="../target" # the workspace target directory
 temp_dir="./temp"
 bundle_id="com.yourcompany.${lib_name}"
 min_macos_version="10.15"
 min_ios_version="14.0"
+min_catalyst_version="14.0"
 
 # Get version from git or fallback to default
 version=$(git describe --tags 2>/dev/null || echo "1.0.0")
@@ -42,7 +44,7 @@ log() {
 }
 
 # Check prerequisites
-for cmd in cargo xcodebuild; do
+for cmd in cargo xcodebuild lipo install_name_tool; do
     if ! command -v $cmd &> /dev/null; then
         log "ERROR" "$cmd command not found. Please install it first."
         exit 1
@@ -51,67 +53,80 @@ done
 
 # Cleanup
 log "INFO" "Cleaning previous builds"
-rm -rf $output_dir $temp_dir
-mkdir -p $output_dir $temp_dir
+rm -rf $output_dir $dylib_dir $temp_dir
+mkdir -p $output_dir $dylib_dir $temp_dir
 
 log "INFO" "Building .a libraries"
 
-# All target architectures
-targets=(
-    "aarch64-apple-darwin"     # Apple Silicon Mac
-    "aarch64-apple-ios"        # iOS devices
-    "aarch64-apple-ios-sim"    # iOS Simulator (ARM)
+# One framework per platform; the targets of a slice are merged with lipo
+slices=(
+    "macos:aarch64-apple-darwin,x86_64-apple-darwin"            # Apple Silicon and Intel Macs
+    "ios:aarch64-apple-ios"                                     # iOS devices
+    "ios-sim:aarch64-apple-ios-sim"                             # iOS Simulator (ARM)
+    "catalyst:aarch64-apple-ios-macabi,x86_64-apple-ios-macabi" # Mac Catalyst
 )
 
 # Determine number of CPU cores for parallel builds
 cores=$(sysctl -n hw.ncpu 2>/dev/null || nproc 2>/dev/null || echo 2)
 
-for target in "${targets[@]}"; do
-    log "INFO" "Building for $target"
-    cargo build --release --target $target -j$cores || {
-        log "ERROR" "Failed to build for $target"
-        exit 1
-    }
-    
+for slice in "${slices[@]}"; do
+    name="${slice%%:*}"
+    IFS=',' read -ra slice_targets <<< "${slice#*:}"
+    libs=()
+    for target in "${slice_targets[@]}"; do
+        log "INFO" "Building for $target"
+        cargo build --release --target $target -j$cores || {
+            log "ERROR" "Failed to build for $target"
+            exit 1
+        }
+        libs+=("$release_dir/$target/release/lib${lib_name}.a")
+    done
+
     # Create temporary framework structure
-    framework_dir="$temp_dir/$target/$lib_name.framework"
+    framework_dir="$temp_dir/$name/$lib_name.framework"
     mkdir -p "$framework_dir/Headers" "$framework_dir/Modules"
-    
-    # Copy library
-    cp "$release_dir/$target/release/lib${lib_name}.a" "$framework_dir/$lib_name" || {
-        log "ERROR" "Failed to copy library for $target"
+
+    # Merge the slice's libraries
+    lipo -create "${libs[@]}" -output "$framework_dir/$lib_name" || {
+        log "ERROR" "Failed to create library for $name"
         exit 1
     }
 
-    header_name="raptorq"
+    header_name="raptorq_ios"
     
-    # Copy headers
-    if [[ ! -f "./generated/${header_name}/${header_name}.h" ]]; then
+    # Copy headers, refreshing the checked-in copies from the build's
+    if [[ ! -f "./generated/raptorq/${header_name}.h" ]]; then
         log "ERROR" "Header files not found. Make sure they have been generated."
         exit 1
     fi
+    cp ./generated/raptorq/raptorq_ios.h ./generated/raptorq/raptorq_cinterop.h ./include/
     
-    cp "./generated/${header_name}/${header_name}.h" "$framework_dir/Headers/"
+    cp "./include/${header_name}.h" "$framework_dir/Headers/"
     
     # Determine platform-specific settings
-    case $target in
-        *"-apple-darwin")
+    case $name in
+        "macos")
             platform="MacOSX"
             sdk="macosx"
             min_os_version="$min_macos_version"
             ;;
-        *"-apple-ios-sim")
+        "ios-sim")
             platform="iPhoneSimulator"
             sdk="iphonesimulator"
             min_os_version="$min_ios_version"
             ;;
-        *"-apple-ios")
+        "ios")
             platform="iPhoneOS"
             sdk="iphoneos"
             min_os_version="$min_ios_version"
             ;;
+        "catalyst")
+            platform="MacOSX"
+            sdk="macosx"
+            min_os_version="$min_catalyst_version"
+            ;;
         *)
-            log "ERROR" "Unsupported target: $target"
+            log "ERROR" "Unsupported slice: $name"
             continue
             ;;
     esac
@@ -155,19 +170,20 @@ framework module ${lib_name} {
     export *
 }
 EOF
-    log "INFO" "Created framework for $target"
+    log "INFO" "Created framework for $name"
 done
 
 # Collect successful builds
 log "INFO" "Creating XCFramework"
 xcframework_args=()
-for target in "${targets[@]}"; do
-    if [[ -d "$temp_dir/$target/$lib_name.framework" ]]; then
+for slice in "${slices[@]}"; do
+    name="${slice%%:*}"
+    if [[ -d "$temp_dir/$name/$lib_name.framework" ]]; then
         xcframework_args+=(
-            -framework "$temp_dir/$target/$lib_name.framework"
+            -framework "$temp_dir/$name/$lib_name.framework"
         )
     else
-        log "WARN" "Skipping missing framework for $target"
+        log "WARN" "Skipping missing framework for $name"
     fi
 done
 
@@ -184,6 +200,20 @@ xcodebuild -create-xcframework "${xcframework_args[@]}" -output "$output_dir/${l
 
 log "SUCCESS" "XCFramework created at $output_dir/${lib_name}.xcframework"
 
+# Universal dylib for desktop apps loading the library at run time
+log "INFO" "Creating macOS dylib"
+lipo -create \
+    "$release_dir/aarch64-apple-darwin/release/lib${lib_name}.dylib" \
+    "$release_dir/x86_64-apple-darwin/release/lib${lib_name}.dylib" \
+    -output "$dylib_dir/lib${lib_name}.dylib" || {
+    log "ERROR" "Failed to create macOS dylib"
+    exit 1
+}
+install_name_tool -id "@rpath/lib${lib_name}.dylib" "$dylib_dir/lib${lib_name}.dylib"
+cp "./include/raptorq_ios.h" "$dylib_dir/"
+
+log "SUCCESS" "macOS dylib created at $dylib_dir/lib${lib_name}.dylib"
+
 # Clean up temporary files
 log "INFO" "Cleaning up"
 rm -rf $temp_dir 
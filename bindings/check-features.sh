@@ -0,0 +1,95 @@
+#!/bin/bash
+
+# Lints and tests the feature combinations the mobile builds do not cover,
+# for CI.  Without `std` the crate only builds as an rlib (the static and
+# shared libraries need a panic handler and an allocator), so those
+# configurations are linted through `cargo rustc --crate-type rlib` with
+# clippy as the compiler wrapper, and only the library's unit tests run.
+# Last, the checked-in headers are compared with the regenerated ones.
+
+set -e
+
+# Terminal colors
+RED='\033[1;31m'
+GREEN='\033[0;32m'
+BLUE='\033[1;34m'
+NC='\033[0m' # No Color
+
+log() {
+    local level=$1
+    shift
+    case $level in
+        "ERROR")
+            echo -e "${RED}[ERROR]${NC} $@" >&2
+            ;;
+        "SUCCESS")
+            echo -e "${GREEN}[SUCCESS]${NC} $@"
+            ;;
+        *)
+            echo -e "${BLUE}[INFO]${NC} $@"
+            ;;
+    esac
+}
+
+# Feature lists passed with --no-default-features
+with_std=(
+    "std"
+    "std,encoder"
+    "std,decoder"
+    "std,decoder,faults"
+    "std,decoder,strict"
+    "std,encoder,decoder,strict"
+)
+without_std=(
+    ""
+    "encoder"
+    "decoder"
+    "encoder,decoder"
+    "decoder,strict"
+)
+
+for features in "${with_std[@]}"; do
+    log "INFO" "Checking [$features]"
+    cargo clippy --all-targets --no-default-features --features "$features" -- -D warnings || {
+        log "ERROR" "clippy failed for [$features]"
+        exit 1
+    }
+    cargo test --no-default-features --features "$features" || {
+        log "ERROR" "Tests failed for [$features]"
+        exit 1
+    }
+done
+
+# The fault switches are process-wide, so their test runs on its own.
+log "INFO" "Checking [faults]"
+cargo clippy --all-targets --features faults -- -D warnings || {
+    log "ERROR" "clippy failed for [faults]"
+    exit 1
+}
+cargo test --lib --features faults faults:: || {
+    log "ERROR" "Tests failed for [faults]"
+    exit 1
+}
+
+for features in "${without_std[@]}"; do
+    log "INFO" "Checking [$features] without std"
+    RUSTC_WORKSPACE_WRAPPER=clippy-driver cargo rustc --lib --crate-type rlib \
+        --no-default-features --features "$features" -- -D warnings || {
+        log "ERROR" "clippy failed for [$features]"
+        exit 1
+    }
+    cargo test --lib --no-default-features --features "$features" || {
+        log "ERROR" "Tests failed for [$features]"
+        exit 1
+    }
+done
+
+# Every build regenerates the headers; the checked-in copies must match.
+for header in raptorq_ios.h raptorq_cinterop.h; do
+    diff -u "./include/$header" "./generated/raptorq/$header" || {
+        log "ERROR" "include/$header is stale: run ./build.sh or copy it from generated/raptorq"
+        exit 1
+    }
+done
+
+log "SUCCESS" "All feature combinations pass"
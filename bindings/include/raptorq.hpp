@@ -0,0 +1,187 @@
+/*
+ * raptorq.hpp
+ * Header-only C++ wrapper over raptorq_ios.h for camera pipelines written in
+ * C++: move-only owners that release contexts, buffers and cancel tokens in
+ * their destructors, and statuses returned as values rather than thrown.
+ * Requires C++11; nothing in it throws or allocates.
+ */
+
+#ifndef __RAPTORQ_HPP__
+#define __RAPTORQ_HPP__
+
+#include <cstddef>
+#include <cstdint>
+
+extern "C" {
+#include "raptorq_ios.h"
+}
+
+namespace raptorq {
+
+/** `true` for the statuses that report a failure rather than progress. */
+inline bool is_error(RQStatus status) noexcept {
+  switch (status) {
+    case RQ_STATUS_OK:
+    case RQ_STATUS_COMPLETE:
+    case RQ_STATUS_PADDING_STRIPPED:
+    case RQ_STATUS_IGNORED:
+    case RQ_STATUS_READY_TO_FINALIZE:
+    case RQ_STATUS_PENDING:
+      return false;
+    default:
+      return true;
+  }
+}
+
+/** `raptorq_status_message`: static, never freed. */
+inline const char *message(RQStatus status) noexcept {
+  return raptorq_status_message(status);
+}
+
+#if defined(RAPTORQ_STD)
+/** `raptorq_last_error` of the calling thread, or `nullptr`. */
+inline const char *last_error() noexcept { return raptorq_last_error(); }
+#endif
+
+/** Owns an `RQBuffer` and releases it with `raptorq_buffer_free`. */
+class Buffer {
+ public:
+  Buffer() noexcept : buf_(empty_buffer()) {}
+  explicit Buffer(RQBuffer buf) noexcept : buf_(buf) {}
+  Buffer(Buffer &&other) noexcept : buf_(other.release()) {}
+  Buffer &operator=(Buffer &&other) noexcept {
+    if (this != &other) {
+      raptorq_buffer_free(buf_);
+      buf_ = other.release();
+    }
+    return *this;
+  }
+  Buffer(const Buffer &) = delete;
+  Buffer &operator=(const Buffer &) = delete;
+  ~Buffer() { raptorq_buffer_free(buf_); }
+
+  explicit operator bool() const noexcept { return buf_.ptr != nullptr; }
+  uint8_t *data() noexcept { return buf_.ptr; }
+  const uint8_t *data() const noexcept { return buf_.ptr; }
+  size_t size() const noexcept { return static_cast<size_t>(buf_.len); }
+  const uint8_t *begin() const noexcept { return buf_.ptr; }
+  const uint8_t *end() const noexcept { return buf_.ptr + size(); }
+
+  /** Give up ownership; free the result with `raptorq_buffer_free`. */
+  RQBuffer release() noexcept {
+    RQBuffer buf = buf_;
+    buf_ = empty_buffer();
+    return buf;
+  }
+
+ private:
+  static RQBuffer empty_buffer() noexcept {
+    RQBuffer buf = {nullptr, 0, 0};
+    return buf;
+  }
+
+  RQBuffer buf_;
+};
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Owns a token from `raptorq_ctx_cancel_token`.  Unlike its context it may
+ * be used from any thread, e.g. to stop a decode when the pipeline shuts
+ * down.
+ */
+class CancelToken {
+ public:
+  CancelToken() noexcept : token_(nullptr) {}
+  explicit CancelToken(const RQCancelToken *token) noexcept : token_(token) {}
+  CancelToken(CancelToken &&other) noexcept : token_(other.token_) {
+    other.token_ = nullptr;
+  }
+  CancelToken &operator=(CancelToken &&other) noexcept {
+    if (this != &other) {
+      raptorq_cancel_token_free(token_);
+      token_ = other.token_;
+      other.token_ = nullptr;
+    }
+    return *this;
+  }
+  CancelToken(const CancelToken &) = delete;
+  CancelToken &operator=(const CancelToken &) = delete;
+  ~CancelToken() { raptorq_cancel_token_free(token_); }
+
+  explicit operator bool() const noexcept { return token_ != nullptr; }
+  void cancel() const noexcept { raptorq_cancel(token_); }
+
+ private:
+  const RQCancelToken *token_;
+};
+
+/**
+ * Owns a decoding context and frees it with `raptorq_ctx_free`.  An empty
+ * `Context` (a constructor the C API rejected, or one moved from) passes
+ * `NULL` on, so its calls fail with `RQ_STATUS_INVALID_ARGUMENT` instead of
+ * crashing; test it with `operator bool` after construction.
+ */
+class Context {
+ public:
+  Context() noexcept : ctx_(nullptr) {}
+  /** Adopt a context from any of the `raptorq_ctx_new*` functions. */
+  explicit Context(RQContext *ctx) noexcept : ctx_(ctx) {}
+  Context(Context &&other) noexcept : ctx_(other.release()) {}
+  Context &operator=(Context &&other) noexcept {
+    if (this != &other) {
+      raptorq_ctx_free(ctx_);
+      ctx_ = other.release();
+    }
+    return *this;
+  }
+  Context(const Context &) = delete;
+  Context &operator=(const Context &) = delete;
+  ~Context() { raptorq_ctx_free(ctx_); }
+
+  static Context create(uint64_t transfer_length, uint16_t max_payload_size) noexcept {
+    return Context(raptorq_ctx_new(transfer_length, max_payload_size));
+  }
+
+  /** From the 12-byte OTI at `oti`. */
+  static Context from_oti(const uint8_t *oti) noexcept {
+    return Context(raptorq_ctx_from_oti(oti));
+  }
+
+  static Context from_handshake(const uint8_t *frame, size_t len) noexcept {
+    return Context(raptorq_ctx_from_handshake(frame, len));
+  }
+
+  explicit operator bool() const noexcept { return ctx_ != nullptr; }
+
+  /** `raptorq_ctx_push_frame_status`; see `is_error`. */
+  RQStatus push(const uint8_t *frame, size_t len) noexcept {
+    return raptorq_ctx_push_frame_status(ctx_, frame, len);
+  }
+
+  bool is_complete() const noexcept { return raptorq_ctx_is_complete(ctx_); }
+  uint32_t total_frames() const noexcept { return raptorq_ctx_total_frames(ctx_); }
+
+  /** The recovered object, once; an empty `Buffer` before and after. */
+  Buffer take_result() noexcept { return Buffer(raptorq_ctx_take_result_buffer(ctx_)); }
+
+  CancelToken cancel_token() const noexcept {
+    return CancelToken(raptorq_ctx_cancel_token(ctx_));
+  }
+
+  RQContext *get() const noexcept { return ctx_; }
+
+  /** Give up ownership; free the result with `raptorq_ctx_free`. */
+  RQContext *release() noexcept {
+    RQContext *ctx = ctx_;
+    ctx_ = nullptr;
+    return ctx;
+  }
+
+ private:
+  RQContext *ctx_;
+};
+#endif
+
+} // namespace raptorq
+
+#endif /* __RAPTORQ_HPP__ */
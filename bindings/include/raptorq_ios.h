@@ -0,0 +1,2619 @@
+#ifndef __RAPTORQ_IOS_H__
+#define __RAPTORQ_IOS_H__
+
+/* Generated with cbindgen:0.14.3 */
+
+/* THIS FILE WAS AUTOMATICALLY GENERATED. DO NOT EDIT.*/
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+#if !defined(RAPTORQ_CUSTOM_FEATURES)
+#define RAPTORQ_STD
+#define RAPTORQ_ENCODER
+#define RAPTORQ_DECODER
+#endif
+#if defined(__unix__) || defined(__APPLE__)
+#define RAPTORQ_UNIX
+#endif
+
+/**
+ * Version of the C API, bumped whenever an existing function, type or
+ * status changes incompatibly (additions do not bump it).
+ *
+ * 2: invalid arguments report [`RQStatus::InvalidArgument`] instead of
+ * [`RQStatus::Failed`].
+ *
+ * 3: overlapping calls on a context report [`RQStatus::Busy`].
+ */
+#define RAPTORQ_ABI_VERSION 3
+
+/**
+ * [`RQErrorInfo::fields`] bit: `expected_len` and `actual_len` are set.
+ */
+#define RQ_ERROR_HAS_LENGTHS 2
+
+/**
+ * [`RQErrorInfo::fields`] bit: `offset` is set.
+ */
+#define RQ_ERROR_HAS_OFFSET 4
+
+/**
+ * [`RQErrorInfo::fields`] bit: `sbn` and `esi` name the offending symbol.
+ */
+#define RQ_ERROR_HAS_SYMBOL 1
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Whether the sender should change its QR density, from
+ * `raptorq_ctx_density_advice`.
+ */
+typedef enum RQDensityAdvice {
+#if defined(RAPTORQ_DECODER)
+  /**
+   * Keep the current codes: they read well and bring new symbols, or
+   * there are too few frames to tell yet.
+   */
+  RQ_DENSITY_ADVICE_KEEP = 0,
+#endif
+#if defined(RAPTORQ_DECODER)
+  /**
+   * Switch to smaller, denser codes (more bytes per code): the camera
+   * reads them cleanly but mostly sees codes it already has.
+   */
+  RQ_DENSITY_ADVICE_DENSER = 1,
+#endif
+#if defined(RAPTORQ_DECODER)
+  /**
+   * Switch to larger, sparser codes: the camera misreads too many.
+   */
+  RQ_DENSITY_ADVICE_SPARSER = 2,
+#endif
+} RQDensityAdvice;
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * What an [`RQEvent`] records.
+ */
+typedef enum RQEventKind {
+#if defined(RAPTORQ_DECODER)
+  /**
+   * No event: the index was out of range.
+   */
+  RQ_EVENT_KIND_NONE = 0,
+#endif
+#if defined(RAPTORQ_DECODER)
+  /**
+   * The context was set up for a transfer; `value` is its length.
+   */
+  RQ_EVENT_KIND_OTI_LEARNED = 1,
+#endif
+#if defined(RAPTORQ_DECODER)
+  /**
+   * The first frame arrived; `value` is its length.  Its outcome is
+   * logged like any other frame's, e.g. as an `Error`.
+   */
+  RQ_EVENT_KIND_FIRST_FRAME = 2,
+#endif
+#if defined(RAPTORQ_DECODER)
+  /**
+   * Solves run by one push or finalize step; `count` is the number of
+   * attempts, `value` the nanoseconds they took, `status` the outcome of
+   * the call.
+   */
+  RQ_EVENT_KIND_SOLVE = 3,
+#endif
+#if defined(RAPTORQ_DECODER)
+  /**
+   * The object was recovered; `value` is its length.
+   */
+  RQ_EVENT_KIND_COMPLETE = 4,
+#endif
+#if defined(RAPTORQ_DECODER)
+  /**
+   * A push or finalize step failed with `status`; `count` consecutive
+   * ones, the first at `at_ms`, `value` the length of the last frame.
+   */
+  RQ_EVENT_KIND_ERROR = 5,
+#endif
+} RQEventKind;
+#endif
+
+#if defined(RAPTORQ_STD)
+/**
+ * Severity of a log line; a logger registered at one level receives that
+ * level and every more severe one.
+ */
+typedef enum RQLogLevel {
+  /**
+   * Nothing is logged.
+   */
+  RQ_LOG_LEVEL_OFF = 0,
+  /**
+   * A call failed, e.g. an internal panic or an I/O error.
+   */
+  RQ_LOG_LEVEL_ERROR = 1,
+  /**
+   * The transfer cannot succeed, e.g. a digest mismatch.
+   */
+  RQ_LOG_LEVEL_WARN = 2,
+  /**
+   * Context lifecycle: creation and completion.
+   */
+  RQ_LOG_LEVEL_INFO = 3,
+  /**
+   * Rejected frames and solve attempts.
+   */
+  RQ_LOG_LEVEL_DEBUG = 4,
+  /**
+   * Every accepted frame.
+   */
+  RQ_LOG_LEVEL_TRACE = 5,
+} RQLogLevel;
+#endif
+
+#if (defined(RAPTORQ_ENCODER) && defined(RAPTORQ_DECODER))
+/**
+ * Outcome of the first failing vector, if any.
+ */
+typedef enum RQRegressionFailure {
+#if (defined(RAPTORQ_ENCODER) && defined(RAPTORQ_DECODER))
+  /**
+   * Every vector passed.
+   */
+  RQ_REGRESSION_FAILURE_NONE = 0,
+#endif
+#if (defined(RAPTORQ_ENCODER) && defined(RAPTORQ_DECODER))
+  /**
+   * The encoded packet stream differs from the recorded digest.
+   */
+  RQ_REGRESSION_FAILURE_ENCODE_MISMATCH = 1,
+#endif
+#if (defined(RAPTORQ_ENCODER) && defined(RAPTORQ_DECODER))
+  /**
+   * The decoder failed to recover the object or recovered wrong bytes.
+   */
+  RQ_REGRESSION_FAILURE_DECODE_MISMATCH = 2,
+#endif
+#if (defined(RAPTORQ_ENCODER) && defined(RAPTORQ_DECODER))
+  /**
+   * The underlying implementation panicked while running the vector.
+   */
+  RQ_REGRESSION_FAILURE_PANIC = 3,
+#endif
+} RQRegressionFailure;
+#endif
+
+/**
+ * GF(256) kernel family in use.  On x86 the binary‑row kernel is vectorized
+ * for `Avx2` only, and scalar code otherwise.
+ */
+typedef enum RQSimdBackend {
+  RQ_SIMD_BACKEND_SCALAR = 0,
+  RQ_SIMD_BACKEND_NEON = 1,
+  RQ_SIMD_BACKEND_SSSE3 = 2,
+  RQ_SIMD_BACKEND_AVX2 = 3,
+} RQSimdBackend;
+
+/**
+ * Outcome of a call into the library.
+ *
+ * Values are stable; new ones are only ever appended.  Each has a
+ * description in [`raptorq_status_message`].
+ */
+typedef enum RQStatus {
+  /**
+   * The frame was accepted; more are needed.
+   */
+  RQ_STATUS_OK = 0,
+  /**
+   * The frame finished decoding the whole object.
+   */
+  RQ_STATUS_COMPLETE = 1,
+  /**
+   * The frame was accepted after trailing zero padding was stripped.
+   */
+  RQ_STATUS_PADDING_STRIPPED = 2,
+  /**
+   * The frame was not an encoding packet for this transfer (a repeated
+   * handshake frame, or anything pushed after completion) and was skipped.
+   */
+  RQ_STATUS_IGNORED = 3,
+  /**
+   * The frame does not match the expected prefix, envelope or size.
+   */
+  RQ_STATUS_MALFORMED = 4,
+  /**
+   * The recovered object does not match the handshake digest.
+   */
+  RQ_STATUS_DIGEST_MISMATCH = 5,
+  /**
+   * An internal panic or I/O error (see `raptorq_last_error`).
+   */
+  RQ_STATUS_FAILED = 6,
+  /**
+   * A length passed in, or the object being assembled in memory, does
+   * not fit the address space of this (32‑bit) target.
+   */
+  RQ_STATUS_LENGTH_OVERFLOW = 7,
+  /**
+   * Deferred finalization: every block has enough symbols, drive the
+   * solve with `raptorq_ctx_finalize_step`.
+   */
+  RQ_STATUS_READY_TO_FINALIZE = 8,
+  /**
+   * A `raptorq_handle_*` call got a handle that was already freed or
+   * never issued.
+   */
+  RQ_STATUS_INVALID_HANDLE = 9,
+  /**
+   * A `NULL` pointer where one is required, a `NULL` buffer with a
+   * non‑zero length or a parameter out of range; `raptorq_last_error`
+   * says which.
+   */
+  RQ_STATUS_INVALID_ARGUMENT = 10,
+  /**
+   * The context is in use by a call on another thread; calls on a
+   * context not created with `raptorq_ctx_new_threadsafe` must not
+   * overlap.  Nothing was done.
+   */
+  RQ_STATUS_BUSY = 11,
+  /**
+   * `raptorq_ctx_poll`: the decode thread has not finished a frame since
+   * the previous poll.
+   */
+  RQ_STATUS_PENDING = 12,
+  /**
+   * The context was cancelled through `raptorq_cancel`; nothing more is
+   * decoded.
+   */
+  RQ_STATUS_CANCELLED = 13,
+} RQStatus;
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Opaque handle from `raptorq_ctx_cancel_token`, shared with the context
+ * it was taken from and released with [`raptorq_cancel_token_free`].
+ */
+typedef struct RQCancelToken RQCancelToken;
+#endif
+
+#if defined(RAPTORQ_DECODER)
+typedef struct RQContext RQContext;
+#endif
+
+#if defined(RAPTORQ_ENCODER)
+typedef struct RQEncoder RQEncoder;
+#endif
+
+#if defined(RAPTORQ_SIMULATOR)
+/**
+ * Opaque handle from [`raptorq_sim_new`].
+ */
+typedef struct RQLossSimulator RQLossSimulator;
+#endif
+
+#if defined(RAPTORQ_DECODER)
+typedef struct RQPool RQPool;
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Opaque handle from [`raptorq_session_new`].
+ */
+typedef struct RQScanSession RQScanSession;
+#endif
+
+/**
+ * A buffer handed out by the library together with its size.  `ptr` is
+ * `NULL` (and both sizes `0`) when there is no data.
+ */
+typedef struct RQBuffer {
+  /**
+   * First byte of the data.
+   */
+  uint8_t *ptr;
+  /**
+   * Number of bytes of data.
+   */
+  uint64_t len;
+  /**
+   * Number of bytes allocated; equal to `len` for every buffer returned
+   * today, but release through [`raptorq_buffer_free`] rather than rely
+   * on it.
+   */
+  uint64_t cap;
+} RQBuffer;
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * One source block of a transfer, from `raptorq_ctx_block_timing`: what
+ * its solve cost, to spot the block a slow transfer spent its time on.
+ */
+typedef struct RQBlockTiming {
+  /**
+   * Source symbols in the block.
+   */
+  uint32_t symbols;
+  /**
+   * Distinct symbols received for the block, source and repair; past
+   * `symbols`, the overhead the block needed.
+   */
+  uint32_t symbols_received;
+  /**
+   * Solves run on the block, failed ones included; `0` when every
+   * source symbol arrived and none was needed.
+   */
+  uint32_t solve_attempts;
+  /**
+   * Time spent in those solves.
+   */
+  uint64_t solve_ns;
+  /**
+   * The block is decoded.
+   */
+  bool decoded;
+} RQBlockTiming;
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * How economically a transfer completed, from `raptorq_ctx_efficiency`,
+ * to compare sender configurations with field data.  Counts stop at the
+ * frame that completed the object.
+ */
+typedef struct RQEfficiency {
+  /**
+   * Frames pushed up to completion, whatever their status.
+   */
+  uint64_t frames;
+  /**
+   * The fewest frames the object could have taken: its `K` source
+   * symbols over all blocks, one per frame unless frames are packed.
+   */
+  uint32_t minimum_frames;
+  /**
+   * `frames` over `minimum_frames`; `1` for a flawless scan.
+   */
+  float overhead_ratio;
+  /**
+   * Frames that brought nothing new, as in `RQStats`.
+   */
+  uint64_t duplicate_frames;
+  /**
+   * `duplicate_frames` as a percentage of `frames`.
+   */
+  float duplicate_percent;
+} RQEfficiency;
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * One entry of the event log, from `raptorq_ctx_event`.
+ */
+typedef struct RQEvent {
+  RQEventKind kind;
+  RQStatus status;
+  uint32_t count;
+  /**
+   * Wall-clock time since the context was created, pauses included;
+   * `0` without a clock.
+   */
+  uint64_t at_ms;
+  uint64_t value;
+} RQEvent;
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * The malformed frames of [`RQStats`] by what was wrong with them, from
+ * `raptorq_ctx_frame_quality`.  Truncated frames and checksum failures
+ * point at the camera misreading codes (a steadier hand, more light);
+ * frames of the wrong length at a sender emitting something this context
+ * was not set up for.
+ */
+typedef struct RQFrameQuality {
+  /**
+   * Frames shorter than a packet (or than the checksum trailer).
+   */
+  uint64_t truncated_frames;
+  /**
+   * Frames longer than a packet, other than by tolerated zero padding.
+   */
+  uint64_t wrong_length_frames;
+  /**
+   * Frames whose CRC trailer did not match, with frame checksums on.
+   */
+  uint64_t checksum_failures;
+  /**
+   * The other malformed frames: broken envelopes, prefixes or packing,
+   * and symbols outside the transfer.
+   */
+  uint64_t other_malformed_frames;
+} RQFrameQuality;
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Limits for `raptorq_ctx_is_stalled`; a field left `0` is not checked.
+ */
+typedef struct RQStallWindow {
+  /**
+   * Pushes in a row that brought no new symbol.
+   */
+  uint32_t pushes;
+  /**
+   * Milliseconds without a new symbol, counted from the context's
+   * creation until the first one arrives; time paused with
+   * `raptorq_ctx_pause` does not count.
+   */
+  uint32_t ms;
+} RQStallWindow;
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Called once when the object is recovered, with the result as
+ * `raptorq_ctx_take_result_buffer` would have returned it (the callee owns
+ * it now; a `NULL` buffer when the object was assembled in an output file)
+ * and the `user_data` given at registration.  It runs on the thread whose
+ * push or finalize step completed the transfer, or on the decode thread,
+ * while that call still holds the context: it must not call back into it.
+ */
+typedef void (*RQCompleteCallback)(RQBuffer result, void *user_data);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Called with the distinct symbols received so far (at most `needed` are
+ * counted) and the number of source symbols of the object, whenever a push
+ * adds a symbol the context did not have, and with the `user_data` given at
+ * registration.  Runs like an [`RQCompleteCallback`]: on the pushing or
+ * decode thread, and must not call back into the context.
+ */
+typedef void (*RQProgressCallback)(uint32_t received, uint32_t needed, void *user_data);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Snapshot returned by `raptorq_ctx_push_stats`.  All durations are in
+ * nanoseconds; every field is `0` before the first push.
+ */
+typedef struct RQPushStats {
+  /**
+   * Number of frames pushed so far, whatever their status.
+   */
+  uint64_t pushes;
+  uint64_t min_ns;
+  uint64_t avg_ns;
+  uint64_t max_ns;
+} RQPushStats;
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Called with each milestone registered through
+ * `raptorq_ctx_set_milestones` (a percentage of the needed symbols), once,
+ * in ascending order, when the received symbols reach it; several may be
+ * passed by one push.  Runs like an [`RQProgressCallback`].
+ */
+typedef void (*RQMilestoneCallback)(uint8_t percent, void *user_data);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Snapshot returned by `raptorq_ctx_stats`, for analytics events.  Stays
+ * available after completion and after the result was taken.  Without a
+ * clock (no `std`, or in the browser) the times stay `0`.
+ */
+typedef struct RQStats {
+  /**
+   * Frames pushed so far, whatever their status.
+   */
+  uint64_t frames;
+  /**
+   * Frames that brought at least one symbol the decoder did not have.
+   */
+  uint64_t unique_frames;
+  /**
+   * Frames with nothing new: symbols already received, and repeated
+   * handshakes or frames after completion (`RQ_STATUS_IGNORED`).
+   */
+  uint64_t duplicate_frames;
+  /**
+   * Frames rejected as `RQ_STATUS_MALFORMED`.
+   */
+  uint64_t malformed_frames;
+  /**
+   * Bytes of every frame pushed.
+   */
+  uint64_t bytes;
+  /**
+   * Solves run over all blocks, failed ones included.
+   */
+  uint32_t solve_attempts;
+  /**
+   * Time spent in those solves, added up over the blocks (blocks solved
+   * on parallel threads overlap).
+   */
+  uint64_t solve_ns;
+  /**
+   * Wall-clock time since the context was created, less the time spent
+   * paused (`raptorq_ctx_pause`).
+   */
+  uint64_t elapsed_ms;
+} RQStats;
+#endif
+
+#if defined(RAPTORQ_FAULTS)
+/**
+ * Failures for `raptorq_set_faults` to inject.  All zero injects none.
+ */
+typedef struct RQFaults {
+  /**
+   * Fail the allocations the API reports: `raptorq_alloc` and
+   * `raptorq_realloc` return `NULL`, `raptorq_ctx_preallocate` `false`.
+   */
+  bool fail_allocations;
+  /**
+   * Throw away the outcome of every solve, as if the symbols did not
+   * determine the block: transfers keep asking for frames, and complete
+   * with the next one once the switch is off again.
+   */
+  bool fail_solves;
+  /**
+   * Hold up the push or finalize step that completes an object for this
+   * long, like a slow device; `0` for none.
+   */
+  uint32_t completion_delay_ms;
+} RQFaults;
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Lifetime counts of the handle table, for spotting leaked contexts.
+ */
+typedef struct RQHandleCounters {
+  /**
+   * Contexts currently behind a handle (expired ones not yet reaped
+   * included).
+   */
+  uint64_t live;
+  uint64_t created;
+  /**
+   * Freed with [`raptorq_handle_free`].
+   */
+  uint64_t freed;
+  /**
+   * Reaped after their time‑to‑live ran out; non‑zero means the app
+   * forgot to free contexts.
+   */
+  uint64_t expired;
+} RQHandleCounters;
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * The contexts behind handles right now, summed up by
+ * `raptorq_handle_stats` for a receive queue screen.
+ */
+typedef struct RQHandleStats {
+  /**
+   * Transfers still collecting frames.
+   */
+  uint64_t active;
+  /**
+   * Transfers whose object was recovered, whether taken or not.
+   */
+  uint64_t completed;
+  /**
+   * Transfers that recovered an object not matching the handshake's
+   * digest.
+   */
+  uint64_t failed;
+  /**
+   * Length of the objects of the completed transfers, added up.
+   */
+  uint64_t bytes_recovered;
+  /**
+   * Heap memory the contexts hold, roughly: buffered symbols, decoded
+   * blocks and results.
+   */
+  uint64_t memory_bytes;
+} RQHandleStats;
+#endif
+
+/**
+ * Detail of the calling thread's last error, from
+ * `raptorq_last_error_info`.  Only the fields named in `fields` are
+ * meaningful; the others are zero.
+ */
+typedef struct RQErrorInfo {
+  /**
+   * The status the failing call reported.
+   */
+  RQStatus status;
+  /**
+   * `RQ_ERROR_HAS_*` bits.
+   */
+  uint32_t fields;
+  /**
+   * Source block number of the offending symbol.
+   */
+  uint32_t sbn;
+  /**
+   * Encoding symbol id of the offending symbol.
+   */
+  uint32_t esi;
+  /**
+   * Length the call expected, e.g. of an encoding packet.
+   */
+  uint64_t expected_len;
+  /**
+   * Length it got.
+   */
+  uint64_t actual_len;
+  /**
+   * Byte offset into the frame where parsing failed.
+   */
+  uint64_t offset;
+} RQErrorInfo;
+
+#if (defined(RAPTORQ_ENCODER) && defined(RAPTORQ_DECODER))
+/**
+ * Summary returned by `raptorq_run_regression_vectors`.
+ */
+typedef struct RQRegressionReport {
+  /**
+   * Number of embedded vectors.
+   */
+  uint32_t total;
+  /**
+   * Number of vectors that passed both the encode and the decode check.
+   */
+  uint32_t passed;
+  /**
+   * Index of the first failing vector, or `-1` when all passed.
+   */
+  int32_t first_failed_vector;
+  /**
+   * Why the first failing vector failed.
+   */
+  RQRegressionFailure first_failure;
+} RQRegressionReport;
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Snapshot returned by `raptorq_session_stats`.  Without a clock (no
+ * `std`, or in the browser) `runtime_ms` and both rates stay `0`.
+ */
+typedef struct RQScanStats {
+  /**
+   * Frames pushed through the session, whatever their status.
+   */
+  uint64_t frames;
+  /**
+   * Frames that gave the decoder a symbol it did not have.
+   */
+  uint64_t unique_frames;
+  /**
+   * Wall-clock time since the session was created.
+   */
+  uint64_t runtime_ms;
+  /**
+   * Frames pushed over the last second, or over the runtime while it is
+   * shorter.
+   */
+  float frames_per_second;
+  /**
+   * Unique frames over the same span.
+   */
+  float unique_frames_per_second;
+} RQScanStats;
+#endif
+
+#if defined(RAPTORQ_STD)
+/**
+ * Called with the level and the NUL‑terminated line (valid only for the
+ * duration of the call).  It may run on any thread that calls into the
+ * library and must not call back into it.
+ */
+typedef void (*RQLogCallback)(RQLogLevel level, const char *message, void *user_data);
+#endif
+
+#if defined(RAPTORQ_STD)
+/**
+ * Called with the NUL‑terminated panic message (valid only for the duration
+ * of the call) and the `user_data` given at registration.  It may run on any
+ * thread that calls into the library, including the decode thread, and must
+ * not call back into the library.
+ */
+typedef void (*RQPanicCallback)(const char *message, void *user_data);
+#endif
+
+#if defined(RAPTORQ_SIMULATOR)
+/**
+ * Counts returned by `raptorq_sim_counters`.
+ */
+typedef struct RQLossCounters {
+  /**
+   * Frames of the loop shown so far, over all passes.
+   */
+  uint64_t shown;
+  /**
+   * Of those, frames the camera missed.
+   */
+  uint64_t dropped;
+  /**
+   * Extra copies delivered.
+   */
+  uint64_t duplicated;
+} RQLossCounters;
+#endif
+
+#if defined(RAPTORQ_SIMULATOR)
+/**
+ * How the simulated camera mangles the loop.  All zero delivers every
+ * frame once, in order.
+ */
+typedef struct RQLossConfig {
+  /**
+   * Seed of every random choice; the same seed and settings give the
+   * same frames.
+   */
+  uint64_t seed;
+  /**
+   * Chance, per mille, that a frame starts a drop; below `1000`.
+   */
+  uint16_t drop_per_mille;
+  /**
+   * Frames lost in a row once a drop starts, like a hand passing over
+   * the camera; `0` and `1` lose single frames.
+   */
+  uint32_t burst_len;
+  /**
+   * Drop every `drop_every`th frame on top of the random drops, a fixed
+   * pattern for reproducing a report; `0` for none, never `1`.
+   */
+  uint32_t drop_every;
+  /**
+   * Chance, per mille, that a delivered frame is delivered twice, like a
+   * code held still for two camera frames; at most `1000`.
+   */
+  uint16_t duplicate_per_mille;
+  /**
+   * Shuffle the delivered frames within consecutive windows of this many
+   * frames of the loop; `0` and `1` keep the order.
+   */
+  uint32_t reorder_window;
+} RQLossConfig;
+#endif
+
+/**
+ * [`RAPTORQ_ABI_VERSION`] of the linked binary, for wrappers to compare
+ * with the value in the header they were compiled against.
+ */
+uint32_t raptorq_abi_version(void);
+
+/**
+ * Allocate a `len`‑byte, zero‑filled buffer, e.g. to fill with a frame and
+ * hand back through `raptorq_ctx_push_frame_owned`.  Returns `NULL` when
+ * `len` is `0`, does not fit the address space or the allocation fails.
+ */
+uint8_t *raptorq_alloc(uint64_t len);
+
+/**
+ * Release a buffer boxed by [`raptorq_buffer_into_box`].  `NULL` is ignored.
+ *
+ * # Safety
+ * `buf` must be `NULL` or a box from [`raptorq_buffer_into_box`] not
+ * released yet.
+ */
+void raptorq_buffer_box_free(RQBuffer *buf);
+
+/**
+ * Like [`raptorq_buffer_box_free`], zeroing the buffer first.
+ *
+ * # Safety
+ * Same as [`raptorq_buffer_box_free`].
+ */
+void raptorq_buffer_box_free_zeroize(RQBuffer *buf);
+
+/**
+ * Release a buffer returned in an [`RQBuffer`].  A `NULL` buffer is ignored.
+ *
+ * # Safety
+ * `buf` must be exactly as returned by this library and not released yet.
+ */
+void raptorq_buffer_free(RQBuffer buf);
+
+/**
+ * Like [`raptorq_buffer_free`], zeroing the buffer first.
+ *
+ * # Safety
+ * Same as [`raptorq_buffer_free`].
+ */
+void raptorq_buffer_free_zeroize(RQBuffer buf);
+
+/**
+ * Move `buf` behind a pointer, for bindings that release memory through a
+ * single pointer argument, such as a Dart `NativeFinalizer` or the
+ * finalizer of `Pointer.asTypedList`: read the bytes through the returned
+ * [`RQBuffer`] and release both with [`raptorq_buffer_box_free`].  `NULL`
+ * for a `NULL` buffer.
+ */
+RQBuffer *raptorq_buffer_into_box(RQBuffer buf);
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Abort what the token's context is doing in the background: frames still
+ * queued for its decode thread are dropped, blocks not yet written to its
+ * output file stay unwritten and a finalize stops after the block it is
+ * solving.  The context then answers every push and finalize step with
+ * [`crate::RQStatus::Cancelled`]; a result recovered before stays
+ * available.  Callable from any thread, any number of times; a `NULL`
+ * token is ignored.
+ *
+ * # Safety
+ * `token` must be `NULL` or a token not yet freed.
+ */
+void raptorq_cancel(const RQCancelToken *token);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Release a token from `raptorq_ctx_cancel_token`.  Tokens and their
+ * context may be freed in any order.  A `NULL` token is ignored.
+ *
+ * # Safety
+ * `token` must be `NULL` or a token not yet freed; it must not be used
+ * afterwards.
+ */
+void raptorq_cancel_token_free(const RQCancelToken *token);
+#endif
+
+#if defined(RAPTORQ_STD)
+/**
+ * Forget the calling thread's last error.
+ */
+void raptorq_clear_last_error(void);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Number of source blocks [`raptorq_ctx_block_timing`] reports on: the
+ * blocks of the transfer once its first symbol arrived, `0` before and for
+ * a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+uint32_t raptorq_ctx_block_count(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * The symbols source block `sbn` took and the time its solves cost, for
+ * diagnosing slow transfers once complete; callable before as well, and
+ * after the result was taken.  All zero for a block out of range and for
+ * a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQBlockTiming raptorq_ctx_block_timing(const RQContext *ctx,
+                                       uint32_t sbn);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * A token that cancels what `ctx` is doing in the background (see
+ * [`raptorq_cancel`]), e.g. when the scanner screen is dismissed, from any
+ * thread and without waiting for a call in progress.  Take it before
+ * handing the context to another thread; free it with
+ * [`raptorq_cancel_token_free`].  Returns `NULL` for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+const RQCancelToken *raptorq_ctx_cancel_token(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Deep‑copy `ctx`: a new, independent context holding the same symbols,
+ * options and (if complete) result, to free with [`raptorq_ctx_free`].  Lets
+ * a background copy attempt a finalize while the original keeps taking
+ * frames.  Returns `NULL` for a `NULL` context, while its decode thread
+ * runs, or when it assembles into a file.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQContext *raptorq_ctx_clone(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Content type announced by the handshake frame, `0` when unknown.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+uint16_t raptorq_ctx_content_type(const RQContext *ctx);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Latest status reported by the decode thread, as
+ * [`raptorq_ctx_push_frame_status`] would have returned it.  Stays
+ * [`RQStatus::Complete`] (or [`RQStatus::DigestMismatch`]) once reached;
+ * [`RQStatus::Failed`] when no decode thread runs.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQStatus raptorq_ctx_decode_thread_status(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Whether the sender should switch to denser or sparser codes, judged
+ * from the last 64 frames: [`RQDensityAdvice::Sparser`] once one in eight
+ * is misread (truncated or failing its checksum), [`RQDensityAdvice::Denser`]
+ * once half are duplicates while hardly any is misread.  For live transfers
+ * with a back channel to the sender; the window lets the advice follow a
+ * switch within a couple of seconds.  [`RQDensityAdvice::Keep`] before 16
+ * frames, once no more frames are needed, and for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQDensityAdvice raptorq_ctx_density_advice(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Resume a transfer from a [`raptorq_ctx_serialize`] snapshot: the new
+ * context holds the same symbols and options and takes frames where the old
+ * one left off.  Returns `NULL` for a blob that is not a well‑formed
+ * snapshot.
+ *
+ * # Safety
+ * `blob` must point to `len` readable bytes.
+ */
+RQContext *raptorq_ctx_deserialize(const uint8_t *blob,
+                                   uint64_t len);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * `true` once the recovered object failed the handshake digest check.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+bool raptorq_ctx_digest_mismatch(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Serialize the OTI and every symbol `ctx` received into a blob for a bug
+ * report, and write its length to `len_out` (when not `NULL`).  Free it with
+ * [`crate::raptorq_free`].  Returns `NULL` for a `NULL` context or one whose
+ * transfer is already complete.  Call it before the decode thread starts or
+ * after it stops.
+ *
+ * The dump contains the payload itself; treat it as sensitively.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; `len_out` must be `NULL` or valid
+ * for writes.
+ */
+uint8_t *raptorq_ctx_dump(const RQContext *ctx,
+                          uint64_t *len_out);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * [`raptorq_ctx_dump`] returning an [`RQBuffer`], to be released with
+ * [`crate::raptorq_buffer_free`].
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQBuffer raptorq_ctx_dump_buffer(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * How economically the transfer completed: the frames it took against
+ * the fewest it could have, and the share of them that were duplicates.
+ * For comparing sender configurations (density, frame rate, repair
+ * symbols) across releases with real scans.  Available from completion
+ * on, also after the result was taken; all zero before, after a digest
+ * mismatch, and for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQEfficiency raptorq_ctx_efficiency(const RQContext *ctx);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Queue a copy of one frame for decoding and return immediately, starting
+ * the decode thread on the first call with a queue of 32 frames sized from
+ * that frame (see [`raptorq_ctx_start_decode_thread`] for other sizes).
+ * Collect the outcome with [`raptorq_ctx_poll`].  Returns `false` when the
+ * thread could not be started, the queue is full (the frame is dropped, as
+ * the camera would) or the frame does not fit a slot.
+ *
+ * Only the first call allocates and spawns; the later ones never wait on
+ * a decode or a lock, like [`raptorq_ctx_enqueue_frame`], even while a
+ * poll or query on another thread holds the decoding state or stops the
+ * decode thread.  Once the object is
+ * complete no thread is started again and frames are refused.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context, and `payload_ptr` must point to
+ * `payload_len` readable bytes.  Enqueue from one thread at a time, e.g.
+ * the camera callback; [`raptorq_ctx_poll`] and the other calls may run on
+ * another thread meanwhile.
+ */
+bool raptorq_ctx_enqueue(RQContext *ctx,
+                         const uint8_t *payload_ptr,
+                         uint64_t payload_len);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Queue a copy of one frame for the decode thread and return immediately,
+ * never waiting on a decode or a lock: safe to call from the camera
+ * callback.
+ * Returns `false` when no decode thread runs, the queue is full (the frame
+ * is dropped, as the camera would) or the frame is longer than
+ * `max_frame_len`.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context, and `payload_ptr` must point to
+ * `payload_len` readable bytes.  Enqueue from one thread at a time; the
+ * other calls, stopping the decode thread included, may run on other
+ * threads meanwhile.
+ */
+bool raptorq_ctx_enqueue_frame(const RQContext *ctx,
+                               const uint8_t *payload_ptr,
+                               uint64_t payload_len);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Estimated time until the transfer has every symbol it needs, in
+ * milliseconds: the symbols still missing times the recent interval per new
+ * symbol, so it follows the user steadying or losing the code.  Does not
+ * include the final solve.  `0` once no more frames are needed;
+ * `UINT32_MAX` while unknown, i.e. before two frames brought new symbols,
+ * without a clock, or for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+uint32_t raptorq_ctx_eta_ms(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Event `index` of `ctx`'s log, oldest first: when the context was set up,
+ * the first frame, every push or finalize step that ran solves, completion
+ * and errors (runs of the same one folded together), with the time since
+ * creation.  The setup and first frame events stay at the front for good;
+ * once 64 events are kept the oldest of the others make way for new ones,
+ * see [`raptorq_ctx_events_dropped`].  An event of kind
+ * [`RQEventKind::None`] for an index out of range and for a `NULL`
+ * context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQEvent raptorq_ctx_event(const RQContext *ctx,
+                          uint32_t index);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Number of events `ctx` holds, at most 64; `0` for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+uint32_t raptorq_ctx_event_count(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Events dropped from `ctx`'s log to make room, from just after the setup
+ * and first frame events; the indices of [`raptorq_ctx_event`] past those
+ * two skip them.  `0` for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+uint64_t raptorq_ctx_events_dropped(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Frames a transfer normally takes, for a progress bar whose denominator
+ * does not move: the `K` source symbols of all blocks plus the standard
+ * overhead of two per block (or the decode overhead, if set higher).
+ * Known as soon as the context is, unlike [`raptorq_ctx_total_frames`]
+ * (the length of the sender's loop).  Counts symbols, one per frame
+ * unless frames are packed.  `0` for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+uint32_t raptorq_ctx_expected_frames(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Solve buffered blocks for roughly `budget_ms` milliseconds and return.
+ * Blocks are solved whole, so a slice takes at least one block's solve
+ * however small the budget.  Writes the percentage of blocks decoded to
+ * `percent_out` when not `NULL`.
+ *
+ * Returns [`RQStatus::Complete`] once the object is recovered (as from a
+ * push, including the digest check), [`RQStatus::Ok`] when it should be
+ * called again, and [`RQStatus::Ignored`] when nothing can be solved until
+ * more frames are pushed.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; `percent_out` must be `NULL` or
+ * valid for writes.
+ */
+RQStatus raptorq_ctx_finalize_step(RQContext *ctx,
+                                   uint32_t budget_ms,
+                                   uint8_t *percent_out);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * The malformed frames counted by [`raptorq_ctx_stats`], split into
+ * truncated frames, frames of the wrong length, checksum failures and the
+ * rest, to tell a camera misreading codes from a sender emitting garbage.
+ * All zero for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQFrameQuality raptorq_ctx_frame_quality(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Destroy the decoding context and release all resources.  For a context
+ * that was retained, this gives back one reference like
+ * [`raptorq_ctx_release`].
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; it must not be used afterwards.
+ */
+void raptorq_ctx_free(RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Rebuild a context from a [`raptorq_ctx_dump`] blob: same OTI and
+ * handshake details, with every dumped symbol pushed again (which completes
+ * the transfer if they are enough).  Options such as profiles, checksums or
+ * the decode overhead are not part of a dump; the symbols are already
+ * unwrapped.  Returns `NULL` for a blob that is not a well‑formed dump.
+ *
+ * # Safety
+ * `blob` must point to `len` readable bytes.
+ */
+RQContext *raptorq_ctx_from_dump(const uint8_t *blob,
+                                 uint64_t len);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Build a fully initialized [`RQContext`] from a handshake frame (see
+ * `raptorq_enc_handshake_frame`).  Besides the OTI the context learns the
+ * SHA‑256 the result must match, the content type and the loop length.
+ *
+ * Returns `NULL` if the frame is not a handshake frame.
+ *
+ * # Safety
+ * `frame_ptr` must point to `frame_len` readable bytes.
+ */
+RQContext *raptorq_ctx_from_handshake(const uint8_t *frame_ptr,
+                                      uint64_t frame_len);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Build a [`RQContext`] from the raw **12‑byte** OTI header that the encoder
+ * usually embeds in its first QR frame.  Returns `NULL` for a `NULL` pointer
+ * or a header describing an empty object or zero‑sized symbols.
+ *
+ * # Safety
+ * `oti_ptr` must be `NULL` or point to at least 12 readable bytes.
+ */
+RQContext *raptorq_ctx_from_oti(const uint8_t *oti_ptr);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Check whether the decoder has recovered enough packets to rebuild the
+ * original object.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+bool raptorq_ctx_is_complete(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Whether scanning has stopped paying off: no frame brought a new symbol
+ * in the last `window.pushes` pushes, or for `window.ms` milliseconds (the
+ * latter counted from the context's creation until the first symbol, and
+ * never reached without a clock).  Either limit left `0` is not checked.
+ * Lets the app suggest adjusting distance or focus rather than scanning
+ * forever.  `false` once no more frames are needed, and for a `NULL`
+ * context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+bool raptorq_ctx_is_stalled(const RQContext *ctx,
+                            RQStallWindow window);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * The SBN of the source block furthest from decodable: the undecoded one
+ * with the most symbols still missing (the lowest SBN among equals).  The
+ * UI can ask the user, or the sender over a back channel, to linger on the
+ * part of the loop carrying it.  `UINT32_MAX` before the first symbol, once
+ * no more frames are needed, and for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+uint32_t raptorq_ctx_lagging_block(const RQContext *ctx);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * [`raptorq_ctx_deserialize`] from a file written by [`raptorq_ctx_save`].
+ * Returns `NULL` if the file is missing, damaged or from an incompatible
+ * version.  Requires `std`.
+ *
+ * # Safety
+ * `path` must be `NULL` or a NUL‑terminated UTF‑8 string.
+ */
+RQContext *raptorq_ctx_load(const char *path);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && (defined(RAPTORQ_STD) && defined(RAPTORQ_UNIX)))
+/**
+ * Borrow the object assembled in the mapping set up by
+ * [`raptorq_ctx_set_output_mapping`], writing its length to `len_out` when
+ * not `NULL`.  `NULL` until decoding completes.  The bytes stay valid until
+ * the context is freed or returned to a pool; do not free them.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; `len_out` must be `NULL` or valid
+ * for writes.
+ */
+const uint8_t *raptorq_ctx_mapped_result(const RQContext *ctx,
+                                         uint64_t *len_out);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Convenience constructor when you **already know** the transfer length and
+ * the maximum payload size of your QR frames.
+ *
+ * Like every constructor this is cheap: the per‑block decoding state is only
+ * built when the first frame for the transfer arrives (or on
+ * [`raptorq_ctx_preallocate`]).
+ *
+ * Returns `NULL` for a zero `transfer_length` or `max_payload_size`, or a
+ * transfer longer than RaptorQ allows.
+ */
+RQContext *raptorq_ctx_new(uint64_t transfer_length,
+                           uint16_t max_payload_size);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Like [`raptorq_ctx_new`], for a context that decodes on a thread of its
+ * own from the start: feed it with [`raptorq_ctx_enqueue`] and it calls
+ * `on_complete` with the recovered object, and `on_progress` (if not
+ * `NULL`) as symbols arrive, both on that thread and with `user_data`.
+ * No GCD queue or polling is needed on the app side, although
+ * [`raptorq_ctx_poll`] still works.
+ *
+ * [`raptorq_ctx_free`] decodes the frames still queued and joins the
+ * thread, so the callbacks may run until it returns.  Returns `NULL` where
+ * [`raptorq_ctx_new`] would, or if the thread cannot be spawned.  Requires
+ * `std`.
+ *
+ * # Safety
+ * The callbacks must stay callable, and `user_data` valid for them, until
+ * the context is freed.
+ */
+RQContext *raptorq_ctx_new_background(uint64_t transfer_length,
+                                      uint16_t max_payload_size,
+                                      RQCompleteCallback on_complete,
+                                      RQProgressCallback on_progress,
+                                      void *user_data);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Like [`raptorq_ctx_new_threadsafe`], for Flutter apps binding the library
+ * with `dart:ffi`: isolates may share the context whichever threads they
+ * run on, and it never starts a thread of its own, so its callbacks only
+ * run inside a call an isolate made and [`raptorq_ctx_free`] neither
+ * blocks nor calls back.  That makes `raptorq_ctx_free` safe to attach as
+ * a `NativeFinalizer`.  [`raptorq_ctx_start_decode_thread`] and
+ * [`raptorq_ctx_enqueue`] fail on it.  Requires `std`.
+ */
+RQContext *raptorq_ctx_new_isolate_safe(uint64_t transfer_length,
+                                        uint16_t max_payload_size);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Like [`raptorq_ctx_new`], for a context shared between threads (say the
+ * camera delegate queue and the UI thread): pushes, finalize steps, the
+ * status queries and [`raptorq_ctx_take_result`] lock the context, so they
+ * may be called concurrently.  Configure it with the `raptorq_ctx_set_*`
+ * functions before sharing it.  Requires `std`.
+ *
+ * On any other context, a call overlapping one still running on another
+ * thread does nothing and reports [`RQStatus::Busy`] (or its usual
+ * `NULL`/`false`/`0`, with the reason in `raptorq_last_error`); status
+ * queries may overlap one another.
+ */
+RQContext *raptorq_ctx_new_threadsafe(uint64_t transfer_length,
+                                      uint16_t max_payload_size);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Create a context for one of the built‑in [`RQProfile`] presets, which fix
+ * the payload size, the frame prefix and the envelope used by that app.
+ *
+ * Returns `NULL` for an unknown `profile_id` or a transfer length
+ * [`raptorq_ctx_new`] would reject.
+ */
+RQContext *raptorq_ctx_new_with_profile(uint32_t profile_id,
+                                        uint64_t transfer_length);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * New symbols per second, smoothed over about two seconds of scanning, for
+ * a progress animation that moves steadily instead of jumping with every
+ * frame: bursts of duplicates slow it down gradually, and it decays while
+ * nothing arrives.  Comparable to the counts of the progress callback;
+ * times the symbol size of the OTI for bytes per second.  `0` before the
+ * first frame, without a clock, once no more frames are needed, and for a
+ * `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+float raptorq_ctx_novelty_rate(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Stop the context's scanning clock while the app is in the background,
+ * so the time away counts neither towards [`raptorq_ctx_is_stalled`] nor
+ * the pace behind [`raptorq_ctx_eta_ms`], nor `elapsed_ms` in
+ * [`raptorq_ctx_stats`].  Frames can still be pushed, and start the clock
+ * again like [`raptorq_ctx_resume`].  Pausing twice is the same as once.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_pause(RQContext *ctx);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * What the decode thread made of the frames queued with
+ * [`raptorq_ctx_enqueue`] since the previous poll: the status of the last
+ * one decoded, or [`RQStatus::Pending`] if none was.  [`RQStatus::Complete`],
+ * [`RQStatus::DigestMismatch`] and [`RQStatus::ReadyToFinalize`] are never
+ * skipped over; on those the thread is stopped and the state moved back, so
+ * the result can be taken (or finalization driven) right away.
+ * [`RQStatus::Failed`] when no decode thread runs.
+ *
+ * Meant to be called from the app's own timer or run loop, keeping the
+ * camera callback down to [`raptorq_ctx_enqueue`].
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQStatus raptorq_ctx_poll(RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Allocate the context's symbol storage and result buffer right away instead
+ * of while frames are arriving, so neither the first pushes nor the final
+ * solve trigger large allocations mid‑scan.  Call it right after creating
+ * the context (and after `raptorq_ctx_set_decode_overhead`, if used).
+ *
+ * Returns `false` if memory could not be reserved; the context stays usable.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+bool raptorq_ctx_preallocate(RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Push one QR‑frame payload into the decoder.
+ *
+ * Returns `true` **iff** this call finished decoding the whole object; see
+ * [`raptorq_ctx_push_frame_status`] for the detailed outcome.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context, and `payload_ptr` must point to
+ * `payload_len` readable bytes.
+ */
+bool raptorq_ctx_push_frame(RQContext *ctx,
+                            const uint8_t *payload_ptr,
+                            uint64_t payload_len);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Like [`raptorq_ctx_push_frame_status`], but the library takes ownership of
+ * the buffer and keeps the symbol in it instead of allocating a copy (the
+ * symbol is only moved forward within the buffer, past the header).  The
+ * buffer is consumed in every case, including errors; do not touch or free
+ * it again.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; `buf` must be `NULL` or a buffer
+ * returned by [`crate::raptorq_alloc`] with exactly `len` bytes.
+ */
+RQStatus raptorq_ctx_push_frame_owned(RQContext *ctx,
+                                      uint8_t *buf,
+                                      uint64_t len);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Push one QR‑frame payload into the decoder and report what happened to it.
+ *
+ * Handshake frames repeated inside the animation loop are ignored.  When the
+ * context was built from a handshake and the recovered object does not match
+ * its digest, the result is discarded and [`RQStatus::DigestMismatch`] is
+ * returned.  Contexts created from a profile expect every frame in that
+ * profile's prefix and envelope and reject anything else as
+ * [`RQStatus::Malformed`].
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context, and `payload_ptr` must point to
+ * `payload_len` readable bytes.
+ */
+RQStatus raptorq_ctx_push_frame_status(RQContext *ctx,
+                                       const uint8_t *payload_ptr,
+                                       uint64_t payload_len);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Recommended minimum interval between pushes, in microseconds, so the
+ * camera pipeline can drop frames instead of queueing them faster than the
+ * decoder absorbs them.  Follows the recent cost of a push, rising to the
+ * worst one seen while the next symbol is likely to trigger a solve; `0`
+ * once no more frames are needed (or before the first push).
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+uint32_t raptorq_ctx_push_interval_hint_us(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Time spent inside the `raptorq_ctx_push_frame*` calls on `ctx` so far,
+ * to correlate with dropped camera frames.  All zero for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQPushStats raptorq_ctx_push_stats(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Give back one reference to `ctx`, destroying it and releasing all
+ * resources if it was the last.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; it must not be used afterwards
+ * through this reference.
+ */
+void raptorq_ctx_release(RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * `true` once the recovered object has been pinned in RAM as requested by
+ * [`raptorq_ctx_set_mlock_result`]; it stays set after the result is taken.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+bool raptorq_ctx_result_locked(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Start the clock stopped by [`raptorq_ctx_pause`] again, e.g. when the
+ * app returns to the foreground.  Does nothing unless paused.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_resume(RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Take another reference to `ctx` for a component that outlives its
+ * creator, e.g. a background persister finishing after the scanner view
+ * is gone.  Every reference, the constructor's included, is given back with
+ * [`raptorq_ctx_release`]; the context is destroyed with the last one.
+ * References manage lifetime only: calls on the context from different
+ * threads must still not overlap (see [`RQStatus::Busy`]).  Returns `ctx`.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQContext *raptorq_ctx_retain(RQContext *ctx);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * [`raptorq_ctx_serialize`] straight to the file at `path`, replaced
+ * atomically.  Returns [`RQStatus::Ignored`] for a completed transfer and
+ * [`RQStatus::Failed`] if the file could not be written.  Requires `std`.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; `path` must be `NULL` or a
+ * NUL‑terminated UTF‑8 string.
+ */
+RQStatus raptorq_ctx_save(const RQContext *ctx,
+                          const char *path);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Snapshot `ctx` so the transfer can be resumed with
+ * [`raptorq_ctx_deserialize`] after the app is terminated, and write the
+ * snapshot's length to `len_out` (when not `NULL`).  Free it with
+ * [`crate::raptorq_free`].  Returns `NULL` for a `NULL` context or a
+ * completed transfer (take its result instead).  Call it before the decode
+ * thread starts or after it stops.
+ *
+ * The snapshot contains the received part of the payload; store it
+ * accordingly (e.g. with complete file protection).
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; `len_out` must be `NULL` or valid
+ * for writes.
+ */
+uint8_t *raptorq_ctx_serialize(const RQContext *ctx,
+                               uint64_t *len_out);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * [`raptorq_ctx_serialize`] returning an [`RQBuffer`], to be released with
+ * [`crate::raptorq_buffer_free`].
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQBuffer raptorq_ctx_serialize_buffer(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Remember received symbols in a Bloom filter of `bits_per_block` bits
+ * (rounded up to a power of two) per source block instead of an exact set,
+ * so memory stays constant however long the scan runs.  A false positive
+ * drops a new symbol as a repeat and costs one more frame; around `16 * K`
+ * bits keep that under one in a thousand.  Symbols received so far are
+ * carried over; a context cannot switch back.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_set_bloom_dedupe(RQContext *ctx,
+                                  uint32_t bits_per_block);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Collect `overhead` symbols beyond the `K` a block needs before attempting
+ * its (expensive) solve; after a failed attempt every new symbol retries.
+ * Defaults to `0`.  Each extra symbol makes a failed solve roughly a hundred
+ * times less likely, at the cost of scanning one more frame per block.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_set_decode_overhead(RQContext *ctx,
+                                     uint32_t overhead);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Solve up to `threads` source blocks at once when several become ready
+ * together (multi‑block transfers only).  `0` selects the default, which is
+ * two threads on multi‑core devices.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_set_decode_threads(RQContext *ctx,
+                                    uint32_t threads);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Leave the final solve to [`raptorq_ctx_finalize_step`] instead of running
+ * it inside the push that completes a block, so the app can spread it over
+ * several run loop turns.  Pushes then return [`RQStatus::ReadyToFinalize`]
+ * once every block has enough symbols.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_set_deferred_finalize(RQContext *ctx,
+                                       bool enabled);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Expect every frame to end in the CRC‑32 trailer added by
+ * `raptorq_enc_set_frame_checksums` and reject frames failing it as
+ * [`RQStatus::Malformed`].  The CRC covers the frame exactly as encoded, so
+ * zero padding added after it fails the check.  Off by default.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_set_frame_checksums(RQContext *ctx,
+                                     bool enabled);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Keep a copy of the symbols handed to each solve until their block is
+ * decoded, so that [`crate::raptorq_ctx_dump`] can include them; without it
+ * a dump taken after a failed solve is missing symbols.  Roughly doubles
+ * the memory of the blocks still solving; enable it before the first push.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_set_keep_symbols(RQContext *ctx,
+                                  bool enabled);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Log this context's lines at `level` instead of the level given to
+ * `raptorq_set_logger` (including [`RQLogLevel::Off`] to silence it).
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_set_log_level(RQContext *ctx,
+                               RQLogLevel level);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Append `tag=<tag>` to this context's log lines, to tell simultaneous
+ * transfers apart; `NULL` or an empty string removes the tag.  Returns
+ * `false`, keeping the previous tag, for a `NULL` context or a tag that is
+ * not UTF‑8, is longer than 32 bytes or contains whitespace.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context, `tag` `NULL` or a NUL‑terminated
+ * string.
+ */
+bool raptorq_ctx_set_log_tag(RQContext *ctx,
+                             const char *tag);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Call `callback` once for each of the `count` percentages at `percents`
+ * (e.g. 25, 50, 75, 100) as the received symbols reach that share of the
+ * needed ones, e.g. to drive haptics or status text.  Replaces earlier
+ * milestones; a `NULL` callback clears them.  Returns `false` for a
+ * percentage over 100.  Register them before starting a decode thread.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; `percents` must point to `count`
+ * readable bytes; `callback` must stay callable, and `user_data` valid for
+ * it, until it is replaced or `ctx` is freed.
+ */
+bool raptorq_ctx_set_milestones(RQContext *ctx,
+                                const uint8_t *percents,
+                                uint32_t count,
+                                RQMilestoneCallback callback,
+                                void *user_data);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Pin the recovered object in RAM with `mlock` so it is never written to
+ * swap.  Where that is refused (e.g. beyond the process's lock limit) the
+ * transfer completes anyway with an ordinary buffer;
+ * [`raptorq_ctx_result_locked`] tells which one it got.  A locked result is
+ * unlocked by [`crate::raptorq_free_zeroize`] and
+ * [`crate::raptorq_buffer_free_zeroize`], which should be used to free it.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_set_mlock_result(RQContext *ctx,
+                                  bool enabled);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Call `callback` once, when `ctx` recovers the object, handing it the
+ * result instead of leaving it for [`raptorq_ctx_take_result`]; right away
+ * if the object is already waiting.  Replaces an earlier registration;
+ * `NULL` clears it.  Register it before starting a decode thread.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; `callback` must stay callable,
+ * and `user_data` valid for it, until it is called or replaced.
+ */
+void raptorq_ctx_set_on_complete(RQContext *ctx,
+                                 RQCompleteCallback callback,
+                                 void *user_data);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Call `callback` whenever a frame pushed to `ctx` adds a symbol it did not
+ * have yet, with the received and needed symbol counts, so the UI can
+ * update only when something changed.  Replaces an earlier registration;
+ * `NULL` clears it.  Register it before starting a decode thread.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; `callback` must stay callable,
+ * and `user_data` valid for it, until it is replaced or `ctx` is freed.
+ */
+void raptorq_ctx_set_on_progress(RQContext *ctx,
+                                 RQProgressCallback callback,
+                                 void *user_data);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Assemble the object in the file at `path` (created or truncated now)
+ * instead of in memory.  Every source block is written there as soon as it
+ * decodes, which bounds memory use by one block for very large transfers.
+ * Once complete, [`raptorq_ctx_is_complete`] reports `true` while
+ * [`raptorq_ctx_take_result`] returns `NULL`: read the file instead.
+ *
+ * Returns `false` if the file could not be created.  Requires `std`.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; `path` must be `NULL` or a
+ * NUL‑terminated UTF‑8 string.
+ */
+bool raptorq_ctx_set_output_file(RQContext *ctx,
+                                 const char *path);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && (defined(RAPTORQ_STD) && defined(RAPTORQ_UNIX)))
+/**
+ * Assemble the object in a memory mapping instead of on the heap: shared
+ * with the file at `path` (created or truncated now) or anonymous when
+ * `path` is `NULL`.  Meant for transfers large enough to trip iOS memory
+ * limits as one allocation.  Once complete, [`raptorq_ctx_take_result`]
+ * returns `NULL`; borrow the object with [`raptorq_ctx_mapped_result`].
+ *
+ * Returns `false` if the mapping could not be created.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; `path` must be `NULL` or a
+ * NUL‑terminated UTF‑8 string.
+ */
+bool raptorq_ctx_set_output_mapping(RQContext *ctx,
+                                    const char *path);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Expect packed frames, each carrying several length‑prefixed packets as
+ * produced by `raptorq_enc_set_packets_per_frame`.  Off by default.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_set_packed_frames(RQContext *ctx,
+                                   bool enabled);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Opt in to (or out of) padding‑tolerant ingestion.  When enabled, frames
+ * longer than one packet (4‑byte header plus symbol size) have their
+ * trailing zero bytes trimmed before deserializing, and such pushes report
+ * [`RQStatus::PaddingStripped`].  Off by default.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_set_padding_tolerant(RQContext *ctx,
+                                      bool enabled);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Overwrite received symbols and decoded bytes with zeros before the
+ * context frees or reuses their buffers, for payloads such as seed phrases.
+ * Copies made inside the RaptorQ solver are freed without being wiped.  The
+ * result handed out is the caller's; release it with
+ * [`crate::raptorq_free_zeroize`] or [`crate::raptorq_buffer_free_zeroize`].
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+void raptorq_ctx_set_zeroize(RQContext *ctx,
+                             bool enabled);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Probability, from `0` to `1`, that solving with the symbols buffered now
+ * succeeds, after the overhead model of RFC 6330: `0` while any block has
+ * fewer than its `K` symbols, about `0.99` with exactly `K`, `0.9999` with
+ * one more and so on, multiplied over the blocks still undecoded.  With
+ * deferred finalization, lets the app choose between running
+ * [`raptorq_ctx_finalize_step`] now and waiting for a couple more frames.
+ * `1` once the object was recovered; `0` for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+float raptorq_ctx_solve_probability(const RQContext *ctx);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Start a library‑owned thread that decodes frames handed over with
+ * [`raptorq_ctx_enqueue_frame`], through a queue of `queue_len` frames of up
+ * to `max_frame_len` bytes each, all allocated now.
+ *
+ * The decoding state moves to that thread until
+ * [`raptorq_ctx_stop_decode_thread`] brings it back.  Meanwhile queries
+ * (statistics, progress, settings, the event log) read it there, waiting
+ * for the frame being decoded if need be; calls that change the context
+ * act on a stand-in and their effect is lost.  Poll
+ * [`raptorq_ctx_decode_thread_status`] for the outcome.
+ *
+ * Returns `false` for a `NULL` context, a zero size, a thread that is
+ * already running or one that could not be spawned.  Requires `std`.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+bool raptorq_ctx_start_decode_thread(RQContext *ctx,
+                                     uint32_t queue_len,
+                                     uint32_t max_frame_len);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Counts of the frames pushed into `ctx` by outcome, the bytes they
+ * carried, the solves run and the time since the context was created.
+ * Callable at any time, including after completion and after the result
+ * was taken.  All zero for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQStats raptorq_ctx_stats(const RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * [`raptorq_ctx_stats`], [`raptorq_ctx_push_stats`], [`raptorq_ctx_frame_quality`] and the progress
+ * figures (`eta_ms`, `null` while unknown; `expected_frames`;
+ * `solve_probability`; completion) as one UTF-8 JSON object, to attach to
+ * analytics events and bug reports as is.  Field names follow the C
+ * structs; new ones may be added.  Release it with
+ * [`crate::raptorq_buffer_free`]; a `NULL` buffer for a `NULL` context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQBuffer raptorq_ctx_stats_json(const RQContext *ctx);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Decode the frames still queued, stop the decode thread and move the
+ * decoding state back into `ctx`, after which the result is available as
+ * usual.  Returns `false` if no decode thread ran or it failed, in which
+ * case `ctx` stays a fresh context.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+bool raptorq_ctx_stop_decode_thread(RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Move the reconstructed buffer **out** of the context.  Caller assumes
+ * ownership and must free it with [`crate::raptorq_free`].  If `len_out` is not
+ * `NULL` the function writes the buffer length to it.  Returns `NULL` for
+ * objects assembled on disk (see [`raptorq_ctx_set_output_file`]).
+ *
+ * All decoding state is released along with the result, so a context kept
+ * around afterwards (e.g. for status display) costs next to nothing; further
+ * frames pushed into it are [`RQStatus::Ignored`].
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context; `len_out` must be `NULL` or valid
+ * for writes.
+ */
+uint8_t *raptorq_ctx_take_result(RQContext *ctx,
+                                 uint64_t *len_out);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * [`raptorq_ctx_take_result`] returning an [`RQBuffer`], to be released
+ * with [`crate::raptorq_buffer_free`].
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQBuffer raptorq_ctx_take_result_buffer(RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Number of frames in one animation loop as announced by the handshake
+ * frame, usable as a progress denominator; `0` when unknown.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+uint32_t raptorq_ctx_total_frames(const RQContext *ctx);
+#endif
+
+/**
+ * Release any buffer allocated by this library, whichever function returned
+ * it.  `NULL` is ignored.
+ *
+ * # Safety
+ * `ptr_` must be `NULL` or a live buffer from this library and `len` its
+ * exact length.
+ */
+void raptorq_dealloc(uint8_t *ptr,
+                     uint64_t len);
+
+#if defined(RAPTORQ_ENCODER)
+/**
+ * Copy out frame `index` of the loop.  Free it with [`crate::raptorq_free`].
+ *
+ * # Safety
+ * `enc` must be `NULL` or a live encoder; `len_out` must be `NULL` or valid
+ * for writes.
+ */
+uint8_t *raptorq_enc_frame(const RQEncoder *enc,
+                           uint32_t index,
+                           uint64_t *len_out);
+#endif
+
+#if defined(RAPTORQ_ENCODER)
+/**
+ * [`raptorq_enc_frame`] returning an [`RQBuffer`], to be released with
+ * [`crate::raptorq_buffer_free`].
+ *
+ * # Safety
+ * `enc` must be `NULL` or a live encoder.
+ */
+RQBuffer raptorq_enc_frame_buffer(const RQEncoder *enc,
+                                  uint32_t index);
+#endif
+
+#if defined(RAPTORQ_ENCODER)
+/**
+ * Number of frames in one animation loop, excluding the handshake frame.
+ *
+ * # Safety
+ * `enc` must be `NULL` or a live encoder.
+ */
+uint32_t raptorq_enc_frame_count(const RQEncoder *enc);
+#endif
+
+#if defined(RAPTORQ_ENCODER)
+/**
+ * Destroy the encoder and release all resources.
+ *
+ * # Safety
+ * `enc` must be `NULL` or a live encoder; it must not be used afterwards.
+ */
+void raptorq_enc_free(RQEncoder *enc);
+#endif
+
+#if defined(RAPTORQ_ENCODER)
+/**
+ * Build the optional handshake frame ("frame zero") announcing the OTI, the
+ * SHA‑256 of the object, `content_type` and the loop length.  Free it with
+ * [`crate::raptorq_free`].
+ *
+ * # Safety
+ * `enc` must be `NULL` or a live encoder; `len_out` must be `NULL` or valid
+ * for writes.
+ */
+uint8_t *raptorq_enc_handshake_frame(const RQEncoder *enc,
+                                     uint16_t content_type,
+                                     uint64_t *len_out);
+#endif
+
+#if defined(RAPTORQ_ENCODER)
+/**
+ * [`raptorq_enc_handshake_frame`] returning an [`RQBuffer`], to be
+ * released with [`crate::raptorq_buffer_free`].
+ *
+ * # Safety
+ * `enc` must be `NULL` or a live encoder.
+ */
+RQBuffer raptorq_enc_handshake_frame_buffer(const RQEncoder *enc,
+                                            uint16_t content_type);
+#endif
+
+#if defined(RAPTORQ_ENCODER)
+/**
+ * Encode `data_len` bytes into frames carrying at most `max_payload_size`
+ * bytes of symbol data each, plus `repair_packets_per_block` repair frames
+ * for every source block.  Returns `NULL` for empty data, a zero
+ * `max_payload_size` or a `data_len` that does not fit the address space
+ * or RaptorQ.
+ *
+ * # Safety
+ * `data_ptr` must point to `data_len` readable bytes.
+ */
+RQEncoder *raptorq_enc_new(const uint8_t *data_ptr,
+                           uint64_t data_len,
+                           uint16_t max_payload_size,
+                           uint32_t repair_packets_per_block);
+#endif
+
+#if defined(RAPTORQ_ENCODER)
+/**
+ * The 12‑byte OTI of the encoded object, for a receiver set up with
+ * `raptorq_ctx_from_oti` instead of a handshake frame.  Release it with
+ * [`crate::raptorq_buffer_free`].
+ *
+ * # Safety
+ * `enc` must be `NULL` or a live encoder.
+ */
+RQBuffer raptorq_enc_oti_buffer(const RQEncoder *enc);
+#endif
+
+#if defined(RAPTORQ_ENCODER)
+/**
+ * End every frame in a CRC‑32 of its contents (4 bytes, little endian) so
+ * the receiver can drop misread frames cheaply; the receiving context must
+ * enable `raptorq_ctx_set_frame_checksums`.  The handshake frame carries
+ * its own digest and stays as it is.
+ *
+ * Returns `false` for a `NULL` encoder.
+ *
+ * # Safety
+ * `enc` must be `NULL` or a live encoder.
+ */
+bool raptorq_enc_set_frame_checksums(RQEncoder *enc,
+                                     bool enabled);
+#endif
+
+#if defined(RAPTORQ_ENCODER)
+/**
+ * Carry `packets_per_frame` length‑prefixed packets in every frame instead
+ * of one bare packet.  The receiving context must enable
+ * `raptorq_ctx_set_packed_frames`.  `1` restores the unpacked layout.
+ *
+ * Returns `false` for a `NULL` encoder or a zero count.
+ *
+ * # Safety
+ * `enc` must be `NULL` or a live encoder.
+ */
+bool raptorq_enc_set_packets_per_frame(RQEncoder *enc,
+                                       uint32_t packets_per_frame);
+#endif
+
+#if defined(RAPTORQ_FAULTS)
+/**
+ * The failures currently injected.
+ */
+RQFaults raptorq_faults(void);
+#endif
+
+/**
+ * Free a buffer returned by [`raptorq_ctx_take_result`], [`raptorq_alloc`]
+ * or one of the `raptorq_enc_*` frame functions.  Same as
+ * [`raptorq_dealloc`], kept for existing callers; the `_buffer` variants
+ * with [`raptorq_buffer_free`] need no length.
+ *
+ * # Safety
+ * `ptr_` must be `NULL` or a buffer returned by this library that has not
+ * been freed yet, and `len` must be the length reported alongside it.
+ */
+void raptorq_free(uint8_t *ptr,
+                  uint64_t len);
+
+/**
+ * Like [`raptorq_dealloc`], zeroing the buffer first (and unlocking it if
+ * it is a result pinned by `raptorq_ctx_set_mlock_result`).
+ *
+ * # Safety
+ * Same as [`raptorq_dealloc`].
+ */
+void raptorq_free_zeroize(uint8_t *ptr,
+                          uint64_t len);
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Move a context created by any `raptorq_ctx_*` constructor behind a
+ * handle.  The pointer must not be used afterwards.  Returns `0` for a
+ * `NULL` context, one that was retained (other references would dangle)
+ * or one with a decode thread running: stop it first with
+ * `raptorq_ctx_stop_decode_thread`.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+uint64_t raptorq_handle_adopt(RQContext *ctx);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Snapshot of the handle table's counters since the process started.
+ */
+RQHandleCounters raptorq_handle_counters(void);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Destroy the context behind `handle`; every copy of the handle becomes
+ * stale.  Returns [`RQStatus::InvalidHandle`] for a handle that was already
+ * freed (or never valid).  The context itself is dropped once a call still
+ * running on it from another thread returns.
+ */
+RQStatus raptorq_handle_free(uint64_t handle);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * [`crate::raptorq_ctx_is_complete`] through a handle; `false` for a stale
+ * handle.
+ */
+bool raptorq_handle_is_complete(uint64_t handle);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Whether `handle` refers to a live context.
+ */
+bool raptorq_handle_is_valid(uint64_t handle);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Create a context like `raptorq_ctx_new` and return a handle to it, or `0`
+ * on failure.
+ */
+uint64_t raptorq_handle_new(uint64_t transfer_length,
+                            uint16_t max_payload_size);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * [`crate::raptorq_ctx_push_frame_status`] through a handle.
+ *
+ * # Safety
+ * `payload_ptr` must point to `payload_len` readable bytes.
+ */
+RQStatus raptorq_handle_push_frame(uint64_t handle,
+                                   const uint8_t *payload_ptr,
+                                   uint64_t payload_len);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Reap every context whose time‑to‑live ran out now rather than on the next
+ * handle call, e.g. on a memory warning.  Returns how many were reaped.
+ */
+uint32_t raptorq_handle_reap_expired(void);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Reap the context behind `handle` once `ttl_ms` milliseconds pass without
+ * a call through the handle; `0` removes the time‑to‑live.  Meant as a
+ * safety net for contexts the app forgets to free, so pick it well above
+ * the longest pause a scan can take.
+ */
+RQStatus raptorq_handle_set_ttl(uint64_t handle,
+                                uint64_t ttl_ms);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * Sum up every context behind a live handle, in one call instead of one
+ * per context per field.  Each context is locked in turn, so a push in
+ * progress on another thread delays the call until it returns.
+ */
+RQHandleStats raptorq_handle_stats(void);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * [`crate::raptorq_ctx_take_result`] through a handle; `NULL` for a stale
+ * handle.
+ *
+ * # Safety
+ * `len_out` must be `NULL` or valid for writes.
+ */
+uint8_t *raptorq_handle_take_result(uint64_t handle,
+                                    uint64_t *len_out);
+#endif
+
+#if (defined(RAPTORQ_DECODER) && defined(RAPTORQ_STD))
+/**
+ * [`raptorq_handle_take_result`] returning an [`RQBuffer`], to be released
+ * with [`crate::raptorq_buffer_free`].
+ */
+RQBuffer raptorq_handle_take_result_buffer(uint64_t handle);
+#endif
+
+#if defined(RAPTORQ_STD)
+/**
+ * The message of the last panic caught, or the reason for the last argument
+ * rejected, on the calling thread; `NULL` if there was none since the last
+ * [`raptorq_clear_last_error`].  The string is owned by the library and
+ * valid until the next error or clear on this thread; copy it out.
+ */
+const char *raptorq_last_error(void);
+#endif
+
+#if defined(RAPTORQ_STD)
+/**
+ * Copy the detail of the calling thread's last error (see
+ * [`raptorq_last_error`]) to `info_out`: the status reported and, for a
+ * rejected frame, the offending symbol, the expected and actual lengths or
+ * the offset where parsing failed.  Returns `false`, leaving `info_out`
+ * untouched, if there was no error or `info_out` is `NULL`.
+ *
+ * # Safety
+ * `info_out` must be `NULL` or valid for writing an [`RQErrorInfo`].
+ */
+bool raptorq_last_error_info(RQErrorInfo *info_out);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Check out a context for a transfer of `transfer_length` bytes, like
+ * `raptorq_ctx_new`, reusing an idle one when available.  Give it back with
+ * [`raptorq_pool_return`] (or destroy it with `raptorq_ctx_free`).
+ *
+ * # Safety
+ * `pool` must be `NULL` or a live pool.
+ */
+RQContext *raptorq_pool_checkout(RQPool *pool,
+                                 uint64_t transfer_length,
+                                 uint16_t max_payload_size);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Destroy the pool and every idle context in it.  Contexts still checked out
+ * stay valid and must be freed with `raptorq_ctx_free`.
+ *
+ * # Safety
+ * `pool` must be `NULL` or a live pool; it must not be used afterwards.
+ */
+void raptorq_pool_free(RQPool *pool);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Number of idle contexts currently held by the pool.
+ *
+ * # Safety
+ * `pool` must be `NULL` or a live pool.
+ */
+uint32_t raptorq_pool_idle_count(const RQPool *pool);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Create a pool keeping at most `max_idle` returned contexts around.
+ */
+RQPool *raptorq_pool_new(uint32_t max_idle);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Return a context to the pool.  It is freed instead if the pool already
+ * holds `max_idle` contexts.  A context that was retained is only given
+ * back once its last reference is returned (or released).
+ *
+ * # Safety
+ * `pool` must be `NULL` or a live pool; `ctx` must be `NULL` or a live
+ * context, which must not be used afterwards through this reference.
+ */
+void raptorq_pool_return(RQPool *pool,
+                         RQContext *ctx);
+#endif
+
+/**
+ * Resize a buffer from `old_len` to `new_len` bytes, zero‑filling any growth.
+ * A `NULL` `ptr_` behaves like [`raptorq_alloc`]; a `new_len` of `0` frees the
+ * buffer and returns `NULL`.  On failure `NULL` is returned and the original
+ * buffer is left untouched.
+ *
+ * # Safety
+ * `ptr_` must be `NULL` or a live buffer from this library of exactly
+ * `old_len` bytes; on success it must not be used afterwards.
+ */
+uint8_t *raptorq_realloc(uint8_t *ptr,
+                         uint64_t old_len,
+                         uint64_t new_len);
+
+#if (defined(RAPTORQ_ENCODER) && defined(RAPTORQ_DECODER))
+/**
+ * Static, NUL‑terminated name of regression vector `index`, or `NULL` if the
+ * index is out of range.
+ */
+const char *raptorq_regression_vector_name(uint32_t index);
+#endif
+
+#if (defined(RAPTORQ_ENCODER) && defined(RAPTORQ_DECODER))
+/**
+ * Run the embedded regression vectors against this build: digests of this
+ * library's own output on a desktop build, not RFC 6330 test vectors.
+ *
+ * Every vector is encoded and compared with the recorded digest, then
+ * decoded again after dropping half of its source symbols.
+ */
+RQRegressionReport raptorq_run_regression_vectors(void);
+#endif
+
+#if (defined(RAPTORQ_ENCODER) && defined(RAPTORQ_DECODER))
+/**
+ * Quick check, for app launch, that this build encodes and decodes: a
+ * 16 KiB object is encoded, its first eight frames are dropped and the rest
+ * is scanned back through a context, solving for the missing symbols on the
+ * GF(256) kernels `raptorq` selects for this CPU.  Takes a few milliseconds, where
+ * `raptorq_run_regression_vectors` runs every embedded vector.
+ *
+ * Returns [`RQStatus::Ok`], or [`RQStatus::Failed`] with the failing step
+ * in `raptorq_last_error` (a broken build or a miscompiled architecture).
+ */
+RQStatus raptorq_self_test(void);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * The session's context, for the calls other than pushes.  Borrowed: it
+ * stays valid while the session does, and is not to be freed through this
+ * pointer.
+ *
+ * # Safety
+ * `session` must be `NULL` or a live session.
+ */
+RQContext *raptorq_session_ctx(const RQScanSession *session);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * End the session and give back its reference to the context.
+ *
+ * # Safety
+ * `session` must be `NULL` or a live session; it must not be used
+ * afterwards.
+ */
+void raptorq_session_free(RQScanSession *session);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Start a scan session over `ctx`, with its clock starting now.  The
+ * session takes a reference of its own to the context (see
+ * `raptorq_ctx_retain`), so the app may free its handle independently;
+ * the context is destroyed with the last reference.  `NULL` for a `NULL`
+ * context.
+ *
+ * Push frames through [`raptorq_session_push`] for them to be counted;
+ * everything else (`raptorq_ctx_is_complete`, taking the result, ...) goes
+ * to the context directly.
+ *
+ * # Safety
+ * `ctx` must be `NULL` or a live context.
+ */
+RQScanSession *raptorq_session_new(RQContext *ctx);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Push a frame like `raptorq_ctx_push_frame_status` and count it.  Frames
+ * the context could not be entered for ([`RQStatus::Busy`] and invalid
+ * arguments) are not counted.
+ *
+ * # Safety
+ * `session` must be `NULL` or a live session, and `payload_ptr` must point
+ * to `payload_len` readable bytes.  Calls on one session must not overlap.
+ */
+RQStatus raptorq_session_push(RQScanSession *session,
+                              const uint8_t *payload_ptr,
+                              uint64_t payload_len);
+#endif
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Frame counts, rates and runtime of the session so far.  All zero for a
+ * `NULL` session.
+ *
+ * # Safety
+ * `session` must be `NULL` or a live session.
+ */
+RQScanStats raptorq_session_stats(const RQScanSession *session);
+#endif
+
+#if defined(RAPTORQ_FAULTS)
+/**
+ * Switch the injected failures to `faults`, replacing the previous
+ * setting; pass all zero to stop injecting.
+ */
+void raptorq_set_faults(RQFaults faults);
+#endif
+
+#if defined(RAPTORQ_STD)
+/**
+ * Register `callback` for every line at `level` or more severe, replacing
+ * any earlier logger.  `NULL` turns logging off; with [`RQLogLevel::Off`]
+ * only contexts given their own level by `raptorq_ctx_set_log_level` log.
+ *
+ * # Safety
+ * `callback` must stay callable, and `user_data` valid for it, until it is
+ * replaced.
+ */
+void raptorq_set_logger(RQLogLevel level,
+                        RQLogCallback callback,
+                        void *user_data);
+#endif
+
+#if defined(RAPTORQ_STD)
+/**
+ * Register `callback` (or clear it with `NULL`) to be told about every panic
+ * caught at the FFI boundary.  Replaces any earlier registration.
+ *
+ * # Safety
+ * `callback` must stay callable, and `user_data` valid for it, until it is
+ * replaced.
+ */
+void raptorq_set_panic_callback(RQPanicCallback callback,
+                                void *user_data);
+#endif
+
+#if defined(RAPTORQ_SIMULATOR)
+/**
+ * What the simulated camera did so far, to check a test run against.
+ * All zero for a `NULL` simulator.
+ *
+ * # Safety
+ * `sim` must be `NULL` or a live simulator.
+ */
+RQLossCounters raptorq_sim_counters(const RQLossSimulator *sim);
+#endif
+
+#if defined(RAPTORQ_SIMULATOR)
+/**
+ * Destroy the simulator.
+ *
+ * # Safety
+ * `sim` must be `NULL` or a live simulator; it must not be used afterwards.
+ */
+void raptorq_sim_free(RQLossSimulator *sim);
+#endif
+
+#if defined(RAPTORQ_SIMULATOR)
+/**
+ * Start replaying the frames of `enc` through a simulated camera.  The
+ * frames are copied, so the encoder may be freed afterwards.  `NULL` for a
+ * `NULL` encoder or settings that would drop every frame
+ * (`drop_per_mille` of `1000` or more, `drop_every` of `1`) or exceed a
+ * certainty (`duplicate_per_mille` over `1000`).
+ *
+ * # Safety
+ * `enc` must be `NULL` or a live encoder.
+ */
+RQLossSimulator *raptorq_sim_new(const RQEncoder *enc,
+                                 RQLossConfig config);
+#endif
+
+#if defined(RAPTORQ_SIMULATOR)
+/**
+ * The next frame the simulated camera delivers, looping over the
+ * animation forever.  Release it with [`crate::raptorq_buffer_free`];
+ * a `NULL` buffer for a `NULL` simulator.
+ *
+ * # Safety
+ * `sim` must be `NULL` or a live simulator.
+ */
+RQBuffer raptorq_sim_next_frame(RQLossSimulator *sim);
+#endif
+
+/**
+ * GF(256) acceleration active on this device.
+ */
+RQSimdBackend raptorq_simd_backend(void);
+
+/**
+ * Static, NUL‑terminated name of the active backend (`"neon"`, `"avx2"`,
+ * `"ssse3"` or `"scalar"`), for logs.
+ */
+const char *raptorq_simd_backend_name(void);
+
+/**
+ * A short, stable English description of the [`RQStatus`] value `status`,
+ * e.g. for an error alert or a log line; "unknown status" for a value that
+ * is not one, such as a status added by a newer library.  The string is
+ * static and must not be freed.
+ */
+const char *raptorq_status_message(uint32_t status);
+
+#if defined(RAPTORQ_DECODER)
+/**
+ * Number of calls refused so far because their context had already been
+ * freed, double frees included, for QA builds to assert on.  Only counted
+ * with the `strict` feature, which keeps freed contexts poisoned instead of
+ * releasing their memory; always `0` without it.
+ */
+uint32_t raptorq_use_after_free_count(void);
+#endif
+
+/**
+ * The crate version as a NUL‑terminated semver string, e.g. `"0.1.0"`.  The
+ * string is static and must not be freed.
+ */
+const char *raptorq_version(void);
+
+#endif /* __RAPTORQ_IOS_H__ */
@@ -0,0 +1,209 @@
+//! `cargo run --bin raptorq-vectors -- <dir>`: write canonical frame sets and
+//! a `manifest.json` describing them, for the iOS unit tests and the other
+//! platforms' decoders to check themselves against.
+//!
+//! Every case encodes a deterministic object with one combination of object
+//! size, payload size and envelope options (packed frames, frame checksums)
+//! through the C API, and writes it under `<dir>/<case>/`:
+//!
+//! - `object.bin`: the object itself;
+//! - `handshake.bin`: its handshake frame, content type `0`;
+//! - `frames.bin`: one animation loop, each frame preceded by its length as
+//!   a 4-byte big-endian integer.
+//!
+//! The output only changes when the encoding does, so a diff of a fresh run
+//! against the checked-in vectors of another platform is the compatibility
+//! check.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::{env, fs, io, process, slice};
+
+use sha2::{Digest, Sha256};
+
+use raptorq::{
+    raptorq_buffer_free, raptorq_enc_frame_buffer, raptorq_enc_frame_count, raptorq_enc_free,
+    raptorq_enc_handshake_frame_buffer, raptorq_enc_new, raptorq_enc_oti_buffer,
+    raptorq_enc_set_frame_checksums, raptorq_enc_set_packets_per_frame, RQBuffer, RQEncoder,
+};
+
+/// Bumped whenever the layout of the output changes.
+const MANIFEST_VERSION: u32 = 1;
+
+const OBJECT_SIZES: &[usize] = &[15, 1_000, 20_000];
+const PAYLOAD_SIZES: &[u16] = &[64, 256, 1_072];
+const REPAIR_PACKETS_PER_BLOCK: u32 = 4;
+
+struct Envelope {
+    name: &'static str,
+    packets_per_frame: u32,
+    checksums: bool,
+}
+
+const ENVELOPES: &[Envelope] = &[
+    Envelope {
+        name: "plain",
+        packets_per_frame: 1,
+        checksums: false,
+    },
+    Envelope {
+        name: "packed",
+        packets_per_frame: 3,
+        checksums: false,
+    },
+    Envelope {
+        name: "checksummed",
+        packets_per_frame: 1,
+        checksums: true,
+    },
+    Envelope {
+        name: "packed-checksummed",
+        packets_per_frame: 3,
+        checksums: true,
+    },
+];
+
+/// The object of a case: xorshift32 from a seed derived from its size, so
+/// other platforms can regenerate it instead of reading `object.bin`.
+fn object(len: usize) -> Vec<u8> {
+    let mut state = 0x9e37_79b9 ^ len as u32;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+/// The bytes of `buf`, released.
+fn take(buf: RQBuffer) -> Vec<u8> {
+    if buf.ptr.is_null() {
+        return Vec::new();
+    }
+    let data = unsafe { slice::from_raw_parts(buf.ptr, buf.len as usize) }.to_vec();
+    unsafe { raptorq_buffer_free(buf) };
+    data
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut s, b| {
+        let _ = write!(s, "{b:02x}");
+        s
+    })
+}
+
+/// Encode one case, write its files to `dir` and return its manifest entry.
+fn write_case(
+    dir: &Path,
+    len: usize,
+    max_payload_size: u16,
+    envelope: &Envelope,
+) -> io::Result<String> {
+    let name = format!("{len}-bytes-{max_payload_size}-payload-{}", envelope.name);
+    let data = object(len);
+    let enc: *mut RQEncoder = unsafe {
+        raptorq_enc_new(
+            data.as_ptr(),
+            data.len() as u64,
+            max_payload_size,
+            REPAIR_PACKETS_PER_BLOCK,
+        )
+    };
+    if enc.is_null() {
+        return Err(io::Error::other(format!("{name}: cannot encode")));
+    }
+    let (oti, handshake, frames) = unsafe {
+        raptorq_enc_set_packets_per_frame(enc, envelope.packets_per_frame);
+        raptorq_enc_set_frame_checksums(enc, envelope.checksums);
+        let frames: Vec<Vec<u8>> = (0..raptorq_enc_frame_count(enc))
+            .map(|index| take(raptorq_enc_frame_buffer(enc, index)))
+            .collect();
+        let oti = take(raptorq_enc_oti_buffer(enc));
+        let handshake = take(raptorq_enc_handshake_frame_buffer(enc, 0));
+        raptorq_enc_free(enc);
+        (oti, handshake, frames)
+    };
+
+    let mut stream = Vec::new();
+    for frame in &frames {
+        stream.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+        stream.extend_from_slice(frame);
+    }
+    let case_dir = dir.join(&name);
+    fs::create_dir_all(&case_dir)?;
+    fs::write(case_dir.join("object.bin"), &data)?;
+    fs::write(case_dir.join("handshake.bin"), &handshake)?;
+    fs::write(case_dir.join("frames.bin"), &stream)?;
+
+    Ok(format!(
+        concat!(
+            "    {{\n",
+            "      \"name\": \"{}\",\n",
+            "      \"object_size\": {},\n",
+            "      \"max_payload_size\": {},\n",
+            "      \"repair_packets_per_block\": {},\n",
+            "      \"packets_per_frame\": {},\n",
+            "      \"frame_checksums\": {},\n",
+            "      \"frame_count\": {},\n",
+            "      \"oti\": \"{}\",\n",
+            "      \"object_sha256\": \"{}\",\n",
+            "      \"handshake_sha256\": \"{}\",\n",
+            "      \"frames_sha256\": \"{}\"\n",
+            "    }}"
+        ),
+        name,
+        len,
+        max_payload_size,
+        REPAIR_PACKETS_PER_BLOCK,
+        envelope.packets_per_frame,
+        envelope.checksums,
+        frames.len(),
+        hex(&oti),
+        hex(&Sha256::digest(&data)),
+        hex(&Sha256::digest(&handshake)),
+        hex(&Sha256::digest(&stream)),
+    ))
+}
+
+fn run(dir: &Path) -> io::Result<usize> {
+    let mut cases = Vec::new();
+    for &len in OBJECT_SIZES {
+        for &max_payload_size in PAYLOAD_SIZES {
+            for envelope in ENVELOPES {
+                cases.push(write_case(dir, len, max_payload_size, envelope)?);
+            }
+        }
+    }
+    let manifest = format!(
+        concat!(
+            "{{\n",
+            "  \"version\": {},\n",
+            "  \"abi_version\": {},\n",
+            "  \"frames_encoding\": \"u32-be-length-prefixed\",\n",
+            "  \"cases\": [\n{}\n  ]\n",
+            "}}\n"
+        ),
+        MANIFEST_VERSION,
+        raptorq::raptorq_abi_version(),
+        cases.join(",\n"),
+    );
+    fs::write(dir.join("manifest.json"), manifest)?;
+    Ok(cases.len())
+}
+
+fn main() {
+    let mut args = env::args_os().skip(1);
+    let (Some(dir), None) = (args.next(), args.next()) else {
+        eprintln!("usage: raptorq-vectors <output-dir>");
+        process::exit(2);
+    };
+    match run(Path::new(&dir)) {
+        Ok(count) => println!("wrote {count} cases to {}", dir.to_string_lossy()),
+        Err(err) => {
+            eprintln!("raptorq-vectors: {err}");
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! `cargo run --features uniffi --bin uniffi-bindgen -- generate ...`: the
+//! uniffi binding generator, pinned to the version the scaffolding uses.
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
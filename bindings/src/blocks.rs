@@ -0,0 +1,1527 @@
+//! Per‑block decoding with a configurable solve threshold.
+//!
+//! `raptorq::Decoder` hands every packet straight to its block decoder, which
+//! runs the full matrix solve on each push once `K` symbols are present and
+//! throws the work away whenever the solve fails.  Here packets are buffered
+//! per block and the solve is only attempted once `K + overhead` distinct
+//! symbols have arrived; after a failed attempt every further packet retries.
+//!
+//! While waiting for the threshold, borrowed symbols are copied into one
+//! growable arena per block (sized for `K + overhead` symbols up front)
+//! instead of one `Vec` per packet, so a long scan session does a handful of
+//! allocations per block rather than one per frame.  The buffer is laid out
+//! as a structure of arrays: the symbol bytes back to back in the arena and
+//! their ESIs in a parallel array (the SBN is the block's own), so the
+//! passes over the buffered symbols before and during a solve stream through
+//! memory instead of chasing one pointer per packet.  Packets whose buffer
+//! the caller handed over are kept as they are.
+//!
+//! A symbol is checked against the block's received ESIs before anything is
+//! buffered, so a sender looping the same frames for millions of pushes
+//! costs no storage beyond its distinct symbols.  Payloads are not stored
+//! content‑addressed: symbols with different ESIs but identical bytes (runs
+//! of zeros in the object, say) are each kept, since sharing them would
+//! mean hashing every payload on the per‑frame path for a saving only such
+//! objects see.
+//!
+//! With more than one decode thread, a multi‑block transfer holds back the
+//! first solve of every block until all of them have reached the threshold
+//! and then solves them side by side on scoped threads.  Blocks whose first
+//! attempt fails fall back to retrying on every new packet.
+//!
+//! A block whose `K` source symbols all arrive verbatim before any solve is
+//! assembled straight from the buffered symbols, skipping the inactivation
+//! decoder (and the overhead wait) entirely.  The `SourceBlockDecoder` is
+//! only built for the first solve, so such a block never allocates one.
+//! Most wallet payloads are a few kilobytes: a single block of a single
+//! sub‑block, whose source symbols are the object itself back to back and
+//! are copied straight into the result.
+//!
+//! With a spool file attached, decoded blocks go straight to disk (see
+//! `crate::spool`) and the object is never held in memory as a whole.
+//!
+//! Solves do not share a scratch workspace: the elimination workspace of a
+//! solve (constraint matrix and symbol vector) is allocated inside `raptorq`
+//! for every attempt, and its API offers no way to pass one in, so each block
+//! solved, in parallel or not, still allocates its own.  What the end of a
+//! large transfer does hold on to beyond that is the symbols each
+//! `SourceBlockDecoder` copied in, so decoders are dropped as soon as their
+//! block is decoded and only the blocks still solving keep theirs.
+//!
+//! The blocks (and their `SourceBlockDecoder`s) are only built once the
+//! first packet for this transfer arrives, so a context created for a
+//! scanner screen that is dismissed straight away allocates nothing.
+//!
+//! In deferred mode nothing is solved while packets arrive; the caller
+//! drives the solves in bounded slices through [`ObjectDecoder::finalize_step`]
+//! once enough symbols are buffered.  A single block's solve cannot be
+//! interrupted, so a slice is at least one block.
+//!
+//! Symbols handed to a solve are gone from here once it fails (the
+//! `SourceBlockDecoder` keeps its own copies, out of reach); to dump the
+//! state of a transfer they can be kept as well, at the cost of holding a
+//! block's symbols twice until it is decoded.
+//!
+//! With zeroizing on, every buffer of ours that held symbols or decoded
+//! bytes is overwritten before it is freed or reused: arenas, handed‑over
+//! packets, decoded blocks, and the arena's old allocation when it grows.
+//! The copies `raptorq` makes (the packets given to a solve and the symbols
+//! a `SourceBlockDecoder` keeps) are out of reach and freed as they are.
+//!
+//! Threads and spool files need `std`; without it blocks are solved one
+//! after the other and always assembled in memory.
+
+use core::cmp::Reverse;
+use core::mem;
+use core::ops::Range;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use core::time::Duration;
+
+use crate::cancel::Cancel;
+use crate::dedupe::Seen;
+use crate::log::LogScope;
+use crate::memory;
+#[cfg(feature = "std")]
+use crate::spool::Spool;
+use crate::stats::{RQBlockTiming, SolveTally, Stopwatch};
+
+use raptorq::{
+    partition, EncodingPacket, ObjectTransmissionInformation, PayloadId, SourceBlockDecoder,
+};
+
+/// What a packet did to the decoder.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    /// The packet does not belong to this transfer.
+    Rejected,
+    /// Already seen, or its block is already decoded.
+    Duplicate,
+    /// New symbol stored; the object is not complete yet.
+    Accepted,
+    /// Deferred mode: every block has enough symbols buffered for
+    /// [`ObjectDecoder::finalize_step`].
+    Ready,
+    /// The object is complete.
+    Complete(Vec<u8>),
+    /// The object is complete and assembled in the spool file.
+    #[cfg(feature = "std")]
+    CompleteOnDisk,
+    /// Every block decoded, but the object is too large to assemble in
+    /// memory on this target; only a spool file can hold it.
+    TooLarge,
+    /// Writing to the spool file failed; the block stays in memory and the
+    /// write is retried with the next packet or finalize step, even one
+    /// whose symbol is a duplicate.
+    #[cfg(feature = "std")]
+    Failed,
+    /// The decoder was cancelled; see [`ObjectDecoder::cancel`].
+    Cancelled,
+}
+
+#[derive(Clone)]
+struct Block {
+    decoder: Option<SourceBlockDecoder>, // built for the first solve
+    sbn: u8,
+    symbols: u32, // K, source symbols in this block
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    offset: u64, // of the block within the object, for the spool file
+    received: Seen,
+    source_received: u32, // distinct ESIs below K
+    arena: Vec<u8>,       // symbols of `esis`, back to back
+    esis: Vec<u32>,
+    owned: Vec<EncodingPacket>,
+    kept: Vec<EncodingPacket>, // copies of the symbols handed to solves
+    keep: bool,
+    lost: bool,      // symbols were handed to a solve without a copy
+    attempted: bool, // a solve was tried at least once
+    solves: SolveTally,
+    data: Option<Vec<u8>>,
+    zeroize: bool,
+}
+
+impl Block {
+    /// Deferred mode: a solve is worth trying, i.e. symbols are buffered and
+    /// either the threshold is reached or an earlier attempt fell short.
+    fn is_ready(&self, overhead: u32) -> bool {
+        self.data.is_none()
+            && self.esis.len() + self.owned.len() > 0
+            && (self.attempted || self.received.len() >= self.symbols + overhead)
+    }
+
+    /// Distinct symbols still to collect before the solve threshold; at
+    /// least one after a solve fell short.
+    fn missing(&self, overhead: u32) -> u32 {
+        let missing = (self.symbols + overhead).saturating_sub(self.received.len());
+        if self.attempted {
+            missing.max(1)
+        } else {
+            missing
+        }
+    }
+
+    /// Run the block decoder over `packets`.  Once the block is decoded its
+    /// decoder, which keeps a copy of every symbol it was given, is dropped
+    /// straight away instead of with the whole object.
+    fn solve(
+        &mut self,
+        oti: &ObjectTransmissionInformation,
+        packets: Vec<EncodingPacket>,
+        log: &LogScope,
+    ) {
+        let block_len = u64::from(self.symbols) * u64::from(oti.symbol_size());
+        let sbn = self.sbn;
+        let decoder = self
+            .decoder
+            .get_or_insert_with(|| SourceBlockDecoder::new2(sbn, oti, block_len));
+        self.attempted = true;
+        if self.keep {
+            self.kept.extend(packets.iter().cloned());
+        } else if !packets.is_empty() {
+            self.lost = true;
+        }
+        rq_log!(
+            in log,
+            Debug,
+            "solve attempt sbn={} symbols={} packets={}",
+            sbn,
+            self.symbols,
+            packets.len()
+        );
+        let watch = Stopwatch::start();
+        self.data = decoder.decode(packets);
+        #[cfg(feature = "faults")]
+        if crate::faults::fail_solves() {
+            self.data = None;
+        }
+        self.solves.record(watch.elapsed());
+        if self.data.is_some() {
+            self.decoder = None;
+            for packet in self.kept.drain(..) {
+                discard(Symbol::Owned(packet), self.zeroize);
+            }
+        } else {
+            rq_log!(in log, Debug, "solve failed sbn={}", sbn);
+        }
+    }
+
+    /// Buffer a symbol until the block is solved.
+    fn store(&mut self, symbol: Symbol, reserve: usize, spare: &mut Vec<Vec<u8>>) {
+        match symbol {
+            Symbol::Borrowed(id, data) => {
+                if self.arena.capacity() == 0 {
+                    self.arena = spare.pop().unwrap_or_default();
+                    self.arena.reserve_exact(reserve * data.len());
+                }
+                if self.zeroize {
+                    memory::reserve_wiping(&mut self.arena, data.len());
+                }
+                self.arena.extend_from_slice(data);
+                self.esis.push(id.encoding_symbol_id());
+            }
+            Symbol::Owned(packet) => self.owned.push(packet),
+        }
+    }
+
+    /// Rebuild the block from its buffered source symbols alone.  Only valid
+    /// once all `K` of them are buffered.
+    fn assemble_systematic(
+        &mut self,
+        oti: &ObjectTransmissionInformation,
+        spare: &mut Vec<Vec<u8>>,
+    ) -> Vec<u8> {
+        let symbol_size = usize::from(oti.symbol_size());
+        let mut out = vec![0; symbol_size * self.symbols as usize];
+        let sources = self.buffered(symbol_size);
+        for (esi, symbol) in sources {
+            if esi < self.symbols {
+                unpack_sub_blocks(&mut out, symbol, esi as usize, self.symbols as usize, oti);
+            }
+        }
+        self.discard_pending(spare);
+        out
+    }
+
+    /// Copy the buffered source symbols of a single sub‑block straight into
+    /// `out`, which becomes the first `len` bytes of the object (the padding
+    /// of the last symbol is clipped).  Only valid once all `K` are buffered.
+    fn assemble_contiguous(
+        &mut self,
+        out: &mut Vec<u8>,
+        len: usize,
+        symbol_size: usize,
+        spare: &mut Vec<Vec<u8>>,
+    ) {
+        out.clear();
+        out.reserve_exact(len);
+        out.resize(len, 0);
+        let sources = self.buffered(symbol_size);
+        for (esi, symbol) in sources {
+            let start = esi as usize * symbol_size;
+            if start < len {
+                let end = (start + symbol_size).min(len);
+                out[start..end].copy_from_slice(&symbol[..end - start]);
+            }
+        }
+        self.discard_pending(spare);
+    }
+
+    /// Every buffered symbol with its ESI, arena first.
+    fn buffered(&self, symbol_size: usize) -> impl Iterator<Item = (u32, &[u8])> + '_ {
+        self.esis
+            .iter()
+            .copied()
+            .zip(self.arena.chunks_exact(symbol_size))
+            .chain(
+                self.owned
+                    .iter()
+                    .map(|p| (p.payload_id().encoding_symbol_id(), p.data())),
+            )
+    }
+
+    /// Drop everything buffered, keeping the emptied arena in `spare`.
+    fn discard_pending(&mut self, spare: &mut Vec<Vec<u8>>) {
+        self.esis.clear();
+        for packet in self.owned.drain(..) {
+            discard(Symbol::Owned(packet), self.zeroize);
+        }
+        if self.arena.capacity() > 0 {
+            let mut arena = mem::take(&mut self.arena);
+            if self.zeroize {
+                memory::wipe(&mut arena);
+            }
+            arena.clear();
+            spare.push(arena);
+        }
+    }
+
+    /// Wipe everything this block holds.
+    fn wipe(&mut self) {
+        for packet in self.owned.drain(..).chain(self.kept.drain(..)) {
+            discard(Symbol::Owned(packet), true);
+        }
+        memory::wipe(&mut self.arena);
+        if let Some(data) = self.data.as_mut() {
+            memory::wipe(data);
+        }
+    }
+
+    /// Drain everything buffered so far as packets for the block decoder,
+    /// keeping the emptied arena in `spare` for the next block.
+    fn take_pending(
+        &mut self,
+        symbol_size: usize,
+        spare: &mut Vec<Vec<u8>>,
+    ) -> Vec<EncodingPacket> {
+        let sbn = self.sbn;
+        let mut packets: Vec<EncodingPacket> = self
+            .esis
+            .iter()
+            .zip(self.arena.chunks_exact(symbol_size))
+            .map(|(esi, symbol)| EncodingPacket::new(PayloadId::new(sbn, *esi), symbol.to_vec()))
+            .collect();
+        packets.append(&mut self.owned);
+        self.discard_pending(spare);
+        packets
+    }
+}
+
+/// Where the pieces of source symbol `index` lie in its block under the
+/// sub‑block interleaving of RFC 6330 section 4.4.1.2, as (range in the
+/// symbol, range in the block); a single piece with one sub‑block.
+fn sub_block_pieces(
+    index: usize,
+    block_symbols: usize,
+    oti: &ObjectTransmissionInformation,
+) -> impl Iterator<Item = (Range<usize>, Range<usize>)> {
+    let alignment = usize::from(oti.symbol_alignment());
+    let (tl, ts, nl, ns) = partition(
+        u32::from(oti.symbol_size()) / u32::from(oti.symbol_alignment()),
+        oti.sub_blocks(),
+    );
+    let mut symbol_offset = 0;
+    let mut sub_block_offset = 0;
+    (0..nl + ns).map(move |sub_block| {
+        let bytes = if sub_block < nl { tl } else { ts } as usize * alignment;
+        let start = sub_block_offset + bytes * index;
+        let piece = (symbol_offset..symbol_offset + bytes, start..start + bytes);
+        symbol_offset += bytes;
+        sub_block_offset += bytes * block_symbols;
+        piece
+    })
+}
+
+/// Scatter one source symbol into its block.
+fn unpack_sub_blocks(
+    out: &mut [u8],
+    symbol: &[u8],
+    index: usize,
+    block_symbols: usize,
+    oti: &ObjectTransmissionInformation,
+) {
+    for (in_symbol, in_block) in sub_block_pieces(index, block_symbols, oti) {
+        out[in_block].copy_from_slice(&symbol[in_symbol]);
+    }
+}
+
+/// Gather one source symbol back out of its decoded block.
+fn pack_sub_blocks(
+    symbol: &mut [u8],
+    block: &[u8],
+    index: usize,
+    block_symbols: usize,
+    oti: &ObjectTransmissionInformation,
+) {
+    for (in_symbol, in_block) in sub_block_pieces(index, block_symbols, oti) {
+        symbol[in_symbol].copy_from_slice(&block[in_block]);
+    }
+}
+
+/// Drop a symbol that is not kept, wiping a handed‑over buffer first if
+/// `zeroize`.
+fn discard(symbol: Symbol, zeroize: bool) {
+    if let (Symbol::Owned(packet), true) = (symbol, zeroize) {
+        memory::wipe(&mut packet.split().1);
+    }
+}
+
+/// A symbol on its way into [`ObjectDecoder`].
+pub(crate) enum Symbol<'a> {
+    Borrowed(PayloadId, &'a [u8]),
+    Owned(EncodingPacket),
+}
+
+impl Symbol<'_> {
+    pub fn id(&self) -> &PayloadId {
+        match self {
+            Symbol::Borrowed(id, _) => id,
+            Symbol::Owned(packet) => packet.payload_id(),
+        }
+    }
+
+    fn into_packet(self) -> EncodingPacket {
+        match self {
+            Symbol::Borrowed(id, data) => EncodingPacket::new(id, data.to_vec()),
+            Symbol::Owned(packet) => packet,
+        }
+    }
+}
+
+pub(crate) struct ObjectDecoder {
+    oti: ObjectTransmissionInformation,
+    blocks: Vec<Block>,
+    built: bool, // `blocks` were built (and possibly released since)
+    bloom_bits: Option<u32>,
+    deferred: bool,
+    overhead: u32,
+    threads: usize,
+    remaining: usize,    // blocks not decoded yet
+    spare: Vec<Vec<u8>>, // emptied arenas, ready for reuse
+    output: Vec<u8>,     // preallocated result buffer, if any
+    zeroize: bool,
+    keep_symbols: bool,
+    log: LogScope,
+    cancel: Cancel,
+    retired_solves: SolveTally, // of the blocks released
+    retired_timings: Vec<RQBlockTiming>,
+    #[cfg(feature = "std")]
+    spool: Option<Spool>,
+    #[cfg(feature = "std")]
+    unflushed: bool, // the last spool write failed; retried with the next packet
+}
+
+/// The options of an [`ObjectDecoder`] worth carrying over to a restored
+/// one; the thread count depends on the device and is left out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Options {
+    pub overhead: u32,
+    pub bloom_bits: Option<u32>,
+    pub deferred: bool,
+    pub zeroize: bool,
+    pub keep_symbols: bool,
+}
+
+/// First solve of each block in `jobs`.
+fn solve_all(
+    jobs: &mut [(&mut Block, Vec<EncodingPacket>)],
+    oti: &ObjectTransmissionInformation,
+    log: &LogScope,
+) {
+    for (block, packets) in jobs.iter_mut() {
+        block.solve(oti, mem::take(packets), log);
+    }
+}
+
+/// Default number of decode threads: two where available, leaving the rest
+/// of the device to the camera pipeline and UI.  Queried from the OS once
+/// per process rather than for every context (the GF(256) and systematic
+/// index tables of `raptorq` are constants and need no setup at all).
+#[cfg(feature = "std")]
+pub(crate) fn default_threads() -> usize {
+    static THREADS: std::sync::OnceLock<usize> = std::sync::OnceLock::new();
+    *THREADS.get_or_init(|| {
+        std::thread::available_parallelism()
+            .map(|n| n.get().min(2))
+            .unwrap_or(1)
+    })
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn default_threads() -> usize {
+    1
+}
+
+impl ObjectDecoder {
+    pub fn new(oti: ObjectTransmissionInformation) -> ObjectDecoder {
+        ObjectDecoder::with_spares(oti, vec![])
+    }
+
+    /// Like [`ObjectDecoder::new`], reusing arenas from a previous transfer.
+    pub fn with_spares(oti: ObjectTransmissionInformation, spare: Vec<Vec<u8>>) -> ObjectDecoder {
+        ObjectDecoder {
+            oti,
+            blocks: vec![],
+            built: false,
+            bloom_bits: None,
+            deferred: false,
+            remaining: 0,
+            overhead: 0,
+            threads: default_threads(),
+            spare,
+            output: Vec::new(),
+            zeroize: false,
+            keep_symbols: false,
+            log: LogScope::default(),
+            cancel: Cancel::default(),
+            retired_solves: SolveTally::default(),
+            retired_timings: Vec::new(),
+            #[cfg(feature = "std")]
+            spool: None,
+            #[cfg(feature = "std")]
+            unflushed: false,
+        }
+    }
+
+    /// Build the per‑block state; a no‑op once built (or released).
+    fn build(&mut self) {
+        if self.built {
+            return;
+        }
+        let oti = self.oti;
+        let symbol_size = u64::from(oti.symbol_size());
+        let kt = oti.transfer_length().div_ceil(symbol_size) as u32;
+        let (kl, ks, zl, zs) = partition(kt, oti.source_blocks());
+        let mut offset = 0;
+        self.blocks = (0..zl + zs)
+            .map(|i| {
+                let symbols = if i < zl { kl } else { ks };
+                offset += u64::from(symbols) * symbol_size;
+                Block {
+                    offset: offset - u64::from(symbols) * symbol_size,
+                    decoder: None,
+                    sbn: i as u8,
+                    symbols,
+                    received: Seen::new(),
+                    source_received: 0,
+                    arena: Vec::new(),
+                    esis: vec![],
+                    owned: vec![],
+                    kept: vec![],
+                    keep: self.keep_symbols,
+                    lost: false,
+                    attempted: false,
+                    solves: SolveTally::default(),
+                    data: None,
+                    zeroize: self.zeroize,
+                }
+            })
+            .collect();
+        self.remaining = self.blocks.len();
+        self.built = true;
+        if let Some(bits) = self.bloom_bits {
+            self.set_bloom_dedupe(bits);
+        }
+    }
+
+    /// A deep copy, buffered symbols and block decoders included; `None`
+    /// while assembling into a spool file, which cannot be shared.
+    pub fn try_clone(&self) -> Option<ObjectDecoder> {
+        #[cfg(feature = "std")]
+        if self.spool.is_some() {
+            return None;
+        }
+        Some(ObjectDecoder {
+            oti: self.oti,
+            blocks: self.blocks.clone(),
+            built: self.built,
+            bloom_bits: self.bloom_bits,
+            deferred: self.deferred,
+            overhead: self.overhead,
+            threads: self.threads,
+            remaining: self.remaining,
+            spare: Vec::new(),
+            output: Vec::new(),
+            zeroize: self.zeroize,
+            keep_symbols: self.keep_symbols,
+            log: self.log.clone(),
+            cancel: Cancel::default(),
+            retired_solves: self.retired_solves,
+            retired_timings: self.retired_timings.clone(),
+            #[cfg(feature = "std")]
+            spool: None,
+            #[cfg(feature = "std")]
+            unflushed: false,
+        })
+    }
+
+    /// Tear down, handing back every arena allocated so far.
+    pub fn take_spares(&mut self) -> Vec<Vec<u8>> {
+        self.release(true);
+        mem::take(&mut self.spare)
+    }
+
+    /// Overwrite symbols and decoded bytes before their buffers are freed or
+    /// reused, from now on and at teardown.
+    pub fn set_zeroize(&mut self, zeroize: bool) {
+        self.zeroize = zeroize;
+        for block in self.blocks.iter_mut() {
+            block.zeroize = zeroize;
+        }
+    }
+
+    pub fn zeroizes(&self) -> bool {
+        self.zeroize
+    }
+
+    /// Keep a copy of the symbols handed to each solve, so that
+    /// [`ObjectDecoder::symbols`] can still produce them.
+    pub fn set_keep_symbols(&mut self, keep: bool) {
+        self.keep_symbols = keep;
+        for block in self.blocks.iter_mut() {
+            block.keep = keep;
+        }
+    }
+
+    /// Call `f` with every symbol needed to get back to the current state:
+    /// those buffered or kept for the blocks still solving, and the source
+    /// symbols of the decoded ones.  `false` if some are missing, i.e. were
+    /// handed to a solve without being kept or written to the spool file.
+    pub fn symbols(&self, mut f: impl FnMut(&PayloadId, &[u8])) -> bool {
+        let symbol_size = usize::from(self.oti.symbol_size());
+        let mut complete = true;
+        let mut symbol = vec![0; symbol_size];
+        for block in self.blocks.iter() {
+            match block.data.as_deref() {
+                Some([]) => complete = false,
+                Some(data) => {
+                    for esi in 0..block.symbols {
+                        let k = block.symbols as usize;
+                        pack_sub_blocks(&mut symbol, data, esi as usize, k, &self.oti);
+                        f(&PayloadId::new(block.sbn, esi), &symbol);
+                    }
+                }
+                None => {
+                    complete &= !block.lost;
+                    for packet in block.kept.iter() {
+                        f(packet.payload_id(), packet.data());
+                    }
+                    for (esi, symbol) in block.buffered(symbol_size) {
+                        f(&PayloadId::new(block.sbn, esi), symbol);
+                    }
+                }
+            }
+        }
+        if self.zeroize {
+            memory::wipe(&mut symbol);
+        }
+        complete
+    }
+
+    /// Wipe every buffer still holding symbols or decoded bytes.
+    fn wipe(&mut self) {
+        for block in self.blocks.iter_mut() {
+            block.wipe();
+        }
+        for arena in self.spare.iter_mut() {
+            memory::wipe(arena);
+        }
+        memory::wipe(&mut self.output);
+    }
+
+    pub fn options(&self) -> Options {
+        Options {
+            overhead: self.overhead,
+            bloom_bits: self.bloom_bits,
+            deferred: self.deferred,
+            zeroize: self.zeroize,
+            keep_symbols: self.keep_symbols,
+        }
+    }
+
+    pub fn set_options(&mut self, options: Options) {
+        self.set_overhead(options.overhead);
+        if let Some(bits) = options.bloom_bits {
+            self.set_bloom_dedupe(bits);
+        }
+        self.set_deferred(options.deferred);
+        self.set_zeroize(options.zeroize);
+        self.set_keep_symbols(options.keep_symbols);
+    }
+
+    /// Only buffer symbols while decoding and leave every solve to
+    /// [`ObjectDecoder::finalize_step`].
+    pub fn set_deferred(&mut self, deferred: bool) {
+        self.deferred = deferred;
+    }
+
+    /// Every undecoded block can be solved by [`ObjectDecoder::finalize_step`].
+    fn all_ready(&self) -> bool {
+        self.remaining > 0
+            && self
+                .blocks
+                .iter()
+                .all(|b| b.data.is_some() || b.is_ready(self.overhead))
+    }
+
+    /// The next new symbol may well run a solve: some block is one symbol
+    /// short of the threshold, or retrying on every symbol after a failed
+    /// attempt.
+    pub fn solve_imminent(&self) -> bool {
+        !self.deferred
+            && self.blocks.iter().any(|b| {
+                b.data.is_none()
+                    && (b.attempted || b.received.len() + 1 >= b.symbols + self.overhead)
+            })
+    }
+
+    /// Some block can be solved by [`ObjectDecoder::finalize_step`].
+    pub fn any_ready(&self) -> bool {
+        self.blocks.iter().any(|b| b.is_ready(self.overhead))
+    }
+
+    /// Share of blocks decoded so far, in percent.
+    pub fn progress(&self) -> u8 {
+        if self.blocks.is_empty() {
+            return 0;
+        }
+        let decoded = self.blocks.len() - self.remaining;
+        (decoded * 100 / self.blocks.len()) as u8
+    }
+
+    /// Distinct symbols received, counting at most `K` per block, and the
+    /// `K` of all blocks together: how far the transfer is from decodable.
+    pub fn symbol_counts(&self) -> (u32, u32) {
+        self.blocks.iter().fold((0, 0), |(received, needed), b| {
+            (
+                received + b.received.len().min(b.symbols),
+                needed + b.symbols,
+            )
+        })
+    }
+
+    /// Distinct symbols still to collect before every undecoded block
+    /// reaches its solve threshold; at least one for a block whose solve
+    /// fell short.
+    pub fn symbols_missing(&self) -> u32 {
+        self.blocks
+            .iter()
+            .filter(|b| b.data.is_none())
+            .map(|b| b.missing(self.overhead))
+            .sum()
+    }
+
+    /// The undecoded block with the most symbols still missing, the lowest
+    /// SBN among equals; `None` before the first symbol and once decoded.
+    pub fn lagging_block(&self) -> Option<u8> {
+        self.blocks
+            .iter()
+            .filter(|b| b.data.is_none())
+            .max_by_key(|b| (b.missing(self.overhead), Reverse(b.sbn)))
+            .map(|b| b.sbn)
+    }
+
+    /// Heap bytes held for the transfer, roughly: symbol arenas, buffered
+    /// and kept packets, decoded blocks, spare arenas and the result buffer,
+    /// plus a block's worth of symbols for each block decoder (which copies
+    /// what it is given).  The id sets and the solver's scratch space are
+    /// left out.
+    #[cfg(feature = "std")]
+    pub fn heap_bytes(&self) -> usize {
+        let symbol_size = usize::from(self.oti.symbol_size());
+        let blocks: usize = self
+            .blocks
+            .iter()
+            .map(|b| {
+                let packets = b.owned.len() + b.kept.len();
+                let decoder = if b.decoder.is_some() {
+                    b.symbols as usize
+                } else {
+                    0
+                };
+                b.arena.capacity()
+                    + b.esis.capacity() * core::mem::size_of::<u32>()
+                    + (packets + decoder) * symbol_size
+                    + b.data.as_ref().map_or(0, Vec::capacity)
+            })
+            .sum();
+        let spare: usize = self.spare.iter().map(Vec::capacity).sum();
+        blocks + spare + self.output.capacity()
+    }
+
+    /// Chance that solving every undecoded block with the distinct symbols
+    /// received so far succeeds, after the overhead model of RFC 6330: a
+    /// block with `K + h` symbols fails about once in `100^(h + 1)` solves,
+    /// and never succeeds with fewer than `K`.
+    pub fn solve_probability(&self) -> f32 {
+        if !self.built {
+            return 0.0;
+        }
+        self.blocks
+            .iter()
+            .filter(|b| b.data.is_none())
+            .map(|b| match b.received.len().checked_sub(b.symbols) {
+                // Beyond four extra symbols the failure rate is below what
+                // an `f32` next to one can show.
+                Some(extra) => 1.0 - (0..extra.min(4)).fold(0.01f32, |p, _| p * 0.01),
+                None => 0.0,
+            })
+            .product()
+    }
+
+    /// Solve attempts on every block so far, and the time they took.
+    pub fn solve_tally(&self) -> SolveTally {
+        self.blocks
+            .iter()
+            .fold(self.retired_solves, |tally, b| tally.merge(b.solves))
+    }
+
+    /// Per source block, the symbols it took and its solves; empty before
+    /// the first symbol, kept once the blocks are released.
+    pub fn block_timings(&self) -> Vec<RQBlockTiming> {
+        if self.blocks.is_empty() {
+            return self.retired_timings.clone();
+        }
+        self.blocks
+            .iter()
+            .map(|b| RQBlockTiming {
+                symbols: b.symbols,
+                symbols_received: b.received.len(),
+                solve_attempts: b.solves.attempts,
+                solve_ns: b.solves.ns,
+                // assembling the object takes the data of every block
+                decoded: b.data.is_some() || self.remaining == 0,
+            })
+            .collect()
+    }
+
+    /// Solve ready blocks one after the other until `budget` is spent (always
+    /// at least one), then assemble the object if none is left.  Without
+    /// `std` there is no clock and every ready block is solved.
+    pub fn finalize_step(&mut self, budget: Duration) -> Outcome {
+        let watch = Stopwatch::start();
+        let symbol_size = usize::from(self.oti.symbol_size());
+        let overhead = self.overhead;
+        for block in self.blocks.iter_mut() {
+            if self.cancel.is_cancelled() {
+                return Outcome::Cancelled;
+            }
+            if !block.is_ready(overhead) {
+                continue;
+            }
+            let packets = block.take_pending(symbol_size, &mut self.spare);
+            block.solve(&self.oti, packets, &self.log);
+            if block.data.is_some() {
+                self.remaining -= 1;
+            }
+            if watch.elapsed() >= budget {
+                break;
+            }
+        }
+        self.finish()
+    }
+
+    /// Extra symbols per block to collect before the first solve attempt.
+    pub fn set_overhead(&mut self, overhead: u32) {
+        self.overhead = overhead;
+    }
+
+    /// Verbosity and tag of the transfer's log lines.
+    pub fn log(&self) -> &LogScope {
+        &self.log
+    }
+
+    #[cfg(feature = "std")]
+    pub fn log_mut(&mut self) -> &mut LogScope {
+        &mut self.log
+    }
+
+    /// Track received symbols in a Bloom filter of `bits` bits per block
+    /// instead of an exact set, bounding memory for very long scans.  Symbols
+    /// already received are carried over.
+    pub fn set_bloom_dedupe(&mut self, bits: u32) {
+        self.bloom_bits = Some(bits);
+        let overhead = self.overhead;
+        for block in self.blocks.iter_mut() {
+            let seen = mem::replace(&mut block.received, Seen::new());
+            block.received = seen.into_bloom(bits, block.symbols + overhead);
+        }
+    }
+
+    /// Reserve, up front, every buffer this decoder will grow while scanning:
+    /// each block's arena and id set (for `K + overhead` symbols) and the
+    /// final result.  Returns `false` if an allocation failed; whatever was
+    /// reserved until then is kept.
+    pub fn preallocate(&mut self) -> bool {
+        #[cfg(feature = "faults")]
+        if crate::faults::fail_allocations() {
+            return false;
+        }
+        self.build();
+        let symbol_size = usize::from(self.oti.symbol_size());
+        for block in self.blocks.iter_mut().filter(|b| b.data.is_none()) {
+            let symbols = (block.symbols + self.overhead) as usize;
+            let pending = symbols.saturating_sub(block.esis.len() + block.owned.len());
+            if block
+                .arena
+                .try_reserve_exact(pending * symbol_size)
+                .is_err()
+                || block.esis.try_reserve_exact(pending).is_err()
+                || !block
+                    .received
+                    .try_reserve(symbols.saturating_sub(block.received.len() as usize))
+            {
+                return false;
+            }
+        }
+        usize::try_from(self.oti.transfer_length())
+            .is_ok_and(|len| self.output.try_reserve_exact(len).is_ok())
+    }
+
+    /// Drop every block decoder and buffered symbol once the result has been
+    /// handed out, and the spare arenas too unless `keep_spares` (pooled
+    /// contexts hold on to them for the next transfer).  Later packets are
+    /// rejected.
+    pub fn release(&mut self, keep_spares: bool) {
+        if self.zeroize {
+            self.wipe();
+        }
+        self.built = true;
+        self.retired_solves = self.solve_tally();
+        self.retired_timings = self.block_timings();
+        let blocks = mem::take(&mut self.blocks);
+        if keep_spares {
+            for mut block in blocks {
+                if block.arena.capacity() > 0 {
+                    block.arena.clear();
+                    self.spare.push(block.arena);
+                }
+            }
+        } else {
+            self.spare = Vec::new();
+        }
+        self.output = Vec::new();
+        #[cfg(feature = "std")]
+        {
+            self.spool = None;
+        }
+        self.remaining = 0;
+    }
+
+    #[cfg(all(test, feature = "encoder"))]
+    pub fn is_released(&self) -> bool {
+        self.built && self.blocks.is_empty()
+    }
+
+    /// Assemble the object in `spool` instead of in memory.
+    #[cfg(feature = "std")]
+    pub fn set_spool(&mut self, spool: Spool) {
+        self.spool = Some(spool);
+    }
+
+    #[cfg(feature = "std")]
+    pub fn spool(&self) -> Option<&Spool> {
+        self.spool.as_ref()
+    }
+
+    #[cfg(feature = "std")]
+    pub fn take_spool(&mut self) -> Option<Spool> {
+        self.spool.take()
+    }
+
+    /// Once cancelled, every packet and finalize step is turned away and
+    /// blocks are no longer written to the spool file.  Shared with the
+    /// app's `RQCancelToken`s.
+    pub fn cancel(&self) -> &Cancel {
+        &self.cancel
+    }
+
+    #[cfg(feature = "std")]
+    pub fn set_cancel(&mut self, cancel: Cancel) {
+        self.cancel = cancel;
+    }
+
+    /// Move decoded blocks still held in memory to the spool file, until
+    /// cancelled.
+    #[cfg(feature = "std")]
+    fn flush_to_spool(&mut self) -> std::io::Result<()> {
+        let spool = match self.spool.as_mut() {
+            Some(spool) => spool,
+            None => return Ok(()),
+        };
+        for block in self.blocks.iter_mut() {
+            if self.cancel.is_cancelled() {
+                return Err(std::io::ErrorKind::Interrupted.into());
+            }
+            if let Some(data) = block.data.as_mut().filter(|d| !d.is_empty()) {
+                spool.write_at(block.offset, data)?;
+                if block.zeroize {
+                    memory::wipe(data);
+                }
+                // An empty buffer still marks the block as decoded.
+                *data = Vec::new();
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of threads used to solve blocks that become ready together;
+    /// `0` restores the default.  Always one without `std`.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = if threads == 0 || cfg!(not(feature = "std")) {
+            default_threads()
+        } else {
+            threads
+        };
+    }
+
+    pub fn decode(&mut self, symbol: Symbol) -> Outcome {
+        if self.cancel.is_cancelled() {
+            return Outcome::Cancelled;
+        }
+        let sbn = usize::from(symbol.id().source_block_number());
+        let esi = symbol.id().encoding_symbol_id();
+        if sbn < usize::from(self.oti.source_blocks()) {
+            self.build();
+        }
+        let overhead = self.overhead;
+        let symbol_size = usize::from(self.oti.symbol_size());
+        let multi_block = self.blocks.len() > 1;
+        let contiguous = self.contiguous_len();
+        let zeroize = self.zeroize;
+        let block = match self.blocks.get_mut(sbn) {
+            Some(b) => b,
+            None => {
+                discard(symbol, zeroize);
+                return Outcome::Rejected;
+            }
+        };
+        // ESIs in [K, K') are padding symbols that are never transmitted.
+        let padding = raptorq::extended_source_block_symbols(block.symbols);
+        if (block.symbols..padding).contains(&esi) {
+            discard(symbol, zeroize);
+            return Outcome::Rejected;
+        }
+        if block.data.is_some() || !block.received.insert(esi) {
+            discard(symbol, zeroize);
+            // Nothing else would retry a failed spool write once every
+            // block is decoded.
+            #[cfg(feature = "std")]
+            if self.unflushed {
+                return match self.finish() {
+                    Outcome::Accepted => Outcome::Duplicate,
+                    outcome => outcome,
+                };
+            }
+            return Outcome::Duplicate;
+        }
+        if esi < block.symbols {
+            block.source_received += 1;
+        }
+        let threshold = block.symbols + overhead;
+        if block.source_received == block.symbols && !block.attempted {
+            block.store(symbol, threshold as usize, &mut self.spare);
+            if let Some(len) = contiguous {
+                let mut result = mem::take(&mut self.output);
+                block.assemble_contiguous(&mut result, len, symbol_size, &mut self.spare);
+                // An empty buffer still marks the block as decoded.
+                block.data = Some(Vec::new());
+                self.remaining = 0;
+                return Outcome::Complete(result);
+            }
+            block.data = Some(block.assemble_systematic(&self.oti, &mut self.spare));
+            self.remaining -= 1;
+            return self.finish();
+        }
+        if self.deferred {
+            block.store(symbol, threshold as usize, &mut self.spare);
+            return if self.all_ready() {
+                Outcome::Ready
+            } else {
+                Outcome::Accepted
+            };
+        }
+        let batched = self.threads > 1 && multi_block && !block.attempted;
+        if block.received.len() < threshold || batched {
+            block.store(symbol, threshold as usize, &mut self.spare);
+            if block.received.len() < threshold {
+                return Outcome::Accepted;
+            }
+            return self.solve_batch();
+        }
+        let mut packets = block.take_pending(symbol_size, &mut self.spare);
+        packets.push(symbol.into_packet());
+        block.solve(&self.oti, packets, &self.log);
+        if block.data.is_some() {
+            self.remaining -= 1;
+        }
+        self.finish()
+    }
+
+    /// Length of the object if it is one block of one sub‑block assembled
+    /// in memory, i.e. its source symbols back to back.
+    fn contiguous_len(&self) -> Option<usize> {
+        #[cfg(feature = "std")]
+        if self.spool.is_some() {
+            return None;
+        }
+        if self.blocks.len() != 1 || self.oti.sub_blocks() != 1 {
+            return None;
+        }
+        usize::try_from(self.oti.transfer_length()).ok()
+    }
+
+    /// Once every undecoded block has reached the threshold, run their first
+    /// solve in parallel.
+    fn solve_batch(&mut self) -> Outcome {
+        let threshold = |b: &Block| b.symbols + self.overhead;
+        if self
+            .blocks
+            .iter()
+            .any(|b| b.data.is_none() && b.received.len() < threshold(b))
+        {
+            return Outcome::Accepted;
+        }
+        let symbol_size = usize::from(self.oti.symbol_size());
+        let oti = self.oti;
+        let spare = &mut self.spare;
+        let log = &self.log;
+        let mut jobs: Vec<(&mut Block, Vec<EncodingPacket>)> = self
+            .blocks
+            .iter_mut()
+            .filter(|b| b.data.is_none() && !b.attempted)
+            .map(|b| {
+                let packets = b.take_pending(symbol_size, spare);
+                (b, packets)
+            })
+            .collect();
+        #[cfg(feature = "std")]
+        if self.threads > 1 && jobs.len() > 1 {
+            let per_thread = jobs.len().div_ceil(self.threads);
+            std::thread::scope(|scope| {
+                for chunk in jobs.chunks_mut(per_thread) {
+                    scope.spawn(move || solve_all(chunk, &oti, log));
+                }
+            });
+        } else {
+            // No thread for a single job; nor any to spawn in the browser.
+            solve_all(&mut jobs, &oti, log);
+        }
+        #[cfg(not(feature = "std"))]
+        solve_all(&mut jobs, &oti, log);
+        self.remaining = self.blocks.iter().filter(|b| b.data.is_none()).count();
+        self.finish()
+    }
+
+    /// Assemble the object once every block is decoded.
+    fn finish(&mut self) -> Outcome {
+        #[cfg(feature = "std")]
+        {
+            self.unflushed = self.flush_to_spool().is_err();
+            if self.unflushed {
+                return if self.cancel.is_cancelled() {
+                    Outcome::Cancelled
+                } else {
+                    Outcome::Failed
+                };
+            }
+        }
+        if self.remaining > 0 {
+            return Outcome::Accepted;
+        }
+        #[cfg(feature = "faults")]
+        crate::faults::delay_completion();
+        #[cfg(feature = "std")]
+        if let Some(spool) = self.spool.as_mut() {
+            return match spool.finish(self.oti.transfer_length()) {
+                Ok(()) => Outcome::CompleteOnDisk,
+                Err(_) => {
+                    self.unflushed = true;
+                    Outcome::Failed
+                }
+            };
+        }
+        let len = match usize::try_from(self.oti.transfer_length()) {
+            Ok(len) => len,
+            Err(_) => return Outcome::TooLarge,
+        };
+        // Capacity stays at exactly `len` (the padding of the last symbol is
+        // never copied), so handing the buffer out does not reallocate it.
+        let mut result = mem::take(&mut self.output);
+        result.reserve_exact(len);
+        for block in self.blocks.iter_mut() {
+            let mut data = block.data.take().unwrap_or_default();
+            let end = data.len().min(len - result.len());
+            result.extend_from_slice(&data[..end]);
+            if block.zeroize {
+                memory::wipe(&mut data);
+            }
+        }
+        Outcome::Complete(result)
+    }
+}
+
+impl Drop for ObjectDecoder {
+    fn drop(&mut self) {
+        if self.zeroize {
+            self.wipe();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use raptorq::Encoder;
+
+    #[test]
+    fn waits_for_overhead() {
+        let data: Vec<u8> = (0..1_920u32).map(|i| (i * 5) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        let mut decoder = ObjectDecoder::new(enc.get_config());
+        decoder.set_overhead(2);
+        let packets = enc.get_encoded_packets(4);
+        // K symbols (one of them a repair symbol) do not trigger the solve.
+        for p in &packets[1..31] {
+            assert_eq!(decoder.decode(Symbol::Owned(p.clone())), Outcome::Accepted);
+        }
+        assert_eq!(
+            decoder.decode(Symbol::Owned(packets[1].clone())),
+            Outcome::Duplicate
+        );
+        assert_eq!(
+            decoder.decode(Symbol::Borrowed(
+                packets[31].payload_id().clone(),
+                packets[31].data()
+            )),
+            Outcome::Accepted
+        );
+        assert!(decoder.decode(Symbol::Owned(packets[32].clone())) == Outcome::Complete(data));
+    }
+
+    #[test]
+    fn systematic_fast_path() {
+        let data: Vec<u8> = (0..50_000u32).map(|i| (i * 31 + i / 7) as u8).collect();
+        let mut builder = raptorq::EncoderBuilder::new();
+        builder.set_max_packet_size(256);
+        builder.set_decoder_memory_requirement(8 * 1024);
+        let enc = builder.build(&data);
+        assert!(enc.get_config().sub_blocks() > 1);
+        let mut decoder = ObjectDecoder::new(enc.get_config());
+        // The overhead wait does not apply when every source symbol is in.
+        decoder.set_overhead(5);
+        let mut outcome = Outcome::Accepted;
+        for (i, p) in enc.get_encoded_packets(0).into_iter().enumerate() {
+            outcome = if i % 2 == 0 {
+                decoder.decode(Symbol::Owned(p))
+            } else {
+                decoder.decode(Symbol::Borrowed(p.payload_id().clone(), p.data()))
+            };
+        }
+        assert!(decoder.blocks.iter().all(|b| !b.attempted));
+        assert!(outcome == Outcome::Complete(data));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn spooled_blocks() {
+        let data: Vec<u8> = (0..40_000u32).map(|i| (i * 13 + i / 300) as u8).collect();
+        let mut builder = raptorq::EncoderBuilder::new();
+        builder.set_max_packet_size(128);
+        builder.set_decoder_memory_requirement(4 * 1024);
+        let enc = builder.build(&data);
+        let dir = std::env::temp_dir().join(format!("rq-spool-{}", std::process::id()));
+        let mut decoder = ObjectDecoder::new(enc.get_config());
+        decoder.set_spool(Spool::create(&dir).unwrap());
+        let mut outcome = Outcome::Accepted;
+        for p in enc.get_encoded_packets(0) {
+            outcome = decoder.decode(Symbol::Owned(p));
+            // Decoded blocks never linger in memory.
+            assert!(decoder
+                .blocks
+                .iter()
+                .all(|b| b.data.as_ref().is_none_or(|d| d.is_empty())));
+        }
+        assert_eq!(outcome, Outcome::CompleteOnDisk);
+        assert_eq!(std::fs::read(&dir).unwrap(), data);
+        std::fs::remove_file(&dir).unwrap();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn spool_write_retried() {
+        let data: Vec<u8> = (0..40_000u32).map(|i| (i * 11 + i / 200) as u8).collect();
+        let mut builder = raptorq::EncoderBuilder::new();
+        builder.set_max_packet_size(128);
+        builder.set_decoder_memory_requirement(4 * 1024);
+        let enc = builder.build(&data);
+        let last = enc.get_config().source_blocks() - 1;
+        let path = std::env::temp_dir().join(format!("rq-retry-{}", std::process::id()));
+        let mut decoder = ObjectDecoder::new(enc.get_config());
+        decoder.set_spool(Spool::create(&path).unwrap());
+        let packets = enc.get_encoded_packets(0);
+        let (head, tail): (Vec<_>, Vec<_>) = packets
+            .into_iter()
+            .partition(|p| p.payload_id().source_block_number() != last);
+        for p in &head {
+            decoder.decode(Symbol::Borrowed(p.payload_id().clone(), p.data()));
+        }
+        // The last block decodes but cannot be written.
+        decoder.spool = Some(Spool::reopen(&path, false).unwrap());
+        let mut outcome = Outcome::Accepted;
+        for p in &tail {
+            outcome = decoder.decode(Symbol::Borrowed(p.payload_id().clone(), p.data()));
+        }
+        assert_eq!(outcome, Outcome::Failed);
+        assert!(decoder.blocks[usize::from(last)]
+            .data
+            .as_ref()
+            .is_some_and(|d| !d.is_empty()));
+        // Every block is decoded: a duplicate symbol retries the write.
+        let again = &tail[0];
+        let retry = |decoder: &mut ObjectDecoder| {
+            decoder.decode(Symbol::Borrowed(again.payload_id().clone(), again.data()))
+        };
+        assert_eq!(retry(&mut decoder), Outcome::Failed);
+        decoder.spool = Some(Spool::reopen(&path, true).unwrap());
+        assert_eq!(retry(&mut decoder), Outcome::CompleteOnDisk);
+        assert_eq!(retry(&mut decoder), Outcome::Duplicate);
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn symbols_reproduce_state() {
+        let data: Vec<u8> = (0..50_000u32).map(|i| (i * 7 + i / 11) as u8).collect();
+        let mut builder = raptorq::EncoderBuilder::new();
+        builder.set_max_packet_size(256);
+        builder.set_decoder_memory_requirement(8 * 1024);
+        let enc = builder.build(&data);
+        assert!(enc.get_config().source_blocks() > 1 && enc.get_config().sub_blocks() > 1);
+        let mut decoder = ObjectDecoder::new(enc.get_config());
+        decoder.set_keep_symbols(true);
+        let packets = enc.get_encoded_packets(0);
+        // All of block 0, which decodes, and every third symbol of block 1.
+        let pushed: Vec<&EncodingPacket> = packets
+            .iter()
+            .filter(|p| match p.payload_id().source_block_number() {
+                0 => true,
+                1 => p.payload_id().encoding_symbol_id() % 3 == 0,
+                _ => false,
+            })
+            .collect();
+        for p in &pushed {
+            decoder.decode(Symbol::Borrowed(p.payload_id().clone(), p.data()));
+        }
+        assert!(decoder.blocks[0].data.is_some());
+        let mut dumped = vec![];
+        assert!(decoder.symbols(|id, symbol| {
+            dumped.push(EncodingPacket::new(id.clone(), symbol.to_vec()))
+        }));
+        // Decoded blocks come back as their source symbols.
+        assert_eq!(dumped, pushed.into_iter().cloned().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn looping_sender_stores_nothing_twice() {
+        let data: Vec<u8> = (0..6_400u32).map(|i| (i * 19) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        let mut decoder = ObjectDecoder::new(enc.get_config());
+        decoder.set_overhead(10);
+        // Too few distinct symbols to solve, replayed like an animation loop.
+        let packets = &enc.get_encoded_packets(0)[1..60];
+        for _ in 0..50 {
+            for p in packets {
+                decoder.decode(Symbol::Borrowed(p.payload_id().clone(), p.data()));
+            }
+        }
+        let block = &decoder.blocks[0];
+        assert_eq!(block.esis.len(), packets.len());
+        assert_eq!(block.arena.len(), packets.len() * 64);
+        assert_eq!(block.received.len() as usize, packets.len());
+    }
+
+    #[test]
+    fn preallocated_decode() {
+        let data: Vec<u8> = (0..1_920u32).map(|i| (i * 3) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        let mut decoder = ObjectDecoder::new(enc.get_config());
+        assert!(decoder.preallocate());
+        let arena = decoder.blocks[0].arena.as_ptr();
+        let mut outcome = Outcome::Accepted;
+        for p in enc.get_encoded_packets(2).iter().skip(1) {
+            if outcome == Outcome::Accepted {
+                if decoder.blocks[0].data.is_none() && !decoder.blocks[0].attempted {
+                    // Buffering never moves the preallocated arena.
+                    assert_eq!(decoder.blocks[0].arena.as_ptr(), arena);
+                }
+                outcome = decoder.decode(Symbol::Borrowed(p.payload_id().clone(), p.data()));
+            }
+        }
+        assert!(outcome == Outcome::Complete(data));
+    }
+
+    #[test]
+    fn built_on_first_packet() {
+        let data: Vec<u8> = (0..1_920u32).map(|i| (i * 11) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        let mut decoder = ObjectDecoder::new(enc.get_config());
+        decoder.set_bloom_dedupe(1 << 10);
+        assert!(decoder.blocks.is_empty());
+        let packet = &enc.get_encoded_packets(0)[0];
+        let stray = EncodingPacket::new(PayloadId::new(7, 0), packet.data().to_vec());
+        assert_eq!(decoder.decode(Symbol::Owned(stray)), Outcome::Rejected);
+        assert!(decoder.blocks.is_empty());
+        assert_eq!(
+            decoder.decode(Symbol::Owned(packet.clone())),
+            Outcome::Accepted
+        );
+        assert_eq!(decoder.blocks.len(), 1);
+        assert!(matches!(decoder.blocks[0].received, Seen::Bloom(_)));
+    }
+
+    #[test]
+    fn exact_result_capacity() {
+        // Not a multiple of the symbol size: the last symbol is padded.
+        let data: Vec<u8> = (0..1_900u32).map(|i| (i * 9) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        let mut decoder = ObjectDecoder::new(enc.get_config());
+        let mut outcome = Outcome::Accepted;
+        for p in enc.get_encoded_packets(0) {
+            outcome = decoder.decode(Symbol::Owned(p));
+        }
+        match outcome {
+            Outcome::Complete(result) => {
+                assert!(result == data);
+                assert_eq!(result.capacity(), result.len());
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn small_transfer() {
+        let data: Vec<u8> = (0..3_001u32).map(|i| (i * 3) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 256);
+        let mut decoder = ObjectDecoder::new(enc.get_config());
+        let mut outcome = Outcome::Accepted;
+        for p in enc.get_encoded_packets(0).iter().rev() {
+            outcome = decoder.decode(Symbol::Borrowed(p.payload_id().clone(), p.data()));
+        }
+        assert_eq!(decoder.blocks.len(), 1);
+        assert!(decoder.blocks[0].decoder.is_none());
+        assert!(matches!(decoder.blocks[0].received, Seen::Small(_)));
+        match outcome {
+            Outcome::Complete(result) => {
+                assert!(result == data);
+                assert_eq!(result.capacity(), result.len());
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn bloom_dedupe() {
+        let data: Vec<u8> = (0..1_920u32).map(|i| (i * 7) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        let mut decoder = ObjectDecoder::new(enc.get_config());
+        let packets = enc.get_encoded_packets(4);
+        assert_eq!(
+            decoder.decode(Symbol::Owned(packets[1].clone())),
+            Outcome::Accepted
+        );
+        decoder.set_bloom_dedupe(1 << 10);
+        assert_eq!(
+            decoder.decode(Symbol::Owned(packets[1].clone())),
+            Outcome::Duplicate
+        );
+        let mut outcome = Outcome::Accepted;
+        for p in packets.iter().skip(2) {
+            if matches!(outcome, Outcome::Accepted | Outcome::Duplicate) {
+                outcome = decoder.decode(Symbol::Owned(p.clone()));
+            }
+        }
+        assert!(outcome == Outcome::Complete(data));
+    }
+
+    #[test]
+    fn deferred_finalize() {
+        let data: Vec<u8> = (0..40_000u32).map(|i| (i * 3 + i / 256) as u8).collect();
+        let mut builder = raptorq::EncoderBuilder::new();
+        builder.set_max_packet_size(128);
+        builder.set_decoder_memory_requirement(4 * 1024);
+        let enc = builder.build(&data);
+        let blocks = enc.get_block_encoders().len();
+        assert!(blocks > 2);
+        let mut decoder = ObjectDecoder::new(enc.get_config());
+        decoder.set_deferred(true);
+        let mut outcome = Outcome::Accepted;
+        for block in enc.get_block_encoders() {
+            let source = block.source_packets().into_iter().skip(1);
+            for p in source.chain(block.repair_packets(0, 1)) {
+                outcome = decoder.decode(Symbol::Owned(p));
+            }
+        }
+        assert_eq!(outcome, Outcome::Ready);
+        assert_eq!(decoder.progress(), 0);
+        // A zero budget solves one block per step.
+        for step in 1..blocks {
+            assert_eq!(decoder.finalize_step(Duration::ZERO), Outcome::Accepted);
+            assert_eq!(decoder.progress() as usize, step * 100 / blocks);
+        }
+        assert!(decoder.finalize_step(Duration::ZERO) == Outcome::Complete(data));
+        assert!(!decoder.any_ready());
+    }
+
+    #[test]
+    fn parallel_blocks() {
+        let data: Vec<u8> = (0..40_000u32).map(|i| (i * 7 + i / 256) as u8).collect();
+        let mut builder = raptorq::EncoderBuilder::new();
+        builder.set_max_packet_size(128);
+        builder.set_decoder_memory_requirement(4 * 1024);
+        let enc = builder.build(&data);
+        assert!(enc.get_block_encoders().len() > 2);
+        for threads in [1, 4] {
+            let mut decoder = ObjectDecoder::new(enc.get_config());
+            decoder.set_threads(threads);
+            let mut outcome = Outcome::Accepted;
+            // Drop the first source symbol of every block to force real solves.
+            for block in enc.get_block_encoders() {
+                for p in block.source_packets().into_iter().skip(1) {
+                    outcome = decoder.decode(Symbol::Owned(p));
+                }
+            }
+            for block in enc.get_block_encoders() {
+                for p in block.repair_packets(0, 2) {
+                    if !matches!(outcome, Outcome::Complete(_)) {
+                        outcome = decoder.decode(Symbol::Owned(p));
+                    }
+                }
+            }
+            assert!(outcome == Outcome::Complete(data.clone()));
+            // Solved blocks do not keep their decoder's symbol copies.
+            assert!(decoder.blocks.iter().all(|b| b.decoder.is_none()));
+        }
+    }
+}
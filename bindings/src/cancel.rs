@@ -0,0 +1,69 @@
+//! Cancellation of the work a context does away from the caller: the
+//! decode thread, blocks waiting to be written to an output file and the
+//! slices of a deferred finalize.  A single block's solve runs inside
+//! `raptorq` and cannot be interrupted, so cancelling takes effect at the
+//! next block boundary.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use alloc::sync::Arc;
+
+/// Opaque handle from `raptorq_ctx_cancel_token`, shared with the context
+/// it was taken from and released with [`raptorq_cancel_token_free`].
+pub struct RQCancelToken {
+    cancelled: AtomicBool,
+}
+
+/// A context's side of its [`RQCancelToken`]s.
+#[derive(Clone)]
+pub(crate) struct Cancel(Arc<RQCancelToken>);
+
+impl Default for Cancel {
+    fn default() -> Cancel {
+        Cancel(Arc::new(RQCancelToken {
+            cancelled: AtomicBool::new(false),
+        }))
+    }
+}
+
+impl Cancel {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// A new reference for the app, to give back to
+    /// [`raptorq_cancel_token_free`].
+    pub fn to_raw(&self) -> *const RQCancelToken {
+        Arc::into_raw(Arc::clone(&self.0))
+    }
+}
+
+/// Abort what the token's context is doing in the background: frames still
+/// queued for its decode thread are dropped, blocks not yet written to its
+/// output file stay unwritten and a finalize stops after the block it is
+/// solving.  The context then answers every push and finalize step with
+/// [`crate::RQStatus::Cancelled`]; a result recovered before stays
+/// available.  Callable from any thread, any number of times; a `NULL`
+/// token is ignored.
+///
+/// # Safety
+/// `token` must be `NULL` or a token not yet freed.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_cancel(token: *const RQCancelToken) {
+    if let Some(token) = token.as_ref() {
+        token.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Release a token from `raptorq_ctx_cancel_token`.  Tokens and their
+/// context may be freed in any order.  A `NULL` token is ignored.
+///
+/// # Safety
+/// `token` must be `NULL` or a token not yet freed; it must not be used
+/// afterwards.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_cancel_token_free(token: *const RQCancelToken) {
+    if !token.is_null() {
+        drop(Arc::from_raw(token));
+    }
+}
@@ -0,0 +1,121 @@
+//! Optional per‑frame CRC‑32 trailer.
+//!
+//! ```text
+//! [frame][crc32: u32 LE]
+//! ```
+//!
+//! The CRC is the zlib / `crc32fast` one (IEEE, reflected) over the frame as
+//! the encoder emitted it.  At 60 fps every scanned frame is checked, so on
+//! arm64 the ARMv8 CRC32 instructions do the work when the CPU has them,
+//! dispatched at runtime like `crc32fast`; elsewhere a lookup table does.
+
+#[cfg(feature = "encoder")]
+use alloc::vec::Vec;
+
+/// Length of the trailer.
+#[cfg(feature = "decoder")]
+pub(crate) const TRAILER_LEN: usize = 4;
+
+const TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn update_table(mut crc: u32, data: &[u8]) -> u32 {
+    for &b in data {
+        crc = TABLE[((crc ^ u32::from(b)) & 0xff) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "crc")]
+unsafe fn update_arm(mut crc: u32, data: &[u8]) -> u32 {
+    use core::arch::aarch64::{__crc32b, __crc32d};
+    let mut words = data.chunks_exact(8);
+    for word in &mut words {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(word);
+        crc = __crc32d(crc, u64::from_le_bytes(bytes));
+    }
+    for &b in words.remainder() {
+        crc = __crc32b(crc, b);
+    }
+    crc
+}
+
+/// Whether the CRC32 instructions can be used; without `std` only when the
+/// target guarantees them at compile time.
+#[cfg(target_arch = "aarch64")]
+fn has_arm_crc() -> bool {
+    #[cfg(feature = "std")]
+    return std::arch::is_aarch64_feature_detected!("crc");
+    #[cfg(not(feature = "std"))]
+    cfg!(target_feature = "crc")
+}
+
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    #[cfg(target_arch = "aarch64")]
+    {
+        if has_arm_crc() {
+            return !unsafe { update_arm(!0, data) };
+        }
+    }
+    !update_table(!0, data)
+}
+
+/// Append the trailer to `frame`.
+#[cfg(feature = "encoder")]
+pub(crate) fn seal(frame: &mut Vec<u8>) {
+    let crc = crc32(frame);
+    frame.extend_from_slice(&crc.to_le_bytes());
+}
+
+/// Check and strip the trailer; `None` if it is missing or does not match.
+#[cfg(feature = "decoder")]
+pub(crate) fn open(frame: &[u8]) -> Option<&[u8]> {
+    let body_len = frame.len().checked_sub(TRAILER_LEN)?;
+    let (body, trailer) = frame.split_at(body_len);
+    let mut expected = [0u8; TRAILER_LEN];
+    expected.copy_from_slice(trailer);
+    (crc32(body) == u32::from_le_bytes(expected)).then_some(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_reference_crc() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        let long: Vec<u8> = (0..1_000u32).map(|i| (i * 31) as u8).collect();
+        assert_eq!(crc32(&long), !update_table(!0, &long));
+    }
+
+    #[cfg(all(feature = "encoder", feature = "decoder"))]
+    #[test]
+    fn seal_and_open() {
+        let mut frame = b"frame".to_vec();
+        seal(&mut frame);
+        assert_eq!(open(&frame), Some(&b"frame"[..]));
+        frame[0] ^= 1;
+        assert_eq!(open(&frame), None);
+        assert_eq!(open(&frame[..3]), None);
+    }
+}
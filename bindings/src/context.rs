@@ -0,0 +1,1007 @@
+//! Decoding state behind the opaque `RQContext` pointer.
+
+use core::ptr;
+#[cfg(feature = "strict")]
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::{fence, AtomicUsize, Ordering};
+use core::time::Duration;
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard};
+
+use raptorq::{EncodingPacket, ObjectTransmissionInformation, PayloadId};
+
+use crate::blocks::{ObjectDecoder, Outcome, Symbol};
+use crate::checksum;
+
+use crate::events::{self, EventLog, RQEventKind};
+use crate::handshake::{self, Handshake};
+use crate::memory::{self, RQBuffer};
+use crate::notify::{Notifier, RQCompleteCallback, RQMilestoneCallback, RQProgressCallback};
+use crate::packing;
+use crate::profile::{Envelope, FramePrefix, RQProfile};
+use crate::rejected;
+#[cfg(feature = "std")]
+use crate::spool::Spool;
+use crate::stats::{
+    Defect, FrameTally, NoveltyRate, PushTimer, RQBlockTiming, RQDensityAdvice, RQEfficiency,
+    RQFrameQuality, RQStallWindow, RQStats, RecentFrames, ScanClock, SolveTally, StallTracker,
+    SymbolRate,
+};
+use crate::status::{RQErrorInfo, RQStatus};
+#[cfg(feature = "std")]
+use crate::worker::{Feed, Worker};
+
+/// Size of the serialized `PayloadId` in front of every symbol.
+const PACKET_HEADER_LEN: usize = 4;
+
+/// Symbols per block beyond `K` that [`RQContext::expected_frames`] allows
+/// for: RFC 6330 puts the chance of a failed solve with `K + 2` symbols
+/// below one in a million.
+const STANDARD_OVERHEAD: u32 = 2;
+
+/// Parse the packet header in place; the symbol stays borrowed.
+fn borrow_symbol(packet: &[u8]) -> Symbol<'_> {
+    let mut header = [0u8; PACKET_HEADER_LEN];
+    header.copy_from_slice(&packet[..PACKET_HEADER_LEN]);
+    Symbol::Borrowed(
+        PayloadId::deserialize(&header),
+        &packet[PACKET_HEADER_LEN..],
+    )
+}
+
+/// Where `part`, a subslice of `frame`, starts within it.
+fn offset_in(frame: &[u8], part: &[u8]) -> usize {
+    part.as_ptr() as usize - frame.as_ptr() as usize
+}
+
+/// Stamped into every live context with the `strict` feature and cleared
+/// when it is dropped.
+#[cfg(feature = "strict")]
+pub(crate) const CONTEXT_MAGIC: u32 = u32::from_be_bytes(*b"RQCX");
+
+/// Left behind in a context freed with the `strict` feature.
+#[cfg(feature = "strict")]
+pub(crate) const CONTEXT_FREED: u32 = u32::from_be_bytes(*b"RQfr");
+
+/// Generation of the next context created with the `strict` feature.
+#[cfg(feature = "strict")]
+static NEXT_GENERATION: AtomicU32 = AtomicU32::new(1);
+
+/// [`RQContext::entered`] while a call holds the context exclusively.
+const ENTERED_EXCLUSIVE: usize = usize::MAX;
+
+/// A call inside a context, see [`RQContext::enter_raw`].
+pub(crate) struct Entered<'a> {
+    count: &'a AtomicUsize,
+    exclusive: bool,
+}
+
+impl Drop for Entered<'_> {
+    fn drop(&mut self) {
+        if self.exclusive {
+            self.count.store(0, Ordering::Release);
+        } else {
+            self.count.fetch_sub(1, Ordering::Release);
+        }
+    }
+}
+
+pub struct RQContext {
+    #[cfg(feature = "strict")]
+    pub(crate) magic: u32,
+    #[cfg(feature = "strict")]
+    pub(crate) generation: u32, // tells contexts apart in misuse reports
+    pub(crate) oti: ObjectTransmissionInformation,
+    pub(crate) decoder: ObjectDecoder,
+    pub(crate) result: Option<Vec<u8>>, // populated when decoding finished
+    pub(crate) expected_digest: Option<[u8; 32]>, // from the handshake frame, if any
+    pub(crate) digest_mismatch: bool,
+    pub(crate) content_type: u16,
+    pub(crate) total_frames: u32, // 0 when unknown
+    pub(crate) prefix: FramePrefix,
+    pub(crate) envelope: Envelope,
+    pub(crate) padding_tolerant: bool,
+    pub(crate) packed_frames: bool,
+    pub(crate) frame_checksums: bool,
+    pub(crate) mlock_result: bool,
+    pub(crate) result_locked: bool, // the result's pages are pinned in RAM
+    on_disk: bool,                  // the object was assembled in the spool file
+    #[cfg(feature = "std")]
+    mapped: Option<Spool>, // the spool, kept when it is a mapping
+    pub(crate) taken: bool,         // the result was handed out and the decoder released
+    pub(crate) pooled: bool,
+    pub(crate) push_timer: PushTimer,
+    pub(crate) new_symbols: u64, // pushed symbols that were not duplicates
+    clock: ScanClock,
+    stall: StallTracker,
+    tally: FrameTally,
+    completion_tally: Option<FrameTally>, // `tally` as of completion
+    defect: Option<Defect>,               // of the frame being pushed, until counted
+    recent: RecentFrames,
+    symbol_rate: SymbolRate,
+    novelty: NoveltyRate,
+    events: EventLog,
+    solves_logged: SolveTally, // solves already in `events`
+    notifier: Notifier,
+    pub(crate) refs: AtomicUsize, // `raptorq_ctx_retain` references
+    pub(crate) entered: Box<AtomicUsize>, // calls inside, see `enter_raw`
+    #[cfg(feature = "std")]
+    pub(crate) lock: Option<Box<Mutex<()>>>, // `raptorq_ctx_new_threadsafe`
+    #[cfg(feature = "std")]
+    pub(crate) worker: Option<Worker>, // decode thread holding the real state
+    #[cfg(feature = "std")]
+    pub(crate) feed: Box<Feed>, // its queue, see `Feed`
+    #[cfg(feature = "std")]
+    pub(crate) isolate_safe: bool, // `raptorq_ctx_new_isolate_safe`
+}
+
+impl RQContext {
+    pub(crate) fn new(oti: ObjectTransmissionInformation) -> RQContext {
+        RQContext::with_decoder(oti, ObjectDecoder::new(oti))
+    }
+
+    fn with_decoder(oti: ObjectTransmissionInformation, decoder: ObjectDecoder) -> RQContext {
+        rq_log!(
+            Info,
+            "context created transfer_length={} symbol_size={} blocks={}",
+            oti.transfer_length(),
+            oti.symbol_size(),
+            oti.source_blocks()
+        );
+        let mut ctx = RQContext {
+            #[cfg(feature = "strict")]
+            magic: CONTEXT_MAGIC,
+            #[cfg(feature = "strict")]
+            generation: NEXT_GENERATION.fetch_add(1, Ordering::Relaxed),
+            oti,
+            decoder,
+            result: None,
+            expected_digest: None,
+            digest_mismatch: false,
+            content_type: 0,
+            total_frames: 0,
+            prefix: FramePrefix::None,
+            envelope: Envelope::None,
+            padding_tolerant: false,
+            packed_frames: false,
+            frame_checksums: false,
+            mlock_result: false,
+            result_locked: false,
+            on_disk: false,
+            #[cfg(feature = "std")]
+            mapped: None,
+            taken: false,
+            pooled: false,
+            push_timer: PushTimer::default(),
+            new_symbols: 0,
+            clock: ScanClock::default(),
+            stall: StallTracker::default(),
+            tally: FrameTally::default(),
+            completion_tally: None,
+            defect: None,
+            recent: RecentFrames::default(),
+            symbol_rate: SymbolRate::default(),
+            novelty: NoveltyRate::default(),
+            events: EventLog::default(),
+            solves_logged: SolveTally::default(),
+            notifier: Notifier::default(),
+            refs: AtomicUsize::new(1),
+            entered: Box::default(),
+            #[cfg(feature = "std")]
+            lock: None,
+            #[cfg(feature = "std")]
+            worker: None,
+            #[cfg(feature = "std")]
+            feed: Box::default(),
+            #[cfg(feature = "std")]
+            isolate_safe: false,
+        };
+        ctx.log_event(RQEventKind::OtiLearned, RQStatus::Ok, oti.transfer_length());
+        ctx
+    }
+
+    pub(crate) fn from_handshake(hs: &Handshake) -> RQContext {
+        let mut ctx = RQContext::new(hs.oti);
+        ctx.expected_digest = Some(hs.digest);
+        ctx.content_type = hs.content_type;
+        ctx.total_frames = hs.total_frames;
+        ctx
+    }
+
+    pub(crate) fn with_profile(profile: RQProfile, transfer_length: u64) -> RQContext {
+        let oti = ObjectTransmissionInformation::with_defaults(
+            transfer_length,
+            profile.max_payload_size(),
+        );
+        let mut ctx = RQContext::new(oti);
+        ctx.prefix = profile.prefix();
+        ctx.envelope = profile.envelope();
+        ctx
+    }
+
+    /// Start over for a new transfer, keeping the buffers allocated so far.
+    pub(crate) fn recycle(mut self, oti: ObjectTransmissionInformation) -> RQContext {
+        let decoder = ObjectDecoder::with_spares(oti, self.decoder.take_spares());
+        RQContext::with_decoder(oti, decoder)
+    }
+
+    /// A deep copy with the same symbols, options and result, e.g. to try a
+    /// finalize on the side.  `None` while a decode thread runs or when the
+    /// object is assembled in a file, neither of which can be shared.
+    pub(crate) fn try_clone(&self) -> Option<RQContext> {
+        #[cfg(feature = "std")]
+        if self.worker.is_some() {
+            return None;
+        }
+        if self.on_disk {
+            return None;
+        }
+        let mut ctx = RQContext::with_decoder(self.oti, self.decoder.try_clone()?);
+        ctx.result = self.result.clone();
+        ctx.expected_digest = self.expected_digest;
+        ctx.digest_mismatch = self.digest_mismatch;
+        ctx.content_type = self.content_type;
+        ctx.total_frames = self.total_frames;
+        ctx.prefix = self.prefix;
+        ctx.envelope = self.envelope;
+        ctx.padding_tolerant = self.padding_tolerant;
+        ctx.packed_frames = self.packed_frames;
+        ctx.frame_checksums = self.frame_checksums;
+        ctx.mlock_result = self.mlock_result;
+        ctx.taken = self.taken;
+        ctx.push_timer = self.push_timer.clone();
+        ctx.new_symbols = self.new_symbols;
+        ctx.clock = self.clock;
+        ctx.stall = self.stall;
+        ctx.tally = self.tally;
+        ctx.completion_tally = self.completion_tally;
+        ctx.recent = self.recent;
+        ctx.symbol_rate = self.symbol_rate;
+        ctx.novelty = self.novelty;
+        ctx.events = self.events.clone();
+        ctx.solves_logged = self.solves_logged;
+        #[cfg(feature = "std")]
+        if self.lock.is_some() {
+            ctx.lock = Some(Box::new(Mutex::new(())));
+        }
+        #[cfg(feature = "std")]
+        {
+            ctx.isolate_safe = self.isolate_safe;
+        }
+        ctx.lock_result();
+        Some(ctx)
+    }
+
+    /// Take the lock of a thread‑safe context.  Reads the field through the
+    /// raw pointer so no reference to the context exists before the lock is
+    /// held; the lock lives in its own allocation.
+    ///
+    /// # Safety
+    /// `ctx` must point to a live context.
+    #[cfg(feature = "std")]
+    pub(crate) unsafe fn lock_raw<'a>(ctx: *const RQContext) -> Option<MutexGuard<'a, ()>> {
+        let lock: &'a Option<Box<Mutex<()>>> = &*ptr::addr_of!((*ctx).lock);
+        lock.as_deref()
+            .map(|lock| lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+
+    /// The queue of the context's decode thread, for the enqueue calls.
+    /// Reads the field through the raw pointer like
+    /// [`RQContext::lock_raw`]: a poll on another thread may be moving the
+    /// decoding state back into the context meanwhile.
+    ///
+    /// # Safety
+    /// `ctx` must point to a live context.
+    #[cfg(feature = "std")]
+    pub(crate) unsafe fn feed_raw<'a>(ctx: *const RQContext) -> &'a Feed {
+        &*ptr::addr_of!((*ctx).feed)
+    }
+
+    /// Mark the context as in use by the calling thread: exclusively for a
+    /// call that changes it, shared with other queries for one that only
+    /// reads it.  `None` when a conflicting call is still inside it on
+    /// another thread, where going ahead would be a data race.  Reads the
+    /// field through the raw pointer like [`RQContext::lock_raw`]; the count
+    /// lives in its own allocation too.
+    ///
+    /// # Safety
+    /// `ctx` must point to a live context.
+    pub(crate) unsafe fn enter_raw<'a>(
+        ctx: *const RQContext,
+        exclusive: bool,
+    ) -> Option<Entered<'a>> {
+        let count: &'a AtomicUsize = &*ptr::addr_of!((*ctx).entered);
+        let ok = if exclusive {
+            count
+                .compare_exchange(0, ENTERED_EXCLUSIVE, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        } else {
+            count
+                .fetch_update(Ordering::Acquire, Ordering::Relaxed, |n| {
+                    (n < ENTERED_EXCLUSIVE - 1).then(|| n + 1)
+                })
+                .is_ok()
+        };
+        // Not `then_some`: dropping a guard built for nothing would release.
+        ok.then(|| Entered { count, exclusive })
+    }
+
+    /// Move the context out of the allocation behind `ctx` for good, to
+    /// drop it or keep it elsewhere.  With `strict` the allocation is never
+    /// freed but left poisoned (a few hundred bytes per context), so a later
+    /// call through a stale pointer finds [`CONTEXT_FREED`] rather than
+    /// whatever reused the memory.
+    ///
+    /// # Safety
+    /// `ctx` must be a live context from `Box::into_raw`, not used
+    /// afterwards.
+    pub(crate) unsafe fn take_raw(ctx: *mut RQContext) -> RQContext {
+        #[cfg(feature = "strict")]
+        {
+            let taken = ptr::read(ctx);
+            ptr::write_volatile(ptr::addr_of_mut!((*ctx).magic), CONTEXT_FREED);
+            taken
+        }
+        #[cfg(not(feature = "strict"))]
+        *Box::from_raw(ctx)
+    }
+
+    pub(crate) fn retain(&self) {
+        self.refs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Drop one reference; `true` when it was the last and the context must
+    /// be destroyed.
+    pub(crate) fn release(&self) -> bool {
+        if self.refs.fetch_sub(1, Ordering::Release) != 1 {
+            return false;
+        }
+        // Everything the other holders did happens before the destruction.
+        fence(Ordering::Acquire);
+        true
+    }
+
+    /// Length of one serialized encoding packet for this transfer.
+    pub(crate) fn packet_len(&self) -> usize {
+        PACKET_HEADER_LEN + usize::from(self.oti.symbol_size())
+    }
+
+    /// Check the CRC trailer if enabled, then strip the profile's envelope
+    /// and prefix, leaving the encoding packet.  [`RQStatus::Malformed`],
+    /// with the stage that failed as the last error, if any of them fails.
+    fn unwrap_frame<'a>(&mut self, frame: &'a [u8]) -> Result<&'a [u8], RQStatus> {
+        let malformed = RQErrorInfo::new(RQStatus::Malformed);
+        let body = if self.frame_checksums {
+            match checksum::open(frame) {
+                Some(body) => body,
+                None if frame.len() < checksum::TRAILER_LEN => {
+                    self.defect = Some(Defect::Truncated);
+                    return Err(rejected(
+                        "frame shorter than its checksum",
+                        malformed.lengths(checksum::TRAILER_LEN, frame.len()),
+                    ));
+                }
+                None => {
+                    self.defect = Some(Defect::Checksum);
+                    let trailer = frame.len() - checksum::TRAILER_LEN;
+                    return Err(rejected(
+                        "frame checksum mismatch",
+                        malformed.offset(trailer),
+                    ));
+                }
+            }
+        } else {
+            frame
+        };
+        self.defect = Some(Defect::Other);
+        let inner = self
+            .envelope
+            .open(body)
+            .ok_or_else(|| rejected("frame envelope malformed", malformed.offset(0)))?;
+        let packet = self
+            .prefix
+            .strip(inner, self.oti.transfer_length())
+            .ok_or_else(|| {
+                let offset = offset_in(frame, inner);
+                rejected(
+                    "frame prefix does not match the transfer",
+                    malformed.offset(offset),
+                )
+            })?;
+        self.defect = None;
+        Ok(packet)
+    }
+
+    /// [`RQContext::unwrap_frame`], then [`RQContext::trim_packet`]: the
+    /// encoding packet in `frame` and whether padding was removed.
+    fn locate_packet<'a>(&mut self, frame: &'a [u8]) -> Result<(&'a [u8], bool), RQStatus> {
+        let inner = self.unwrap_frame(frame)?;
+        self.trim_packet(inner).ok_or_else(|| {
+            self.defect = Some(Defect::of_length(inner.len(), self.packet_len()));
+            let info = RQErrorInfo::new(RQStatus::Malformed)
+                .lengths(self.packet_len(), inner.len())
+                .offset(offset_in(frame, inner));
+            rejected("frame length does not match the packet size", info)
+        })
+    }
+
+    /// Check the packet length, trimming zero padding when tolerated.
+    /// Returns the packet and whether padding was removed.
+    fn trim_packet<'a>(&self, packet: &'a [u8]) -> Option<(&'a [u8], bool)> {
+        let expected = self.packet_len();
+        if packet.len() == expected {
+            return Some((packet, false));
+        }
+        if self.padding_tolerant
+            && packet.len() > expected
+            && packet[expected..].iter().all(|b| *b == 0)
+        {
+            return Some((&packet[..expected], true));
+        }
+        None
+    }
+
+    pub(crate) fn push(&mut self, frame: &[u8]) -> RQStatus {
+        let before = self.new_symbols;
+        let status = self.push_unlogged(frame);
+        self.count_frame(status, frame.len(), before);
+        status
+    }
+
+    /// Log a pushed frame and count it for the stall window and
+    /// [`RQStats`]; `before` is [`RQContext::new_symbols`] before the push.
+    fn count_frame(&mut self, status: RQStatus, len: usize, before: u64) {
+        let fresh = self.new_symbols != before;
+        // Frames arriving means the app is scanning again.
+        self.clock.resume();
+        let now = self.clock.now();
+        self.stall.record(fresh, now);
+        let defect = self.defect.take();
+        // Only the arrival: the outcome is logged below like any other, so
+        // a first frame that failed is not counted as two errors.
+        if self.tally.frames() == 0 {
+            self.log_event(RQEventKind::FirstFrame, RQStatus::Ok, len as u64);
+        }
+        self.tally.record(status, len, fresh, defect);
+        if fresh {
+            self.log_solves(status);
+        }
+        self.log_outcome(status, len as u64);
+        self.note_completion(status);
+        self.recent.record(status, fresh, defect);
+        self.symbol_rate.record(self.new_symbols - before, now);
+        self.novelty.record(self.new_symbols - before, now);
+        self.log_frame(status, len);
+    }
+
+    /// Accepted frames are traced, everything else is worth a look when
+    /// debugging a scan.
+    fn log_frame(&self, status: RQStatus, len: usize) {
+        let log = self.decoder.log();
+        match status {
+            RQStatus::Ok | RQStatus::PaddingStripped | RQStatus::ReadyToFinalize => {
+                rq_log!(in log, Trace, "frame accepted status={:?} len={}", status, len)
+            }
+            RQStatus::Failed => rq_log!(in log, Error, "frame failed len={}", len),
+            _ => rq_log!(in log, Debug, "frame rejected status={:?} len={}", status, len),
+        }
+    }
+
+    fn push_unlogged(&mut self, frame: &[u8]) -> RQStatus {
+        if self.digest_mismatch {
+            return RQStatus::DigestMismatch;
+        }
+        if self.is_complete() || self.taken || handshake::is_handshake(frame) {
+            return RQStatus::Ignored;
+        }
+        if !self.packed_frames {
+            return match self.locate_packet(frame) {
+                Ok((packet, padded)) => self.push_packet(borrow_symbol(packet), padded),
+                Err(status) => status,
+            };
+        }
+        let inner = match self.unwrap_frame(frame) {
+            Ok(p) => p,
+            Err(status) => return status,
+        };
+        let (packets, padded) = match packing::split(inner, self.padding_tolerant) {
+            Some(p) => p,
+            None => {
+                self.defect = Some(Defect::Other);
+                let info = RQErrorInfo::new(RQStatus::Malformed).offset(offset_in(frame, inner));
+                return rejected("packed frame malformed", info);
+            }
+        };
+        let mut status = RQStatus::Ok;
+        for packet in packets {
+            if packet.len() != self.packet_len() {
+                self.defect = Some(Defect::of_length(packet.len(), self.packet_len()));
+                let info = RQErrorInfo::new(RQStatus::Malformed)
+                    .lengths(self.packet_len(), packet.len())
+                    .offset(offset_in(frame, packet));
+                return rejected("packed packet length does not match the packet size", info);
+            }
+            status = self.push_packet(borrow_symbol(packet), padded);
+            if !matches!(
+                status,
+                RQStatus::Ok | RQStatus::PaddingStripped | RQStatus::ReadyToFinalize
+            ) {
+                break;
+            }
+        }
+        status
+    }
+
+    /// Like [`RQContext::push`], but takes over the frame's allocation.  The
+    /// packet is located inside the frame as for a borrowed push (checksum,
+    /// envelope, prefix and padding), then the symbol is moved to the front
+    /// of the frame's buffer and the rest cut off, so that buffer becomes the
+    /// symbol storage without a new allocation.  `raptorq` wants the symbol
+    /// at the start of its own `Vec`, so the move (a `memmove` of the symbol
+    /// within the buffer) cannot be avoided.  Packed frames hold several
+    /// symbols and take the copying path.
+    pub(crate) fn push_owned(&mut self, frame: Vec<u8>) -> RQStatus {
+        let frame_len = frame.len();
+        let before = self.new_symbols;
+        let status = self.push_owned_unlogged(frame);
+        self.count_frame(status, frame_len, before);
+        status
+    }
+
+    fn push_owned_unlogged(&mut self, mut frame: Vec<u8>) -> RQStatus {
+        if self.packed_frames
+            || self.digest_mismatch
+            || self.is_complete()
+            || self.taken
+            || handshake::is_handshake(&frame)
+        {
+            let status = self.push_unlogged(&frame);
+            self.discard(frame);
+            return status;
+        }
+        let located = self
+            .locate_packet(&frame)
+            .map(|(packet, padded)| (offset_in(&frame, packet), packet.len(), padded));
+        let (start, len, padded) = match located {
+            Ok(located) => located,
+            Err(status) => {
+                self.discard(frame);
+                return status;
+            }
+        };
+        let mut header = [0u8; PACKET_HEADER_LEN];
+        header.copy_from_slice(&frame[start..start + PACKET_HEADER_LEN]);
+        frame.truncate(start + len);
+        frame.drain(..start + PACKET_HEADER_LEN);
+        let packet = EncodingPacket::new(PayloadId::deserialize(&header), frame);
+        self.push_packet(Symbol::Owned(packet), padded)
+    }
+
+    /// Free a handed‑over frame that was not kept, wiping it first if
+    /// zeroizing.
+    fn discard(&self, mut frame: Vec<u8>) {
+        if self.decoder.zeroizes() {
+            memory::wipe(&mut frame);
+        }
+    }
+
+    fn push_packet(&mut self, packet: Symbol, padded: bool) -> RQStatus {
+        let (sbn, esi) = (
+            packet.id().source_block_number(),
+            packet.id().encoding_symbol_id(),
+        );
+        match self.decoder.decode(packet) {
+            Outcome::Rejected => {
+                self.defect = Some(Defect::Other);
+                let info = RQErrorInfo::new(RQStatus::Malformed).symbol(sbn, esi);
+                rejected("symbol outside the transfer", info)
+            }
+            outcome => self.settle(outcome, padded),
+        }
+    }
+
+    /// Run one bounded slice of a deferred final solve.  `Ignored` when there
+    /// is nothing to solve until more frames arrive.
+    pub(crate) fn finalize_step(&mut self, budget: Duration) -> RQStatus {
+        if self.digest_mismatch {
+            return RQStatus::DigestMismatch;
+        }
+        if self.is_complete() {
+            return RQStatus::Complete;
+        }
+        if self.decoder.cancel().is_cancelled() {
+            return RQStatus::Cancelled;
+        }
+        if self.taken || !self.decoder.any_ready() {
+            return RQStatus::Ignored;
+        }
+        let outcome = self.decoder.finalize_step(budget);
+        let status = self.settle(outcome, false);
+        self.log_solves(status);
+        self.log_outcome(status, 0);
+        self.note_completion(status);
+        status
+    }
+
+    pub(crate) fn events(&self) -> &EventLog {
+        &self.events
+    }
+
+    fn log_event(&mut self, kind: RQEventKind, status: RQStatus, value: u64) {
+        let at = self.clock.since_creation();
+        self.events.record(kind, status, at, value);
+    }
+
+    /// Log the solves run since the last logged ones, if any.
+    fn log_solves(&mut self, status: RQStatus) {
+        let tally = self.decoder.solve_tally();
+        if tally.attempts > self.solves_logged.attempts {
+            let at = self.clock.since_creation();
+            self.events.record_solves(
+                tally.attempts - self.solves_logged.attempts,
+                tally.ns - self.solves_logged.ns,
+                status,
+                at,
+            );
+            self.solves_logged = tally;
+        }
+    }
+
+    /// Log a call ending in completion or an error; `value` is the length
+    /// of its frame.
+    fn log_outcome(&mut self, status: RQStatus, value: u64) {
+        if status == RQStatus::Complete && self.completion_tally.is_none() {
+            self.log_event(RQEventKind::Complete, status, self.oti.transfer_length());
+        } else if events::is_error(status) {
+            self.log_event(RQEventKind::Error, status, value);
+        }
+    }
+
+    /// Keep the frame counts as of completion for
+    /// [`RQContext::efficiency`].
+    fn note_completion(&mut self, status: RQStatus) {
+        if status == RQStatus::Complete && self.completion_tally.is_none() {
+            self.completion_tally = Some(self.tally);
+        }
+    }
+
+    /// Distinct symbols a transfer normally takes: `K` over all blocks,
+    /// plus the standard overhead (or the decode overhead, if larger) per
+    /// block.  Known from the OTI alone.
+    pub(crate) fn expected_frames(&self) -> u32 {
+        let k = self.source_symbols();
+        let overhead = self.decoder.options().overhead.max(STANDARD_OVERHEAD);
+        let total = k + u64::from(self.oti.source_blocks()) * u64::from(overhead);
+        u32::try_from(total).unwrap_or(u32::MAX)
+    }
+
+    /// `K` over all blocks.
+    fn source_symbols(&self) -> u64 {
+        let symbol_size = u64::from(self.oti.symbol_size());
+        self.oti.transfer_length().div_ceil(symbol_size)
+    }
+
+    /// The efficiency of the transfer, once it completed.
+    pub(crate) fn efficiency(&self) -> Option<RQEfficiency> {
+        let minimum = u32::try_from(self.source_symbols()).unwrap_or(u32::MAX);
+        self.completion_tally.map(|tally| tally.efficiency(minimum))
+    }
+
+    /// Chance that a solve with the symbols buffered now succeeds; certain
+    /// once the object was recovered, nil after a digest mismatch.
+    pub(crate) fn solve_probability(&self) -> f32 {
+        if self.digest_mismatch {
+            return 0.0;
+        }
+        if self.is_complete() || self.taken {
+            return 1.0;
+        }
+        self.decoder.solve_probability()
+    }
+
+    /// Minimum interval between pushes the app should keep to; zero once
+    /// no more frames are needed.
+    pub(crate) fn pacing_hint(&self) -> Duration {
+        if self.is_complete() || self.taken || self.digest_mismatch {
+            return Duration::ZERO;
+        }
+        self.push_timer.pacing_hint(self.decoder.solve_imminent())
+    }
+
+    pub(crate) fn stats(&self) -> RQStats {
+        self.tally
+            .snapshot(self.decoder.solve_tally(), self.clock.now())
+    }
+
+    /// Heap bytes held, roughly; see [`ObjectDecoder::heap_bytes`].
+    #[cfg(feature = "std")]
+    pub(crate) fn heap_bytes(&self) -> usize {
+        self.decoder.heap_bytes() + self.result.as_ref().map_or(0, Vec::capacity)
+    }
+
+    /// Stop the scanning clock, see `raptorq_ctx_pause`.
+    pub(crate) fn pause(&mut self) {
+        self.clock.pause();
+    }
+
+    pub(crate) fn resume(&mut self) {
+        self.clock.resume();
+    }
+
+    pub(crate) fn frame_quality(&self) -> RQFrameQuality {
+        self.tally.quality()
+    }
+
+    pub(crate) fn block_timings(&self) -> Vec<RQBlockTiming> {
+        self.decoder.block_timings()
+    }
+
+    /// Density advice from the recent frames, while more are needed.
+    pub(crate) fn density_advice(&self) -> RQDensityAdvice {
+        if self.is_complete() || self.taken || self.digest_mismatch {
+            return RQDensityAdvice::Keep;
+        }
+        self.recent.advice()
+    }
+
+    /// Everything the statistics calls report, as one JSON object for
+    /// analytics events and bug reports.
+    pub(crate) fn stats_json(&self) -> String {
+        let stats = self.stats();
+        let push = self.push_timer.snapshot();
+        let quality = self.frame_quality();
+        let eta = match self.eta() {
+            Some(eta) => format!("{}", eta.as_millis()),
+            None => String::from("null"),
+        };
+        format!(
+            concat!(
+                "{{\"frames\":{},\"unique_frames\":{},\"duplicate_frames\":{},",
+                "\"malformed_frames\":{},\"bytes\":{},\"solve_attempts\":{},",
+                "\"solve_ns\":{},\"elapsed_ms\":{},\"paused_ms\":{},\"paused\":{},",
+                "\"push\":{{\"pushes\":{},\"min_ns\":{},\"avg_ns\":{},\"max_ns\":{}}},",
+                "\"quality\":{{\"truncated_frames\":{},\"wrong_length_frames\":{},",
+                "\"checksum_failures\":{},\"other_malformed_frames\":{}}},",
+                "\"complete\":{},\"taken\":{},\"digest_mismatch\":{},",
+                "\"total_frames\":{},\"expected_frames\":{},\"eta_ms\":{},",
+                "\"solve_probability\":{}}}"
+            ),
+            stats.frames,
+            stats.unique_frames,
+            stats.duplicate_frames,
+            stats.malformed_frames,
+            stats.bytes,
+            stats.solve_attempts,
+            stats.solve_ns,
+            stats.elapsed_ms,
+            self.clock.paused().as_millis(),
+            self.clock.is_paused(),
+            push.pushes,
+            push.min_ns,
+            push.avg_ns,
+            push.max_ns,
+            quality.truncated_frames,
+            quality.wrong_length_frames,
+            quality.checksum_failures,
+            quality.other_malformed_frames,
+            self.is_complete(),
+            self.taken,
+            self.digest_mismatch,
+            self.total_frames,
+            self.expected_frames(),
+            eta,
+            self.solve_probability(),
+        )
+    }
+
+    /// Time left to collect the symbols still missing at the recent pace;
+    /// zero once no more frames are needed, `None` before a pace is known
+    /// (which takes two pushes with new symbols, by when the blocks are
+    /// built).
+    pub(crate) fn eta(&self) -> Option<Duration> {
+        if self.is_complete() || self.taken || self.digest_mismatch {
+            return Some(Duration::ZERO);
+        }
+        self.symbol_rate.eta(self.decoder.symbols_missing())
+    }
+
+    /// Smoothed new symbols per second, while more frames are needed.
+    pub(crate) fn novelty_rate(&self) -> f32 {
+        if self.is_complete() || self.taken || self.digest_mismatch {
+            return 0.0;
+        }
+        self.novelty.at(self.clock.now())
+    }
+
+    /// The block to linger on, while more frames are needed.
+    pub(crate) fn lagging_block(&self) -> Option<u8> {
+        if self.is_complete() || self.taken || self.digest_mismatch {
+            return None;
+        }
+        self.decoder.lagging_block()
+    }
+
+    /// No new symbol within `window`, while more are needed.
+    pub(crate) fn is_stalled(&self, window: RQStallWindow) -> bool {
+        if self.is_complete() || self.taken || self.digest_mismatch {
+            return false;
+        }
+        self.stall.is_stalled(window, self.clock.now())
+    }
+
+    /// Percentage of the final solve done so far.
+    pub(crate) fn finalize_progress(&self) -> u8 {
+        if self.is_complete() || self.taken {
+            100
+        } else {
+            self.decoder.progress()
+        }
+    }
+
+    /// Turn what the decoder did into a status, checking a finished object
+    /// against the handshake digest.
+    fn settle(&mut self, outcome: Outcome, padded: bool) -> RQStatus {
+        if self.notifier.wants_symbols() {
+            let (received, needed) = self.decoder.symbol_counts();
+            self.notifier.symbols(received, needed);
+        }
+        match outcome {
+            Outcome::Accepted | Outcome::Ready | Outcome::Complete(_) => self.new_symbols += 1,
+            #[cfg(feature = "std")]
+            Outcome::CompleteOnDisk => self.new_symbols += 1,
+            _ => {}
+        }
+        let mut data = match outcome {
+            Outcome::Complete(data) => data,
+            #[cfg(feature = "std")]
+            Outcome::CompleteOnDisk => return self.complete_on_disk(),
+            Outcome::Rejected => return RQStatus::Malformed,
+            #[cfg(feature = "std")]
+            Outcome::Failed => return RQStatus::Failed,
+            Outcome::TooLarge => return RQStatus::LengthOverflow,
+            Outcome::Cancelled => return RQStatus::Cancelled,
+            Outcome::Accepted | Outcome::Duplicate if padded => return RQStatus::PaddingStripped,
+            Outcome::Accepted | Outcome::Duplicate => return RQStatus::Ok,
+            Outcome::Ready => return RQStatus::ReadyToFinalize,
+        };
+        if let Some(expected) = self.expected_digest {
+            if handshake::digest(&data) != expected {
+                rq_log!(in self.decoder.log(), Warn, "digest mismatch len={}", data.len());
+                self.digest_mismatch = true;
+                if self.decoder.zeroizes() {
+                    memory::wipe(&mut data);
+                }
+                return RQStatus::DigestMismatch;
+            }
+        }
+        rq_log!(in self.decoder.log(), Info, "transfer complete len={}", data.len());
+        self.result = Some(data);
+        self.lock_result();
+        self.notify_complete();
+        RQStatus::Complete
+    }
+
+    /// Register the completion callback, calling it right away when the
+    /// object was already recovered (and not taken).
+    pub(crate) fn set_on_complete(&mut self, callback: RQCompleteCallback, user_data: usize) {
+        self.notifier.set_on_complete(callback, user_data);
+        if self.is_complete() {
+            self.notify_complete();
+        }
+    }
+
+    pub(crate) fn set_on_progress(&mut self, callback: RQProgressCallback, user_data: usize) {
+        self.notifier.set_on_progress(callback, user_data);
+    }
+
+    pub(crate) fn set_milestones(
+        &mut self,
+        percents: Vec<u8>,
+        callback: RQMilestoneCallback,
+        user_data: usize,
+    ) {
+        self.notifier.set_milestones(percents, callback, user_data);
+    }
+
+    /// Hand the result to the completion callback, if there is one.
+    fn notify_complete(&mut self) {
+        if !self.notifier.wants_result() {
+            return;
+        }
+        let result = match self.take_result() {
+            Some(data) => {
+                let mut len = 0;
+                RQBuffer::from_raw(unsafe { crate::into_raw_buffer(data, &mut len) }, len)
+            }
+            None => RQBuffer::from_raw(ptr::null_mut(), 0),
+        };
+        self.notifier.complete(result);
+    }
+
+    /// Pin the result in RAM if asked to.  The buffer is handed out as is
+    /// (its capacity is its length), so the pages stay pinned until the
+    /// caller frees it.
+    fn lock_result(&mut self) {
+        let data = match self.result.as_ref().filter(|_| self.mlock_result) {
+            Some(data) => data,
+            None => return,
+        };
+        self.result_locked = unsafe { memory::lock_raw(data.as_ptr(), data.capacity()) };
+        if !self.result_locked {
+            rq_log!(in self.decoder.log(), Warn, "mlock failed len={}", data.len());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn complete_on_disk(&mut self) -> RQStatus {
+        if let (Some(expected), Some(spool)) = (self.expected_digest, self.decoder.spool()) {
+            match spool.reader().and_then(handshake::digest_reader) {
+                Ok(digest) if digest == expected => {}
+                Ok(_) => {
+                    rq_log!(in self.decoder.log(), Warn, "digest mismatch on_disk=true");
+                    self.digest_mismatch = true;
+                    return RQStatus::DigestMismatch;
+                }
+                Err(error) => {
+                    rq_log!(in self.decoder.log(), Error, "spool read failed error={}", error);
+                    return RQStatus::Failed;
+                }
+            }
+        }
+        rq_log!(in self.decoder.log(), Info, "transfer complete on_disk=true");
+        self.on_disk = true;
+        // Nothing left to hand out in memory; the file or mapping is the
+        // result, and a mapping has to outlive the decoder.
+        self.mapped = self
+            .decoder
+            .take_spool()
+            .filter(|spool| spool.mapped_bytes().is_some());
+        self.decoder.release(self.pooled);
+        self.notify_complete();
+        RQStatus::Complete
+    }
+
+    /// The recovered object, when it was assembled in a memory mapping.
+    #[cfg(all(feature = "std", unix))]
+    pub(crate) fn mapped_result(&self) -> Option<&[u8]> {
+        self.mapped.as_ref().and_then(Spool::mapped_bytes)
+    }
+
+    /// Hand out the recovered object and free all decoding state with it.
+    pub(crate) fn take_result(&mut self) -> Option<Vec<u8>> {
+        let data = self.result.take()?;
+        self.taken = true;
+        self.decoder.release(self.pooled);
+        Some(data)
+    }
+
+    /// `true` while a recovered object is waiting to be collected.
+    pub(crate) fn is_complete(&self) -> bool {
+        self.result.is_some() || self.on_disk
+    }
+}
+
+impl Drop for RQContext {
+    fn drop(&mut self) {
+        // Volatile so a use after free has a chance to see it.
+        #[cfg(feature = "strict")]
+        unsafe {
+            ptr::write_volatile(&mut self.magic, 0)
+        };
+        let result = match self.result.as_mut() {
+            Some(result) => result,
+            None => return,
+        };
+        if self.result_locked {
+            unsafe { memory::unlock_raw(result.as_ptr(), result.capacity()) };
+        }
+        if self.decoder.zeroizes() {
+            memory::wipe(result);
+        }
+    }
+}
@@ -0,0 +1,1439 @@
+//! Decoder side of the C API: the `raptorq_ctx_*` functions operating on an
+//! opaque [`RQContext`].
+
+use core::ffi::c_void;
+#[cfg(feature = "std")]
+use core::ffi::{c_char, CStr};
+use core::ptr;
+use core::slice;
+use core::time::Duration;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use raptorq::ObjectTransmissionInformation;
+
+use crate::cancel::RQCancelToken;
+use crate::context::RQContext;
+use crate::handshake::Handshake;
+#[cfg(feature = "std")]
+use crate::log::RQLogLevel;
+use crate::memory::RQBuffer;
+use crate::notify::{RQCompleteCallback, RQMilestoneCallback, RQProgressCallback};
+use crate::profile::RQProfile;
+#[cfg(feature = "std")]
+use crate::spool::Spool;
+use crate::stats::{
+    RQBlockTiming, RQDensityAdvice, RQEfficiency, RQFrameQuality, RQPushStats, RQStallWindow,
+    RQStats, Stopwatch,
+};
+use crate::status::RQStatus;
+use crate::{
+    busy, check_ctx, check_transfer, checked_len, into_raw_buffer, invalid_argument,
+    slice_from_raw, try_catch_hot, try_catch_unwind,
+};
+
+/// Run `f` with exclusive access to `ctx`: under its lock for a context from
+/// [`raptorq_ctx_new_threadsafe`], directly otherwise (the app keeps calls
+/// from overlapping).  [`RQStatus::Busy`] instead when the app let a call
+/// on another thread overlap this one.
+pub(crate) unsafe fn with_ctx<R>(
+    ctx: *mut RQContext,
+    f: impl FnOnce(&mut RQContext) -> R,
+) -> Result<R, RQStatus> {
+    #[cfg(feature = "std")]
+    let _guard = RQContext::lock_raw(ctx);
+    let _entered = RQContext::enter_raw(ctx, true).ok_or_else(busy)?;
+    Ok(f(&mut *ctx))
+}
+
+/// [`with_ctx`] for queries, which may overlap one another.  While a decode
+/// thread runs they read the decoding state it holds, not the stand-in left
+/// in `ctx`.
+pub(crate) unsafe fn with_ctx_ref<R>(
+    ctx: *const RQContext,
+    f: impl FnOnce(&RQContext) -> R,
+) -> Result<R, RQStatus> {
+    #[cfg(feature = "std")]
+    let _guard = RQContext::lock_raw(ctx);
+    let _entered = RQContext::enter_raw(ctx, false).ok_or_else(busy)?;
+    #[cfg(feature = "std")]
+    return Ok((*ctx).with_state(f));
+    #[cfg(not(feature = "std"))]
+    Ok(f(&*ctx))
+}
+
+/// Build a [`RQContext`] from the raw **12‑byte** OTI header that the encoder
+/// usually embeds in its first QR frame.  Returns `NULL` for a `NULL` pointer
+/// or a header describing an empty object or zero‑sized symbols.
+///
+/// # Safety
+/// `oti_ptr` must be `NULL` or point to at least 12 readable bytes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_from_oti(oti_ptr: *const u8) -> *mut RQContext {
+    if oti_ptr.is_null() {
+        invalid_argument("OTI pointer is NULL");
+        return ptr::null_mut();
+    }
+    let mut buf = [0u8; 12];
+    buf.copy_from_slice(slice::from_raw_parts(oti_ptr, 12));
+    let oti = ObjectTransmissionInformation::deserialize(&buf);
+    if oti.transfer_length() == 0 || oti.symbol_size() == 0 {
+        invalid_argument("OTI header describes no data");
+        return ptr::null_mut();
+    }
+    try_catch_unwind(|| Box::into_raw(Box::new(RQContext::new(oti)))).unwrap_or(ptr::null_mut())
+}
+
+/// Convenience constructor when you **already know** the transfer length and
+/// the maximum payload size of your QR frames.
+///
+/// Like every constructor this is cheap: the per‑block decoding state is only
+/// built when the first frame for the transfer arrives (or on
+/// [`raptorq_ctx_preallocate`]).
+///
+/// Returns `NULL` for a zero `transfer_length` or `max_payload_size`, or a
+/// transfer longer than RaptorQ allows.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_ctx_new(transfer_length: u64, max_payload_size: u16) -> *mut RQContext {
+    if check_transfer(transfer_length, max_payload_size).is_err() {
+        return ptr::null_mut();
+    }
+    try_catch_unwind(|| {
+        let oti = ObjectTransmissionInformation::with_defaults(transfer_length, max_payload_size);
+        Box::into_raw(Box::new(RQContext::new(oti)))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Like [`raptorq_ctx_new`], for a context shared between threads (say the
+/// camera delegate queue and the UI thread): pushes, finalize steps, the
+/// status queries and [`raptorq_ctx_take_result`] lock the context, so they
+/// may be called concurrently.  Configure it with the `raptorq_ctx_set_*`
+/// functions before sharing it.  Requires `std`.
+///
+/// On any other context, a call overlapping one still running on another
+/// thread does nothing and reports [`RQStatus::Busy`] (or its usual
+/// `NULL`/`false`/`0`, with the reason in `raptorq_last_error`); status
+/// queries may overlap one another.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_ctx_new_threadsafe(
+    transfer_length: u64,
+    max_payload_size: u16,
+) -> *mut RQContext {
+    if check_transfer(transfer_length, max_payload_size).is_err() {
+        return ptr::null_mut();
+    }
+    try_catch_unwind(|| {
+        let oti = ObjectTransmissionInformation::with_defaults(transfer_length, max_payload_size);
+        let mut ctx = RQContext::new(oti);
+        ctx.lock = Some(Box::default());
+        Box::into_raw(Box::new(ctx))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Like [`raptorq_ctx_new_threadsafe`], for Flutter apps binding the library
+/// with `dart:ffi`: isolates may share the context whichever threads they
+/// run on, and it never starts a thread of its own, so its callbacks only
+/// run inside a call an isolate made and [`raptorq_ctx_free`] neither
+/// blocks nor calls back.  That makes `raptorq_ctx_free` safe to attach as
+/// a `NativeFinalizer`.  [`raptorq_ctx_start_decode_thread`] and
+/// [`raptorq_ctx_enqueue`] fail on it.  Requires `std`.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_ctx_new_isolate_safe(
+    transfer_length: u64,
+    max_payload_size: u16,
+) -> *mut RQContext {
+    let ctx = raptorq_ctx_new_threadsafe(transfer_length, max_payload_size);
+    if !ctx.is_null() {
+        unsafe { (*ctx).isolate_safe = true };
+    }
+    ctx
+}
+
+/// Like [`raptorq_ctx_new`], for a context that decodes on a thread of its
+/// own from the start: feed it with [`raptorq_ctx_enqueue`] and it calls
+/// `on_complete` with the recovered object, and `on_progress` (if not
+/// `NULL`) as symbols arrive, both on that thread and with `user_data`.
+/// No GCD queue or polling is needed on the app side, although
+/// [`raptorq_ctx_poll`] still works.
+///
+/// [`raptorq_ctx_free`] decodes the frames still queued and joins the
+/// thread, so the callbacks may run until it returns.  Returns `NULL` where
+/// [`raptorq_ctx_new`] would, or if the thread cannot be spawned.  Requires
+/// `std`.
+///
+/// # Safety
+/// The callbacks must stay callable, and `user_data` valid for them, until
+/// the context is freed.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_new_background(
+    transfer_length: u64,
+    max_payload_size: u16,
+    on_complete: RQCompleteCallback,
+    on_progress: RQProgressCallback,
+    user_data: *mut c_void,
+) -> *mut RQContext {
+    if check_transfer(transfer_length, max_payload_size).is_err() {
+        return ptr::null_mut();
+    }
+    try_catch_unwind(|| {
+        let oti = ObjectTransmissionInformation::with_defaults(transfer_length, max_payload_size);
+        let mut ctx = RQContext::new(oti);
+        ctx.set_on_complete(on_complete, user_data as usize);
+        ctx.set_on_progress(on_progress, user_data as usize);
+        if !ctx.ensure_worker(0) {
+            return ptr::null_mut();
+        }
+        Box::into_raw(Box::new(ctx))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Create a context for one of the built‑in [`RQProfile`] presets, which fix
+/// the payload size, the frame prefix and the envelope used by that app.
+///
+/// Returns `NULL` for an unknown `profile_id` or a transfer length
+/// [`raptorq_ctx_new`] would reject.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_ctx_new_with_profile(
+    profile_id: u32,
+    transfer_length: u64,
+) -> *mut RQContext {
+    let profile = match RQProfile::from_id(profile_id) {
+        Some(p) => p,
+        None => {
+            invalid_argument("unknown profile id");
+            return ptr::null_mut();
+        }
+    };
+    if check_transfer(transfer_length, profile.max_payload_size()).is_err() {
+        return ptr::null_mut();
+    }
+    try_catch_unwind(|| Box::into_raw(Box::new(RQContext::with_profile(profile, transfer_length))))
+        .unwrap_or(ptr::null_mut())
+}
+
+/// Build a fully initialized [`RQContext`] from a handshake frame (see
+/// `raptorq_enc_handshake_frame`).  Besides the OTI the context learns the
+/// SHA‑256 the result must match, the content type and the loop length.
+///
+/// Returns `NULL` if the frame is not a handshake frame.
+///
+/// # Safety
+/// `frame_ptr` must point to `frame_len` readable bytes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_from_handshake(
+    frame_ptr: *const u8,
+    frame_len: u64,
+) -> *mut RQContext {
+    let frame = match slice_from_raw(frame_ptr, frame_len) {
+        Ok(frame) => frame,
+        Err(_) => return ptr::null_mut(),
+    };
+    let hs = match Handshake::parse(frame) {
+        Some(hs) => hs,
+        None => {
+            invalid_argument("not a handshake frame");
+            return ptr::null_mut();
+        }
+    };
+    try_catch_unwind(|| Box::into_raw(Box::new(RQContext::from_handshake(&hs))))
+        .unwrap_or(ptr::null_mut())
+}
+
+/// Push one QR‑frame payload into the decoder.
+///
+/// Returns `true` **iff** this call finished decoding the whole object; see
+/// [`raptorq_ctx_push_frame_status`] for the detailed outcome.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context, and `payload_ptr` must point to
+/// `payload_len` readable bytes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_push_frame(
+    ctx: *mut RQContext,
+    payload_ptr: *const u8,
+    payload_len: u64,
+) -> bool {
+    raptorq_ctx_push_frame_status(ctx, payload_ptr, payload_len) == RQStatus::Complete
+}
+
+/// Push one QR‑frame payload into the decoder and report what happened to it.
+///
+/// Handshake frames repeated inside the animation loop are ignored.  When the
+/// context was built from a handshake and the recovered object does not match
+/// its digest, the result is discarded and [`RQStatus::DigestMismatch`] is
+/// returned.  Contexts created from a profile expect every frame in that
+/// profile's prefix and envelope and reject anything else as
+/// [`RQStatus::Malformed`].
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context, and `payload_ptr` must point to
+/// `payload_len` readable bytes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_push_frame_status(
+    ctx: *mut RQContext,
+    payload_ptr: *const u8,
+    payload_len: u64,
+) -> RQStatus {
+    if let Err(status) = check_ctx(ctx) {
+        return status;
+    }
+    let payload = match slice_from_raw(payload_ptr, payload_len) {
+        Ok(payload) => payload,
+        Err(status) => return status,
+    };
+    with_ctx(ctx, |ctx| push_timed(ctx, payload)).unwrap_or_else(|status| status)
+}
+
+/// [`RQContext::push`] under [`try_catch_hot`], timed into the context's
+/// push stats.
+pub(crate) fn push_timed(ctx: &mut RQContext, payload: &[u8]) -> RQStatus {
+    let watch = Stopwatch::start();
+    let status = try_catch_hot(|| ctx.push(payload)).unwrap_or(RQStatus::Failed);
+    ctx.push_timer.record(watch.elapsed());
+    status
+}
+
+/// Like [`raptorq_ctx_push_frame_status`], but the library takes ownership of
+/// the buffer and keeps the symbol in it instead of allocating a copy (the
+/// symbol is only moved forward within the buffer, past the header).  The
+/// buffer is consumed in every case, including errors; do not touch or free
+/// it again.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; `buf` must be `NULL` or a buffer
+/// returned by [`crate::raptorq_alloc`] with exactly `len` bytes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_push_frame_owned(
+    ctx: *mut RQContext,
+    buf: *mut u8,
+    len: u64,
+) -> RQStatus {
+    if buf.is_null() || len == 0 {
+        return invalid_argument("frame buffer is NULL or empty");
+    }
+    // A buffer from `raptorq_alloc` always fits; anything else is not ours.
+    let len = match checked_len(len) {
+        // Leaked: freeing it with a length that is likely wrong is worse.
+        #[cfg(feature = "strict")]
+        Some(len) if len > crate::STRICT_MAX_LEN => {
+            return invalid_argument("length over 1 GiB, likely garbage")
+        }
+        Some(len) => len,
+        None => return RQStatus::LengthOverflow,
+    };
+    let frame = Vec::from(Box::from_raw(ptr::slice_from_raw_parts_mut(buf, len)));
+    if let Err(status) = check_ctx(ctx) {
+        return status;
+    }
+    with_ctx(ctx, |ctx| {
+        let watch = Stopwatch::start();
+        let status = try_catch_hot(|| ctx.push_owned(frame)).unwrap_or(RQStatus::Failed);
+        ctx.push_timer.record(watch.elapsed());
+        status
+    })
+    .unwrap_or_else(|status| status)
+}
+
+/// Opt in to (or out of) padding‑tolerant ingestion.  When enabled, frames
+/// longer than one packet (4‑byte header plus symbol size) have their
+/// trailing zero bytes trimmed before deserializing, and such pushes report
+/// [`RQStatus::PaddingStripped`].  Off by default.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_padding_tolerant(ctx: *mut RQContext, enabled: bool) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.padding_tolerant = enabled);
+}
+
+/// Expect packed frames, each carrying several length‑prefixed packets as
+/// produced by `raptorq_enc_set_packets_per_frame`.  Off by default.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_packed_frames(ctx: *mut RQContext, enabled: bool) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.packed_frames = enabled);
+}
+
+/// Expect every frame to end in the CRC‑32 trailer added by
+/// `raptorq_enc_set_frame_checksums` and reject frames failing it as
+/// [`RQStatus::Malformed`].  The CRC covers the frame exactly as encoded, so
+/// zero padding added after it fails the check.  Off by default.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_frame_checksums(ctx: *mut RQContext, enabled: bool) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.frame_checksums = enabled);
+}
+
+/// Log this context's lines at `level` instead of the level given to
+/// `raptorq_set_logger` (including [`RQLogLevel::Off`] to silence it).
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_log_level(ctx: *mut RQContext, level: RQLogLevel) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.decoder.log_mut().set_level(level));
+}
+
+/// Append `tag=<tag>` to this context's log lines, to tell simultaneous
+/// transfers apart; `NULL` or an empty string removes the tag.  Returns
+/// `false`, keeping the previous tag, for a `NULL` context or a tag that is
+/// not UTF‑8, is longer than 32 bytes or contains whitespace.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context, `tag` `NULL` or a NUL‑terminated
+/// string.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_log_tag(ctx: *mut RQContext, tag: *const c_char) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    let tag = if tag.is_null() {
+        ""
+    } else {
+        match CStr::from_ptr(tag).to_str() {
+            Ok(tag) => tag,
+            Err(_) => {
+                invalid_argument("log tag is not UTF-8");
+                return false;
+            }
+        }
+    };
+    match with_ctx(ctx, |ctx| ctx.decoder.log_mut().set_tag(tag)) {
+        Ok(true) => true,
+        Ok(false) => {
+            invalid_argument("log tag is too long or contains whitespace");
+            false
+        }
+        Err(_) => false,
+    }
+}
+
+/// Collect `overhead` symbols beyond the `K` a block needs before attempting
+/// its (expensive) solve; after a failed attempt every new symbol retries.
+/// Defaults to `0`.  Each extra symbol makes a failed solve roughly a hundred
+/// times less likely, at the cost of scanning one more frame per block.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_decode_overhead(ctx: *mut RQContext, overhead: u32) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.decoder.set_overhead(overhead));
+}
+
+/// Remember received symbols in a Bloom filter of `bits_per_block` bits
+/// (rounded up to a power of two) per source block instead of an exact set,
+/// so memory stays constant however long the scan runs.  A false positive
+/// drops a new symbol as a repeat and costs one more frame; around `16 * K`
+/// bits keep that under one in a thousand.  Symbols received so far are
+/// carried over; a context cannot switch back.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_bloom_dedupe(ctx: *mut RQContext, bits_per_block: u32) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    if bits_per_block == 0 {
+        invalid_argument("Bloom filter size is 0");
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.decoder.set_bloom_dedupe(bits_per_block));
+}
+
+/// Leave the final solve to [`raptorq_ctx_finalize_step`] instead of running
+/// it inside the push that completes a block, so the app can spread it over
+/// several run loop turns.  Pushes then return [`RQStatus::ReadyToFinalize`]
+/// once every block has enough symbols.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_deferred_finalize(ctx: *mut RQContext, enabled: bool) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.decoder.set_deferred(enabled));
+}
+
+/// Call `callback` once, when `ctx` recovers the object, handing it the
+/// result instead of leaving it for [`raptorq_ctx_take_result`]; right away
+/// if the object is already waiting.  Replaces an earlier registration;
+/// `NULL` clears it.  Register it before starting a decode thread.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; `callback` must stay callable,
+/// and `user_data` valid for it, until it is called or replaced.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_on_complete(
+    ctx: *mut RQContext,
+    callback: RQCompleteCallback,
+    user_data: *mut c_void,
+) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.set_on_complete(callback, user_data as usize));
+}
+
+/// Call `callback` whenever a frame pushed to `ctx` adds a symbol it did not
+/// have yet, with the received and needed symbol counts, so the UI can
+/// update only when something changed.  Replaces an earlier registration;
+/// `NULL` clears it.  Register it before starting a decode thread.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; `callback` must stay callable,
+/// and `user_data` valid for it, until it is replaced or `ctx` is freed.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_on_progress(
+    ctx: *mut RQContext,
+    callback: RQProgressCallback,
+    user_data: *mut c_void,
+) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.set_on_progress(callback, user_data as usize));
+}
+
+/// Call `callback` once for each of the `count` percentages at `percents`
+/// (e.g. 25, 50, 75, 100) as the received symbols reach that share of the
+/// needed ones, e.g. to drive haptics or status text.  Replaces earlier
+/// milestones; a `NULL` callback clears them.  Returns `false` for a
+/// percentage over 100.  Register them before starting a decode thread.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; `percents` must point to `count`
+/// readable bytes; `callback` must stay callable, and `user_data` valid for
+/// it, until it is replaced or `ctx` is freed.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_milestones(
+    ctx: *mut RQContext,
+    percents: *const u8,
+    count: u32,
+    callback: RQMilestoneCallback,
+    user_data: *mut c_void,
+) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    let percents = match slice_from_raw(percents, u64::from(count)) {
+        Ok(percents) => percents,
+        Err(_) => return false,
+    };
+    if percents.iter().any(|percent| *percent > 100) {
+        invalid_argument("milestone over 100 percent");
+        return false;
+    }
+    let percents = percents.to_vec();
+    with_ctx(ctx, |ctx| {
+        ctx.set_milestones(percents, callback, user_data as usize)
+    })
+    .is_ok()
+}
+
+/// Overwrite received symbols and decoded bytes with zeros before the
+/// context frees or reuses their buffers, for payloads such as seed phrases.
+/// Copies made inside the RaptorQ solver are freed without being wiped.  The
+/// result handed out is the caller's; release it with
+/// [`crate::raptorq_free_zeroize`] or [`crate::raptorq_buffer_free_zeroize`].
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_zeroize(ctx: *mut RQContext, enabled: bool) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.decoder.set_zeroize(enabled));
+}
+
+/// Keep a copy of the symbols handed to each solve until their block is
+/// decoded, so that [`crate::raptorq_ctx_dump`] can include them; without it
+/// a dump taken after a failed solve is missing symbols.  Roughly doubles
+/// the memory of the blocks still solving; enable it before the first push.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_keep_symbols(ctx: *mut RQContext, enabled: bool) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.decoder.set_keep_symbols(enabled));
+}
+
+/// Pin the recovered object in RAM with `mlock` so it is never written to
+/// swap.  Where that is refused (e.g. beyond the process's lock limit) the
+/// transfer completes anyway with an ordinary buffer;
+/// [`raptorq_ctx_result_locked`] tells which one it got.  A locked result is
+/// unlocked by [`crate::raptorq_free_zeroize`] and
+/// [`crate::raptorq_buffer_free_zeroize`], which should be used to free it.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_mlock_result(ctx: *mut RQContext, enabled: bool) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.mlock_result = enabled);
+}
+
+/// `true` once the recovered object has been pinned in RAM as requested by
+/// [`raptorq_ctx_set_mlock_result`]; it stays set after the result is taken.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_result_locked(ctx: *const RQContext) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    with_ctx_ref(ctx, |ctx| ctx.result_locked).unwrap_or(false)
+}
+
+/// Solve buffered blocks for roughly `budget_ms` milliseconds and return.
+/// Blocks are solved whole, so a slice takes at least one block's solve
+/// however small the budget.  Writes the percentage of blocks decoded to
+/// `percent_out` when not `NULL`.
+///
+/// Returns [`RQStatus::Complete`] once the object is recovered (as from a
+/// push, including the digest check), [`RQStatus::Ok`] when it should be
+/// called again, and [`RQStatus::Ignored`] when nothing can be solved until
+/// more frames are pushed.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; `percent_out` must be `NULL` or
+/// valid for writes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_finalize_step(
+    ctx: *mut RQContext,
+    budget_ms: u32,
+    percent_out: *mut u8,
+) -> RQStatus {
+    if let Err(status) = check_ctx(ctx) {
+        return status;
+    }
+    let budget = Duration::from_millis(u64::from(budget_ms));
+    with_ctx(ctx, |ctx| {
+        let status = try_catch_hot(|| ctx.finalize_step(budget)).unwrap_or(RQStatus::Failed);
+        if !percent_out.is_null() {
+            *percent_out = ctx.finalize_progress();
+        }
+        status
+    })
+    .unwrap_or_else(|status| status)
+}
+
+/// Solve up to `threads` source blocks at once when several become ready
+/// together (multi‑block transfers only).  `0` selects the default, which is
+/// two threads on multi‑core devices.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_decode_threads(ctx: *mut RQContext, threads: u32) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, |ctx| ctx.decoder.set_threads(threads as usize));
+}
+
+/// Allocate the context's symbol storage and result buffer right away instead
+/// of while frames are arriving, so neither the first pushes nor the final
+/// solve trigger large allocations mid‑scan.  Call it right after creating
+/// the context (and after `raptorq_ctx_set_decode_overhead`, if used).
+///
+/// Returns `false` if memory could not be reserved; the context stays usable.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_preallocate(ctx: *mut RQContext) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    try_catch_unwind(|| with_ctx(ctx, |ctx| ctx.decoder.preallocate()).unwrap_or(false))
+        .unwrap_or(false)
+}
+
+/// Assemble the object in the file at `path` (created or truncated now)
+/// instead of in memory.  Every source block is written there as soon as it
+/// decodes, which bounds memory use by one block for very large transfers.
+/// Once complete, [`raptorq_ctx_is_complete`] reports `true` while
+/// [`raptorq_ctx_take_result`] returns `NULL`: read the file instead.
+///
+/// Returns `false` if the file could not be created.  Requires `std`.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; `path` must be `NULL` or a
+/// NUL‑terminated UTF‑8 string.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_output_file(
+    ctx: *mut RQContext,
+    path: *const c_char,
+) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    if path.is_null() {
+        invalid_argument("path is NULL");
+        return false;
+    }
+    let path = match CStr::from_ptr(path).to_str() {
+        Ok(p) => p,
+        Err(_) => {
+            invalid_argument("path is not UTF-8");
+            return false;
+        }
+    };
+    with_ctx(ctx, |ctx| {
+        try_catch_unwind(|| match Spool::create(path.as_ref()) {
+            Ok(spool) => {
+                ctx.decoder.set_spool(spool);
+                true
+            }
+            Err(_) => false,
+        })
+        .unwrap_or(false)
+    })
+    .unwrap_or(false)
+}
+
+/// Assemble the object in a memory mapping instead of on the heap: shared
+/// with the file at `path` (created or truncated now) or anonymous when
+/// `path` is `NULL`.  Meant for transfers large enough to trip iOS memory
+/// limits as one allocation.  Once complete, [`raptorq_ctx_take_result`]
+/// returns `NULL`; borrow the object with [`raptorq_ctx_mapped_result`].
+///
+/// Returns `false` if the mapping could not be created.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; `path` must be `NULL` or a
+/// NUL‑terminated UTF‑8 string.
+#[cfg(all(feature = "std", unix))]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_set_output_mapping(
+    ctx: *mut RQContext,
+    path: *const c_char,
+) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    let path = if path.is_null() {
+        None
+    } else {
+        match CStr::from_ptr(path).to_str() {
+            Ok(p) => Some(p),
+            Err(_) => {
+                invalid_argument("path is not UTF-8");
+                return false;
+            }
+        }
+    };
+    with_ctx(ctx, |ctx| {
+        let len = match checked_len(ctx.oti.transfer_length()) {
+            Some(len) => len,
+            None => return false,
+        };
+        try_catch_unwind(|| match Spool::mapped(path.map(AsRef::as_ref), len) {
+            Ok(spool) => {
+                ctx.decoder.set_spool(spool);
+                true
+            }
+            Err(_) => false,
+        })
+        .unwrap_or(false)
+    })
+    .unwrap_or(false)
+}
+
+/// Borrow the object assembled in the mapping set up by
+/// [`raptorq_ctx_set_output_mapping`], writing its length to `len_out` when
+/// not `NULL`.  `NULL` until decoding completes.  The bytes stay valid until
+/// the context is freed or returned to a pool; do not free them.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; `len_out` must be `NULL` or valid
+/// for writes.
+#[cfg(all(feature = "std", unix))]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_mapped_result(
+    ctx: *const RQContext,
+    len_out: *mut u64,
+) -> *const u8 {
+    if check_ctx(ctx).is_err() {
+        return ptr::null();
+    }
+    with_ctx_ref(ctx, |ctx| match ctx.mapped_result() {
+        Some(bytes) => {
+            if !len_out.is_null() {
+                *len_out = bytes.len() as u64;
+            }
+            bytes.as_ptr()
+        }
+        None => ptr::null(),
+    })
+    .unwrap_or(ptr::null())
+}
+
+/// `true` once the recovered object failed the handshake digest check.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_digest_mismatch(ctx: *const RQContext) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    with_ctx_ref(ctx, |ctx| ctx.digest_mismatch).unwrap_or(false)
+}
+
+/// Content type announced by the handshake frame, `0` when unknown.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_content_type(ctx: *const RQContext) -> u16 {
+    if check_ctx(ctx).is_err() {
+        return 0;
+    }
+    with_ctx_ref(ctx, |ctx| ctx.content_type).unwrap_or(0)
+}
+
+/// Number of frames in one animation loop as announced by the handshake
+/// frame, usable as a progress denominator; `0` when unknown.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_total_frames(ctx: *const RQContext) -> u32 {
+    if check_ctx(ctx).is_err() {
+        return 0;
+    }
+    with_ctx_ref(ctx, |ctx| ctx.total_frames).unwrap_or(0)
+}
+
+/// Frames a transfer normally takes, for a progress bar whose denominator
+/// does not move: the `K` source symbols of all blocks plus the standard
+/// overhead of two per block (or the decode overhead, if set higher).
+/// Known as soon as the context is, unlike [`raptorq_ctx_total_frames`]
+/// (the length of the sender's loop).  Counts symbols, one per frame
+/// unless frames are packed.  `0` for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_expected_frames(ctx: *const RQContext) -> u32 {
+    if check_ctx(ctx).is_err() {
+        return 0;
+    }
+    with_ctx_ref(ctx, RQContext::expected_frames).unwrap_or(0)
+}
+
+/// Probability, from `0` to `1`, that solving with the symbols buffered now
+/// succeeds, after the overhead model of RFC 6330: `0` while any block has
+/// fewer than its `K` symbols, about `0.99` with exactly `K`, `0.9999` with
+/// one more and so on, multiplied over the blocks still undecoded.  With
+/// deferred finalization, lets the app choose between running
+/// [`raptorq_ctx_finalize_step`] now and waiting for a couple more frames.
+/// `1` once the object was recovered; `0` for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_solve_probability(ctx: *const RQContext) -> f32 {
+    if check_ctx(ctx).is_err() {
+        return 0.0;
+    }
+    with_ctx_ref(ctx, RQContext::solve_probability).unwrap_or(0.0)
+}
+
+/// Time spent inside the `raptorq_ctx_push_frame*` calls on `ctx` so far,
+/// to correlate with dropped camera frames.  All zero for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_push_stats(ctx: *const RQContext) -> RQPushStats {
+    if check_ctx(ctx).is_err() {
+        return RQPushStats::default();
+    }
+    with_ctx_ref(ctx, |ctx| ctx.push_timer.snapshot()).unwrap_or_default()
+}
+
+/// Counts of the frames pushed into `ctx` by outcome, the bytes they
+/// carried, the solves run and the time since the context was created.
+/// Callable at any time, including after completion and after the result
+/// was taken.  All zero for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_stats(ctx: *const RQContext) -> RQStats {
+    if check_ctx(ctx).is_err() {
+        return RQStats::default();
+    }
+    with_ctx_ref(ctx, RQContext::stats).unwrap_or_default()
+}
+
+/// The malformed frames counted by [`raptorq_ctx_stats`], split into
+/// truncated frames, frames of the wrong length, checksum failures and the
+/// rest, to tell a camera misreading codes from a sender emitting garbage.
+/// All zero for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_frame_quality(ctx: *const RQContext) -> RQFrameQuality {
+    if check_ctx(ctx).is_err() {
+        return RQFrameQuality::default();
+    }
+    with_ctx_ref(ctx, RQContext::frame_quality).unwrap_or_default()
+}
+
+/// Number of source blocks [`raptorq_ctx_block_timing`] reports on: the
+/// blocks of the transfer once its first symbol arrived, `0` before and for
+/// a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_block_count(ctx: *const RQContext) -> u32 {
+    if check_ctx(ctx).is_err() {
+        return 0;
+    }
+    with_ctx_ref(ctx, |ctx| ctx.block_timings().len() as u32).unwrap_or(0)
+}
+
+/// The symbols source block `sbn` took and the time its solves cost, for
+/// diagnosing slow transfers once complete; callable before as well, and
+/// after the result was taken.  All zero for a block out of range and for
+/// a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_block_timing(
+    ctx: *const RQContext,
+    sbn: u32,
+) -> RQBlockTiming {
+    if check_ctx(ctx).is_err() {
+        return RQBlockTiming::default();
+    }
+    with_ctx_ref(ctx, |ctx| {
+        let timings = ctx.block_timings();
+        timings.get(sbn as usize).copied().unwrap_or_default()
+    })
+    .unwrap_or_default()
+}
+
+/// New symbols per second, smoothed over about two seconds of scanning, for
+/// a progress animation that moves steadily instead of jumping with every
+/// frame: bursts of duplicates slow it down gradually, and it decays while
+/// nothing arrives.  Comparable to the counts of the progress callback;
+/// times the symbol size of the OTI for bytes per second.  `0` before the
+/// first frame, without a clock, once no more frames are needed, and for a
+/// `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_novelty_rate(ctx: *const RQContext) -> f32 {
+    if check_ctx(ctx).is_err() {
+        return 0.0;
+    }
+    with_ctx_ref(ctx, RQContext::novelty_rate).unwrap_or(0.0)
+}
+
+/// The SBN of the source block furthest from decodable: the undecoded one
+/// with the most symbols still missing (the lowest SBN among equals).  The
+/// UI can ask the user, or the sender over a back channel, to linger on the
+/// part of the loop carrying it.  `UINT32_MAX` before the first symbol, once
+/// no more frames are needed, and for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_lagging_block(ctx: *const RQContext) -> u32 {
+    if check_ctx(ctx).is_err() {
+        return u32::MAX;
+    }
+    match with_ctx_ref(ctx, RQContext::lagging_block) {
+        Ok(Some(sbn)) => u32::from(sbn),
+        _ => u32::MAX,
+    }
+}
+
+/// How economically the transfer completed: the frames it took against
+/// the fewest it could have, and the share of them that were duplicates.
+/// For comparing sender configurations (density, frame rate, repair
+/// symbols) across releases with real scans.  Available from completion
+/// on, also after the result was taken; all zero before, after a digest
+/// mismatch, and for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_efficiency(ctx: *const RQContext) -> RQEfficiency {
+    if check_ctx(ctx).is_err() {
+        return RQEfficiency::default();
+    }
+    with_ctx_ref(ctx, RQContext::efficiency)
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// Whether the sender should switch to denser or sparser codes, judged
+/// from the last 64 frames: [`RQDensityAdvice::Sparser`] once one in eight
+/// is misread (truncated or failing its checksum), [`RQDensityAdvice::Denser`]
+/// once half are duplicates while hardly any is misread.  For live transfers
+/// with a back channel to the sender; the window lets the advice follow a
+/// switch within a couple of seconds.  [`RQDensityAdvice::Keep`] before 16
+/// frames, once no more frames are needed, and for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_density_advice(ctx: *const RQContext) -> RQDensityAdvice {
+    if check_ctx(ctx).is_err() {
+        return RQDensityAdvice::Keep;
+    }
+    with_ctx_ref(ctx, RQContext::density_advice).unwrap_or(RQDensityAdvice::Keep)
+}
+
+/// [`raptorq_ctx_stats`], [`raptorq_ctx_push_stats`], [`raptorq_ctx_frame_quality`] and the progress
+/// figures (`eta_ms`, `null` while unknown; `expected_frames`;
+/// `solve_probability`; completion) as one UTF-8 JSON object, to attach to
+/// analytics events and bug reports as is.  Field names follow the C
+/// structs; new ones may be added.  Release it with
+/// [`crate::raptorq_buffer_free`]; a `NULL` buffer for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_stats_json(ctx: *const RQContext) -> RQBuffer {
+    if check_ctx(ctx).is_err() {
+        return RQBuffer::from_raw(ptr::null_mut(), 0);
+    }
+    match with_ctx_ref(ctx, RQContext::stats_json) {
+        Ok(json) => {
+            let mut len = 0;
+            RQBuffer::from_raw(into_raw_buffer(json.into_bytes(), &mut len), len)
+        }
+        Err(_) => RQBuffer::from_raw(ptr::null_mut(), 0),
+    }
+}
+
+/// Recommended minimum interval between pushes, in microseconds, so the
+/// camera pipeline can drop frames instead of queueing them faster than the
+/// decoder absorbs them.  Follows the recent cost of a push, rising to the
+/// worst one seen while the next symbol is likely to trigger a solve; `0`
+/// once no more frames are needed (or before the first push).
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_push_interval_hint_us(ctx: *const RQContext) -> u32 {
+    if check_ctx(ctx).is_err() {
+        return 0;
+    }
+    let micros = with_ctx_ref(ctx, |ctx| ctx.pacing_hint().as_micros()).unwrap_or(0);
+    u32::try_from(micros).unwrap_or(u32::MAX)
+}
+
+/// Whether scanning has stopped paying off: no frame brought a new symbol
+/// in the last `window.pushes` pushes, or for `window.ms` milliseconds (the
+/// latter counted from the context's creation until the first symbol, and
+/// never reached without a clock).  Either limit left `0` is not checked.
+/// Lets the app suggest adjusting distance or focus rather than scanning
+/// forever.  `false` once no more frames are needed, and for a `NULL`
+/// context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_is_stalled(
+    ctx: *const RQContext,
+    window: RQStallWindow,
+) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    with_ctx_ref(ctx, |ctx| ctx.is_stalled(window)).unwrap_or(false)
+}
+
+/// Stop the context's scanning clock while the app is in the background,
+/// so the time away counts neither towards [`raptorq_ctx_is_stalled`] nor
+/// the pace behind [`raptorq_ctx_eta_ms`], nor `elapsed_ms` in
+/// [`raptorq_ctx_stats`].  Frames can still be pushed, and start the clock
+/// again like [`raptorq_ctx_resume`].  Pausing twice is the same as once.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_pause(ctx: *mut RQContext) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, RQContext::pause);
+}
+
+/// Start the clock stopped by [`raptorq_ctx_pause`] again, e.g. when the
+/// app returns to the foreground.  Does nothing unless paused.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_resume(ctx: *mut RQContext) {
+    if check_ctx(ctx).is_err() {
+        return;
+    }
+    let _ = with_ctx(ctx, RQContext::resume);
+}
+
+/// Estimated time until the transfer has every symbol it needs, in
+/// milliseconds: the symbols still missing times the recent interval per new
+/// symbol, so it follows the user steadying or losing the code.  Does not
+/// include the final solve.  `0` once no more frames are needed;
+/// `UINT32_MAX` while unknown, i.e. before two frames brought new symbols,
+/// without a clock, or for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_eta_ms(ctx: *const RQContext) -> u32 {
+    if check_ctx(ctx).is_err() {
+        return u32::MAX;
+    }
+    match with_ctx_ref(ctx, RQContext::eta) {
+        Ok(Some(eta)) => u32::try_from(eta.as_millis()).unwrap_or(u32::MAX - 1),
+        _ => u32::MAX,
+    }
+}
+
+/// Start a library‑owned thread that decodes frames handed over with
+/// [`raptorq_ctx_enqueue_frame`], through a queue of `queue_len` frames of up
+/// to `max_frame_len` bytes each, all allocated now.
+///
+/// The decoding state moves to that thread until
+/// [`raptorq_ctx_stop_decode_thread`] brings it back.  Meanwhile queries
+/// (statistics, progress, settings, the event log) read it there, waiting
+/// for the frame being decoded if need be; calls that change the context
+/// act on a stand-in and their effect is lost.  Poll
+/// [`raptorq_ctx_decode_thread_status`] for the outcome.
+///
+/// Returns `false` for a `NULL` context, a zero size, a thread that is
+/// already running or one that could not be spawned.  Requires `std`.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_start_decode_thread(
+    ctx: *mut RQContext,
+    queue_len: u32,
+    max_frame_len: u32,
+) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    if queue_len == 0 || max_frame_len == 0 {
+        invalid_argument("decode queue size is 0");
+        return false;
+    }
+    let start = |ctx: &mut RQContext| {
+        try_catch_unwind(|| ctx.start_worker(queue_len as usize, max_frame_len as usize))
+            .unwrap_or(false)
+    };
+    with_ctx(ctx, start).unwrap_or(false)
+}
+
+/// Queue a copy of one frame for the decode thread and return immediately,
+/// never waiting on a decode or a lock: safe to call from the camera
+/// callback.
+/// Returns `false` when no decode thread runs, the queue is full (the frame
+/// is dropped, as the camera would) or the frame is longer than
+/// `max_frame_len`.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context, and `payload_ptr` must point to
+/// `payload_len` readable bytes.  Enqueue from one thread at a time; the
+/// other calls, stopping the decode thread included, may run on other
+/// threads meanwhile.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_enqueue_frame(
+    ctx: *const RQContext,
+    payload_ptr: *const u8,
+    payload_len: u64,
+) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    match slice_from_raw(payload_ptr, payload_len) {
+        Ok(payload) => RQContext::feed_raw(ctx).enqueue(payload).unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+/// Latest status reported by the decode thread, as
+/// [`raptorq_ctx_push_frame_status`] would have returned it.  Stays
+/// [`RQStatus::Complete`] (or [`RQStatus::DigestMismatch`]) once reached;
+/// [`RQStatus::Failed`] when no decode thread runs.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_decode_thread_status(ctx: *const RQContext) -> RQStatus {
+    if let Err(status) = check_ctx(ctx) {
+        return status;
+    }
+    RQContext::feed_raw(ctx)
+        .status()
+        .unwrap_or(RQStatus::Failed)
+}
+
+/// Decode the frames still queued, stop the decode thread and move the
+/// decoding state back into `ctx`, after which the result is available as
+/// usual.  Returns `false` if no decode thread ran or it failed, in which
+/// case `ctx` stays a fresh context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_stop_decode_thread(ctx: *mut RQContext) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    with_ctx(ctx, |ctx| {
+        try_catch_unwind(|| ctx.stop_worker()).unwrap_or(false)
+    })
+    .unwrap_or(false)
+}
+
+/// Queue a copy of one frame for decoding and return immediately, starting
+/// the decode thread on the first call with a queue of 32 frames sized from
+/// that frame (see [`raptorq_ctx_start_decode_thread`] for other sizes).
+/// Collect the outcome with [`raptorq_ctx_poll`].  Returns `false` when the
+/// thread could not be started, the queue is full (the frame is dropped, as
+/// the camera would) or the frame does not fit a slot.
+///
+/// Only the first call allocates and spawns; the later ones never wait on
+/// a decode or a lock, like [`raptorq_ctx_enqueue_frame`], even while a
+/// poll or query on another thread holds the decoding state or stops the
+/// decode thread.  Once the object is
+/// complete no thread is started again and frames are refused.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context, and `payload_ptr` must point to
+/// `payload_len` readable bytes.  Enqueue from one thread at a time, e.g.
+/// the camera callback; [`raptorq_ctx_poll`] and the other calls may run on
+/// another thread meanwhile.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_enqueue(
+    ctx: *mut RQContext,
+    payload_ptr: *const u8,
+    payload_len: u64,
+) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    let payload = match slice_from_raw(payload_ptr, payload_len) {
+        Ok(payload) => payload,
+        Err(_) => return false,
+    };
+    if let Some(queued) = RQContext::feed_raw(ctx).enqueue(payload) {
+        return queued;
+    }
+    let start = |ctx: &mut RQContext| {
+        try_catch_unwind(|| ctx.ensure_worker(payload.len())).unwrap_or(false)
+    };
+    with_ctx(ctx, start).unwrap_or(false)
+        && RQContext::feed_raw(ctx).enqueue(payload).unwrap_or(false)
+}
+
+/// What the decode thread made of the frames queued with
+/// [`raptorq_ctx_enqueue`] since the previous poll: the status of the last
+/// one decoded, or [`RQStatus::Pending`] if none was.  [`RQStatus::Complete`],
+/// [`RQStatus::DigestMismatch`] and [`RQStatus::ReadyToFinalize`] are never
+/// skipped over; on those the thread is stopped and the state moved back, so
+/// the result can be taken (or finalization driven) right away.
+/// [`RQStatus::Failed`] when no decode thread runs.
+///
+/// Meant to be called from the app's own timer or run loop, keeping the
+/// camera callback down to [`raptorq_ctx_enqueue`].
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_poll(ctx: *mut RQContext) -> RQStatus {
+    if let Err(status) = check_ctx(ctx) {
+        return status;
+    }
+    with_ctx(ctx, |ctx| {
+        try_catch_unwind(|| ctx.poll_worker()).unwrap_or(RQStatus::Failed)
+    })
+    .unwrap_or_else(|status| status)
+}
+
+/// A token that cancels what `ctx` is doing in the background (see
+/// [`raptorq_cancel`]), e.g. when the scanner screen is dismissed, from any
+/// thread and without waiting for a call in progress.  Take it before
+/// handing the context to another thread; free it with
+/// [`raptorq_cancel_token_free`].  Returns `NULL` for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_cancel_token(ctx: *const RQContext) -> *const RQCancelToken {
+    if check_ctx(ctx).is_err() {
+        return ptr::null();
+    }
+    with_ctx_ref(ctx, |ctx| ctx.decoder.cancel().to_raw()).unwrap_or(ptr::null())
+}
+
+/// Check whether the decoder has recovered enough packets to rebuild the
+/// original object.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_is_complete(ctx: *const RQContext) -> bool {
+    if check_ctx(ctx).is_err() {
+        return false;
+    }
+    with_ctx_ref(ctx, |ctx| ctx.is_complete()).unwrap_or(false)
+}
+
+/// Move the reconstructed buffer **out** of the context.  Caller assumes
+/// ownership and must free it with [`crate::raptorq_free`].  If `len_out` is not
+/// `NULL` the function writes the buffer length to it.  Returns `NULL` for
+/// objects assembled on disk (see [`raptorq_ctx_set_output_file`]).
+///
+/// All decoding state is released along with the result, so a context kept
+/// around afterwards (e.g. for status display) costs next to nothing; further
+/// frames pushed into it are [`RQStatus::Ignored`].
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; `len_out` must be `NULL` or valid
+/// for writes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_take_result(
+    ctx: *mut RQContext,
+    len_out: *mut u64,
+) -> *mut u8 {
+    if check_ctx(ctx).is_err() {
+        return ptr::null_mut();
+    }
+    match with_ctx(ctx, |ctx| ctx.take_result()) {
+        Ok(Some(data)) => into_raw_buffer(data, len_out),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// [`raptorq_ctx_take_result`] returning an [`RQBuffer`], to be released
+/// with [`crate::raptorq_buffer_free`].
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_take_result_buffer(ctx: *mut RQContext) -> RQBuffer {
+    let mut len = 0;
+    RQBuffer::from_raw(raptorq_ctx_take_result(ctx, &mut len), len)
+}
+
+/// Deep‑copy `ctx`: a new, independent context holding the same symbols,
+/// options and (if complete) result, to free with [`raptorq_ctx_free`].  Lets
+/// a background copy attempt a finalize while the original keeps taking
+/// frames.  Returns `NULL` for a `NULL` context, while its decode thread
+/// runs, or when it assembles into a file.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_clone(ctx: *const RQContext) -> *mut RQContext {
+    if check_ctx(ctx).is_err() {
+        return ptr::null_mut();
+    }
+    match try_catch_unwind(|| with_ctx_ref(ctx, RQContext::try_clone).ok().flatten()).flatten() {
+        Some(copy) => Box::into_raw(Box::new(copy)),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Take another reference to `ctx` for a component that outlives its
+/// creator, e.g. a background persister finishing after the scanner view
+/// is gone.  Every reference, the constructor's included, is given back with
+/// [`raptorq_ctx_release`]; the context is destroyed with the last one.
+/// References manage lifetime only: calls on the context from different
+/// threads must still not overlap (see [`RQStatus::Busy`]).  Returns `ctx`.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_retain(ctx: *mut RQContext) -> *mut RQContext {
+    if !ctx.is_null() && check_ctx(ctx).is_ok() {
+        (*ctx).retain();
+    }
+    ctx
+}
+
+/// Give back one reference to `ctx`, destroying it and releasing all
+/// resources if it was the last.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; it must not be used afterwards
+/// through this reference.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_release(ctx: *mut RQContext) {
+    if ctx.is_null() || check_ctx(ctx).is_err() || !(*ctx).release() {
+        return;
+    }
+    drop(RQContext::take_raw(ctx));
+}
+
+/// Destroy the decoding context and release all resources.  For a context
+/// that was retained, this gives back one reference like
+/// [`raptorq_ctx_release`].
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; it must not be used afterwards.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_free(ctx: *mut RQContext) {
+    raptorq_ctx_release(ctx);
+}
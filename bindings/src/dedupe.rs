@@ -0,0 +1,194 @@
+//! Remembering which encoding symbols of a block have already been received.
+//!
+//! The exact set grows with every distinct ESI, which for multi‑minute scans
+//! of large objects adds up.  The Bloom filter mode keeps a fixed number of
+//! bits per block instead; a false positive makes a genuinely new symbol look
+//! like a repeat, so it is dropped and the scan simply needs another frame.
+//! There are no false negatives, so a symbol is never handed to the block
+//! decoder twice.
+//!
+//! Every block starts out with a fixed bitmap of the first [`SMALL_ESIS`]
+//! ESIs, which covers a few‑kilobyte payload and its repair symbols without
+//! hashing or allocating; the first ESI beyond it moves to the exact set.
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeSet as ExactSet;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashSet as ExactSet;
+
+/// ESIs tracked by the inline bitmap.
+const SMALL_ESIS: u32 = 256;
+
+#[derive(Clone)]
+pub(crate) enum Seen {
+    Small([u64; SMALL_ESIS as usize / 64]),
+    Exact(ExactSet<u32>),
+    Bloom(BloomFilter),
+}
+
+impl Seen {
+    pub fn new() -> Seen {
+        Seen::Small([0; SMALL_ESIS as usize / 64])
+    }
+
+    /// Record `esi`; `false` if it was (or, for a Bloom filter, may have
+    /// been) seen before.
+    pub fn insert(&mut self, esi: u32) -> bool {
+        match self {
+            Seen::Small(words) if esi < SMALL_ESIS => {
+                let (word, bit) = ((esi / 64) as usize, esi % 64);
+                let new = words[word] & (1 << bit) == 0;
+                words[word] |= 1 << bit;
+                new
+            }
+            Seen::Small(_) => {
+                self.spill();
+                self.insert(esi)
+            }
+            Seen::Exact(set) => set.insert(esi),
+            Seen::Bloom(filter) => filter.insert(esi),
+        }
+    }
+
+    /// Number of symbols accepted as new.
+    pub fn len(&self) -> u32 {
+        match self {
+            Seen::Small(words) => words.iter().map(|w| w.count_ones()).sum(),
+            Seen::Exact(set) => set.len() as u32,
+            Seen::Bloom(filter) => filter.count,
+        }
+    }
+
+    /// ESIs recorded in the inline bitmap.
+    fn small_esis(words: &[u64]) -> impl Iterator<Item = u32> + '_ {
+        (0..SMALL_ESIS).filter(move |esi| words[(esi / 64) as usize] & (1 << (esi % 64)) != 0)
+    }
+
+    /// Move from the inline bitmap to the exact set.
+    fn spill(&mut self) {
+        if let Seen::Small(words) = self {
+            let set = Seen::small_esis(words).collect();
+            *self = Seen::Exact(set);
+        }
+    }
+
+    /// Make room for `additional` more symbols; a Bloom filter never grows
+    /// and the `no_std` B‑tree allocates per node.
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    pub fn try_reserve(&mut self, additional: usize) -> bool {
+        if matches!(self, Seen::Small(_)) && additional > SMALL_ESIS as usize {
+            self.spill();
+        }
+        match self {
+            #[cfg(feature = "std")]
+            Seen::Exact(set) => set.try_reserve(additional).is_ok(),
+            _ => true,
+        }
+    }
+
+    /// Switch to a Bloom filter of `bits` bits sized for about `expected`
+    /// symbols, carrying over everything recorded so far.
+    pub fn into_bloom(self, bits: u32, expected: u32) -> Seen {
+        let mut filter = BloomFilter::new(bits, expected);
+        match self {
+            Seen::Small(words) => {
+                for esi in Seen::small_esis(&words) {
+                    filter.insert(esi);
+                }
+                Seen::Bloom(filter)
+            }
+            Seen::Exact(set) => {
+                for esi in set {
+                    filter.insert(esi);
+                }
+                Seen::Bloom(filter)
+            }
+            // Resizing would need the original ESIs; keep the filter.
+            bloom @ Seen::Bloom(_) => bloom,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct BloomFilter {
+    words: Vec<u64>,
+    mask: u64,   // bit count - 1, a power of two
+    hashes: u32, // probes per symbol
+    count: u32,  // symbols accepted as new
+}
+
+impl BloomFilter {
+    /// `bits` is rounded up to a power of two of at least 64; the number of
+    /// probes is the optimum for `expected` symbols.
+    pub fn new(bits: u32, expected: u32) -> BloomFilter {
+        let bits = u64::from(bits.max(64)).next_power_of_two();
+        // round(bits / expected * ln 2), in integers for `no_std`.
+        let expected = u64::from(expected.max(1));
+        let hashes = (bits * 693 / 1000 + expected / 2) / expected;
+        BloomFilter {
+            words: vec![0; (bits / 64) as usize],
+            mask: bits - 1,
+            hashes: hashes.clamp(1, 16) as u32,
+            count: 0,
+        }
+    }
+
+    fn insert(&mut self, esi: u32) -> bool {
+        // Double hashing over one 64-bit mix (splitmix64 finalizer).
+        let mut h = u64::from(esi).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        h ^= h >> 31;
+        let (h1, h2) = (h & 0xffff_ffff, (h >> 32) | 1);
+        let mut new = false;
+        for i in 0..u64::from(self.hashes) {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) & self.mask;
+            let word = &mut self.words[(bit / 64) as usize];
+            new |= *word & (1 << (bit % 64)) == 0;
+            *word |= 1 << (bit % 64);
+        }
+        if new {
+            self.count += 1;
+        }
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bloom_has_no_false_negatives() {
+        let mut seen = Seen::new().into_bloom(1 << 14, 1_000);
+        let fresh = (0..1_000).filter(|esi| seen.insert(*esi)).count() as u32;
+        assert_eq!(seen.len(), fresh);
+        assert!(fresh > 990);
+        assert!((0..1_000).all(|esi| !seen.insert(esi)));
+        assert_eq!(seen.len(), fresh);
+    }
+
+    #[test]
+    fn spills_past_the_bitmap() {
+        let mut seen = Seen::new();
+        assert!(seen.insert(3) && seen.insert(255));
+        assert!(!seen.insert(3));
+        assert!(matches!(seen, Seen::Small(_)));
+        assert!(seen.insert(256));
+        assert!(matches!(seen, Seen::Exact(_)));
+        assert!(!seen.insert(255) && !seen.insert(256));
+        assert_eq!(seen.len(), 3);
+    }
+
+    #[test]
+    fn carries_over_exact_entries() {
+        let mut exact = Seen::new();
+        assert!(exact.insert(300));
+        assert!(exact.insert(7));
+        let mut seen = exact.into_bloom(4_096, 100);
+        assert_eq!(seen.len(), 2);
+        assert!(!seen.insert(7) && !seen.insert(300));
+    }
+}
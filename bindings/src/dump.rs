@@ -0,0 +1,235 @@
+//! Debug dumps of a transfer in progress.
+//!
+//! When a scan never completes in the field, the app can attach a dump of
+//! its context to the report: the OTI, what the handshake said, and every
+//! symbol received so far, enough to put a decoder back into the same state
+//! offline (`raptorq_ctx_from_dump`), turn a field report into a regression
+//! test, or carry a half‑finished scan over to another device.
+//!
+//! ```text
+//! offset  size  field
+//!      0     4  magic "RQDP"
+//!      4     1  format version (1)
+//!      5     1  flags: 1 = symbols are missing, 2 = a digest follows
+//!      6    12  OTI, as serialized by `ObjectTransmissionInformation`
+//!     18    32  SHA‑256 from the handshake (only with flag 2)
+//!      …     2  content type (big endian)
+//!      …     4  total frames in one loop (big endian)
+//!      …     4  symbol count (big endian)
+//!      …        symbols: 4‑byte payload id + symbol size bytes each
+//! ```
+//!
+//! Symbols handed to a failed solve are only in the dump if the context kept
+//! them (`raptorq_ctx_set_keep_symbols`); otherwise, and for blocks written
+//! to a spool file, the dump is marked as missing symbols.
+
+use core::ptr;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use raptorq::ObjectTransmissionInformation;
+
+use crate::context::RQContext;
+use crate::decoder::with_ctx_ref;
+use crate::memory::RQBuffer;
+use crate::{check_ctx, into_raw_buffer, invalid_argument, slice_from_raw, try_catch_unwind};
+
+pub(crate) const DUMP_MAGIC: [u8; 4] = *b"RQDP";
+pub(crate) const DUMP_VERSION: u8 = 1;
+
+/// Some received symbols could not be included.
+pub(crate) const DUMP_PARTIAL: u8 = 1;
+/// The handshake digest follows the OTI.
+pub(crate) const DUMP_DIGEST: u8 = 2;
+
+impl RQContext {
+    /// Serialize the transfer; `None` once it is complete.
+    pub(crate) fn dump(&self) -> Option<Vec<u8>> {
+        self.dump_framed(&[], 0)
+    }
+
+    /// [`RQContext::dump`] after `head`, sized for exactly `trailer` more
+    /// bytes: the buffer is never reallocated, so no copy of the symbols is
+    /// left behind in freed memory.
+    pub(crate) fn dump_framed(&self, head: &[u8], trailer: usize) -> Option<Vec<u8>> {
+        if self.is_complete() || self.taken {
+            return None;
+        }
+        let mut count: u32 = 0;
+        let mut symbols_len = 0;
+        let complete = self.decoder.symbols(|_, symbol| {
+            symbols_len += 4 + symbol.len();
+            count += 1;
+        });
+        let mut flags = 0;
+        // A digest mismatch leaves no blocks to dump.
+        if !complete || self.digest_mismatch {
+            flags |= DUMP_PARTIAL;
+        }
+        let mut header_len = 28;
+        if self.expected_digest.is_some() {
+            flags |= DUMP_DIGEST;
+            header_len += 32;
+        }
+        let mut out = Vec::with_capacity(head.len() + header_len + symbols_len + trailer);
+        out.extend_from_slice(head);
+        out.extend_from_slice(&DUMP_MAGIC);
+        out.push(DUMP_VERSION);
+        out.push(flags);
+        out.extend_from_slice(&self.oti.serialize());
+        if let Some(digest) = self.expected_digest {
+            out.extend_from_slice(&digest);
+        }
+        out.extend_from_slice(&self.content_type.to_be_bytes());
+        out.extend_from_slice(&self.total_frames.to_be_bytes());
+        out.extend_from_slice(&count.to_be_bytes());
+        self.decoder.symbols(|id, symbol| {
+            out.extend_from_slice(&id.serialize());
+            out.extend_from_slice(symbol);
+        });
+        debug_assert_eq!(out.capacity() - out.len(), trailer);
+        Some(out)
+    }
+
+    /// Rebuild a context from [`RQContext::dump`] output by pushing its
+    /// symbols again; `None` if `blob` is not a well‑formed dump.
+    pub(crate) fn from_dump(blob: &[u8]) -> Option<RQContext> {
+        RQContext::from_dump_with(blob, |_| {})
+    }
+
+    /// [`RQContext::from_dump`], letting `configure` set up the decoder
+    /// before the symbols are replayed into it.
+    pub(crate) fn from_dump_with(
+        blob: &[u8],
+        configure: impl FnOnce(&mut RQContext),
+    ) -> Option<RQContext> {
+        let (header, rest) = blob.split_first_chunk::<18>()?;
+        if header[..4] != DUMP_MAGIC || header[4] != DUMP_VERSION {
+            return None;
+        }
+        let flags = header[5];
+        let mut oti = [0u8; 12];
+        oti.copy_from_slice(&header[6..]);
+        let oti = ObjectTransmissionInformation::deserialize(&oti);
+        if oti.transfer_length() == 0 || oti.symbol_size() == 0 {
+            return None;
+        }
+        let (digest, rest) = if flags & DUMP_DIGEST != 0 {
+            let (digest, rest) = rest.split_first_chunk::<32>()?;
+            (Some(*digest), rest)
+        } else {
+            (None, rest)
+        };
+        let (fields, symbols) = rest.split_first_chunk::<10>()?;
+        let content_type = u16::from_be_bytes([fields[0], fields[1]]);
+        let total_frames = u32::from_be_bytes([fields[2], fields[3], fields[4], fields[5]]);
+        let count = u32::from_be_bytes([fields[6], fields[7], fields[8], fields[9]]);
+        let packet_len = 4 + usize::from(oti.symbol_size());
+        if symbols.len() as u64 != u64::from(count) * packet_len as u64 {
+            return None;
+        }
+        let mut ctx = RQContext::new(oti);
+        ctx.expected_digest = digest;
+        ctx.content_type = content_type;
+        ctx.total_frames = total_frames;
+        configure(&mut ctx);
+        for packet in symbols.chunks_exact(packet_len) {
+            ctx.push(packet);
+        }
+        Some(ctx)
+    }
+}
+
+/// Serialize the OTI and every symbol `ctx` received into a blob for a bug
+/// report, and write its length to `len_out` (when not `NULL`).  Free it with
+/// [`crate::raptorq_free`].  Returns `NULL` for a `NULL` context or one whose
+/// transfer is already complete.  Call it before the decode thread starts or
+/// after it stops.
+///
+/// The dump contains the payload itself; treat it as sensitively.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; `len_out` must be `NULL` or valid
+/// for writes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_dump(ctx: *const RQContext, len_out: *mut u64) -> *mut u8 {
+    if check_ctx(ctx).is_err() {
+        return ptr::null_mut();
+    }
+    match try_catch_unwind(|| with_ctx_ref(ctx, RQContext::dump).ok().flatten()).flatten() {
+        Some(blob) => into_raw_buffer(blob, len_out),
+        None => ptr::null_mut(),
+    }
+}
+
+/// [`raptorq_ctx_dump`] returning an [`RQBuffer`], to be released with
+/// [`crate::raptorq_buffer_free`].
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_dump_buffer(ctx: *const RQContext) -> RQBuffer {
+    let mut len = 0;
+    RQBuffer::from_raw(raptorq_ctx_dump(ctx, &mut len), len)
+}
+
+/// Rebuild a context from a [`raptorq_ctx_dump`] blob: same OTI and
+/// handshake details, with every dumped symbol pushed again (which completes
+/// the transfer if they are enough).  Options such as profiles, checksums or
+/// the decode overhead are not part of a dump; the symbols are already
+/// unwrapped.  Returns `NULL` for a blob that is not a well‑formed dump.
+///
+/// # Safety
+/// `blob` must point to `len` readable bytes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_from_dump(blob: *const u8, len: u64) -> *mut RQContext {
+    let blob = match slice_from_raw(blob, len) {
+        Ok(blob) => blob,
+        Err(_) => return ptr::null_mut(),
+    };
+    match try_catch_unwind(|| RQContext::from_dump(blob)) {
+        Some(Some(ctx)) => Box::into_raw(Box::new(ctx)),
+        Some(None) => {
+            invalid_argument("not a well-formed dump");
+            ptr::null_mut()
+        }
+        None => ptr::null_mut(),
+    }
+}
+
+#[cfg(all(test, feature = "encoder"))]
+mod tests {
+    use super::*;
+    use raptorq::Encoder;
+
+    #[test]
+    fn layout() {
+        let data: Vec<u8> = (0..1_000u32).map(|i| (i * 3) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        let mut ctx = RQContext::new(enc.get_config());
+        let packets = enc.get_encoded_packets(0);
+        for p in &packets[..5] {
+            ctx.push(&p.serialize());
+        }
+        let blob = ctx.dump().unwrap();
+        // Handed out as is, without a reallocation leaving a copy behind.
+        assert_eq!(blob.capacity(), blob.len());
+        assert_eq!(&blob[..6], b"RQDP\x01\x00");
+        assert_eq!(&blob[6..18], &enc.get_config().serialize());
+        assert_eq!(&blob[24..28], &5u32.to_be_bytes());
+        assert_eq!(blob.len(), 28 + 5 * 68);
+        assert_eq!(&blob[28..28 + 68], &packets[0].serialize()[..]);
+        assert!(RQContext::from_dump(&blob[..blob.len() - 1]).is_none());
+        let mut restored = RQContext::from_dump(&blob).unwrap();
+        assert_eq!(restored.dump().unwrap(), blob);
+        for p in &packets[5..] {
+            restored.push(&p.serialize());
+        }
+        assert_eq!(restored.take_result().unwrap(), data);
+        for p in &packets[5..] {
+            ctx.push(&p.serialize());
+        }
+        assert!(ctx.dump().is_none());
+    }
+}
@@ -0,0 +1,266 @@
+//! Encoder side of the C API, producing the frames of an animated QR loop.
+//!
+//! The encoder is built once from the whole object and pre‑serializes every
+//! frame of one loop (all source packets followed by the requested number of
+//! repair packets per block), so fetching a frame is a plain copy.  Frames
+//! can optionally carry several packets each (see [`crate::packing`]).
+
+use core::ptr;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use raptorq::Encoder;
+
+use crate::checksum;
+use crate::handshake::{self, Handshake};
+use crate::memory::RQBuffer;
+use crate::packing;
+use crate::{check_transfer, into_raw_buffer, invalid_argument, slice_from_raw, try_catch_unwind};
+
+pub struct RQEncoder {
+    packets: Vec<Vec<u8>>,
+    pub(crate) frames: Vec<Vec<u8>>,
+    handshake: Handshake,
+    packets_per_frame: usize,
+    checksums: bool,
+}
+
+impl RQEncoder {
+    /// Lay the packets out into frames again after a layout change.
+    fn rebuild_frames(&mut self) {
+        let mut frames = if self.packets_per_frame == 1 {
+            self.packets.clone()
+        } else {
+            packing::pack(&self.packets, self.packets_per_frame)
+        };
+        if self.checksums {
+            frames.iter_mut().for_each(checksum::seal);
+        }
+        self.frames = frames;
+        self.handshake.total_frames = self.frames.len() as u32;
+    }
+}
+
+/// Encode `data_len` bytes into frames carrying at most `max_payload_size`
+/// bytes of symbol data each, plus `repair_packets_per_block` repair frames
+/// for every source block.  Returns `NULL` for empty data, a zero
+/// `max_payload_size` or a `data_len` that does not fit the address space
+/// or RaptorQ.
+///
+/// # Safety
+/// `data_ptr` must point to `data_len` readable bytes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_enc_new(
+    data_ptr: *const u8,
+    data_len: u64,
+    max_payload_size: u16,
+    repair_packets_per_block: u32,
+) -> *mut RQEncoder {
+    if check_transfer(data_len, max_payload_size).is_err() {
+        return ptr::null_mut();
+    }
+    let data = match slice_from_raw(data_ptr, data_len) {
+        Ok(data) => data,
+        Err(_) => return ptr::null_mut(),
+    };
+    try_catch_unwind(|| {
+        let encoder = Encoder::with_defaults(data, max_payload_size);
+        let packets: Vec<Vec<u8>> = encoder
+            .get_encoded_packets(repair_packets_per_block)
+            .iter()
+            .map(|p| p.serialize())
+            .collect();
+        rq_log!(
+            Info,
+            "encoder created len={} frames={}",
+            data.len(),
+            packets.len()
+        );
+        let handshake = Handshake {
+            oti: encoder.get_config(),
+            digest: handshake::digest(data),
+            content_type: 0,
+            total_frames: packets.len() as u32,
+        };
+        Box::into_raw(Box::new(RQEncoder {
+            frames: packets.clone(),
+            packets,
+            handshake,
+            packets_per_frame: 1,
+            checksums: false,
+        }))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Carry `packets_per_frame` length‑prefixed packets in every frame instead
+/// of one bare packet.  The receiving context must enable
+/// `raptorq_ctx_set_packed_frames`.  `1` restores the unpacked layout.
+///
+/// Returns `false` for a `NULL` encoder or a zero count.
+///
+/// # Safety
+/// `enc` must be `NULL` or a live encoder.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_enc_set_packets_per_frame(
+    enc: *mut RQEncoder,
+    packets_per_frame: u32,
+) -> bool {
+    if enc.is_null() {
+        invalid_argument("encoder is NULL");
+        return false;
+    }
+    if packets_per_frame == 0 {
+        invalid_argument("packets per frame is 0");
+        return false;
+    }
+    let enc = &mut *enc;
+    enc.packets_per_frame = packets_per_frame as usize;
+    enc.rebuild_frames();
+    true
+}
+
+/// End every frame in a CRC‑32 of its contents (4 bytes, little endian) so
+/// the receiver can drop misread frames cheaply; the receiving context must
+/// enable `raptorq_ctx_set_frame_checksums`.  The handshake frame carries
+/// its own digest and stays as it is.
+///
+/// Returns `false` for a `NULL` encoder.
+///
+/// # Safety
+/// `enc` must be `NULL` or a live encoder.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_enc_set_frame_checksums(
+    enc: *mut RQEncoder,
+    enabled: bool,
+) -> bool {
+    if enc.is_null() {
+        invalid_argument("encoder is NULL");
+        return false;
+    }
+    let enc = &mut *enc;
+    enc.checksums = enabled;
+    enc.rebuild_frames();
+    true
+}
+
+/// Number of frames in one animation loop, excluding the handshake frame.
+///
+/// # Safety
+/// `enc` must be `NULL` or a live encoder.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_enc_frame_count(enc: *const RQEncoder) -> u32 {
+    if enc.is_null() {
+        invalid_argument("encoder is NULL");
+        return 0;
+    }
+    (*enc).frames.len() as u32
+}
+
+/// Copy out frame `index` of the loop.  Free it with [`crate::raptorq_free`].
+///
+/// # Safety
+/// `enc` must be `NULL` or a live encoder; `len_out` must be `NULL` or valid
+/// for writes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_enc_frame(
+    enc: *const RQEncoder,
+    index: u32,
+    len_out: *mut u64,
+) -> *mut u8 {
+    if enc.is_null() {
+        invalid_argument("encoder is NULL");
+        return ptr::null_mut();
+    }
+    let enc = &*enc;
+    match enc.frames.get(index as usize) {
+        Some(frame) => into_raw_buffer(frame.clone(), len_out),
+        None => {
+            invalid_argument("frame index out of range");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// [`raptorq_enc_frame`] returning an [`RQBuffer`], to be released with
+/// [`crate::raptorq_buffer_free`].
+///
+/// # Safety
+/// `enc` must be `NULL` or a live encoder.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_enc_frame_buffer(enc: *const RQEncoder, index: u32) -> RQBuffer {
+    let mut len = 0;
+    RQBuffer::from_raw(raptorq_enc_frame(enc, index, &mut len), len)
+}
+
+/// Build the optional handshake frame ("frame zero") announcing the OTI, the
+/// SHA‑256 of the object, `content_type` and the loop length.  Free it with
+/// [`crate::raptorq_free`].
+///
+/// # Safety
+/// `enc` must be `NULL` or a live encoder; `len_out` must be `NULL` or valid
+/// for writes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_enc_handshake_frame(
+    enc: *const RQEncoder,
+    content_type: u16,
+    len_out: *mut u64,
+) -> *mut u8 {
+    if enc.is_null() {
+        invalid_argument("encoder is NULL");
+        return ptr::null_mut();
+    }
+    let enc = &*enc;
+    let handshake = Handshake {
+        content_type,
+        ..enc.handshake
+    };
+    into_raw_buffer(handshake.serialize(), len_out)
+}
+
+/// [`raptorq_enc_handshake_frame`] returning an [`RQBuffer`], to be
+/// released with [`crate::raptorq_buffer_free`].
+///
+/// # Safety
+/// `enc` must be `NULL` or a live encoder.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_enc_handshake_frame_buffer(
+    enc: *const RQEncoder,
+    content_type: u16,
+) -> RQBuffer {
+    let mut len = 0;
+    RQBuffer::from_raw(
+        raptorq_enc_handshake_frame(enc, content_type, &mut len),
+        len,
+    )
+}
+
+/// The 12‑byte OTI of the encoded object, for a receiver set up with
+/// `raptorq_ctx_from_oti` instead of a handshake frame.  Release it with
+/// [`crate::raptorq_buffer_free`].
+///
+/// # Safety
+/// `enc` must be `NULL` or a live encoder.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_enc_oti_buffer(enc: *const RQEncoder) -> RQBuffer {
+    if enc.is_null() {
+        invalid_argument("encoder is NULL");
+        return RQBuffer::from_raw(ptr::null_mut(), 0);
+    }
+    let mut len = 0;
+    let oti = (*enc).handshake.oti.serialize().to_vec();
+    RQBuffer::from_raw(into_raw_buffer(oti, &mut len), len)
+}
+
+/// Destroy the encoder and release all resources.
+///
+/// # Safety
+/// `enc` must be `NULL` or a live encoder; it must not be used afterwards.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_enc_free(enc: *mut RQEncoder) {
+    if enc.is_null() {
+        return;
+    }
+    drop(Box::from_raw(enc));
+}
@@ -0,0 +1,266 @@
+//! Notable moments of a transfer kept per context, for support to piece
+//! together what happened in a scan gone wrong from a bug report.
+//!
+//! The log holds at most [`EVENT_CAPACITY`] events.  The setup and first
+//! frame events are kept for the life of the context; the others go into a
+//! ring that drops its oldest entry when full, so a long scan keeps how it
+//! began and how it is going.  Runs of one error are folded into a single
+//! event with a count, so a camera misreading codes for a minute does not
+//! crowd the other events out.
+
+use core::time::Duration;
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::check_ctx;
+use crate::context::RQContext;
+use crate::decoder::with_ctx_ref;
+use crate::status::RQStatus;
+
+/// Events kept per context; older ones are dropped first, except the
+/// pinned ones.
+pub(crate) const EVENT_CAPACITY: usize = 64;
+
+/// Kinds recorded once per context and never dropped.
+const PINNED: [RQEventKind; 2] = [RQEventKind::OtiLearned, RQEventKind::FirstFrame];
+
+/// What an [`RQEvent`] records.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RQEventKind {
+    /// No event: the index was out of range.
+    None = 0,
+    /// The context was set up for a transfer; `value` is its length.
+    OtiLearned = 1,
+    /// The first frame arrived; `value` is its length.  Its outcome is
+    /// logged like any other frame's, e.g. as an `Error`.
+    FirstFrame = 2,
+    /// Solves run by one push or finalize step; `count` is the number of
+    /// attempts, `value` the nanoseconds they took, `status` the outcome of
+    /// the call.
+    Solve = 3,
+    /// The object was recovered; `value` is its length.
+    Complete = 4,
+    /// A push or finalize step failed with `status`; `count` consecutive
+    /// ones, the first at `at_ms`, `value` the length of the last frame.
+    Error = 5,
+}
+
+/// One entry of the event log, from `raptorq_ctx_event`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RQEvent {
+    pub kind: RQEventKind,
+    pub status: RQStatus,
+    pub count: u32,
+    /// Wall-clock time since the context was created, pauses included;
+    /// `0` without a clock.
+    pub at_ms: u64,
+    pub value: u64,
+}
+
+impl RQEvent {
+    const NONE: RQEvent = RQEvent {
+        kind: RQEventKind::None,
+        status: RQStatus::Ok,
+        count: 0,
+        at_ms: 0,
+        value: 0,
+    };
+}
+
+/// The bounded log behind `raptorq_ctx_event`: the pinned events, then the
+/// ring of the others.
+#[derive(Clone, Default)]
+pub(crate) struct EventLog {
+    pinned: Vec<RQEvent>,
+    events: VecDeque<RQEvent>,
+    dropped: u64,
+}
+
+impl EventLog {
+    /// Add an event at `at`, folding an error into the previous one when
+    /// it repeats its status.
+    pub fn record(&mut self, kind: RQEventKind, status: RQStatus, at: Duration, value: u64) {
+        if kind == RQEventKind::Error {
+            if let Some(last) = self.events.back_mut() {
+                if last.kind == kind && last.status == status {
+                    last.count = last.count.saturating_add(1);
+                    last.value = value;
+                    return;
+                }
+            }
+        }
+        let event = RQEvent {
+            kind,
+            status,
+            count: 1,
+            at_ms: u64::try_from(at.as_millis()).unwrap_or(u64::MAX),
+            value,
+        };
+        if PINNED.contains(&kind) && !self.pinned.iter().any(|e| e.kind == kind) {
+            self.pinned.push(event);
+        } else {
+            self.push(event);
+        }
+    }
+
+    /// Add the solves of one call: `attempts` taking `ns` in all.
+    pub fn record_solves(&mut self, attempts: u32, ns: u64, status: RQStatus, at: Duration) {
+        self.push(RQEvent {
+            kind: RQEventKind::Solve,
+            status,
+            count: attempts,
+            at_ms: u64::try_from(at.as_millis()).unwrap_or(u64::MAX),
+            value: ns,
+        });
+    }
+
+    fn push(&mut self, event: RQEvent) {
+        if self.events.len() == EVENT_CAPACITY - PINNED.len() {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+
+    pub fn len(&self) -> usize {
+        self.pinned.len() + self.events.len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<RQEvent> {
+        match index.checked_sub(self.pinned.len()) {
+            None => self.pinned.get(index).copied(),
+            Some(index) => self.events.get(index).copied(),
+        }
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+/// Whether a call ending in `status` is logged as an error.
+pub(crate) fn is_error(status: RQStatus) -> bool {
+    !matches!(
+        status,
+        RQStatus::Ok
+            | RQStatus::PaddingStripped
+            | RQStatus::ReadyToFinalize
+            | RQStatus::Complete
+            | RQStatus::Ignored
+    )
+}
+
+/// Number of events `ctx` holds, at most 64; `0` for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_event_count(ctx: *const RQContext) -> u32 {
+    if check_ctx(ctx).is_err() {
+        return 0;
+    }
+    with_ctx_ref(ctx, |ctx| ctx.events().len() as u32).unwrap_or(0)
+}
+
+/// Event `index` of `ctx`'s log, oldest first: when the context was set up,
+/// the first frame, every push or finalize step that ran solves, completion
+/// and errors (runs of the same one folded together), with the time since
+/// creation.  The setup and first frame events stay at the front for good;
+/// once 64 events are kept the oldest of the others make way for new ones,
+/// see [`raptorq_ctx_events_dropped`].  An event of kind
+/// [`RQEventKind::None`] for an index out of range and for a `NULL`
+/// context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_event(ctx: *const RQContext, index: u32) -> RQEvent {
+    if check_ctx(ctx).is_err() {
+        return RQEvent::NONE;
+    }
+    with_ctx_ref(ctx, |ctx| ctx.events().get(index as usize))
+        .ok()
+        .flatten()
+        .unwrap_or(RQEvent::NONE)
+}
+
+/// Events dropped from `ctx`'s log to make room, from just after the setup
+/// and first frame events; the indices of [`raptorq_ctx_event`] past those
+/// two skip them.  `0` for a `NULL` context.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_events_dropped(ctx: *const RQContext) -> u64 {
+    if check_ctx(ctx).is_err() {
+        return 0;
+    }
+    with_ctx_ref(ctx, |ctx| ctx.events().dropped()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounded_and_folded() {
+        let mut log = EventLog::default();
+        let at = Duration::from_millis(1_500);
+        log.record(RQEventKind::OtiLearned, RQStatus::Ok, Duration::ZERO, 9);
+        for len in 0..3 {
+            log.record(RQEventKind::Error, RQStatus::Malformed, at, len);
+        }
+        log.record(RQEventKind::Error, RQStatus::Cancelled, at, 0);
+        assert_eq!(log.len(), 3);
+        assert_eq!(
+            log.get(1),
+            Some(RQEvent {
+                kind: RQEventKind::Error,
+                status: RQStatus::Malformed,
+                count: 3,
+                at_ms: 1_500,
+                value: 2,
+            })
+        );
+        for _ in 0..EVENT_CAPACITY {
+            log.record_solves(1, 10, RQStatus::Ok, at);
+        }
+        // The two errors and two solves made way; the setup stays.
+        assert_eq!(log.len(), EVENT_CAPACITY - 1);
+        assert_eq!(log.dropped(), 4);
+        assert_eq!(log.get(0).map(|e| e.kind), Some(RQEventKind::OtiLearned));
+        assert_eq!(log.get(1).map(|e| e.kind), Some(RQEventKind::Solve));
+        assert_eq!(log.get(EVENT_CAPACITY - 1), None);
+    }
+
+    #[test]
+    fn overflow_keeps_the_start() {
+        let mut log = EventLog::default();
+        let at = Duration::from_millis(20);
+        log.record(RQEventKind::OtiLearned, RQStatus::Ok, Duration::ZERO, 9);
+        log.record(RQEventKind::FirstFrame, RQStatus::Ok, at, 30);
+        for i in 0..100 {
+            log.record_solves(1, i, RQStatus::Ok, at);
+            let status = [RQStatus::Malformed, RQStatus::Busy][i as usize % 2];
+            log.record(RQEventKind::Error, status, at, i);
+        }
+        log.record(RQEventKind::Complete, RQStatus::Complete, at, 9);
+        assert_eq!(log.len(), EVENT_CAPACITY);
+        assert_eq!(log.dropped(), 201 - (EVENT_CAPACITY as u64 - 2));
+        assert_eq!(log.get(0).map(|e| e.value), Some(9));
+        assert_eq!(log.get(0).map(|e| e.kind), Some(RQEventKind::OtiLearned));
+        assert_eq!(
+            log.get(1).map(|e| (e.kind, e.status)),
+            Some((RQEventKind::FirstFrame, RQStatus::Ok))
+        );
+        assert_eq!(log.get(2).map(|e| e.kind), Some(RQEventKind::Error));
+        assert_eq!(
+            log.get(EVENT_CAPACITY - 1).map(|e| e.kind),
+            Some(RQEventKind::Complete)
+        );
+        assert_eq!(log.get(EVENT_CAPACITY), None);
+    }
+}
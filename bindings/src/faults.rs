@@ -0,0 +1,125 @@
+//! Fault injection for QA builds (`faults` feature): failures switched on
+//! from the app, so its error handling can be tested on demand instead of
+//! waiting for a device to run out of memory or a transfer to go wrong.
+//!
+//! The switches are process-wide, like the QA menu they are set from, and
+//! apply to every context from the next call on.
+
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use core::time::Duration;
+
+static FAIL_ALLOCATIONS: AtomicBool = AtomicBool::new(false);
+static FAIL_SOLVES: AtomicBool = AtomicBool::new(false);
+static COMPLETION_DELAY_MS: AtomicU32 = AtomicU32::new(0);
+
+/// Failures for `raptorq_set_faults` to inject.  All zero injects none.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RQFaults {
+    /// Fail the allocations the API reports: `raptorq_alloc` and
+    /// `raptorq_realloc` return `NULL`, `raptorq_ctx_preallocate` `false`.
+    pub fail_allocations: bool,
+    /// Throw away the outcome of every solve, as if the symbols did not
+    /// determine the block: transfers keep asking for frames, and complete
+    /// with the next one once the switch is off again.
+    pub fail_solves: bool,
+    /// Hold up the push or finalize step that completes an object for this
+    /// long, like a slow device; `0` for none.
+    pub completion_delay_ms: u32,
+}
+
+pub(crate) fn fail_allocations() -> bool {
+    FAIL_ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+pub(crate) fn fail_solves() -> bool {
+    FAIL_SOLVES.load(Ordering::Relaxed)
+}
+
+/// Sleep for the completion delay, if any.
+pub(crate) fn delay_completion() {
+    let ms = COMPLETION_DELAY_MS.load(Ordering::Relaxed);
+    if ms > 0 {
+        std::thread::sleep(Duration::from_millis(u64::from(ms)));
+    }
+}
+
+/// Switch the injected failures to `faults`, replacing the previous
+/// setting; pass all zero to stop injecting.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_set_faults(faults: RQFaults) {
+    FAIL_ALLOCATIONS.store(faults.fail_allocations, Ordering::Relaxed);
+    FAIL_SOLVES.store(faults.fail_solves, Ordering::Relaxed);
+    COMPLETION_DELAY_MS.store(faults.completion_delay_ms, Ordering::Relaxed);
+}
+
+/// The failures currently injected.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_faults() -> RQFaults {
+    RQFaults {
+        fail_allocations: fail_allocations(),
+        fail_solves: fail_solves(),
+        completion_delay_ms: COMPLETION_DELAY_MS.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(all(test, feature = "encoder"))]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::status::RQStatus;
+    use crate::*;
+
+    // The switches are global: this is the only test touching them, and the
+    // gates run it on its own.
+    #[test]
+    fn injected_failures() {
+        let data: Vec<u8> = (0..4_000u32).map(|i| (i * 7) as u8).collect();
+        let enc = unsafe { raptorq_enc_new(data.as_ptr(), data.len() as u64, 256, 8) };
+        let frames = unsafe { (*enc).frames.clone() };
+        let oti = unsafe { raptorq_enc_oti_buffer(enc) };
+        let new_ctx = || unsafe { raptorq_ctx_from_oti(oti.ptr) };
+        let push = |ctx, frame: &Vec<u8>| unsafe {
+            raptorq_ctx_push_frame_status(ctx, frame.as_ptr(), frame.len() as u64)
+        };
+
+        raptorq_set_faults(RQFaults {
+            fail_allocations: true,
+            ..RQFaults::default()
+        });
+        assert!(raptorq_alloc(16).is_null());
+        let ctx = new_ctx();
+        assert!(!unsafe { raptorq_ctx_preallocate(ctx) });
+        unsafe { raptorq_ctx_free(ctx) };
+
+        // Without its first source symbol the block has to be solved for.
+        raptorq_set_faults(RQFaults {
+            fail_solves: true,
+            ..RQFaults::default()
+        });
+        let ctx = new_ctx();
+        let (last, rest) = frames[1..].split_last().unwrap();
+        for frame in rest {
+            assert_eq!(push(ctx, frame), RQStatus::Ok);
+        }
+        raptorq_set_faults(RQFaults {
+            completion_delay_ms: 50,
+            ..RQFaults::default()
+        });
+        assert_eq!(raptorq_faults().completion_delay_ms, 50);
+        let start = Instant::now();
+        assert_eq!(push(ctx, last), RQStatus::Complete);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        unsafe { raptorq_ctx_free(ctx) };
+
+        raptorq_set_faults(RQFaults::default());
+        let ctx = new_ctx();
+        assert!(unsafe { raptorq_ctx_preallocate(ctx) });
+        unsafe {
+            raptorq_ctx_free(ctx);
+            raptorq_buffer_free(oti);
+            raptorq_enc_free(enc);
+        }
+    }
+}
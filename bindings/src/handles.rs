@@ -0,0 +1,394 @@
+//! Generation‑checked `u64` handles to decoding contexts.
+//!
+//! A raw `*mut RQContext` used after `raptorq_ctx_free` is undefined
+//! behaviour: a crash at best, silent corruption of whatever reused the
+//! memory at worst.  A handle is an index into a process‑wide table plus the
+//! generation of that slot; freeing bumps the generation, so a stale or
+//! forged handle is detected on every call and reported as
+//! [`RQStatus::InvalidHandle`] (or `NULL`/`false`) instead.
+//!
+//! ```text
+//! [generation: u32][slot index + 1: u32]   (0 is never a valid handle)
+//! ```
+//!
+//! Each slot holds its context behind its own lock, so calls on different
+//! handles only contend for the brief table lookup.
+//!
+//! A handle given a time‑to‑live ([`raptorq_handle_set_ttl`]) is reaped once
+//! that long passes without a call through it, so a context whose owner was
+//! torn down mid‑scan does not leak for the rest of the process.  An expired
+//! handle is stale from then on; its context is dropped the next time the
+//! handle is used, a handle is created, or [`raptorq_handle_reap_expired`]
+//! runs.  [`raptorq_handle_counters`] makes leaks visible in QA builds.
+
+use std::ptr;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use raptorq::ObjectTransmissionInformation;
+
+use crate::context::RQContext;
+use crate::memory::RQBuffer;
+use crate::stats::Stopwatch;
+use crate::status::RQStatus;
+use crate::{
+    check_ctx, check_transfer, into_raw_buffer, invalid_argument, slice_from_raw, try_catch_hot,
+    try_catch_unwind,
+};
+
+/// Lifetime counts of the handle table, for spotting leaked contexts.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RQHandleCounters {
+    /// Contexts currently behind a handle (expired ones not yet reaped
+    /// included).
+    pub live: u64,
+    pub created: u64,
+    /// Freed with [`raptorq_handle_free`].
+    pub freed: u64,
+    /// Reaped after their time‑to‑live ran out; non‑zero means the app
+    /// forgot to free contexts.
+    pub expired: u64,
+}
+
+/// The contexts behind handles right now, summed up by
+/// `raptorq_handle_stats` for a receive queue screen.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RQHandleStats {
+    /// Transfers still collecting frames.
+    pub active: u64,
+    /// Transfers whose object was recovered, whether taken or not.
+    pub completed: u64,
+    /// Transfers that recovered an object not matching the handshake's
+    /// digest.
+    pub failed: u64,
+    /// Length of the objects of the completed transfers, added up.
+    pub bytes_recovered: u64,
+    /// Heap memory the contexts hold, roughly: buffered symbols, decoded
+    /// blocks and results.
+    pub memory_bytes: u64,
+}
+
+struct Slot {
+    generation: u32,
+    ctx: Option<Arc<Mutex<RQContext>>>,
+    ttl: Option<Duration>,
+    deadline: Option<Instant>, // refreshed by every call through the handle
+}
+
+struct Table {
+    slots: Vec<Slot>,
+    free: Vec<u32>, // indices of empty slots
+    counters: RQHandleCounters,
+}
+
+static TABLE: Mutex<Table> = Mutex::new(Table {
+    slots: Vec::new(),
+    free: Vec::new(),
+    counters: RQHandleCounters {
+        live: 0,
+        created: 0,
+        freed: 0,
+        expired: 0,
+    },
+});
+
+/// A panic while a lock was held is caught at the FFI boundary; the data is
+/// still consistent enough to keep serving (or to free).
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn split(handle: u64) -> Option<(usize, u32)> {
+    let index = (handle as u32).checked_sub(1)?;
+    Some((index as usize, (handle >> 32) as u32))
+}
+
+impl Table {
+    /// Empty slot `index`, making every copy of its handle stale.
+    fn vacate(&mut self, index: usize) -> Option<Arc<Mutex<RQContext>>> {
+        let slot = &mut self.slots[index];
+        let ctx = slot.ctx.take()?;
+        slot.ttl = None;
+        slot.deadline = None;
+        // Retire a slot whose generation would wrap instead of reusing it.
+        slot.generation = slot.generation.wrapping_add(1);
+        if slot.generation != 0 {
+            self.free.push(index as u32);
+        }
+        self.counters.live -= 1;
+        Some(ctx)
+    }
+
+    /// Empty every slot whose time‑to‑live ran out, handing the contexts
+    /// back to be dropped outside the table lock.
+    fn reap(&mut self, now: Instant) -> Vec<Arc<Mutex<RQContext>>> {
+        let expired: Vec<usize> = (0..self.slots.len())
+            .filter(|&i| self.slots[i].deadline.is_some_and(|d| d <= now))
+            .collect();
+        self.counters.expired += expired.len() as u64;
+        expired.into_iter().filter_map(|i| self.vacate(i)).collect()
+    }
+}
+
+fn insert(ctx: RQContext) -> u64 {
+    let mut table = lock(&TABLE);
+    let expired = table.reap(Instant::now());
+    let handle = insert_locked(&mut table, ctx);
+    drop(table);
+    drop(expired);
+    handle
+}
+
+fn insert_locked(table: &mut Table, ctx: RQContext) -> u64 {
+    let index = match table.free.pop() {
+        Some(index) => index,
+        None => {
+            let index = table.slots.len() as u32;
+            // Index + 1 must stay a non-zero `u32`.
+            if index == u32::MAX {
+                return 0;
+            }
+            table.slots.push(Slot {
+                generation: 1,
+                ctx: None,
+                ttl: None,
+                deadline: None,
+            });
+            index
+        }
+    };
+    table.counters.live += 1;
+    table.counters.created += 1;
+    let slot = &mut table.slots[index as usize];
+    slot.ctx = Some(Arc::new(Mutex::new(ctx)));
+    u64::from(slot.generation) << 32 | u64::from(index + 1)
+}
+
+/// The context behind a live handle, restarting its time‑to‑live.  An
+/// expired handle is reaped here and reported as stale.
+fn resolve(handle: u64) -> Option<Arc<Mutex<RQContext>>> {
+    let (index, generation) = split(handle)?;
+    let mut table = lock(&TABLE);
+    let slot = table.slots.get_mut(index)?;
+    if slot.generation != generation || slot.ctx.is_none() {
+        return None;
+    }
+    if let Some(ttl) = slot.ttl {
+        let now = Instant::now();
+        if slot.deadline.is_some_and(|d| d <= now) {
+            table.counters.expired += 1;
+            let expired = table.vacate(index);
+            drop(table);
+            drop(expired);
+            return None;
+        }
+        slot.deadline = now.checked_add(ttl);
+    }
+    slot.ctx.clone()
+}
+
+/// Run `f` on the context behind `handle`, `None` if the handle is stale.
+fn with_ctx<R>(handle: u64, f: impl FnOnce(&mut RQContext) -> R) -> Option<R> {
+    let ctx = resolve(handle)?;
+    let mut ctx = lock(&ctx);
+    Some(f(&mut ctx))
+}
+
+/// Create a context like `raptorq_ctx_new` and return a handle to it, or `0`
+/// on failure.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_handle_new(transfer_length: u64, max_payload_size: u16) -> u64 {
+    if check_transfer(transfer_length, max_payload_size).is_err() {
+        return 0;
+    }
+    try_catch_unwind(|| {
+        let oti = ObjectTransmissionInformation::with_defaults(transfer_length, max_payload_size);
+        insert(RQContext::new(oti))
+    })
+    .unwrap_or(0)
+}
+
+/// Move a context created by any `raptorq_ctx_*` constructor behind a
+/// handle.  The pointer must not be used afterwards.  Returns `0` for a
+/// `NULL` context, one that was retained (other references would dangle)
+/// or one with a decode thread running: stop it first with
+/// `raptorq_ctx_stop_decode_thread`.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_handle_adopt(ctx: *mut RQContext) -> u64 {
+    if check_ctx(ctx).is_err() {
+        return 0;
+    }
+    if (*ctx).refs.load(Ordering::Acquire) != 1 {
+        invalid_argument("context is retained");
+        return 0;
+    }
+    if (*ctx).worker.is_some() {
+        invalid_argument("context has a decode thread running");
+        return 0;
+    }
+    let ctx = RQContext::take_raw(ctx);
+    try_catch_unwind(|| insert(ctx)).unwrap_or(0)
+}
+
+/// Whether `handle` refers to a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_handle_is_valid(handle: u64) -> bool {
+    try_catch_unwind(|| resolve(handle).is_some()).unwrap_or(false)
+}
+
+/// [`crate::raptorq_ctx_push_frame_status`] through a handle.
+///
+/// # Safety
+/// `payload_ptr` must point to `payload_len` readable bytes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_handle_push_frame(
+    handle: u64,
+    payload_ptr: *const u8,
+    payload_len: u64,
+) -> RQStatus {
+    let payload = match slice_from_raw(payload_ptr, payload_len) {
+        Ok(payload) => payload,
+        Err(status) => return status,
+    };
+    with_ctx(handle, |ctx| {
+        let watch = Stopwatch::start();
+        let status = try_catch_hot(|| ctx.push(payload)).unwrap_or(RQStatus::Failed);
+        ctx.push_timer.record(watch.elapsed());
+        status
+    })
+    .unwrap_or(RQStatus::InvalidHandle)
+}
+
+/// [`crate::raptorq_ctx_is_complete`] through a handle; `false` for a stale
+/// handle.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_handle_is_complete(handle: u64) -> bool {
+    with_ctx(handle, |ctx| ctx.is_complete()).unwrap_or(false)
+}
+
+/// [`crate::raptorq_ctx_take_result`] through a handle; `NULL` for a stale
+/// handle.
+///
+/// # Safety
+/// `len_out` must be `NULL` or valid for writes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_handle_take_result(handle: u64, len_out: *mut u64) -> *mut u8 {
+    let data = try_catch_unwind(|| with_ctx(handle, |ctx| ctx.take_result()).flatten());
+    match data.flatten() {
+        Some(data) => into_raw_buffer(data, len_out),
+        None => ptr::null_mut(),
+    }
+}
+
+/// [`raptorq_handle_take_result`] returning an [`RQBuffer`], to be released
+/// with [`crate::raptorq_buffer_free`].
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_handle_take_result_buffer(handle: u64) -> RQBuffer {
+    let mut len = 0;
+    RQBuffer::from_raw(unsafe { raptorq_handle_take_result(handle, &mut len) }, len)
+}
+
+/// Destroy the context behind `handle`; every copy of the handle becomes
+/// stale.  Returns [`RQStatus::InvalidHandle`] for a handle that was already
+/// freed (or never valid).  The context itself is dropped once a call still
+/// running on it from another thread returns.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_handle_free(handle: u64) -> RQStatus {
+    try_catch_unwind(|| {
+        let (index, generation) = match split(handle) {
+            Some(parts) => parts,
+            None => return RQStatus::InvalidHandle,
+        };
+        let ctx = {
+            let mut table = lock(&TABLE);
+            match table.slots.get(index) {
+                Some(slot) if slot.generation == generation && slot.ctx.is_some() => {}
+                _ => return RQStatus::InvalidHandle,
+            }
+            table.counters.freed += 1;
+            table.vacate(index)
+        };
+        // Dropped outside the table lock.
+        drop(ctx);
+        RQStatus::Ok
+    })
+    .unwrap_or(RQStatus::Failed)
+}
+
+/// Reap the context behind `handle` once `ttl_ms` milliseconds pass without
+/// a call through the handle; `0` removes the time‑to‑live.  Meant as a
+/// safety net for contexts the app forgets to free, so pick it well above
+/// the longest pause a scan can take.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_handle_set_ttl(handle: u64, ttl_ms: u64) -> RQStatus {
+    try_catch_unwind(|| {
+        let (index, generation) = match split(handle) {
+            Some(parts) => parts,
+            None => return RQStatus::InvalidHandle,
+        };
+        let mut table = lock(&TABLE);
+        let slot = match table.slots.get_mut(index) {
+            Some(slot) if slot.generation == generation && slot.ctx.is_some() => slot,
+            _ => return RQStatus::InvalidHandle,
+        };
+        slot.ttl = (ttl_ms > 0).then(|| Duration::from_millis(ttl_ms));
+        slot.deadline = slot.ttl.and_then(|ttl| Instant::now().checked_add(ttl));
+        RQStatus::Ok
+    })
+    .unwrap_or(RQStatus::Failed)
+}
+
+/// Reap every context whose time‑to‑live ran out now rather than on the next
+/// handle call, e.g. on a memory warning.  Returns how many were reaped.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_handle_reap_expired() -> u32 {
+    try_catch_unwind(|| {
+        let expired = lock(&TABLE).reap(Instant::now());
+        expired.len() as u32
+    })
+    .unwrap_or(0)
+}
+
+/// Sum up every context behind a live handle, in one call instead of one
+/// per context per field.  Each context is locked in turn, so a push in
+/// progress on another thread delays the call until it returns.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_handle_stats() -> RQHandleStats {
+    try_catch_unwind(|| {
+        // Collected first, so no context is locked under the table lock.
+        let contexts: Vec<Arc<Mutex<RQContext>>> = lock(&TABLE)
+            .slots
+            .iter()
+            .filter_map(|slot| slot.ctx.clone())
+            .collect();
+        let mut stats = RQHandleStats::default();
+        for ctx in contexts {
+            let ctx = lock(&ctx);
+            if ctx.digest_mismatch {
+                stats.failed += 1;
+            } else if ctx.is_complete() || ctx.taken {
+                stats.completed += 1;
+                stats.bytes_recovered += ctx.oti.transfer_length();
+            } else {
+                stats.active += 1;
+            }
+            stats.memory_bytes += ctx.heap_bytes() as u64;
+        }
+        stats
+    })
+    .unwrap_or_default()
+}
+
+/// Snapshot of the handle table's counters since the process started.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_handle_counters() -> RQHandleCounters {
+    try_catch_unwind(|| lock(&TABLE).counters).unwrap_or_default()
+}
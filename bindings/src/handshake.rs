@@ -0,0 +1,110 @@
+//! Optional "frame zero" carrying everything a receiver needs up front.
+//!
+//! ```text
+//! offset  size  field
+//!      0     4  magic "RQHS"
+//!      4     1  format version (1)
+//!      5    12  OTI, as serialized by `ObjectTransmissionInformation`
+//!     17    32  SHA‑256 of the original object
+//!     49     2  content type (big endian, application defined)
+//!     51     4  total frames in one animation loop (big endian)
+//! ```
+//!
+//! The magic makes the frame distinguishable from an encoding packet, whose
+//! first byte is a source block number and is never `'R'` for the block counts
+//! used over QR.
+
+#[cfg(all(feature = "decoder", feature = "std"))]
+use std::io::{self, Read};
+
+#[cfg(feature = "encoder")]
+use alloc::vec::Vec;
+
+use raptorq::ObjectTransmissionInformation;
+use sha2::{Digest, Sha256};
+
+pub(crate) const HANDSHAKE_MAGIC: [u8; 4] = *b"RQHS";
+pub(crate) const HANDSHAKE_VERSION: u8 = 1;
+pub(crate) const HANDSHAKE_LEN: usize = 55;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Handshake {
+    pub oti: ObjectTransmissionInformation,
+    pub digest: [u8; 32],
+    pub content_type: u16,
+    pub total_frames: u32,
+}
+
+impl Handshake {
+    #[cfg(feature = "encoder")]
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HANDSHAKE_LEN);
+        out.extend_from_slice(&HANDSHAKE_MAGIC);
+        out.push(HANDSHAKE_VERSION);
+        out.extend_from_slice(&self.oti.serialize());
+        out.extend_from_slice(&self.digest);
+        out.extend_from_slice(&self.content_type.to_be_bytes());
+        out.extend_from_slice(&self.total_frames.to_be_bytes());
+        out
+    }
+
+    /// Parse a handshake frame; `None` if `bytes` is not one.
+    #[cfg(feature = "decoder")]
+    pub fn parse(bytes: &[u8]) -> Option<Handshake> {
+        if !is_handshake(bytes) {
+            return None;
+        }
+        let mut oti = [0u8; 12];
+        oti.copy_from_slice(&bytes[5..17]);
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&bytes[17..49]);
+        Some(Handshake {
+            oti: ObjectTransmissionInformation::deserialize(&oti),
+            digest,
+            content_type: u16::from_be_bytes([bytes[49], bytes[50]]),
+            total_frames: u32::from_be_bytes([bytes[51], bytes[52], bytes[53], bytes[54]]),
+        })
+    }
+}
+
+#[cfg(feature = "decoder")]
+pub(crate) fn is_handshake(bytes: &[u8]) -> bool {
+    bytes.len() == HANDSHAKE_LEN && bytes[..4] == HANDSHAKE_MAGIC && bytes[4] == HANDSHAKE_VERSION
+}
+
+pub(crate) fn digest(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// [`digest`] of everything `reader` yields.
+#[cfg(all(feature = "decoder", feature = "std"))]
+pub(crate) fn digest_reader(mut reader: impl Read) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok(hasher.finalize().into());
+        }
+        hasher.update(&buf[..n]);
+    }
+}
+
+#[cfg(all(test, feature = "encoder", feature = "decoder"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handshake_roundtrip() {
+        let hs = Handshake {
+            oti: ObjectTransmissionInformation::with_defaults(4096, 1072),
+            digest: digest(b"payload"),
+            content_type: 7,
+            total_frames: 9,
+        };
+        let bytes = hs.serialize();
+        assert_eq!(bytes.len(), HANDSHAKE_LEN);
+        assert_eq!(Handshake::parse(&bytes), Some(hs));
+        assert_eq!(Handshake::parse(&bytes[1..]), None);
+    }
+}
@@ -13,9 +13,14 @@
 //! // 2. Feed every scanned QR frame.
 //! if raptorq_ctx_push_frame(ctx, dataPtr, dataLen) {
 //!     if raptorq_ctx_is_complete(ctx) {
-//!         var outLen: UInt = 0
+//!         var outLen: UInt64 = 0
 //!         if let buf = raptorq_ctx_take_result(ctx, &outLen) {
-//!             let recovered = Data(bytesNoCopy: buf, count: Int(outLen), deallocator: .free)
+//!             // The buffer belongs to the library: hand it back to it.
+//!             let recovered = Data(bytesNoCopy: buf, count: Int(outLen),
+//!                                  deallocator: .custom { ptr, len in
+//!                                      raptorq_free(ptr.assumingMemoryBound(to: UInt8.self),
+//!                                                   UInt64(len))
+//!                                  })
 //!             // …use `recovered`…
 //!         }
 //!     }
@@ -27,150 +32,721 @@
 //! > **Safety**  All functions catch panics so no Rust unwind can cross the FFI
 //! > boundary; on error they return a sentinel value (usually `NULL`/`false`).
 //! > The caller is responsible for eventually freeing any heap memory returned
-//! > by this library using [`raptorq_free`].
+//! > by this library using [`raptorq_free`], or [`raptorq_buffer_free`] for
+//! > the [`RQBuffer`] returned by every `_buffer` function.  The message of a caught panic is
+//! > available from `raptorq_last_error` and `raptorq_set_panic_callback`.
+//!
+//! ## `no_std`
+//! Everything but output files (`raptorq_ctx_set_output_file`), parallel
+//! solving and push timing builds on `core` + `alloc` alone: disable the
+//! default `std` feature to embed the decoder in firmware or simulators.
+//! Panics can then not be caught and must abort.
 
-use std::panic::{catch_unwind, AssertUnwindSafe};
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 
-use core::{ptr, slice};
-use raptorq::{Decoder, EncodingPacket, ObjectTransmissionInformation};
+extern crate alloc;
+extern crate raptorq_codec as raptorq;
 
-pub struct RQContext {
-    oti: ObjectTransmissionInformation,
-    decoder: Decoder,
-    result: Option<Vec<u8>>, // populated when decoding finished
+/// Log a line through [`raptorq_set_logger`] if the logger (or, with
+/// `in scope,`, a context's [`log::LogScope`]) wants `$level`; the arguments
+/// are not evaluated otherwise.  Compiles to nothing without `std`.
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+macro_rules! rq_log {
+    (in $scope:expr, $level:ident, $($arg:tt)*) => {{
+        #[cfg(feature = "std")]
+        if $scope.enabled($crate::log::RQLogLevel::$level) {
+            $scope.emit($crate::log::RQLogLevel::$level, format_args!($($arg)*));
+        }
+        #[cfg(not(feature = "std"))]
+        if false {
+            let _ = &$scope;
+            let _ = format_args!($($arg)*);
+        }
+    }};
+    ($level:ident, $($arg:tt)*) => {{
+        #[cfg(feature = "std")]
+        if $crate::log::enabled($crate::log::RQLogLevel::$level) {
+            $crate::log::emit($crate::log::RQLogLevel::$level, format_args!($($arg)*));
+        }
+        #[cfg(not(feature = "std"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
 }
 
+use core::ffi::c_char;
+#[cfg(all(feature = "encoder", feature = "decoder"))]
+use core::ptr;
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+use core::slice;
+
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+use alloc::boxed::Box;
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "decoder")]
+mod blocks;
+#[cfg(feature = "decoder")]
+mod cancel;
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+mod checksum;
+#[cfg(feature = "decoder")]
+mod context;
+#[cfg(feature = "decoder")]
+mod decoder;
+#[cfg(feature = "decoder")]
+mod dedupe;
+#[cfg(feature = "decoder")]
+mod dump;
+#[cfg(feature = "encoder")]
+mod encoder;
+#[cfg(feature = "decoder")]
+mod events;
+#[cfg(feature = "faults")]
+mod faults;
+#[cfg(all(feature = "decoder", feature = "std"))]
+mod handles;
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+mod handshake;
+mod log;
+#[cfg(all(feature = "decoder", feature = "std", unix))]
+mod mapping;
+mod memory;
+#[cfg(feature = "decoder")]
+mod notify;
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+mod packing;
+#[cfg(feature = "std")]
+mod panics;
+#[cfg(feature = "decoder")]
+mod pool;
+#[cfg(feature = "decoder")]
+mod profile;
+#[cfg(all(feature = "encoder", feature = "decoder"))]
+mod regression;
+#[cfg(feature = "decoder")]
+mod session;
+mod simd;
+#[cfg(feature = "simulator")]
+mod simulator;
+#[cfg(feature = "decoder")]
+mod snapshot;
+#[cfg(all(feature = "decoder", feature = "std"))]
+mod spool;
+#[cfg(feature = "decoder")]
+mod stats;
+mod status;
+#[cfg(feature = "uniffi")]
+mod uniffi_api;
+#[cfg(feature = "wasm")]
+mod wasm_api;
+#[cfg(all(feature = "decoder", feature = "std"))]
+mod worker;
+
+/// Every `raptorq_*` export again under the `prefixed` feature's prefix,
+/// generated by `build.rs`.
+#[cfg(feature = "prefixed")]
+mod prefixed {
+    use super::*;
+    #[cfg(any(feature = "std", feature = "decoder"))]
+    use core::ffi::c_void;
+    include!(concat!(env!("OUT_DIR"), "/prefixed.rs"));
+}
+
+#[cfg(feature = "decoder")]
+pub use cancel::{raptorq_cancel, raptorq_cancel_token_free, RQCancelToken};
+#[cfg(feature = "decoder")]
+pub use context::RQContext;
+#[cfg(feature = "decoder")]
+pub use decoder::*;
+#[cfg(feature = "decoder")]
+pub use dump::{raptorq_ctx_dump, raptorq_ctx_dump_buffer, raptorq_ctx_from_dump};
+#[cfg(feature = "encoder")]
+pub use encoder::*;
+#[cfg(feature = "decoder")]
+pub use events::{
+    raptorq_ctx_event, raptorq_ctx_event_count, raptorq_ctx_events_dropped, RQEvent, RQEventKind,
+};
+#[cfg(feature = "faults")]
+pub use faults::{raptorq_faults, raptorq_set_faults, RQFaults};
+#[cfg(all(feature = "decoder", feature = "std"))]
+pub use handles::*;
+#[cfg(feature = "std")]
+pub use log::{raptorq_set_logger, RQLogCallback, RQLogLevel};
+pub use memory::{
+    raptorq_alloc, raptorq_buffer_box_free, raptorq_buffer_box_free_zeroize, raptorq_buffer_free,
+    raptorq_buffer_free_zeroize, raptorq_buffer_into_box, raptorq_dealloc, raptorq_free_zeroize,
+    raptorq_realloc, RQBuffer,
+};
+#[cfg(feature = "decoder")]
+pub use notify::{RQCompleteCallback, RQMilestoneCallback, RQProgressCallback};
+#[cfg(feature = "std")]
+pub use panics::*;
+#[cfg(feature = "decoder")]
+pub use pool::RQPool;
+#[cfg(feature = "decoder")]
+pub use profile::RQProfile;
+#[cfg(all(feature = "encoder", feature = "decoder"))]
+pub use regression::{RQRegressionFailure, RQRegressionReport};
+#[cfg(feature = "decoder")]
+pub use session::{RQScanSession, RQScanStats};
+pub use simd::RQSimdBackend;
+#[cfg(feature = "simulator")]
+pub use simulator::{RQLossConfig, RQLossCounters, RQLossSimulator};
+#[cfg(feature = "decoder")]
+pub use snapshot::{raptorq_ctx_deserialize, raptorq_ctx_serialize, raptorq_ctx_serialize_buffer};
+#[cfg(all(feature = "decoder", feature = "std"))]
+pub use snapshot::{raptorq_ctx_load, raptorq_ctx_save};
+#[cfg(feature = "decoder")]
+pub use stats::{
+    RQBlockTiming, RQDensityAdvice, RQEfficiency, RQFrameQuality, RQPushStats, RQStallWindow,
+    RQStats,
+};
+pub use status::{
+    raptorq_status_message, RQErrorInfo, RQStatus, RQ_ERROR_HAS_LENGTHS, RQ_ERROR_HAS_OFFSET,
+    RQ_ERROR_HAS_SYMBOL,
+};
+/// The scaffolding looks its tag type up at the crate root.
+#[cfg(feature = "uniffi")]
+use uniffi_api::UniFfiTag;
+
 //—‑ helpers ————————————————————————————————————————————————————————————————
 
+#[cfg(feature = "std")]
 #[inline]
 fn try_catch_unwind<F: FnOnce() -> R, R>(f: F) -> Option<R> {
-    catch_unwind(AssertUnwindSafe(f)).ok()
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f))
+        .map_err(panics::report)
+        .ok()
 }
 
+/// Without `std` there is no unwinding to catch; panics abort.
+#[cfg(not(feature = "std"))]
 #[inline]
-unsafe fn slice_from_raw<'a>(ptr: *const u8, len: usize) -> &'a [u8] {
-    if ptr.is_null() || len == 0 {
-        &[]
+fn try_catch_unwind<F: FnOnce() -> R, R>(f: F) -> Option<R> {
+    Some(f())
+}
+
+/// [`try_catch_unwind`] for the calls made once per camera frame.  With the
+/// `panic-abort` feature a panic is not caught there: under
+/// `panic = "abort"` there is nothing to catch, and otherwise unwinding out of
+/// the `extern "C"` function aborts anyway.
+#[cfg(all(feature = "decoder", not(feature = "panic-abort")))]
+#[inline]
+fn try_catch_hot<F: FnOnce() -> R, R>(f: F) -> Option<R> {
+    try_catch_unwind(f)
+}
+
+#[cfg(all(feature = "decoder", feature = "panic-abort"))]
+#[inline(always)]
+fn try_catch_hot<F: FnOnce() -> R, R>(f: F) -> Option<R> {
+    Some(f())
+}
+
+/// Lengths cross the C API as `u64` on every target and are narrowed here
+/// only, so 32‑bit builds (armv7k/watchOS) reject what they cannot address
+/// instead of silently truncating it.
+#[inline]
+fn checked_len(len: u64) -> Option<usize> {
+    usize::try_from(len).ok()
+}
+
+/// Record why a call rejected its arguments as the calling thread's last
+/// error (with `std`, see `raptorq_last_error`).  Functions returning a
+/// status return the [`RQStatus::InvalidArgument`] this gives; the others
+/// return their usual `NULL`/`false`/`0`.
+#[cold]
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+fn invalid_argument(reason: &'static str) -> RQStatus {
+    #[cfg(feature = "std")]
+    panics::set_last_error(reason, RQErrorInfo::new(RQStatus::InvalidArgument));
+    #[cfg(not(feature = "std"))]
+    let _ = reason;
+    RQStatus::InvalidArgument
+}
+
+/// Record that a call found its context in use on another thread, like
+/// [`invalid_argument`], and return [`RQStatus::Busy`].
+#[cfg(feature = "decoder")]
+#[cold]
+fn busy() -> RQStatus {
+    #[cfg(feature = "std")]
+    panics::set_last_error(
+        "context is in use by another thread",
+        RQErrorInfo::new(RQStatus::Busy),
+    );
+    RQStatus::Busy
+}
+
+/// Record why a frame was turned away, with `info` saying where, like
+/// [`invalid_argument`], and return its status.
+#[cfg(feature = "decoder")]
+#[cold]
+fn rejected(reason: &'static str, info: RQErrorInfo) -> RQStatus {
+    #[cfg(feature = "std")]
+    panics::set_last_error(reason, info);
+    #[cfg(not(feature = "std"))]
+    let _ = reason;
+    info.status
+}
+
+/// The `len` bytes at `ptr`; `NULL` is accepted for an empty buffer only.
+/// [`RQStatus::LengthOverflow`] when `len` does not fit the address space.
+#[inline]
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+unsafe fn slice_from_raw<'a>(ptr: *const u8, len: u64) -> Result<&'a [u8], RQStatus> {
+    let len = checked_len(len).ok_or(RQStatus::LengthOverflow)?;
+    #[cfg(feature = "strict")]
+    if len > STRICT_MAX_LEN {
+        return Err(invalid_argument("length over 1 GiB, likely garbage"));
+    }
+    if len == 0 {
+        Ok(&[])
+    } else if ptr.is_null() {
+        Err(invalid_argument("NULL buffer with a non-zero length"))
     } else {
-        slice::from_raw_parts(ptr, len)
+        Ok(slice::from_raw_parts(ptr, len))
     }
 }
 
-//—‑ public C/Swift API ————————————————————————————————————————————————————
+/// With `strict`, longer buffers are taken for an uninitialized or corrupted
+/// length rather than data.
+#[cfg(feature = "strict")]
+const STRICT_MAX_LEN: usize = 1 << 30;
+
+/// Calls refused by [`check_ctx`] for a context that was already freed.
+#[cfg(feature = "strict")]
+static USE_AFTER_FREE: core::sync::atomic::AtomicU32 = core::sync::atomic::AtomicU32::new(0);
 
-/// Build a [`RQContext`] from the raw **12‑byte** OTI header that the encoder
-/// usually embeds in its first QR frame.
-#[no_mangle]
-pub unsafe extern "C" fn raptorq_ctx_from_oti(oti_ptr: *const u8) -> *mut RQContext {
-    let oti_bytes = slice_from_raw(oti_ptr, 12);
-    if oti_bytes.len() != 12 {
-        return ptr::null_mut();
-    }
-    let mut buf = [0u8; 12];
-    buf.copy_from_slice(oti_bytes);
-    try_catch_unwind(|| {
-        let oti = ObjectTransmissionInformation::deserialize(&buf);
-        let decoder = Decoder::new(oti);
-        Box::into_raw(Box::new(RQContext { oti, decoder, result: None }))
-    })
-    .unwrap_or(ptr::null_mut())
-}
-
-/// Convenience constructor when you **already know** the transfer length and
-/// the maximum payload size of your QR frames.
-#[no_mangle]
-pub extern "C" fn raptorq_ctx_new(transfer_length: u64, max_payload_size: u16) -> *mut RQContext {
-    try_catch_unwind(|| {
-        let oti = ObjectTransmissionInformation::with_defaults(transfer_length, max_payload_size);
-        let decoder = Decoder::new(oti);
-        Box::into_raw(Box::new(RQContext { oti, decoder, result: None }))
-    })
-    .unwrap_or(ptr::null_mut())
-}
-
-/// Push one QR‑frame payload into the decoder.
+/// Refuse a context pointer that is `NULL` or, with `strict`, misaligned or
+/// not pointing to a live context (freed, or some other object).  Records
+/// the reason like [`invalid_argument`] and returns its status.
 ///
-/// Returns `true` **iff** this call finished decoding the whole object.
-#[no_mangle]
-pub unsafe extern "C" fn raptorq_ctx_push_frame(
-    ctx: *mut RQContext,
-    payload_ptr: *const u8,
-    payload_len: usize,
-) -> bool {
-    if ctx.is_null() {
-        return false;
-    }
-    let ctx = &mut *ctx;
-    let payload = slice_from_raw(payload_ptr, payload_len);
-    try_catch_unwind(|| {
-        let packet = EncodingPacket::deserialize(payload);
-        if let Some(data) = ctx.decoder.decode(packet) {
-            ctx.result = Some(data);
-            true
-        } else {
-            false
-        }
-    })
-    .unwrap_or(false)
-}
-
-/// Check whether the decoder has recovered enough packets to rebuild the
-/// original object.
-#[no_mangle]
-pub extern "C" fn raptorq_ctx_is_complete(ctx: *const RQContext) -> bool {
+/// # Safety
+/// Without `strict`, `ctx` must be `NULL` or a live context.  With it, a
+/// stale pointer is still read from, so this only catches misuse on a best
+/// effort basis while developing.
+#[cfg(feature = "decoder")]
+#[inline]
+unsafe fn check_ctx(ctx: *const RQContext) -> Result<(), RQStatus> {
     if ctx.is_null() {
-        return false;
+        return Err(invalid_argument("context is NULL"));
+    }
+    #[cfg(feature = "strict")]
+    if !ctx.is_aligned() {
+        return Err(invalid_argument("context pointer is misaligned"));
+    }
+    #[cfg(feature = "strict")]
+    match core::ptr::read_volatile(core::ptr::addr_of!((*ctx).magic)) {
+        context::CONTEXT_MAGIC => {}
+        context::CONTEXT_FREED => {
+            USE_AFTER_FREE.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+            rq_log!(
+                Warn,
+                "freed context used generation={}",
+                core::ptr::read_volatile(core::ptr::addr_of!((*ctx).generation))
+            );
+            return Err(invalid_argument("context was already freed"));
+        }
+        _ => return Err(invalid_argument("pointer is not a live context")),
     }
-    unsafe { (*ctx).result.is_some() }
+    Ok(())
 }
 
-/// Move the reconstructed buffer **out** of the context.  Caller assumes
-/// ownership and must free it with [`raptorq_free`].  If `len_out` is not
-/// `NULL` the function writes the buffer length to it.
-#[no_mangle]
-pub unsafe extern "C" fn raptorq_ctx_take_result(
-    ctx: *mut RQContext,
-    len_out: *mut usize,
-) -> *mut u8 {
-    if ctx.is_null() {
-        return ptr::null_mut();
+/// Largest object RaptorQ (RFC 6330) can describe.
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+const MAX_TRANSFER_LENGTH: u64 = 946_270_874_880;
+
+/// Reject transfer parameters `raptorq` cannot derive an OTI from.
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+fn check_transfer(transfer_length: u64, max_payload_size: u16) -> Result<(), RQStatus> {
+    if transfer_length == 0 {
+        Err(invalid_argument("transfer length is 0"))
+    } else if transfer_length > MAX_TRANSFER_LENGTH {
+        Err(invalid_argument(
+            "transfer length exceeds the RaptorQ maximum",
+        ))
+    } else if max_payload_size == 0 {
+        Err(invalid_argument("max payload size is 0"))
+    } else {
+        Ok(())
     }
-    let ctx = &mut *ctx;
-    let data = match ctx.result.take() {
-        Some(v) => v,
-        None => return ptr::null_mut(),
-    };
+}
+
+/// Hand `data` over to the caller as a boxed slice to be released with
+/// [`raptorq_free`], writing its length to `len_out` when not `NULL`.  Only
+/// spare capacity forces a reallocation here, so large buffers (decoded
+/// objects) are built with exactly their length.
+#[inline]
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+unsafe fn into_raw_buffer(data: Vec<u8>, len_out: *mut u64) -> *mut u8 {
     if !len_out.is_null() {
-        *len_out = data.len();
+        *len_out = data.len() as u64;
     }
     let boxed = data.into_boxed_slice();
     Box::into_raw(boxed) as *mut u8
 }
 
-/// Free a buffer returned by [`raptorq_ctx_take_result`].
-#[no_mangle]
-pub unsafe extern "C" fn raptorq_free(ptr_: *mut u8, len: usize) {
-    if ptr_.is_null() {
-        return;
+//—‑ public C/Swift API ————————————————————————————————————————————————————
+
+/// Free a buffer returned by [`raptorq_ctx_take_result`], [`raptorq_alloc`]
+/// or one of the `raptorq_enc_*` frame functions.  Same as
+/// [`raptorq_dealloc`], kept for existing callers; the `_buffer` variants
+/// with [`raptorq_buffer_free`] need no length.
+///
+/// # Safety
+/// `ptr_` must be `NULL` or a buffer returned by this library that has not
+/// been freed yet, and `len` must be the length reported alongside it.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_free(ptr_: *mut u8, len: u64) {
+    raptorq_dealloc(ptr_, len);
+}
+
+/// Version of the C API, bumped whenever an existing function, type or
+/// status changes incompatibly (additions do not bump it).
+///
+/// 2: invalid arguments report [`RQStatus::InvalidArgument`] instead of
+/// [`RQStatus::Failed`].
+///
+/// 3: overlapping calls on a context report [`RQStatus::Busy`].
+pub const RAPTORQ_ABI_VERSION: u32 = 3;
+
+/// The crate version as a NUL‑terminated semver string, e.g. `"0.1.0"`.  The
+/// string is static and must not be freed.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+/// [`RAPTORQ_ABI_VERSION`] of the linked binary, for wrappers to compare
+/// with the value in the header they were compiled against.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_abi_version() -> u32 {
+    RAPTORQ_ABI_VERSION
+}
+
+/// Number of calls refused so far because their context had already been
+/// freed, double frees included, for QA builds to assert on.  Only counted
+/// with the `strict` feature, which keeps freed contexts poisoned instead of
+/// releasing their memory; always `0` without it.
+#[cfg(feature = "decoder")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_use_after_free_count() -> u32 {
+    #[cfg(feature = "strict")]
+    return USE_AFTER_FREE.load(core::sync::atomic::Ordering::Relaxed);
+    #[cfg(not(feature = "strict"))]
+    0
+}
+
+/// Run the embedded regression vectors against this build: digests of this
+/// library's own output on a desktop build, not RFC 6330 test vectors.
+///
+/// Every vector is encoded and compared with the recorded digest, then
+/// decoded again after dropping half of its source symbols.
+#[cfg(all(feature = "encoder", feature = "decoder"))]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_run_regression_vectors() -> RQRegressionReport {
+    let vectors = regression::VECTORS;
+    let mut report = RQRegressionReport {
+        total: vectors.len() as u32,
+        passed: 0,
+        first_failed_vector: -1,
+        first_failure: RQRegressionFailure::None,
+    };
+    for (i, vector) in vectors.iter().enumerate() {
+        let outcome = try_catch_unwind(|| vector.run()).unwrap_or(RQRegressionFailure::Panic);
+        if outcome == RQRegressionFailure::None {
+            report.passed += 1;
+        } else if report.first_failed_vector < 0 {
+            report.first_failed_vector = i as i32;
+            report.first_failure = outcome;
+        }
     }
-    drop(Box::from_raw(slice::from_raw_parts_mut(ptr_, len)));
+    report
 }
 
-/// Destroy the decoding context and release all resources.
-#[no_mangle]
-pub extern "C" fn raptorq_ctx_free(ctx: *mut RQContext) {
-    if ctx.is_null() {
-        return;
+/// Quick check, for app launch, that this build encodes and decodes: a
+/// 16 KiB object is encoded, its first eight frames are dropped and the rest
+/// is scanned back through a context, solving for the missing symbols on the
+/// GF(256) kernels `raptorq` selects for this CPU.  Takes a few milliseconds, where
+/// `raptorq_run_regression_vectors` runs every embedded vector.
+///
+/// Returns [`RQStatus::Ok`], or [`RQStatus::Failed`] with the failing step
+/// in `raptorq_last_error` (a broken build or a miscompiled architecture).
+#[cfg(all(feature = "encoder", feature = "decoder"))]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_self_test() -> RQStatus {
+    match try_catch_unwind(regression::self_test) {
+        Some(Ok(())) => RQStatus::Ok,
+        Some(Err(reason)) => {
+            #[cfg(feature = "std")]
+            panics::set_last_error(reason, RQErrorInfo::new(RQStatus::Failed));
+            #[cfg(not(feature = "std"))]
+            let _ = reason;
+            RQStatus::Failed
+        }
+        None => RQStatus::Failed,
+    }
+}
+
+/// Static, NUL‑terminated name of regression vector `index`, or `NULL` if the
+/// index is out of range.
+#[cfg(all(feature = "encoder", feature = "decoder"))]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_regression_vector_name(index: u32) -> *const c_char {
+    match regression::VECTORS.get(index as usize) {
+        Some(vector) => vector.name.as_ptr() as *const c_char,
+        None => ptr::null(),
     }
-    unsafe { drop(Box::from_raw(ctx)) };
 }
 
 //—‑ tests (run with `cargo test --features std`) ————————————————————————
 
-#[cfg(test)]
+#[cfg(all(test, feature = "encoder", feature = "decoder"))]
 mod tests {
     use super::*;
-    use raptorq::EncoderBuilder;
+    use raptorq::{Encoder, EncoderBuilder};
+
+    #[test]
+    fn version_strings() {
+        let version = unsafe { std::ffi::CStr::from_ptr(raptorq_version()) };
+        assert_eq!(version.to_str(), Ok(env!("CARGO_PKG_VERSION")));
+        assert_eq!(raptorq_abi_version(), RAPTORQ_ABI_VERSION);
+    }
+
+    #[cfg(feature = "prefixed")]
+    #[test]
+    fn prefixed_exports() {
+        assert_eq!(prefixed::raptorq_abi_version(), RAPTORQ_ABI_VERSION);
+        let buf = prefixed::raptorq_alloc(4);
+        unsafe { prefixed::raptorq_dealloc(buf, 4) };
+    }
+
+    #[test]
+    fn self_describing_buffers() {
+        let data = b"no length to remember";
+        let enc = unsafe { raptorq_enc_new(data.as_ptr(), data.len() as u64, 8, 0) };
+        let hs = unsafe { raptorq_enc_handshake_frame_buffer(enc, 7) };
+        let ctx = unsafe { raptorq_ctx_from_handshake(hs.ptr, hs.len) };
+        unsafe { raptorq_buffer_free(hs) };
+        for i in 0..unsafe { raptorq_enc_frame_count(enc) } {
+            let frame = unsafe { raptorq_enc_frame_buffer(enc, i) };
+            assert_eq!(frame.cap, frame.len);
+            unsafe { raptorq_ctx_push_frame_status(ctx, frame.ptr, frame.len) };
+            unsafe { raptorq_buffer_free(frame) };
+        }
+        let missing = unsafe { raptorq_enc_frame_buffer(enc, u32::MAX) };
+        assert!(missing.ptr.is_null() && missing.len == 0);
+        unsafe { raptorq_buffer_free(missing) };
+        let oti = unsafe { raptorq_enc_oti_buffer(enc) };
+        assert_eq!(oti.len, 12);
+        let twin = unsafe { raptorq_ctx_from_oti(oti.ptr) };
+        unsafe { raptorq_buffer_free(oti) };
+        let frame = unsafe { raptorq_enc_frame_buffer(enc, 0) };
+        unsafe { raptorq_ctx_push_frame_status(twin, frame.ptr, frame.len) };
+        unsafe { raptorq_buffer_free(frame) };
+        for blob in [unsafe { raptorq_ctx_dump_buffer(twin) }, unsafe {
+            raptorq_ctx_serialize_buffer(twin)
+        }] {
+            assert!(!blob.ptr.is_null() && blob.len == blob.cap);
+            unsafe { raptorq_buffer_free(blob) };
+        }
+        unsafe { raptorq_ctx_free(twin) };
+        let result = unsafe { raptorq_ctx_take_result_buffer(ctx) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(result.ptr, result.len as usize) },
+            data
+        );
+        unsafe { raptorq_buffer_free(result) };
+        unsafe { raptorq_ctx_free(ctx) };
+        unsafe { raptorq_enc_free(enc) };
+    }
+
+    #[test]
+    fn zeroized_transfer() {
+        let data = b"correct horse battery staple";
+        let enc = unsafe { raptorq_enc_new(data.as_ptr(), data.len() as u64, 8, 0) };
+        let hs = unsafe { raptorq_enc_handshake_frame_buffer(enc, 0) };
+        let ctx = unsafe { raptorq_ctx_from_handshake(hs.ptr, hs.len) };
+        unsafe { raptorq_buffer_free(hs) };
+        unsafe { raptorq_ctx_set_zeroize(ctx, true) };
+        for i in 0..unsafe { raptorq_enc_frame_count(enc) } {
+            // Every frame twice: the repeat is handed over and thrown away.
+            for _ in 0..2 {
+                let frame = unsafe { raptorq_enc_frame_buffer(enc, i) };
+                unsafe { raptorq_ctx_push_frame_owned(ctx, frame.ptr, frame.len) };
+            }
+        }
+        let result = unsafe { raptorq_ctx_take_result_buffer(ctx) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(result.ptr, result.len as usize) },
+            data
+        );
+        unsafe { raptorq_buffer_free_zeroize(result) };
+        unsafe { raptorq_free_zeroize(ptr::null_mut(), 4) };
+        unsafe { raptorq_ctx_free(ctx) };
+        unsafe { raptorq_enc_free(enc) };
+    }
+
+    #[test]
+    fn locked_result() {
+        let data = b"pinned for the length of the transfer";
+        let enc = unsafe { raptorq_enc_new(data.as_ptr(), data.len() as u64, 16, 0) };
+        let ctx = raptorq_ctx_new(data.len() as u64, 16);
+        unsafe { raptorq_ctx_set_mlock_result(ctx, true) };
+        assert!(!unsafe { raptorq_ctx_result_locked(ctx) });
+        for i in 0..unsafe { raptorq_enc_frame_count(enc) } {
+            let frame = unsafe { raptorq_enc_frame_buffer(enc, i) };
+            unsafe { raptorq_ctx_push_frame_status(ctx, frame.ptr, frame.len) };
+            unsafe { raptorq_buffer_free(frame) };
+        }
+        // Whether the OS grants the lock depends on its limits; the result is
+        // the same either way.
+        let result = unsafe { raptorq_ctx_take_result_buffer(ctx) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(result.ptr, result.len as usize) },
+            data
+        );
+        assert_eq!(result.cap, result.len);
+        unsafe { raptorq_buffer_free_zeroize(result) };
+        unsafe { raptorq_ctx_free(ctx) };
+        unsafe { raptorq_enc_free(enc) };
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    fn strict_pointer_checks() {
+        let frame = [0u8; 8];
+        let ctx = raptorq_ctx_new(100, 16);
+        let misaligned = unsafe { (ctx as *mut u8).add(1) } as *mut RQContext;
+        let push = |ctx, len| unsafe { raptorq_ctx_push_frame_status(ctx, frame.as_ptr(), len) };
+        assert_eq!(push(misaligned, 8), RQStatus::InvalidArgument);
+        // Something that is not a context.
+        let mut other = vec![0u64; 4096];
+        assert_eq!(
+            push(other.as_mut_ptr() as *mut RQContext, 8),
+            RQStatus::InvalidArgument
+        );
+        assert_eq!(push(ctx, 3 << 30), RQStatus::InvalidArgument);
+        assert_eq!(push(ctx, 8), RQStatus::Malformed);
+        let freed = raptorq_use_after_free_count();
+        unsafe { raptorq_ctx_free(ctx) };
+        assert_eq!(push(ctx, 8), RQStatus::InvalidArgument);
+        unsafe { raptorq_ctx_free(ctx) };
+        assert_eq!(raptorq_use_after_free_count(), freed + 2);
+    }
+
+    #[test]
+    fn overlapping_calls() {
+        let data = b"two threads, one context";
+        let enc = unsafe { raptorq_enc_new(data.as_ptr(), data.len() as u64, 8, 0) };
+        let frame = unsafe { raptorq_enc_frame_buffer(enc, 0) };
+        let ctx = raptorq_ctx_new(data.len() as u64, 8);
+        let push = || unsafe { raptorq_ctx_push_frame_status(ctx, frame.ptr, frame.len) };
+        // Stand-ins for a call still running on another thread.
+        let pushing = unsafe { RQContext::enter_raw(ctx, true) }.unwrap();
+        assert_eq!(push(), RQStatus::Busy);
+        assert!(!unsafe { raptorq_ctx_is_complete(ctx) });
+        assert!(unsafe { RQContext::enter_raw(ctx, false) }.is_none());
+        drop(pushing);
+        let querying = unsafe { RQContext::enter_raw(ctx, false) }.unwrap();
+        assert_eq!(unsafe { raptorq_ctx_total_frames(ctx) }, 0);
+        assert_eq!(push(), RQStatus::Busy);
+        drop(querying);
+        assert_eq!(push(), RQStatus::Ok);
+        #[cfg(feature = "std")]
+        {
+            assert!(unsafe { raptorq_ctx_start_decode_thread(ctx, 4, 64) });
+            assert!(unsafe { raptorq_ctx_stop_decode_thread(ctx) });
+            assert_eq!(push(), RQStatus::Ok);
+        }
+        unsafe { raptorq_buffer_free(frame) };
+        unsafe { raptorq_ctx_free(ctx) };
+        unsafe { raptorq_enc_free(enc) };
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn invalid_arguments() {
+        use std::ffi::CStr;
+        let last_error = || {
+            unsafe { CStr::from_ptr(raptorq_last_error()) }
+                .to_str()
+                .unwrap()
+        };
+        let frame = [0u8; 8];
+        assert_eq!(
+            unsafe { raptorq_ctx_push_frame_status(ptr::null_mut(), frame.as_ptr(), 8) },
+            RQStatus::InvalidArgument
+        );
+        assert_eq!(last_error(), "context is NULL");
+        let ctx = raptorq_ctx_new(100, 16);
+        assert_eq!(
+            unsafe { raptorq_ctx_push_frame_status(ctx, ptr::null(), 8) },
+            RQStatus::InvalidArgument
+        );
+        assert_eq!(last_error(), "NULL buffer with a non-zero length");
+        assert_eq!(
+            unsafe { raptorq_ctx_push_frame_status(ctx, ptr::null(), 0) },
+            RQStatus::Malformed
+        );
+        unsafe { raptorq_ctx_free(ctx) };
+        assert!(raptorq_ctx_new(0, 16).is_null());
+        assert_eq!(last_error(), "transfer length is 0");
+        assert!(raptorq_ctx_new(100, 0).is_null());
+        assert!(raptorq_ctx_new(MAX_TRANSFER_LENGTH + 1, 1024).is_null());
+        assert!(unsafe { raptorq_enc_new(frame.as_ptr(), 8, 0, 0) }.is_null());
+        assert_eq!(last_error(), "max payload size is 0");
+        let ctx = raptorq_ctx_new(100, 10);
+        let over = [50, 101];
+        assert!(!unsafe {
+            raptorq_ctx_set_milestones(ctx, over.as_ptr(), 2, None, ptr::null_mut())
+        });
+        assert_eq!(last_error(), "milestone over 100 percent");
+        unsafe { raptorq_ctx_free(ctx) };
+        assert!(unsafe { raptorq_ctx_from_oti(ptr::null()) }.is_null());
+        assert!(unsafe { raptorq_ctx_from_oti([0; 12].as_ptr()) }.is_null());
+        assert_eq!(last_error(), "OTI header describes no data");
+        raptorq_clear_last_error();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn error_info() {
+        let info = || {
+            let mut info = RQErrorInfo::new(RQStatus::Ok);
+            assert!(unsafe { raptorq_last_error_info(&mut info) });
+            info
+        };
+        raptorq_clear_last_error();
+        assert!(!unsafe { raptorq_last_error_info(ptr::null_mut()) });
+        let data = [7u8; 1_000];
+        let enc = Encoder::with_defaults(&data, 64);
+        let ctx = raptorq_ctx_new(data.len() as u64, 64);
+        let mut frame = enc.get_encoded_packets(0)[0].serialize();
+        frame.push(1);
+        let push =
+            |s: &[u8]| unsafe { raptorq_ctx_push_frame_status(ctx, s.as_ptr(), s.len() as u64) };
+        assert_eq!(push(&frame), RQStatus::Malformed);
+        let detail = info();
+        assert_eq!(detail.status, RQStatus::Malformed);
+        assert_eq!(detail.fields, RQ_ERROR_HAS_LENGTHS | RQ_ERROR_HAS_OFFSET);
+        assert_eq!((detail.expected_len, detail.actual_len), (68, 69));
+        frame.pop();
+        frame[0] = 9; // SBN of a block this transfer does not have
+        assert_eq!(push(&frame), RQStatus::Malformed);
+        assert_eq!(info().fields, RQ_ERROR_HAS_SYMBOL);
+        assert_eq!((info().sbn, info().esi), (9, 0));
+        assert_eq!(
+            unsafe { raptorq_ctx_push_frame_status(ctx, ptr::null(), 1) },
+            RQStatus::InvalidArgument
+        );
+        assert_eq!(info().status, RQStatus::InvalidArgument);
+        assert_eq!(info().fields, 0);
+        unsafe { raptorq_ctx_free(ctx) };
+    }
 
     #[test]
     fn roundtrip() {
@@ -181,17 +757,1496 @@ mod tests {
         // feed just enough packets to recover
         for p in enc.get_encoded_packets(0) {
             let s = p.serialize();
-            if unsafe { raptorq_ctx_push_frame(ctx, s.as_ptr(), s.len()) } {
+            if unsafe { raptorq_ctx_push_frame(ctx, s.as_ptr(), s.len() as u64) } {
                 break;
             }
         }
-        assert!(raptorq_ctx_is_complete(ctx));
-        let mut out_len = 0usize;
+        assert!(unsafe { raptorq_ctx_is_complete(ctx) });
+        let mut out_len = 0u64;
         let out_ptr = unsafe { raptorq_ctx_take_result(ctx, &mut out_len) };
-        assert_eq!(out_len, data.len());
-        let recovered = unsafe { slice::from_raw_parts(out_ptr, out_len) };
+        assert_eq!(out_len, data.len() as u64);
+        let recovered = unsafe { slice::from_raw_parts(out_ptr, out_len as usize) };
         assert_eq!(recovered, data);
         unsafe { raptorq_free(out_ptr, out_len) };
-        raptorq_ctx_free(ctx);
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[test]
+    fn taking_the_result_releases_the_decoder() {
+        let data = b"helloMyFountain";
+        let enc = EncoderBuilder::new().build(data);
+        let packets = enc.get_encoded_packets(0);
+        let ctx = unsafe { raptorq_ctx_from_oti(enc.get_config().serialize().as_ptr()) };
+        for p in &packets {
+            let s = p.serialize();
+            unsafe { raptorq_ctx_push_frame(ctx, s.as_ptr(), s.len() as u64) };
+        }
+        let result = unsafe { raptorq_ctx_take_result_buffer(ctx) };
+        assert!(!result.ptr.is_null());
+        assert!(unsafe { (*ctx).decoder.is_released() });
+        // Nothing left to pace, and late frames change nothing.
+        assert_eq!(unsafe { raptorq_ctx_push_interval_hint_us(ctx) }, 0);
+        let s = packets[0].serialize();
+        let status = unsafe { raptorq_ctx_push_frame_status(ctx, s.as_ptr(), s.len() as u64) };
+        assert_eq!(status, RQStatus::Ignored);
+        unsafe { raptorq_buffer_free(result) };
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[test]
+    fn completion_callback() {
+        use core::ffi::c_void;
+
+        unsafe extern "C" fn collect(result: RQBuffer, user_data: *mut c_void) {
+            let results = &*(user_data as *const std::sync::Mutex<Vec<Vec<u8>>>);
+            let data = slice::from_raw_parts(result.ptr, result.len as usize).to_vec();
+            results.lock().unwrap().push(data);
+            raptorq_buffer_free(result);
+        }
+
+        let results = std::sync::Mutex::new(Vec::<Vec<u8>>::new());
+        let user_data = &results as *const _ as *mut c_void;
+        let data = b"helloMyFountain";
+        let enc = EncoderBuilder::new().build(data);
+        let packets = enc.get_encoded_packets(1);
+        let ctx = unsafe { raptorq_ctx_from_oti(enc.get_config().serialize().as_ptr()) };
+        unsafe { raptorq_ctx_set_on_complete(ctx, Some(collect), user_data) };
+        for p in &packets {
+            let s = p.serialize();
+            unsafe { raptorq_ctx_push_frame_status(ctx, s.as_ptr(), s.len() as u64) };
+        }
+        assert_eq!(*results.lock().unwrap(), vec![data.to_vec()]);
+        // The callback took the result.
+        assert!(!unsafe { raptorq_ctx_is_complete(ctx) });
+        assert!(unsafe { raptorq_ctx_take_result_buffer(ctx) }.ptr.is_null());
+        unsafe { raptorq_ctx_free(ctx) };
+
+        // Registering after completion delivers the waiting result at once.
+        let ctx = unsafe { raptorq_ctx_from_oti(enc.get_config().serialize().as_ptr()) };
+        for p in &packets {
+            let s = p.serialize();
+            unsafe { raptorq_ctx_push_frame_status(ctx, s.as_ptr(), s.len() as u64) };
+        }
+        unsafe { raptorq_ctx_set_on_complete(ctx, Some(collect), user_data) };
+        assert_eq!(results.lock().unwrap().len(), 2);
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[test]
+    fn progress_callback() {
+        use core::ffi::c_void;
+
+        unsafe extern "C" fn collect(received: u32, needed: u32, user_data: *mut c_void) {
+            (*(user_data as *mut Vec<(u32, u32)>)).push((received, needed));
+        }
+
+        let data: Vec<u8> = (0..6_000u32).map(|i| (i * 11) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 128);
+        let ctx = raptorq_ctx_new(data.len() as u64, 128);
+        let mut reports: Vec<(u32, u32)> = Vec::new();
+        let user_data = &mut reports as *mut _ as *mut c_void;
+        unsafe { raptorq_ctx_set_on_progress(ctx, Some(collect), user_data) };
+        let mut pushed = 0;
+        for p in enc.get_encoded_packets(0) {
+            let s = p.serialize();
+            // A repeated frame adds nothing and is not reported.
+            for _ in 0..2 {
+                unsafe { raptorq_ctx_push_frame_status(ctx, s.as_ptr(), s.len() as u64) };
+            }
+            pushed += 1;
+            if unsafe { raptorq_ctx_is_complete(ctx) } {
+                break;
+            }
+        }
+        let needed = reports[0].1;
+        assert_eq!(reports.len(), pushed);
+        assert_eq!(reports.first(), Some(&(1, needed)));
+        assert_eq!(reports.last(), Some(&(needed, needed)));
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[test]
+    fn milestone_callback() {
+        use core::ffi::c_void;
+
+        unsafe extern "C" fn collect(percent: u8, user_data: *mut c_void) {
+            (*(user_data as *mut Vec<u8>)).push(percent);
+        }
+
+        let data: Vec<u8> = (0..6_000u32).map(|i| (i * 11) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 128);
+        let ctx = raptorq_ctx_new(data.len() as u64, 128);
+        let mut passed: Vec<u8> = Vec::new();
+        let user_data = &mut passed as *mut _ as *mut c_void;
+        let milestones = [75, 25, 100, 50, 25];
+        assert!(unsafe {
+            raptorq_ctx_set_milestones(ctx, milestones.as_ptr(), 5, Some(collect), user_data)
+        });
+        let packets = enc.get_encoded_packets(0);
+        let (first, rest) = packets.split_at(packets.len() * 3 / 5);
+        for p in first {
+            let s = p.serialize();
+            unsafe { raptorq_ctx_push_frame_status(ctx, s.as_ptr(), s.len() as u64) };
+        }
+        assert_eq!(passed, [25, 50]);
+        for p in rest {
+            let s = p.serialize();
+            unsafe { raptorq_ctx_push_frame_status(ctx, s.as_ptr(), s.len() as u64) };
+        }
+        assert_eq!(passed, [25, 50, 75, 100]);
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[test]
+    fn handshake_roundtrip() {
+        let data: Vec<u8> = (0..5_000u32).map(|i| (i * 7) as u8).collect();
+        let enc = unsafe { raptorq_enc_new(data.as_ptr(), data.len() as u64, 256, 4) };
+        assert!(!enc.is_null());
+        let mut hs_len = 0u64;
+        let hs = unsafe { raptorq_enc_handshake_frame(enc, 42, &mut hs_len) };
+        let ctx = unsafe { raptorq_ctx_from_handshake(hs, hs_len) };
+        assert!(!ctx.is_null());
+        assert_eq!(unsafe { raptorq_ctx_content_type(ctx) }, 42);
+        let frames = unsafe { raptorq_enc_frame_count(enc) };
+        assert_eq!(unsafe { raptorq_ctx_total_frames(ctx) }, frames);
+        // A repeated handshake inside the loop must not disturb decoding.
+        assert!(!unsafe { raptorq_ctx_push_frame(ctx, hs, hs_len) });
+        for i in 0..frames {
+            let mut len = 0u64;
+            let frame = unsafe { raptorq_enc_frame(enc, i, &mut len) };
+            let done = unsafe { raptorq_ctx_push_frame(ctx, frame, len) };
+            unsafe { raptorq_free(frame, len) };
+            if done {
+                break;
+            }
+        }
+        assert!(unsafe { raptorq_ctx_is_complete(ctx) });
+        assert!(!unsafe { raptorq_ctx_digest_mismatch(ctx) });
+        unsafe { raptorq_free(hs, hs_len) };
+        unsafe { raptorq_ctx_free(ctx) };
+        unsafe { raptorq_enc_free(enc) };
+    }
+
+    // Failures build a JavaScript `Error`, so only wasm32 can exercise them.
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn wasm_decoder() {
+        use crate::wasm_api::{FrameDecoder, PushResult};
+
+        let data: Vec<u8> = (0..6_000u32).map(|i| (i * 7) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 128);
+        let mut decoder = FrameDecoder::new(data.len() as u64, 128).unwrap();
+        assert_eq!(decoder.take_result(), None);
+        let mut last = PushResult::Accepted;
+        for p in enc.get_encoded_packets(1) {
+            last = decoder.push(&p.serialize()).unwrap();
+            if last == PushResult::Complete {
+                break;
+            }
+        }
+        assert_eq!(last, PushResult::Complete);
+        assert!(decoder.is_complete());
+        assert_eq!(decoder.total_frames(), 0); // no handshake
+        let packet = enc.get_encoded_packets(0)[0].serialize();
+        assert_eq!(decoder.push(&packet).unwrap(), PushResult::Ignored);
+        assert_eq!(decoder.take_result(), Some(data));
+        assert!(!decoder.is_complete());
+    }
+
+    #[cfg(feature = "uniffi")]
+    #[test]
+    fn uniffi_decoder() {
+        use crate::uniffi_api::{DecodeError, FrameDecoder, PushResult};
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake, Waker};
+
+        struct Unpark(std::thread::Thread);
+        impl Wake for Unpark {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+        fn block_on<F: std::future::Future>(future: F) -> F::Output {
+            let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+            let mut future = std::pin::pin!(future);
+            loop {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(&waker))
+                {
+                    return output;
+                }
+                std::thread::park();
+            }
+        }
+
+        let data: Vec<u8> = (0..6_000u32).map(|i| (i * 13) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 128);
+        assert_eq!(
+            FrameDecoder::from_oti(vec![0; 11]).err(),
+            Some(DecodeError::InvalidArgument)
+        );
+        let decoder = Arc::new(FrameDecoder::new(data.len() as u64, 128).unwrap());
+        assert_eq!(decoder.push(vec![0xff; 3]), Err(DecodeError::Malformed));
+        assert_eq!(DecodeError::Malformed.to_string(), "malformed frame");
+        assert_eq!(
+            block_on(Arc::clone(&decoder).finish()),
+            Err(DecodeError::Incomplete)
+        );
+        // Skipping a source packet leaves a solve for `finish`.
+        for p in enc.get_encoded_packets(2).iter().skip(1) {
+            if decoder.push(p.serialize()) == Ok(PushResult::ReadyToFinish) {
+                break;
+            }
+        }
+        assert!(!decoder.is_complete());
+        assert_eq!(block_on(Arc::clone(&decoder).finish()), Ok(data.clone()));
+        assert_eq!(decoder.take_result(), None);
+        assert_eq!(
+            block_on(Arc::clone(&decoder).finish()),
+            Err(DecodeError::Incomplete)
+        );
+        let packet = enc.get_encoded_packets(0)[0].serialize();
+        assert_eq!(decoder.push(packet.clone()), Ok(PushResult::Ignored));
+
+        let decoder = Arc::new(FrameDecoder::new(data.len() as u64, 128).unwrap());
+        decoder.cancel();
+        assert_eq!(decoder.push(packet), Err(DecodeError::Cancelled));
+        assert_eq!(
+            block_on(Arc::clone(&decoder).finish()),
+            Err(DecodeError::Cancelled)
+        );
+    }
+
+    #[test]
+    fn vault_profile_roundtrip() {
+        let data: Vec<u8> = (0..3_000u32).map(|i| (i * 13) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 1072);
+        let ctx = raptorq_ctx_new_with_profile(RQProfile::VaultClassic as u32, data.len() as u64);
+        assert!(!ctx.is_null());
+        assert!(raptorq_ctx_new_with_profile(99, 10).is_null());
+        let header = (0x8000_0000u32 | data.len() as u32).to_be_bytes();
+        let mut done = false;
+        for p in enc.get_encoded_packets(2) {
+            // Frames without the Vault prefix are rejected outright.
+            let bare = p.serialize();
+            assert!(!unsafe { raptorq_ctx_push_frame(ctx, bare.as_ptr(), bare.len() as u64) });
+            let mut frame = header.to_vec();
+            frame.extend_from_slice(&bare);
+            if unsafe { raptorq_ctx_push_frame(ctx, frame.as_ptr(), frame.len() as u64) } {
+                done = true;
+                break;
+            }
+        }
+        assert!(done);
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[test]
+    fn padded_frames() {
+        let data = b"zero padded frames from a fixed-size QR pipeline";
+        let enc = Encoder::with_defaults(data, 16);
+        let ctx = raptorq_ctx_new(data.len() as u64, 16);
+        let mut padded = enc.get_encoded_packets(0)[0].serialize();
+        padded.resize(64, 0);
+        let status =
+            unsafe { raptorq_ctx_push_frame_status(ctx, padded.as_ptr(), padded.len() as u64) };
+        assert_eq!(status, RQStatus::Malformed);
+        unsafe { raptorq_ctx_set_padding_tolerant(ctx, true) };
+        // Non-zero trailing bytes are garbage, not padding.
+        let mut garbage = padded.clone();
+        garbage[60] = 1;
+        let status =
+            unsafe { raptorq_ctx_push_frame_status(ctx, garbage.as_ptr(), garbage.len() as u64) };
+        assert_eq!(status, RQStatus::Malformed);
+        let mut last = RQStatus::Ok;
+        for p in enc.get_encoded_packets(0) {
+            let mut frame = p.serialize();
+            frame.resize(64, 0);
+            last =
+                unsafe { raptorq_ctx_push_frame_status(ctx, frame.as_ptr(), frame.len() as u64) };
+            if last != RQStatus::PaddingStripped {
+                break;
+            }
+        }
+        assert_eq!(last, RQStatus::Complete);
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[test]
+    fn packed_frames_roundtrip() {
+        let data: Vec<u8> = (0..4_000u32).map(|i| (i * 3) as u8).collect();
+        let enc = unsafe { raptorq_enc_new(data.as_ptr(), data.len() as u64, 200, 2) };
+        let unpacked = unsafe { raptorq_enc_frame_count(enc) };
+        assert!(unsafe { raptorq_enc_set_packets_per_frame(enc, 3) });
+        let frames = unsafe { raptorq_enc_frame_count(enc) };
+        assert_eq!(frames, unpacked.div_ceil(3));
+
+        let ctx = raptorq_ctx_new(data.len() as u64, 200);
+        unsafe { raptorq_ctx_set_packed_frames(ctx, true) };
+        let mut last = RQStatus::Ok;
+        for i in 0..frames {
+            let mut len = 0u64;
+            let frame = unsafe { raptorq_enc_frame(enc, i, &mut len) };
+            last = unsafe { raptorq_ctx_push_frame_status(ctx, frame, len) };
+            unsafe { raptorq_free(frame, len) };
+            if last != RQStatus::Ok {
+                break;
+            }
+        }
+        assert_eq!(last, RQStatus::Complete);
+        unsafe { raptorq_ctx_free(ctx) };
+        unsafe { raptorq_enc_free(enc) };
+    }
+
+    #[test]
+    fn checksummed_frames() {
+        let data: Vec<u8> = (0..3_000u32).map(|i| (i * 5) as u8).collect();
+        let enc = unsafe { raptorq_enc_new(data.as_ptr(), data.len() as u64, 200, 2) };
+        assert!(unsafe { raptorq_enc_set_frame_checksums(enc, true) });
+        let ctx = raptorq_ctx_new(data.len() as u64, 200);
+        unsafe { raptorq_ctx_set_frame_checksums(ctx, true) };
+        let mut len = 0u64;
+        let frame = unsafe { raptorq_enc_frame(enc, 0, &mut len) };
+        let mut misread = unsafe { slice::from_raw_parts(frame, len as usize) }.to_vec();
+        misread[10] ^= 0x40;
+        let status =
+            unsafe { raptorq_ctx_push_frame_status(ctx, misread.as_ptr(), misread.len() as u64) };
+        assert_eq!(status, RQStatus::Malformed);
+        unsafe { raptorq_free(frame, len) };
+        let mut last = RQStatus::Ok;
+        for i in 0..unsafe { raptorq_enc_frame_count(enc) } {
+            // The owned path strips the trailer in place.
+            let frame = unsafe { raptorq_enc_frame(enc, i, &mut len) };
+            last = unsafe { raptorq_ctx_push_frame_owned(ctx, frame, len) };
+            if last != RQStatus::Ok {
+                break;
+            }
+        }
+        assert_eq!(last, RQStatus::Complete);
+        unsafe { raptorq_ctx_free(ctx) };
+        unsafe { raptorq_enc_free(enc) };
+    }
+
+    #[test]
+    fn owned_frames() {
+        let data: Vec<u8> = (0..1_500u32).map(|i| (i * 11) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 128);
+        let ctx = raptorq_ctx_new(data.len() as u64, 128);
+        let mut last = RQStatus::Ok;
+        for p in enc.get_encoded_packets(1) {
+            let bytes = p.serialize();
+            let buf = raptorq_alloc(bytes.len() as u64);
+            unsafe { slice::from_raw_parts_mut(buf, bytes.len()) }.copy_from_slice(&bytes);
+            last = unsafe { raptorq_ctx_push_frame_owned(ctx, buf, bytes.len() as u64) };
+            if last != RQStatus::Ok {
+                break;
+            }
+        }
+        assert_eq!(last, RQStatus::Complete);
+        let mut out_len = 0u64;
+        let out = unsafe { raptorq_ctx_take_result(ctx, &mut out_len) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(out, out_len as usize) },
+            &data[..]
+        );
+        unsafe { raptorq_free(out, out_len) };
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[test]
+    fn owned_profile_frames() {
+        let data: Vec<u8> = (0..2_000u32).map(|i| (i * 23) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 200);
+        let ctx = raptorq_ctx_new_with_profile(RQProfile::BcUr as u32, data.len() as u64);
+        let mut last = RQStatus::Ok;
+        for p in enc.get_encoded_packets(1) {
+            // CBOR byte string header, 16-bit length.
+            let bytes = p.serialize();
+            let mut frame = vec![0x59];
+            frame.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+            frame.extend_from_slice(&bytes);
+            let buf = raptorq_alloc(frame.len() as u64);
+            unsafe { slice::from_raw_parts_mut(buf, frame.len()) }.copy_from_slice(&frame);
+            last = unsafe { raptorq_ctx_push_frame_owned(ctx, buf, frame.len() as u64) };
+            if last != RQStatus::Ok {
+                break;
+            }
+        }
+        assert_eq!(last, RQStatus::Complete);
+        let buf = raptorq_alloc(3);
+        let status = unsafe { raptorq_ctx_push_frame_owned(ctx, buf, 3) };
+        assert_eq!(status, RQStatus::Ignored);
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[test]
+    fn deferred_finalize_steps() {
+        let data: Vec<u8> = (0..6_000u32).map(|i| (i * 17) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 128);
+        let ctx = raptorq_ctx_new(data.len() as u64, 128);
+        unsafe { raptorq_ctx_set_deferred_finalize(ctx, true) };
+        let mut percent = 0u8;
+        let status = unsafe { raptorq_ctx_finalize_step(ctx, 10, &mut percent) };
+        assert_eq!((status, percent), (RQStatus::Ignored, 0));
+        let mut last = RQStatus::Ok;
+        for p in enc.get_encoded_packets(2).iter().skip(1) {
+            let s = p.serialize();
+            last = unsafe { raptorq_ctx_push_frame_status(ctx, s.as_ptr(), s.len() as u64) };
+            if last != RQStatus::Ok {
+                break;
+            }
+        }
+        assert_eq!(last, RQStatus::ReadyToFinalize);
+        assert!(!unsafe { raptorq_ctx_is_complete(ctx) });
+        let status = unsafe { raptorq_ctx_finalize_step(ctx, 10, &mut percent) };
+        assert_eq!((status, percent), (RQStatus::Complete, 100));
+        let mut out_len = 0u64;
+        let out = unsafe { raptorq_ctx_take_result(ctx, &mut out_len) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(out, out_len as usize) },
+            &data[..]
+        );
+        unsafe { raptorq_free(out, out_len) };
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[test]
+    fn cancel_token() {
+        let data: Vec<u8> = (0..6_000u32).map(|i| (i * 29) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 128);
+        assert!(unsafe { raptorq_ctx_cancel_token(ptr::null()) }.is_null());
+        unsafe { raptorq_cancel(ptr::null()) };
+        let ctx = raptorq_ctx_new(data.len() as u64, 128);
+        unsafe { raptorq_ctx_set_deferred_finalize(ctx, true) };
+        let token = unsafe { raptorq_ctx_cancel_token(ctx) };
+        let packets: Vec<Vec<u8>> = enc.get_encoded_packets(2)[1..]
+            .iter()
+            .map(|p| p.serialize())
+            .collect();
+        let push =
+            |s: &[u8]| unsafe { raptorq_ctx_push_frame_status(ctx, s.as_ptr(), s.len() as u64) };
+        assert!(packets.iter().any(|s| push(s) == RQStatus::ReadyToFinalize));
+        unsafe { raptorq_cancel(token) };
+        let status = unsafe { raptorq_ctx_finalize_step(ctx, 10, ptr::null_mut()) };
+        assert_eq!(status, RQStatus::Cancelled);
+        assert_eq!(push(&packets[0]), RQStatus::Cancelled);
+        assert!(!unsafe { raptorq_ctx_is_complete(ctx) });
+        // The token outlives its context.
+        unsafe { raptorq_ctx_free(ctx) };
+        unsafe { raptorq_cancel(token) };
+        unsafe { raptorq_cancel_token_free(token) };
+
+        // A running decode thread shares the token and stops on it.
+        #[cfg(feature = "std")]
+        {
+            let ctx = raptorq_ctx_new(data.len() as u64, 128);
+            let token = unsafe { raptorq_ctx_cancel_token(ctx) };
+            assert!(unsafe { raptorq_ctx_enqueue(ctx, packets[0].as_ptr(), 132) });
+            unsafe { raptorq_cancel(token) };
+            unsafe { raptorq_cancel_token_free(token) };
+            for s in &packets {
+                unsafe { raptorq_ctx_enqueue(ctx, s.as_ptr(), s.len() as u64) };
+            }
+            let mut status = RQStatus::Pending;
+            while !matches!(status, RQStatus::Cancelled | RQStatus::Failed) {
+                std::thread::yield_now();
+                status = unsafe { raptorq_ctx_poll(ctx) };
+            }
+            assert_eq!(status, RQStatus::Cancelled);
+            assert!(!unsafe { raptorq_ctx_is_complete(ctx) });
+            unsafe { raptorq_ctx_free(ctx) };
+        }
+    }
+
+    #[cfg(all(feature = "std", unix))]
+    #[test]
+    fn mapped_output() {
+        let data: Vec<u8> = (0..5_000u32).map(|i| (i * 29) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 128);
+        let ctx = raptorq_ctx_new(data.len() as u64, 128);
+        assert!(unsafe { raptorq_ctx_set_output_mapping(ctx, ptr::null()) });
+        assert!(unsafe { raptorq_ctx_mapped_result(ctx, ptr::null_mut()) }.is_null());
+        for p in enc.get_encoded_packets(1) {
+            let s = p.serialize();
+            if unsafe { raptorq_ctx_push_frame(ctx, s.as_ptr(), s.len() as u64) } {
+                break;
+            }
+        }
+        assert!(unsafe { raptorq_ctx_is_complete(ctx) });
+        assert!(unsafe { raptorq_ctx_take_result(ctx, ptr::null_mut()) }.is_null());
+        let mut len = 0u64;
+        let mapped = unsafe { raptorq_ctx_mapped_result(ctx, &mut len) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(mapped, len as usize) },
+            &data[..]
+        );
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn decode_thread() {
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i * 17) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 256);
+        let ctx = raptorq_ctx_new(data.len() as u64, 256);
+        let frame = enc.get_encoded_packets(0)[0].serialize();
+        assert!(!unsafe { raptorq_ctx_enqueue_frame(ctx, frame.as_ptr(), 260) });
+        assert!(unsafe { raptorq_ctx_start_decode_thread(ctx, 4, 260) });
+        assert!(!unsafe { raptorq_ctx_start_decode_thread(ctx, 4, 260) });
+        assert!(!unsafe { raptorq_ctx_enqueue_frame(ctx, frame.as_ptr(), 261) });
+        for p in enc.get_encoded_packets(8) {
+            let s = p.serialize();
+            // A full queue drops the frame; retry like the next camera frame.
+            while !unsafe { raptorq_ctx_enqueue_frame(ctx, s.as_ptr(), s.len() as u64) } {
+                std::thread::yield_now();
+            }
+            if unsafe { raptorq_ctx_decode_thread_status(ctx) } == RQStatus::Complete {
+                break;
+            }
+        }
+        while unsafe { raptorq_ctx_decode_thread_status(ctx) } != RQStatus::Complete {
+            std::thread::yield_now();
+        }
+        // Queries read the state the thread holds, not the stand-in.
+        assert!(unsafe { raptorq_ctx_is_complete(ctx) });
+        assert!(unsafe { raptorq_ctx_stats(ctx) }.unique_frames > 0);
+        let last = unsafe { raptorq_ctx_event_count(ctx) } - 1;
+        assert_eq!(
+            unsafe { raptorq_ctx_event(ctx, last) }.kind,
+            RQEventKind::Complete
+        );
+        assert!(unsafe { raptorq_ctx_stop_decode_thread(ctx) });
+        assert!(!unsafe { raptorq_ctx_stop_decode_thread(ctx) });
+        assert_eq!(
+            unsafe { raptorq_ctx_decode_thread_status(ctx) },
+            RQStatus::Failed
+        );
+        assert!(unsafe { raptorq_ctx_is_complete(ctx) });
+        assert!(unsafe { raptorq_ctx_push_stats(ctx) }.pushes > 0);
+        let mut len = 0u64;
+        let out = unsafe { raptorq_ctx_take_result(ctx, &mut len) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(out, len as usize) },
+            &data[..]
+        );
+        unsafe { raptorq_free(out, len) };
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn enqueue_and_poll() {
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i * 13) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 256);
+        let ctx = raptorq_ctx_new(data.len() as u64, 256);
+        assert_eq!(unsafe { raptorq_ctx_poll(ctx) }, RQStatus::Failed);
+        let mut status = RQStatus::Pending;
+        for p in enc.get_encoded_packets(8) {
+            let s = p.serialize();
+            while !unsafe { raptorq_ctx_enqueue(ctx, s.as_ptr(), s.len() as u64) } {
+                std::thread::yield_now();
+            }
+            status = unsafe { raptorq_ctx_poll(ctx) };
+            if status == RQStatus::Complete {
+                break;
+            }
+        }
+        while status != RQStatus::Complete {
+            assert_ne!(status, RQStatus::Failed);
+            std::thread::yield_now();
+            status = unsafe { raptorq_ctx_poll(ctx) };
+        }
+        // The state is back: no thread left to poll, the result is here.
+        assert_eq!(unsafe { raptorq_ctx_poll(ctx) }, RQStatus::Failed);
+        let mut len = 0u64;
+        let out = unsafe { raptorq_ctx_take_result(ctx, &mut len) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(out, len as usize) },
+            &data[..]
+        );
+        unsafe { raptorq_free(out, len) };
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn enqueue_and_poll_on_two_threads() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let data: Vec<u8> = (0..30_000u32).map(|i| (i * 7 + i / 3) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 256);
+        let frames: Vec<Vec<u8>> = enc
+            .get_encoded_packets(8)
+            .iter()
+            .map(|p| p.serialize())
+            .collect();
+        let ctx = raptorq_ctx_new_threadsafe(data.len() as u64, 256);
+        let done = AtomicBool::new(false);
+        let addr = ctx as usize;
+        std::thread::scope(|scope| {
+            // The camera: the animation loop, over and over.
+            scope.spawn(|| {
+                let ctx = addr as *mut RQContext;
+                for frame in frames.iter().cycle() {
+                    if done.load(Ordering::Acquire) {
+                        break;
+                    }
+                    unsafe { raptorq_ctx_enqueue(ctx, frame.as_ptr(), frame.len() as u64) };
+                }
+            });
+            // The timer, stopping the thread and taking the state back
+            // while frames keep coming.
+            loop {
+                match unsafe { raptorq_ctx_poll(ctx) } {
+                    RQStatus::Complete => break,
+                    status => assert_ne!(status, RQStatus::DigestMismatch),
+                }
+                std::thread::yield_now();
+            }
+            // A few more late frames, refused without restarting the thread.
+            for frame in &frames[..4] {
+                assert!(!unsafe { raptorq_ctx_enqueue(ctx, frame.as_ptr(), frame.len() as u64) });
+            }
+            done.store(true, Ordering::Release);
+        });
+        assert_eq!(unsafe { raptorq_ctx_poll(ctx) }, RQStatus::Failed);
+        let result = unsafe { raptorq_ctx_take_result_buffer(ctx) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(result.ptr, result.len as usize) },
+            &data[..]
+        );
+        unsafe { raptorq_buffer_free(result) };
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn enqueue_while_state_is_held() {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i * 3 + i / 5) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 256);
+        let frames: Vec<Vec<u8>> = enc
+            .get_encoded_packets(0)
+            .iter()
+            .take(8)
+            .map(|p| p.serialize())
+            .collect();
+        let ctx = raptorq_ctx_new(data.len() as u64, 256);
+        assert!(unsafe { raptorq_ctx_enqueue(ctx, frames[0].as_ptr(), frames[0].len() as u64) });
+        let addr = ctx as usize;
+        let (held, wait_held) = mpsc::channel();
+        let (release, wait_release) = mpsc::channel::<()>();
+        std::thread::scope(|scope| {
+            // A query holding the decoding state, like a slow progress read.
+            scope.spawn(move || {
+                let ctx = unsafe { &*(addr as *const RQContext) };
+                ctx.with_state(|_| {
+                    held.send(()).unwrap();
+                    wait_release.recv_timeout(Duration::from_secs(5))
+                })
+                .expect("an enqueue waited on the decoding state");
+            });
+            wait_held.recv().unwrap();
+            for frame in &frames[1..] {
+                assert!(unsafe { raptorq_ctx_enqueue(ctx, frame.as_ptr(), frame.len() as u64) });
+            }
+            release.send(()).unwrap();
+        });
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn background_context() {
+        use core::ffi::c_void;
+
+        unsafe extern "C" fn collect(result: RQBuffer, user_data: *mut c_void) {
+            let results = &*(user_data as *const std::sync::Mutex<Vec<Vec<u8>>>);
+            let data = slice::from_raw_parts(result.ptr, result.len as usize).to_vec();
+            results.lock().unwrap().push(data);
+            raptorq_buffer_free(result);
+        }
+
+        let results = std::sync::Mutex::new(Vec::<Vec<u8>>::new());
+        let user_data = &results as *const _ as *mut c_void;
+        assert!(
+            unsafe { raptorq_ctx_new_background(0, 256, Some(collect), None, user_data) }.is_null()
+        );
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i * 7) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 256);
+        let ctx = unsafe {
+            raptorq_ctx_new_background(data.len() as u64, 256, Some(collect), None, user_data)
+        };
+        for p in enc.get_encoded_packets(8) {
+            let s = p.serialize();
+            while !unsafe { raptorq_ctx_enqueue(ctx, s.as_ptr(), s.len() as u64) } {
+                std::thread::yield_now();
+            }
+        }
+        // Freeing decodes what is still queued before joining the thread.
+        unsafe { raptorq_ctx_free(ctx) };
+        assert_eq!(*results.lock().unwrap(), vec![data]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn stale_handles() {
+        let data: Vec<u8> = (0..2_500u32).map(|i| (i * 41) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 128);
+        let handle = raptorq_handle_new(data.len() as u64, 128);
+        assert!(raptorq_handle_is_valid(handle));
+        let mut last = RQStatus::Ok;
+        for p in enc.get_encoded_packets(1) {
+            let s = p.serialize();
+            last = unsafe { raptorq_handle_push_frame(handle, s.as_ptr(), s.len() as u64) };
+            if last != RQStatus::Ok {
+                break;
+            }
+        }
+        assert_eq!(last, RQStatus::Complete);
+        assert!(raptorq_handle_is_complete(handle));
+        let mut len = 0u64;
+        let out = unsafe { raptorq_handle_take_result(handle, &mut len) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(out, len as usize) },
+            &data[..]
+        );
+        unsafe { raptorq_free(out, len) };
+
+        assert_eq!(raptorq_handle_free(handle), RQStatus::Ok);
+        assert_eq!(raptorq_handle_free(handle), RQStatus::InvalidHandle);
+        // The slot is reused under a new generation; the old handle stays dead.
+        let adopted = unsafe { raptorq_handle_adopt(raptorq_ctx_new(10, 8)) };
+        assert_ne!(adopted, handle);
+        assert!(!raptorq_handle_is_valid(handle));
+        let s = enc.get_encoded_packets(0)[0].serialize();
+        let status = unsafe { raptorq_handle_push_frame(handle, s.as_ptr(), s.len() as u64) };
+        assert_eq!(status, RQStatus::InvalidHandle);
+        assert!(unsafe { raptorq_handle_take_result(handle, ptr::null_mut()) }.is_null());
+        assert!(!raptorq_handle_is_valid(0));
+        assert_eq!(raptorq_handle_free(adopted), RQStatus::Ok);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn adopting_needs_the_decode_thread_stopped() {
+        use std::ffi::CStr;
+        let data: Vec<u8> = (0..4_000u32).map(|i| (i * 9) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 256);
+        let ctx = raptorq_ctx_new(data.len() as u64, 256);
+        assert!(unsafe { raptorq_ctx_start_decode_thread(ctx, 4, 512) });
+        assert_eq!(unsafe { raptorq_handle_adopt(ctx) }, 0);
+        assert_eq!(
+            unsafe { CStr::from_ptr(raptorq_last_error()) }.to_str(),
+            Ok("context has a decode thread running")
+        );
+        // Still a usable context.
+        for p in enc.get_encoded_packets(0) {
+            let s = p.serialize();
+            while !unsafe { raptorq_ctx_enqueue_frame(ctx, s.as_ptr(), s.len() as u64) } {
+                std::thread::yield_now();
+            }
+        }
+        assert!(unsafe { raptorq_ctx_stop_decode_thread(ctx) });
+        let handle = unsafe { raptorq_handle_adopt(ctx) };
+        assert_ne!(handle, 0);
+        assert!(raptorq_handle_is_complete(handle));
+        assert_eq!(raptorq_handle_free(handle), RQStatus::Ok);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn expiring_handles() {
+        // Other tests use handles concurrently, so only count upwards.
+        let before = raptorq_handle_counters();
+        let kept = raptorq_handle_new(100, 16);
+        let forgotten = raptorq_handle_new(100, 16);
+        assert_eq!(raptorq_handle_set_ttl(kept, 60_000), RQStatus::Ok);
+        assert_eq!(raptorq_handle_set_ttl(forgotten, 1), RQStatus::Ok);
+        assert_eq!(raptorq_handle_set_ttl(0, 1), RQStatus::InvalidHandle);
+        std::thread::sleep(core::time::Duration::from_millis(5));
+        assert!(raptorq_handle_reap_expired() >= 1);
+        assert!(!raptorq_handle_is_valid(forgotten));
+        assert_eq!(raptorq_handle_free(forgotten), RQStatus::InvalidHandle);
+        assert!(raptorq_handle_is_valid(kept));
+        assert_eq!(raptorq_handle_free(kept), RQStatus::Ok);
+        let after = raptorq_handle_counters();
+        assert!(after.created >= before.created + 2);
+        assert!(after.freed > before.freed);
+        assert!(after.expired > before.expired);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn handle_stats() {
+        // Other tests use handles concurrently: only check for these two.
+        let data: Vec<u8> = (0..5_000u32).map(|i| (i * 19) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 128);
+        let done = raptorq_handle_new(data.len() as u64, 128);
+        let scanning = raptorq_handle_new(data.len() as u64, 128);
+        for p in enc.get_encoded_packets(0) {
+            let s = p.serialize();
+            unsafe { raptorq_handle_push_frame(done, s.as_ptr(), s.len() as u64) };
+        }
+        let s = enc.get_encoded_packets(0)[1].serialize();
+        unsafe { raptorq_handle_push_frame(scanning, s.as_ptr(), s.len() as u64) };
+        let stats = raptorq_handle_stats();
+        assert!(stats.active >= 1 && stats.completed >= 1);
+        assert!(stats.bytes_recovered >= data.len() as u64);
+        // The result, and the symbol buffered for the other transfer.
+        assert!(stats.memory_bytes >= data.len() as u64 + 128);
+        assert_eq!(raptorq_handle_free(done), RQStatus::Ok);
+        assert_eq!(raptorq_handle_free(scanning), RQStatus::Ok);
+    }
+
+    #[test]
+    fn cloned_contexts() {
+        let data: Vec<u8> = (0..4_000u32).map(|i| (i * 23) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        // Some source symbols missing, so only a solve completes it.
+        let frames: Vec<Vec<u8>> = enc.get_encoded_packets(10)[3..]
+            .iter()
+            .map(|p| p.serialize())
+            .collect();
+        let ctx = raptorq_ctx_new(data.len() as u64, 64);
+        unsafe { raptorq_ctx_set_deferred_finalize(ctx, true) };
+        let (before, after) = frames.split_at(frames.len() - 3);
+        for f in before {
+            unsafe { raptorq_ctx_push_frame_status(ctx, f.as_ptr(), f.len() as u64) };
+        }
+        // The copy finalizes on its own; the original is untouched.
+        let copy = unsafe { raptorq_ctx_clone(ctx) };
+        let mut percent = 0;
+        while unsafe { raptorq_ctx_finalize_step(copy, 5, &mut percent) } == RQStatus::Ok {}
+        assert!(unsafe { raptorq_ctx_is_complete(copy) });
+        assert!(!unsafe { raptorq_ctx_is_complete(ctx) });
+        for f in after {
+            unsafe { raptorq_ctx_push_frame_status(ctx, f.as_ptr(), f.len() as u64) };
+        }
+        while unsafe { raptorq_ctx_finalize_step(ctx, 5, &mut percent) } == RQStatus::Ok {}
+        for ctx in [ctx, copy] {
+            let mut len = 0u64;
+            let out = unsafe { raptorq_ctx_take_result(ctx, &mut len) };
+            assert_eq!(
+                unsafe { slice::from_raw_parts(out, len as usize) },
+                &data[..]
+            );
+            unsafe { raptorq_free(out, len) };
+            unsafe { raptorq_ctx_free(ctx) };
+        }
+        assert!(unsafe { raptorq_ctx_clone(ptr::null()) }.is_null());
+    }
+
+    #[test]
+    fn retained_contexts() {
+        use crate::pool::*;
+        let data = b"shared between the scanner and a persister";
+        let enc = Encoder::with_defaults(data, 16);
+        let pool = raptorq_pool_new(1);
+        let ctx = unsafe { raptorq_pool_checkout(pool, data.len() as u64, 16) };
+        let persister = unsafe { raptorq_ctx_retain(ctx) };
+        assert_eq!(persister, ctx);
+        #[cfg(feature = "std")]
+        assert_eq!(unsafe { raptorq_handle_adopt(ctx) }, 0);
+        // The scanner gives its reference back mid-transfer.
+        unsafe { raptorq_pool_return(pool, ctx) };
+        assert_eq!(unsafe { raptorq_pool_idle_count(pool) }, 0);
+        for p in enc.get_encoded_packets(0) {
+            let s = p.serialize();
+            unsafe { raptorq_ctx_push_frame(persister, s.as_ptr(), s.len() as u64) };
+        }
+        assert!(unsafe { raptorq_ctx_is_complete(persister) });
+        unsafe { raptorq_pool_return(pool, persister) };
+        assert_eq!(unsafe { raptorq_pool_idle_count(pool) }, 1);
+        let ctx = unsafe { raptorq_ctx_retain(raptorq_ctx_new(10, 8)) };
+        unsafe { raptorq_ctx_release(ctx) };
+        unsafe { raptorq_ctx_free(ctx) };
+        unsafe { raptorq_pool_free(pool) };
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn threadsafe_context() {
+        let data: Vec<u8> = (0..4_000u32).map(|i| (i * 7) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        let frames: Vec<Vec<u8>> = enc
+            .get_encoded_packets(8)
+            .iter()
+            .map(|p| p.serialize())
+            .collect();
+        let ctx = raptorq_ctx_new_threadsafe(data.len() as u64, 64) as usize;
+        std::thread::scope(|scope| {
+            for half in frames.chunks(frames.len() / 2 + 1) {
+                scope.spawn(move || {
+                    for f in half {
+                        let ctx = ctx as *mut RQContext;
+                        unsafe { raptorq_ctx_push_frame_status(ctx, f.as_ptr(), f.len() as u64) };
+                        unsafe { raptorq_ctx_is_complete(ctx) };
+                    }
+                });
+            }
+        });
+        let ctx = ctx as *mut RQContext;
+        assert!(unsafe { raptorq_ctx_is_complete(ctx) });
+        let mut len = 0u64;
+        let out = unsafe { raptorq_ctx_take_result(ctx, &mut len) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(out, len as usize) },
+            &data[..]
+        );
+        unsafe { raptorq_free(out, len) };
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn isolate_safe_context() {
+        use std::ffi::CStr;
+        let data: Vec<u8> = (0..3_000u32).map(|i| (i * 11) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        let ctx = raptorq_ctx_new_isolate_safe(data.len() as u64, 64);
+        let frame = enc.get_encoded_packets(0)[0].serialize();
+        assert!(!unsafe { raptorq_ctx_enqueue(ctx, frame.as_ptr(), frame.len() as u64) });
+        assert_eq!(
+            unsafe { CStr::from_ptr(raptorq_last_error()) }.to_str(),
+            Ok("isolate-safe contexts have no decode thread")
+        );
+        assert!(!unsafe { raptorq_ctx_start_decode_thread(ctx, 4, 256) });
+        for p in enc.get_encoded_packets(0) {
+            let f = p.serialize();
+            unsafe { raptorq_ctx_push_frame_status(ctx, f.as_ptr(), f.len() as u64) };
+        }
+        let boxed = raptorq_buffer_into_box(unsafe { raptorq_ctx_take_result_buffer(ctx) });
+        let result = unsafe { slice::from_raw_parts((*boxed).ptr, (*boxed).len as usize) };
+        assert_eq!(result, &data[..]);
+        unsafe { raptorq_buffer_box_free(boxed) };
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn logs_a_transfer() {
+        use std::ffi::{c_void, CStr, CString};
+        use std::sync::Mutex;
+        static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        unsafe extern "C" fn collect(level: RQLogLevel, message: *const c_char, _: *mut c_void) {
+            let line = CStr::from_ptr(message).to_string_lossy();
+            LINES.lock().unwrap().push(format!("{level:?} {line}"));
+        }
+        unsafe { raptorq_set_logger(RQLogLevel::Debug, Some(collect), ptr::null_mut()) };
+        let data: Vec<u8> = (0..3_000u32).map(|i| (i * 13) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        let ctx = raptorq_ctx_new(data.len() as u64, 64);
+        let (camera, spaced) = (
+            CString::new("camera").unwrap(),
+            CString::new("a b").unwrap(),
+        );
+        assert!(unsafe { raptorq_ctx_set_log_tag(ctx, camera.as_ptr()) });
+        assert!(!unsafe { raptorq_ctx_set_log_tag(ctx, spaced.as_ptr()) });
+        unsafe { raptorq_ctx_push_frame_status(ctx, [1, 2, 3].as_ptr(), 3) };
+        // Skipping the first source packet forces a real solve.
+        for p in enc.get_encoded_packets(4).iter().skip(1) {
+            let s = p.serialize();
+            unsafe { raptorq_ctx_push_frame_status(ctx, s.as_ptr(), s.len() as u64) };
+        }
+        assert!(unsafe { raptorq_ctx_is_complete(ctx) });
+        unsafe { raptorq_ctx_free(ctx) };
+        let quiet = raptorq_ctx_new(10, 8);
+        unsafe { raptorq_ctx_set_log_level(quiet, RQLogLevel::Off) };
+        let tag = CString::new("quiet").unwrap();
+        unsafe { raptorq_ctx_set_log_tag(quiet, tag.as_ptr()) };
+        unsafe { raptorq_ctx_push_frame_status(quiet, [0; 7].as_ptr(), 7) };
+        unsafe { raptorq_ctx_free(quiet) };
+        unsafe { raptorq_set_logger(RQLogLevel::Off, None, ptr::null_mut()) };
+        // Other tests may log concurrently; only look for this one's lines.
+        let lines = LINES.lock().unwrap();
+        for (start, end) in [
+            (
+                "Info context created transfer_length=3000 symbol_size=64",
+                "",
+            ),
+            ("Debug frame rejected status=Malformed len=3", " tag=camera"),
+            ("Debug solve attempt sbn=0", " tag=camera"),
+            ("Info transfer complete len=3000", " tag=camera"),
+        ] {
+            assert!(
+                lines
+                    .iter()
+                    .any(|l| l.starts_with(start) && l.ends_with(end)),
+                "{start}"
+            );
+        }
+        assert!(!lines
+            .iter()
+            .any(|l| l.starts_with("Trace") || l.ends_with("tag=quiet")));
+    }
+
+    #[test]
+    fn pooled_contexts() {
+        use crate::pool::*;
+        let pool = raptorq_pool_new(1);
+        for round in 0..3u32 {
+            let data: Vec<u8> = (0..2_000u32).map(|i| (i * round + 1) as u8).collect();
+            let enc = Encoder::with_defaults(&data, 64);
+            let ctx = unsafe { raptorq_pool_checkout(pool, data.len() as u64, 64) };
+            let mut packets = enc.get_encoded_packets(4);
+            packets.remove(0); // force the repair path through the arena
+            let mut done = false;
+            for p in packets {
+                let s = p.serialize();
+                if unsafe { raptorq_ctx_push_frame(ctx, s.as_ptr(), s.len() as u64) } {
+                    done = true;
+                    break;
+                }
+            }
+            assert!(done);
+            let mut out_len = 0u64;
+            let out = unsafe { raptorq_ctx_take_result(ctx, &mut out_len) };
+            assert_eq!(
+                unsafe { slice::from_raw_parts(out, out_len as usize) },
+                &data[..]
+            );
+            unsafe { raptorq_free(out, out_len) };
+            unsafe { raptorq_pool_return(pool, ctx) };
+            assert_eq!(unsafe { raptorq_pool_idle_count(pool) }, 1);
+        }
+        unsafe { raptorq_pool_free(pool) };
+    }
+
+    #[test]
+    fn scan_session() {
+        use crate::session::*;
+        let data: Vec<u8> = (0..2_000u32).map(|i| (i * 3) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        let frames: Vec<Vec<u8>> = enc
+            .get_encoded_packets(4)
+            .iter()
+            .map(|p| p.serialize())
+            .collect();
+        let ctx = raptorq_ctx_new(data.len() as u64, 64);
+        let session = unsafe { raptorq_session_new(ctx) };
+        unsafe { raptorq_ctx_free(ctx) }; // the session keeps it alive
+        assert_eq!(unsafe { raptorq_session_ctx(session) }, ctx);
+        // The loop comes round again before the decoder has seen it all.
+        let (first, rest) = frames.split_at(frames.len() / 2);
+        for frame in first.iter().chain(first).chain(rest) {
+            let status =
+                unsafe { raptorq_session_push(session, frame.as_ptr(), frame.len() as u64) };
+            if status == RQStatus::Complete {
+                break;
+            }
+        }
+        assert!(unsafe { raptorq_ctx_is_complete(raptorq_session_ctx(session)) });
+        let stats = unsafe { raptorq_session_stats(session) };
+        assert_eq!(stats.frames - stats.unique_frames, first.len() as u64);
+        assert!(stats.frames_per_second >= stats.unique_frames_per_second);
+        unsafe { raptorq_session_free(session) };
+        assert_eq!(
+            unsafe { raptorq_session_push(ptr::null_mut(), first[0].as_ptr(), 8) },
+            RQStatus::InvalidArgument
+        );
+        assert!(unsafe { raptorq_session_new(ptr::null_mut()) }.is_null());
+    }
+
+    #[test]
+    fn stalled_scan() {
+        let data = b"the camera is stuck on one code";
+        let enc = Encoder::with_defaults(data, 16);
+        let frames: Vec<Vec<u8>> = enc
+            .get_encoded_packets(0)
+            .iter()
+            .map(|p| p.serialize())
+            .collect();
+        let ctx = raptorq_ctx_new(data.len() as u64, 16);
+        let window = RQStallWindow { pushes: 4, ms: 0 };
+        assert!(!unsafe { raptorq_ctx_is_stalled(ctx, window) });
+        // The first push brings a symbol, the four after it nothing.
+        for _ in 0..5 {
+            unsafe { raptorq_ctx_push_frame(ctx, frames[0].as_ptr(), frames[0].len() as u64) };
+        }
+        assert!(unsafe { raptorq_ctx_is_stalled(ctx, window) });
+        for frame in &frames[1..] {
+            unsafe { raptorq_ctx_push_frame(ctx, frame.as_ptr(), frame.len() as u64) };
+        }
+        assert!(unsafe { raptorq_ctx_is_complete(ctx) });
+        assert!(!unsafe { raptorq_ctx_is_stalled(ctx, RQStallWindow { pushes: 0, ms: 1 }) });
+        unsafe { raptorq_ctx_free(ctx) };
+        assert!(!unsafe { raptorq_ctx_is_stalled(ptr::null(), window) });
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn paused_scan() {
+        let ctx = raptorq_ctx_new(1_000, 32);
+        let window = RQStallWindow { pushes: 0, ms: 40 };
+        unsafe { raptorq_ctx_pause(ctx) };
+        unsafe { raptorq_ctx_pause(ctx) };
+        std::thread::sleep(core::time::Duration::from_millis(60));
+        assert!(!unsafe { raptorq_ctx_is_stalled(ctx, window) });
+        assert!(unsafe { raptorq_ctx_stats(ctx) }.elapsed_ms < 40);
+        unsafe { raptorq_ctx_resume(ctx) };
+        assert!(!unsafe { raptorq_ctx_is_stalled(ctx, window) });
+        std::thread::sleep(core::time::Duration::from_millis(60));
+        assert!(unsafe { raptorq_ctx_is_stalled(ctx, window) });
+        let json = unsafe { raptorq_ctx_stats_json(ctx) };
+        let text = unsafe { slice::from_raw_parts(json.ptr, json.len as usize) };
+        let text = core::str::from_utf8(text).unwrap();
+        assert!(text.contains(",\"paused\":false,"));
+        let paused_ms: u64 = text
+            .split("\"paused_ms\":")
+            .nth(1)
+            .unwrap()
+            .split(',')
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(paused_ms >= 60);
+        unsafe {
+            raptorq_buffer_free(json);
+            raptorq_ctx_free(ctx);
+            raptorq_ctx_pause(ptr::null_mut());
+            raptorq_ctx_resume(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn transfer_stats() {
+        let data: Vec<u8> = (0..1_000u32).map(|i| (i * 11) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 32);
+        let frames: Vec<Vec<u8>> = enc
+            .get_encoded_packets(4)
+            .iter()
+            .map(|p| p.serialize())
+            .collect();
+        let ctx = raptorq_ctx_new(data.len() as u64, 32);
+        let push = |frame: &[u8]| unsafe {
+            raptorq_ctx_push_frame_status(ctx, frame.as_ptr(), frame.len() as u64)
+        };
+        assert_eq!(push(&[1, 2, 3]), RQStatus::Malformed);
+        // Without the first source symbol the block takes a real solve.
+        push(&frames[1]);
+        push(&frames[1]);
+        #[cfg(feature = "std")]
+        assert!(unsafe { raptorq_ctx_novelty_rate(ctx) } > 0.0);
+        assert_eq!(
+            unsafe { raptorq_ctx_efficiency(ctx) },
+            RQEfficiency::default()
+        );
+        let mut bytes = 3 + 2 * frames[1].len() as u64;
+        let mut pushed = 3;
+        for frame in &frames[2..] {
+            pushed += 1;
+            bytes += frame.len() as u64;
+            if push(frame) == RQStatus::Complete {
+                break;
+            }
+        }
+        let result = unsafe { raptorq_ctx_take_result_buffer(ctx) };
+        assert_eq!(result.len, data.len() as u64);
+        unsafe { raptorq_buffer_free(result) };
+        let stats = unsafe { raptorq_ctx_stats(ctx) };
+        assert_eq!(stats.frames, pushed);
+        assert_eq!(stats.unique_frames, pushed - 2);
+        assert_eq!((stats.duplicate_frames, stats.malformed_frames), (1, 1));
+        assert_eq!(stats.bytes, bytes);
+        assert!(stats.solve_attempts >= 1);
+        let efficiency = unsafe { raptorq_ctx_efficiency(ctx) };
+        assert_eq!(efficiency.frames, pushed);
+        assert_eq!(efficiency.minimum_frames, 32);
+        assert_eq!(efficiency.overhead_ratio, pushed as f32 / 32.0);
+        assert_eq!(efficiency.duplicate_frames, 1);
+        assert_eq!(efficiency.duplicate_percent, 100.0 / pushed as f32);
+        let json = unsafe { raptorq_ctx_stats_json(ctx) };
+        let text = unsafe { slice::from_raw_parts(json.ptr, json.len as usize) };
+        let text = core::str::from_utf8(text).unwrap();
+        assert!(text.starts_with(&format!(
+            "{{\"frames\":{pushed},\"unique_frames\":{},\"duplicate_frames\":1,",
+            pushed - 2
+        )));
+        assert!(text.contains(&format!("\"push\":{{\"pushes\":{pushed},")));
+        assert!(text.ends_with(concat!(
+            "\"complete\":false,\"taken\":true,\"digest_mismatch\":false,",
+            "\"total_frames\":0,\"expected_frames\":34,\"eta_ms\":0,\"solve_probability\":1}"
+        )));
+        unsafe { raptorq_buffer_free(json) };
+        // Frames pushed after completion leave the efficiency alone.
+        assert_eq!(push(&frames[0]), RQStatus::Ignored);
+        assert_eq!(unsafe { raptorq_ctx_efficiency(ctx) }, efficiency);
+        unsafe { raptorq_ctx_free(ctx) };
+        assert_eq!(
+            unsafe { raptorq_ctx_stats(ptr::null()) },
+            RQStats::default()
+        );
+        assert!(unsafe { raptorq_ctx_stats_json(ptr::null()) }.ptr.is_null());
+        assert_eq!(unsafe { raptorq_ctx_novelty_rate(ptr::null()) }, 0.0);
+        assert_eq!(
+            unsafe { raptorq_ctx_efficiency(ptr::null()) },
+            RQEfficiency::default()
+        );
+    }
+
+    #[test]
+    fn frame_quality() {
+        let data: Vec<u8> = (0..1_000u32).map(|i| (i * 3) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 32);
+        let packet = enc.get_encoded_packets(0)[0].serialize();
+        let sealed = |packet: &[u8]| {
+            let mut frame = packet.to_vec();
+            checksum::seal(&mut frame);
+            frame
+        };
+        let ctx = raptorq_ctx_new(data.len() as u64, 32);
+        unsafe { raptorq_ctx_set_frame_checksums(ctx, true) };
+        let push = |frame: &[u8]| unsafe {
+            raptorq_ctx_push_frame_status(ctx, frame.as_ptr(), frame.len() as u64)
+        };
+        let mut corrupted = sealed(&packet);
+        corrupted[5] ^= 1;
+        let mut longer = packet.clone();
+        longer.push(7);
+        let mut outside = packet.clone();
+        outside[0] = 9; // no source block 9
+        for frame in [
+            vec![1, 2],
+            corrupted,
+            sealed(&packet[..20]),
+            sealed(&longer),
+            sealed(&outside),
+            sealed(&packet),
+        ] {
+            push(&frame);
+        }
+        let quality = unsafe { raptorq_ctx_frame_quality(ctx) };
+        assert_eq!(
+            quality,
+            RQFrameQuality {
+                truncated_frames: 2,
+                wrong_length_frames: 1,
+                checksum_failures: 1,
+                other_malformed_frames: 1,
+            }
+        );
+        assert_eq!(unsafe { raptorq_ctx_stats(ctx) }.malformed_frames, 5);
+        // Too few frames to advise on.
+        assert_eq!(
+            unsafe { raptorq_ctx_density_advice(ctx) },
+            RQDensityAdvice::Keep
+        );
+        unsafe { raptorq_ctx_free(ctx) };
+        assert_eq!(
+            unsafe { raptorq_ctx_frame_quality(ptr::null()) },
+            RQFrameQuality::default()
+        );
+    }
+
+    #[test]
+    fn block_timings() {
+        let data: Vec<u8> = (0..2_000u32).map(|i| (i * 13) as u8).collect();
+        let oti = raptorq::ObjectTransmissionInformation::new(data.len() as u64, 32, 2, 1, 1);
+        let enc = Encoder::new(&data, oti);
+        let ctx = unsafe { raptorq_ctx_from_oti(oti.serialize().as_ptr()) };
+        assert_eq!(unsafe { raptorq_ctx_block_count(ctx) }, 0);
+        // Block 1 misses its first source symbol and has to be solved for.
+        for p in enc.get_encoded_packets(2) {
+            let id = p.payload_id();
+            if (id.source_block_number(), id.encoding_symbol_id()) != (1, 0) {
+                let s = p.serialize();
+                unsafe { raptorq_ctx_push_frame_status(ctx, s.as_ptr(), s.len() as u64) };
+            }
+        }
+        assert!(unsafe { raptorq_ctx_is_complete(ctx) });
+        assert_eq!(unsafe { raptorq_ctx_block_count(ctx) }, 2);
+        let first = unsafe { raptorq_ctx_block_timing(ctx, 0) };
+        assert!(first.decoded);
+        assert_eq!(first.solve_attempts, 0);
+        assert!(first.symbols_received >= first.symbols);
+        let second = unsafe { raptorq_ctx_block_timing(ctx, 1) };
+        assert!(second.decoded);
+        assert!(second.solve_attempts >= 1);
+        assert!(second.symbols_received >= second.symbols);
+        unsafe { raptorq_buffer_free(raptorq_ctx_take_result_buffer(ctx)) };
+        // Still there once the blocks are released.
+        assert_eq!(unsafe { raptorq_ctx_block_timing(ctx, 1) }, second);
+        assert_eq!(
+            unsafe { raptorq_ctx_block_timing(ctx, 2) },
+            RQBlockTiming::default()
+        );
+        unsafe { raptorq_ctx_free(ctx) };
+        assert_eq!(unsafe { raptorq_ctx_block_count(ptr::null()) }, 0);
+    }
+
+    #[test]
+    fn lagging_block() {
+        let data: Vec<u8> = (0..2_000u32).map(|i| (i * 7) as u8).collect();
+        let oti = raptorq::ObjectTransmissionInformation::new(data.len() as u64, 32, 2, 1, 1);
+        let enc = Encoder::new(&data, oti);
+        let ctx = unsafe { raptorq_ctx_from_oti(oti.serialize().as_ptr()) };
+        let push = |p: &raptorq::EncodingPacket| {
+            let s = p.serialize();
+            unsafe { raptorq_ctx_push_frame_status(ctx, s.as_ptr(), s.len() as u64) }
+        };
+        assert_eq!(unsafe { raptorq_ctx_lagging_block(ctx) }, u32::MAX);
+        let (ahead, behind): (Vec<_>, Vec<_>) = enc
+            .get_encoded_packets(0)
+            .into_iter()
+            .partition(|p| p.payload_id().encoding_symbol_id() % 3 != 0);
+        for p in &ahead {
+            push(p);
+        }
+        // Both blocks miss 11 symbols: the lower SBN wins the tie.
+        assert_eq!(unsafe { raptorq_ctx_lagging_block(ctx) }, 0);
+        for p in behind
+            .iter()
+            .filter(|p| p.payload_id().source_block_number() == 0)
+        {
+            push(p);
+        }
+        assert_eq!(unsafe { raptorq_ctx_lagging_block(ctx) }, 1);
+        for p in &behind {
+            push(p);
+        }
+        assert!(unsafe { raptorq_ctx_is_complete(ctx) });
+        assert_eq!(unsafe { raptorq_ctx_lagging_block(ctx) }, u32::MAX);
+        unsafe { raptorq_ctx_free(ctx) };
+        assert_eq!(unsafe { raptorq_ctx_lagging_block(ptr::null()) }, u32::MAX);
+    }
+
+    #[test]
+    fn event_log() {
+        let data: Vec<u8> = (0..1_000u32).map(|i| (i * 17) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 32);
+        let ctx = raptorq_ctx_new(data.len() as u64, 32);
+        let push = |frame: &[u8]| unsafe {
+            raptorq_ctx_push_frame_status(ctx, frame.as_ptr(), frame.len() as u64)
+        };
+        push(&[1, 2, 3]);
+        push(&[4, 5]);
+        // Without the first source symbol the block takes a real solve.
+        for p in &enc.get_encoded_packets(4)[1..] {
+            if push(&p.serialize()) == RQStatus::Complete {
+                break;
+            }
+        }
+        let count = unsafe { raptorq_ctx_event_count(ctx) };
+        let events: Vec<RQEvent> = (0..count)
+            .map(|i| unsafe { raptorq_ctx_event(ctx, i) })
+            .collect();
+        let kinds: Vec<RQEventKind> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds[..3],
+            [
+                RQEventKind::OtiLearned,
+                RQEventKind::FirstFrame,
+                RQEventKind::Error
+            ]
+        );
+        assert_eq!(events[0].value, data.len() as u64);
+        assert_eq!((events[1].status, events[1].value), (RQStatus::Ok, 3));
+        // Both malformed frames are counted once, the first one included.
+        assert_eq!(
+            (events[2].status, events[2].count),
+            (RQStatus::Malformed, 2)
+        );
+        let (last, solves) = events[3..].split_last().unwrap();
+        assert!(!solves.is_empty());
+        assert!(solves.iter().all(|e| e.kind == RQEventKind::Solve));
+        assert_eq!(
+            solves.iter().map(|e| e.count).sum::<u32>(),
+            unsafe { raptorq_ctx_stats(ctx) }.solve_attempts
+        );
+        assert_eq!((last.kind, last.value), (RQEventKind::Complete, 1_000));
+        assert!(events.windows(2).all(|w| w[0].at_ms <= w[1].at_ms));
+        assert_eq!(
+            unsafe { raptorq_ctx_event(ctx, count) }.kind,
+            RQEventKind::None
+        );
+        assert_eq!(unsafe { raptorq_ctx_events_dropped(ctx) }, 0);
+        unsafe { raptorq_ctx_free(ctx) };
+        assert_eq!(unsafe { raptorq_ctx_event_count(ptr::null()) }, 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn completion_eta() {
+        let data: Vec<u8> = (0..640u32).map(|i| (i * 5) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 32);
+        let frames: Vec<Vec<u8>> = enc
+            .get_encoded_packets(0)
+            .iter()
+            .map(|p| p.serialize())
+            .collect();
+        let ctx = raptorq_ctx_new(data.len() as u64, 32);
+        let push = |frame: &[u8]| unsafe {
+            raptorq_ctx_push_frame_status(ctx, frame.as_ptr(), frame.len() as u64)
+        };
+        assert_eq!(unsafe { raptorq_ctx_eta_ms(ctx) }, u32::MAX);
+        push(&frames[0]);
+        assert_eq!(unsafe { raptorq_ctx_eta_ms(ctx) }, u32::MAX);
+        std::thread::sleep(core::time::Duration::from_millis(5));
+        push(&frames[1]);
+        // 18 of 20 symbols to go, at no less than 5 ms each.
+        let eta = unsafe { raptorq_ctx_eta_ms(ctx) };
+        assert!((90..u32::MAX).contains(&eta), "{eta}");
+        for frame in &frames[2..] {
+            push(frame);
+        }
+        assert_eq!(unsafe { raptorq_ctx_eta_ms(ctx) }, 0);
+        unsafe { raptorq_ctx_free(ctx) };
+    }
+
+    #[test]
+    fn expected_frames() {
+        // 20 source symbols in one block, plus two.
+        let ctx = raptorq_ctx_new(640, 32);
+        assert_eq!(unsafe { raptorq_ctx_expected_frames(ctx) }, 22);
+        unsafe { raptorq_ctx_set_decode_overhead(ctx, 5) };
+        assert_eq!(unsafe { raptorq_ctx_expected_frames(ctx) }, 25);
+        unsafe { raptorq_ctx_free(ctx) };
+        assert_eq!(unsafe { raptorq_ctx_expected_frames(ptr::null()) }, 0);
+    }
+
+    #[test]
+    fn solve_probability() {
+        let data: Vec<u8> = (0..640u32).map(|i| (i * 9) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 32);
+        let frames: Vec<Vec<u8>> = enc
+            .get_encoded_packets(4)
+            .iter()
+            .map(|p| p.serialize())
+            .collect();
+        let ctx = raptorq_ctx_new(data.len() as u64, 32);
+        unsafe { raptorq_ctx_set_deferred_finalize(ctx, true) };
+        let probability = || unsafe { raptorq_ctx_solve_probability(ctx) };
+        assert_eq!(probability(), 0.0);
+        // Source symbols 1 to 19 of 20, then repair symbols.
+        let mut frames = frames[1..].iter();
+        for frame in frames.by_ref().take(19) {
+            unsafe { raptorq_ctx_push_frame_status(ctx, frame.as_ptr(), frame.len() as u64) };
+        }
+        assert_eq!(probability(), 0.0);
+        let mut expected = [0.99, 0.9999].into_iter();
+        for frame in frames.by_ref().take(2) {
+            let status =
+                unsafe { raptorq_ctx_push_frame_status(ctx, frame.as_ptr(), frame.len() as u64) };
+            assert_eq!(status, RQStatus::ReadyToFinalize);
+            assert!((probability() - expected.next().unwrap()).abs() < 1e-6);
+        }
+        let mut percent = 0u8;
+        let status = unsafe { raptorq_ctx_finalize_step(ctx, 10, &mut percent) };
+        assert_eq!(status, RQStatus::Complete);
+        assert_eq!(probability(), 1.0);
+        unsafe { raptorq_ctx_free(ctx) };
+        assert_eq!(unsafe { raptorq_ctx_solve_probability(ptr::null()) }, 0.0);
+    }
+
+    #[test]
+    fn regression_report() {
+        let report = raptorq_run_regression_vectors();
+        assert_eq!(report.passed, report.total);
+        assert_eq!(report.first_failed_vector, -1);
+        assert!(raptorq_regression_vector_name(report.total).is_null());
+        assert_eq!(raptorq_self_test(), RQStatus::Ok);
     }
 }
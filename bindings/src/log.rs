@@ -0,0 +1,174 @@
+//! Process‑wide logging callback.
+//!
+//! Lines are `key=value` pairs after a short event name, e.g.
+//! `frame rejected status=Malformed len=183`, so a Swift shim can forward
+//! them to `os_log` unchanged and they stay greppable.  Nothing is formatted
+//! unless a logger is registered at a level that wants the line.
+//!
+//! A context can override the logger's level and label its lines with a
+//! `tag=` pair ([`LogScope`]) to tell simultaneous transfers apart.  Logging
+//! needs `std`; without it only the (empty) [`LogScope`] remains.
+
+#[cfg(feature = "std")]
+use core::ffi::{c_char, c_void};
+#[cfg(all(feature = "std", any(feature = "encoder", feature = "decoder")))]
+use core::fmt::{self, Write};
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+#[cfg(all(feature = "std", any(feature = "encoder", feature = "decoder")))]
+use std::ffi::CString;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+/// Longest tag accepted by [`LogScope::set_tag`], in bytes.
+#[cfg(all(feature = "decoder", feature = "std"))]
+const MAX_TAG_LEN: usize = 32;
+
+/// Severity of a log line; a logger registered at one level receives that
+/// level and every more severe one.
+#[cfg(feature = "std")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RQLogLevel {
+    /// Nothing is logged.
+    Off = 0,
+    /// A call failed, e.g. an internal panic or an I/O error.
+    Error = 1,
+    /// The transfer cannot succeed, e.g. a digest mismatch.
+    Warn = 2,
+    /// Context lifecycle: creation and completion.
+    Info = 3,
+    /// Rejected frames and solve attempts.
+    Debug = 4,
+    /// Every accepted frame.
+    Trace = 5,
+}
+
+/// Called with the level and the NUL‑terminated line (valid only for the
+/// duration of the call).  It may run on any thread that calls into the
+/// library and must not call back into it.
+#[cfg(feature = "std")]
+pub type RQLogCallback =
+    Option<unsafe extern "C" fn(level: RQLogLevel, message: *const c_char, user_data: *mut c_void)>;
+
+#[cfg(feature = "std")]
+struct Logger {
+    f: RQLogCallback,
+    user_data: usize,
+}
+
+/// The logger's level, checked before formatting; `Off` without a logger.
+#[cfg(feature = "std")]
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(RQLogLevel::Off as u8);
+
+/// A logger is registered (possibly at `Off`, for contexts that override it).
+#[cfg(feature = "std")]
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(feature = "std")]
+static LOGGER: Mutex<Logger> = Mutex::new(Logger {
+    f: None,
+    user_data: 0,
+});
+
+#[cfg(all(feature = "std", any(feature = "encoder", feature = "decoder")))]
+#[inline]
+pub(crate) fn enabled(level: RQLogLevel) -> bool {
+    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+#[cfg(all(feature = "std", any(feature = "encoder", feature = "decoder")))]
+pub(crate) fn emit(level: RQLogLevel, args: fmt::Arguments) {
+    emit_tagged(level, None, args);
+}
+
+#[cfg(all(feature = "std", any(feature = "encoder", feature = "decoder")))]
+fn emit_tagged(level: RQLogLevel, tag: Option<&str>, args: fmt::Arguments) {
+    let (f, user_data) = match LOGGER.lock() {
+        Ok(logger) => (logger.f, logger.user_data),
+        Err(_) => return,
+    };
+    let f = match f {
+        Some(f) => f,
+        None => return,
+    };
+    let mut line = String::new();
+    if line.write_fmt(args).is_err() {
+        return;
+    }
+    if let Some(tag) = tag {
+        line.push_str(" tag=");
+        line.push_str(tag);
+    }
+    if let Ok(line) = CString::new(line) {
+        unsafe { f(level, line.as_ptr(), user_data as *mut c_void) };
+    }
+}
+
+/// Per‑context verbosity and label.
+#[cfg(feature = "decoder")]
+#[derive(Clone, Default)]
+pub(crate) struct LogScope {
+    #[cfg(feature = "std")]
+    level: Option<RQLogLevel>, // `None`: the logger's level
+    #[cfg(feature = "std")]
+    tag: Option<String>,
+}
+
+#[cfg(all(feature = "decoder", feature = "std"))]
+impl LogScope {
+    pub(crate) fn set_level(&mut self, level: RQLogLevel) {
+        self.level = Some(level);
+    }
+
+    /// Label lines with `tag`, or stop labelling them for an empty one.
+    /// `false` (and no change) for a tag longer than [`MAX_TAG_LEN`] bytes or
+    /// containing whitespace, which would break the `key=value` format.
+    pub(crate) fn set_tag(&mut self, tag: &str) -> bool {
+        if tag.len() > MAX_TAG_LEN || tag.contains(char::is_whitespace) {
+            return false;
+        }
+        self.tag = (!tag.is_empty()).then(|| tag.into());
+        true
+    }
+
+    #[inline]
+    pub(crate) fn enabled(&self, level: RQLogLevel) -> bool {
+        match self.level {
+            Some(max) => level <= max && INSTALLED.load(Ordering::Relaxed),
+            None => enabled(level),
+        }
+    }
+
+    pub(crate) fn emit(&self, level: RQLogLevel, args: fmt::Arguments) {
+        emit_tagged(level, self.tag.as_deref(), args);
+    }
+}
+
+/// Register `callback` for every line at `level` or more severe, replacing
+/// any earlier logger.  `NULL` turns logging off; with [`RQLogLevel::Off`]
+/// only contexts given their own level by `raptorq_ctx_set_log_level` log.
+///
+/// # Safety
+/// `callback` must stay callable, and `user_data` valid for it, until it is
+/// replaced.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_set_logger(
+    level: RQLogLevel,
+    callback: RQLogCallback,
+    user_data: *mut c_void,
+) {
+    let mut logger = LOGGER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    logger.f = callback;
+    logger.user_data = user_data as usize;
+    let level = if callback.is_some() {
+        level
+    } else {
+        RQLogLevel::Off
+    };
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+    INSTALLED.store(callback.is_some(), Ordering::Relaxed);
+}
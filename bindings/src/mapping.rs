@@ -0,0 +1,116 @@
+//! `mmap`ed result buffers.
+//!
+//! A transfer approaching 100 MB assembled in one `Vec` is a single huge
+//! dirty heap allocation, exactly what iOS Jetsam reacts to.  A shared file
+//! mapping lets the kernel write pages back to the file and evict them under
+//! pressure; an anonymous mapping at least keeps the buffer out of the malloc
+//! zones and is returned to the system in one piece.
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr::{self, NonNull};
+use std::slice;
+
+pub(crate) struct Mapping {
+    ptr: NonNull<u8>,
+    len: usize,
+    file: Option<File>,
+}
+
+// The mapping is owned exclusively, like a `Vec`.
+unsafe impl Send for Mapping {}
+
+impl Mapping {
+    /// Map `len` zeroed bytes, shared with the file at `path` (created or
+    /// truncated to `len`) or anonymous when `path` is `None`.
+    pub fn create(path: Option<&Path>, len: usize) -> io::Result<Mapping> {
+        if len == 0 {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        let file = match path {
+            Some(path) => {
+                let file = OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(path)?;
+                file.set_len(len as u64)?;
+                Some(file)
+            }
+            None => None,
+        };
+        let (flags, fd) = match &file {
+            Some(file) => (libc::MAP_SHARED, file.as_raw_fd()),
+            None => (libc::MAP_PRIVATE | libc::MAP_ANON, -1),
+        };
+        let addr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                flags,
+                fd,
+                0,
+            )
+        };
+        if addr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Mapping {
+            ptr: NonNull::new(addr as *mut u8).ok_or(io::ErrorKind::Other)?,
+            len,
+            file,
+        })
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Write a file backed mapping back to disk.
+    pub fn flush(&self) -> io::Result<()> {
+        if self.file.is_none() {
+            return Ok(());
+        }
+        let ret = unsafe { libc::msync(self.ptr.as_ptr().cast(), self.len, libc::MS_SYNC) };
+        if ret != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr.as_ptr().cast(), self.len) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anonymous_and_file_backed() {
+        let mut anon = Mapping::create(None, 4096).unwrap();
+        assert!(anon.as_slice().iter().all(|b| *b == 0));
+        anon.as_mut_slice()[4095] = 7;
+        assert_eq!(anon.as_slice()[4095], 7);
+
+        let path = std::env::temp_dir().join(format!("rq-map-{}", std::process::id()));
+        let mut mapped = Mapping::create(Some(&path), 10).unwrap();
+        mapped.as_mut_slice().copy_from_slice(b"0123456789");
+        mapped.flush().unwrap();
+        drop(mapped);
+        assert_eq!(std::fs::read(&path).unwrap(), b"0123456789");
+        std::fs::remove_file(&path).unwrap();
+        assert!(Mapping::create(None, 0).is_err());
+    }
+}
@@ -0,0 +1,296 @@
+//! One allocator for every buffer that crosses the FFI boundary.
+//!
+//! Buffers handed out by the library (results, frames) and buffers handed in
+//! (owned frames) all come from Rust's global allocator with a byte layout of
+//! exactly their length, so any of them can be grown with
+//! [`raptorq_realloc`] and released with [`raptorq_dealloc`].  Never pass them
+//! to `free(3)`, and never pass `malloc`ed memory to these functions.
+//!
+//! Every function handing out bytes has a form ending in `_buffer` that
+//! returns an [`RQBuffer`] carrying its own size, released with
+//! [`raptorq_buffer_free`] alone; passing a wrong length to the other release
+//! functions is undefined behaviour.  New byte‑returning functions only come
+//! in that form.
+//!
+//! The `_zeroize` release functions overwrite the buffer before freeing it,
+//! for results holding secrets that must not linger in freed heap pages, and
+//! unlock it if it was pinned with `mlock`.
+
+use core::ptr;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use alloc::alloc::{alloc_zeroed, realloc, Layout};
+use alloc::boxed::Box;
+#[cfg(feature = "decoder")]
+use alloc::vec::Vec;
+
+use crate::{checked_len, try_catch_unwind};
+
+/// Allocate a `len`‑byte, zero‑filled buffer, e.g. to fill with a frame and
+/// hand back through `raptorq_ctx_push_frame_owned`.  Returns `NULL` when
+/// `len` is `0`, does not fit the address space or the allocation fails.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_alloc(len: u64) -> *mut u8 {
+    let len = match checked_len(len) {
+        Some(len) if len > 0 => len,
+        _ => return ptr::null_mut(),
+    };
+    #[cfg(feature = "faults")]
+    if crate::faults::fail_allocations() {
+        return ptr::null_mut();
+    }
+    match Layout::array::<u8>(len) {
+        Ok(layout) => unsafe { alloc_zeroed(layout) },
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Resize a buffer from `old_len` to `new_len` bytes, zero‑filling any growth.
+/// A `NULL` `ptr_` behaves like [`raptorq_alloc`]; a `new_len` of `0` frees the
+/// buffer and returns `NULL`.  On failure `NULL` is returned and the original
+/// buffer is left untouched.
+///
+/// # Safety
+/// `ptr_` must be `NULL` or a live buffer from this library of exactly
+/// `old_len` bytes; on success it must not be used afterwards.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_realloc(ptr_: *mut u8, old_len: u64, new_len: u64) -> *mut u8 {
+    if ptr_.is_null() || old_len == 0 {
+        return raptorq_alloc(new_len);
+    }
+    if new_len == 0 {
+        raptorq_dealloc(ptr_, old_len);
+        return ptr::null_mut();
+    }
+    let (old_len, new_len) = match (checked_len(old_len), checked_len(new_len)) {
+        (Some(old_len), Some(new_len)) if Layout::array::<u8>(new_len).is_ok() => {
+            (old_len, new_len)
+        }
+        _ => return ptr::null_mut(),
+    };
+    #[cfg(feature = "faults")]
+    if crate::faults::fail_allocations() {
+        return ptr::null_mut();
+    }
+    let grown = realloc(ptr_, Layout::array::<u8>(old_len).unwrap(), new_len);
+    if !grown.is_null() && new_len > old_len {
+        ptr::write_bytes(grown.add(old_len), 0, new_len - old_len);
+    }
+    grown
+}
+
+/// Release any buffer allocated by this library, whichever function returned
+/// it.  `NULL` is ignored.
+///
+/// # Safety
+/// `ptr_` must be `NULL` or a live buffer from this library and `len` its
+/// exact length.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_dealloc(ptr_: *mut u8, len: u64) {
+    let len = match checked_len(len) {
+        Some(len) if len > 0 && !ptr_.is_null() => len,
+        _ => return,
+    };
+    let _ = try_catch_unwind(|| drop(Box::from_raw(ptr::slice_from_raw_parts_mut(ptr_, len))));
+}
+
+/// Overwrite `len` bytes at `ptr` with zeros in a way the optimizer cannot
+/// drop as a dead store ahead of the deallocation.
+unsafe fn wipe_raw(ptr: *mut u8, len: usize) {
+    for i in 0..len {
+        ptr::write_volatile(ptr.add(i), 0);
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Zero the whole allocation of `buf`, spare capacity included (it may hold
+/// bytes from before a `clear` or `truncate`), and empty it.
+#[cfg(feature = "decoder")]
+pub(crate) fn wipe(buf: &mut Vec<u8>) {
+    unsafe { wipe_raw(buf.as_mut_ptr(), buf.capacity()) };
+    buf.clear();
+}
+
+/// Make room for `additional` more bytes, wiping the old allocation instead
+/// of leaving a copy of its contents behind when it has to move.
+#[cfg(feature = "decoder")]
+pub(crate) fn reserve_wiping(buf: &mut Vec<u8>, additional: usize) {
+    if buf.capacity() - buf.len() >= additional {
+        return;
+    }
+    let mut grown = Vec::with_capacity((buf.len() + additional).max(buf.capacity() * 2));
+    grown.extend_from_slice(buf);
+    wipe(buf);
+    *buf = grown;
+}
+
+/// Pin `len` bytes at `ptr` in RAM so they are never written to swap.
+/// `false` where the OS refuses (e.g. beyond `RLIMIT_MEMLOCK`) or has no
+/// `mlock`; the memory stays usable either way.
+#[cfg(feature = "decoder")]
+pub(crate) unsafe fn lock_raw(ptr: *const u8, len: usize) -> bool {
+    #[cfg(unix)]
+    return len > 0 && libc::mlock(ptr.cast(), len) == 0;
+    #[cfg(not(unix))]
+    {
+        let _ = (ptr, len);
+        false
+    }
+}
+
+/// Undo [`lock_raw`]; harmless on memory that was never locked.
+pub(crate) unsafe fn unlock_raw(ptr: *const u8, len: usize) {
+    #[cfg(unix)]
+    if len > 0 {
+        libc::munlock(ptr.cast(), len);
+    }
+    #[cfg(not(unix))]
+    let _ = (ptr, len);
+}
+
+/// Like [`raptorq_dealloc`], zeroing the buffer first (and unlocking it if
+/// it is a result pinned by `raptorq_ctx_set_mlock_result`).
+///
+/// # Safety
+/// Same as [`raptorq_dealloc`].
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_free_zeroize(ptr_: *mut u8, len: u64) {
+    if let Some(len) = checked_len(len).filter(|_| !ptr_.is_null()) {
+        wipe_raw(ptr_, len);
+        unlock_raw(ptr_, len);
+    }
+    raptorq_dealloc(ptr_, len);
+}
+
+/// A buffer handed out by the library together with its size.  `ptr` is
+/// `NULL` (and both sizes `0`) when there is no data.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RQBuffer {
+    /// First byte of the data.
+    pub ptr: *mut u8,
+    /// Number of bytes of data.
+    pub len: u64,
+    /// Number of bytes allocated; equal to `len` for every buffer returned
+    /// today, but release through [`raptorq_buffer_free`] rather than rely
+    /// on it.
+    pub cap: u64,
+}
+
+impl RQBuffer {
+    /// Wrap a buffer of exactly `len` bytes returned by another function.
+    #[cfg(any(feature = "encoder", feature = "decoder", test))]
+    pub(crate) fn from_raw(ptr: *mut u8, len: u64) -> RQBuffer {
+        if ptr.is_null() {
+            return RQBuffer {
+                ptr,
+                len: 0,
+                cap: 0,
+            };
+        }
+        RQBuffer { ptr, len, cap: len }
+    }
+}
+
+/// Release a buffer returned in an [`RQBuffer`].  A `NULL` buffer is ignored.
+///
+/// # Safety
+/// `buf` must be exactly as returned by this library and not released yet.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_buffer_free(buf: RQBuffer) {
+    raptorq_dealloc(buf.ptr, buf.cap);
+}
+
+/// Like [`raptorq_buffer_free`], zeroing the buffer first.
+///
+/// # Safety
+/// Same as [`raptorq_buffer_free`].
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_buffer_free_zeroize(buf: RQBuffer) {
+    raptorq_free_zeroize(buf.ptr, buf.cap);
+}
+
+/// Move `buf` behind a pointer, for bindings that release memory through a
+/// single pointer argument, such as a Dart `NativeFinalizer` or the
+/// finalizer of `Pointer.asTypedList`: read the bytes through the returned
+/// [`RQBuffer`] and release both with [`raptorq_buffer_box_free`].  `NULL`
+/// for a `NULL` buffer.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_buffer_into_box(buf: RQBuffer) -> *mut RQBuffer {
+    if buf.ptr.is_null() {
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new(buf))
+}
+
+/// Release a buffer boxed by [`raptorq_buffer_into_box`].  `NULL` is ignored.
+///
+/// # Safety
+/// `buf` must be `NULL` or a box from [`raptorq_buffer_into_box`] not
+/// released yet.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_buffer_box_free(buf: *mut RQBuffer) {
+    if !buf.is_null() {
+        raptorq_buffer_free(*Box::from_raw(buf));
+    }
+}
+
+/// Like [`raptorq_buffer_box_free`], zeroing the buffer first.
+///
+/// # Safety
+/// Same as [`raptorq_buffer_box_free`].
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_buffer_box_free_zeroize(buf: *mut RQBuffer) {
+    if !buf.is_null() {
+        raptorq_buffer_free_zeroize(*Box::from_raw(buf));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::slice;
+
+    #[test]
+    fn alloc_realloc_dealloc() {
+        let buf = raptorq_alloc(4);
+        unsafe { slice::from_raw_parts_mut(buf, 4) }.copy_from_slice(&[1, 2, 3, 4]);
+        let buf = unsafe { raptorq_realloc(buf, 4, 8) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(buf, 8) },
+            &[1, 2, 3, 4, 0, 0, 0, 0]
+        );
+        let buf = unsafe { raptorq_realloc(buf, 8, 2) };
+        assert_eq!(unsafe { slice::from_raw_parts(buf, 2) }, &[1, 2]);
+        // Buffers from either direction can be turned back into Rust values.
+        let owned = Vec::from(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(buf, 2)) });
+        assert_eq!(owned, vec![1, 2]);
+        assert!(raptorq_alloc(0).is_null());
+        unsafe { raptorq_dealloc(ptr::null_mut(), 3) };
+    }
+
+    #[test]
+    fn boxed_buffer() {
+        let boxed = raptorq_buffer_into_box(RQBuffer::from_raw(raptorq_alloc(4), 4));
+        assert_eq!(unsafe { (*boxed).len }, 4);
+        unsafe { raptorq_buffer_box_free(boxed) };
+        assert!(raptorq_buffer_into_box(RQBuffer::from_raw(ptr::null_mut(), 0)).is_null());
+        unsafe { raptorq_buffer_box_free_zeroize(ptr::null_mut()) };
+    }
+
+    #[cfg(feature = "decoder")]
+    #[test]
+    fn wipe_covers_spare_capacity() {
+        let mut buf = vec![7u8; 16];
+        buf.truncate(4);
+        wipe(&mut buf);
+        assert!(buf.is_empty() && buf.capacity() >= 16);
+        assert!(unsafe { slice::from_raw_parts(buf.as_ptr(), 16) }
+            .iter()
+            .all(|b| *b == 0));
+        buf.extend_from_slice(&[1, 2, 3]);
+        reserve_wiping(&mut buf, 100);
+        assert_eq!(buf, [1, 2, 3]);
+        assert!(buf.capacity() >= 103);
+    }
+}
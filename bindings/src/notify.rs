@@ -0,0 +1,108 @@
+//! Per-context callbacks telling the app how a transfer goes as it happens,
+//! so it does not have to poll from a timer while pushes run on a
+//! background queue.
+
+use core::ffi::c_void;
+
+use alloc::vec::Vec;
+
+use crate::memory::RQBuffer;
+
+/// Called once when the object is recovered, with the result as
+/// `raptorq_ctx_take_result_buffer` would have returned it (the callee owns
+/// it now; a `NULL` buffer when the object was assembled in an output file)
+/// and the `user_data` given at registration.  It runs on the thread whose
+/// push or finalize step completed the transfer, or on the decode thread,
+/// while that call still holds the context: it must not call back into it.
+pub type RQCompleteCallback =
+    Option<unsafe extern "C" fn(result: RQBuffer, user_data: *mut c_void)>;
+
+/// Called with the distinct symbols received so far (at most `needed` are
+/// counted) and the number of source symbols of the object, whenever a push
+/// adds a symbol the context did not have, and with the `user_data` given at
+/// registration.  Runs like an [`RQCompleteCallback`]: on the pushing or
+/// decode thread, and must not call back into the context.
+pub type RQProgressCallback =
+    Option<unsafe extern "C" fn(received: u32, needed: u32, user_data: *mut c_void)>;
+
+/// Called with each milestone registered through
+/// `raptorq_ctx_set_milestones` (a percentage of the needed symbols), once,
+/// in ascending order, when the received symbols reach it; several may be
+/// passed by one push.  Runs like an [`RQProgressCallback`].
+pub type RQMilestoneCallback = Option<unsafe extern "C" fn(percent: u8, user_data: *mut c_void)>;
+
+/// The callbacks registered on one context.  Not carried over to clones or
+/// recycled contexts.
+#[derive(Default)]
+pub(crate) struct Notifier {
+    on_complete: Option<(unsafe extern "C" fn(RQBuffer, *mut c_void), usize)>,
+    on_progress: Option<(unsafe extern "C" fn(u32, u32, *mut c_void), usize)>,
+    on_milestone: Option<(unsafe extern "C" fn(u8, *mut c_void), usize)>,
+    milestones: Vec<u8>, // ascending, without duplicates
+    passed: usize,       // milestones already reported
+    reported: u32,       // `received` at the last report
+}
+
+impl Notifier {
+    pub fn set_on_complete(&mut self, callback: RQCompleteCallback, user_data: usize) {
+        self.on_complete = callback.map(|f| (f, user_data));
+    }
+
+    pub fn set_on_progress(&mut self, callback: RQProgressCallback, user_data: usize) {
+        self.on_progress = callback.map(|f| (f, user_data));
+    }
+
+    pub fn set_milestones(
+        &mut self,
+        mut percents: Vec<u8>,
+        callback: RQMilestoneCallback,
+        user_data: usize,
+    ) {
+        percents.sort_unstable();
+        percents.dedup();
+        self.milestones = percents;
+        self.passed = 0;
+        self.on_milestone = callback.map(|f| (f, user_data));
+    }
+
+    /// Whether [`Notifier::symbols`] would tell anyone.
+    pub fn wants_symbols(&self) -> bool {
+        self.on_progress.is_some() || self.on_milestone.is_some()
+    }
+
+    /// Report the symbol counts if `received` changed since the last report,
+    /// and every milestone they reach.
+    pub fn symbols(&mut self, received: u32, needed: u32) {
+        if received == self.reported {
+            return;
+        }
+        self.reported = received;
+        if let Some((f, user_data)) = self.on_progress {
+            unsafe { f(received, needed, user_data as *mut c_void) };
+        }
+        if let Some((f, user_data)) = self.on_milestone {
+            let percent = (u64::from(received) * 100)
+                .checked_div(u64::from(needed))
+                .unwrap_or(0);
+            while let Some(&milestone) = self.milestones.get(self.passed) {
+                if u64::from(milestone) > percent {
+                    break;
+                }
+                self.passed += 1;
+                unsafe { f(milestone, user_data as *mut c_void) };
+            }
+        }
+    }
+
+    /// Whether [`Notifier::complete`] would hand the result to someone.
+    pub fn wants_result(&self) -> bool {
+        self.on_complete.is_some()
+    }
+
+    /// Hand `result` to the completion callback and forget it.
+    pub fn complete(&mut self, result: RQBuffer) {
+        if let Some((f, user_data)) = self.on_complete.take() {
+            unsafe { f(result, user_data as *mut c_void) };
+        }
+    }
+}
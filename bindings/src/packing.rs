@@ -0,0 +1,80 @@
+//! Packed frames: several encoding packets in one QR payload.
+//!
+//! ```text
+//! [len: u16 BE][packet] [len: u16 BE][packet] ...
+//! ```
+//!
+//! Large QR versions have room for two or three symbols, so packing them
+//! divides the number of animation frames accordingly.  A zero length marks
+//! the end of the packets; everything after it must be zero padding.
+
+#[cfg(feature = "decoder")]
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Group serialized packets into frames of at most `per_frame` packets each.
+#[cfg(feature = "encoder")]
+pub(crate) fn pack(packets: &[Vec<u8>], per_frame: usize) -> Vec<Vec<u8>> {
+    packets
+        .chunks(per_frame.max(1))
+        .map(|chunk| {
+            let mut frame = Vec::with_capacity(chunk.iter().map(|p| p.len() + 2).sum());
+            for packet in chunk {
+                frame.extend_from_slice(&(packet.len() as u16).to_be_bytes());
+                frame.extend_from_slice(packet);
+            }
+            frame
+        })
+        .collect()
+}
+
+/// Split a packed frame back into its packets.  Returns the packets and
+/// whether trailing zero padding was found, or `None` if the framing is
+/// broken (or padded while `allow_padding` is off).
+#[cfg(feature = "decoder")]
+pub(crate) fn split(mut frame: &[u8], allow_padding: bool) -> Option<(Vec<&[u8]>, bool)> {
+    let mut packets = vec![];
+    while !frame.is_empty() {
+        if frame.len() < 2 {
+            return None;
+        }
+        let len = usize::from(u16::from_be_bytes([frame[0], frame[1]]));
+        if len == 0 {
+            if allow_padding && frame.iter().all(|b| *b == 0) {
+                return Some((packets, true));
+            }
+            return None;
+        }
+        let packet = frame.get(2..2 + len)?;
+        packets.push(packet);
+        frame = &frame[2 + len..];
+    }
+    if packets.is_empty() {
+        return None;
+    }
+    Some((packets, false))
+}
+
+#[cfg(all(test, feature = "encoder", feature = "decoder"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_split_roundtrip() {
+        let packets = vec![vec![1u8, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+        let frames = pack(&packets, 2);
+        assert_eq!(frames.len(), 2);
+        let (first, padded) = split(&frames[0], false).unwrap();
+        assert_eq!(first, vec![&[1u8, 2, 3][..], &[4, 5, 6][..]]);
+        assert!(!padded);
+
+        let mut padded_frame = frames[1].clone();
+        padded_frame.resize(16, 0);
+        assert_eq!(split(&padded_frame, false), None);
+        assert_eq!(
+            split(&padded_frame, true),
+            Some((vec![&[7u8, 8, 9][..]], true))
+        );
+        assert_eq!(split(&frames[0][..4], false), None);
+    }
+}
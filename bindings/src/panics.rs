@@ -0,0 +1,153 @@
+//! Forwarding of caught panics to the app.
+//!
+//! Every entry point catches panics so none unwinds into Swift, but a caught
+//! panic used to surface as a bare `NULL`/`false`/[`crate::RQStatus::Failed`]
+//! with nothing to put in a bug report.  The panic message is now kept as the
+//! calling thread's last error ([`raptorq_last_error`]) and handed to the
+//! callback registered with [`raptorq_set_panic_callback`], if any.  Calls
+//! rejecting their arguments leave the reason there too, and rejected
+//! frames their reason and detail ([`raptorq_last_error_info`]).
+
+use core::ffi::{c_char, c_void};
+use core::ptr;
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::sync::Mutex;
+
+use crate::status::{RQErrorInfo, RQStatus};
+
+/// Called with the NUL‑terminated panic message (valid only for the duration
+/// of the call) and the `user_data` given at registration.  It may run on any
+/// thread that calls into the library, including the decode thread, and must
+/// not call back into the library.
+pub type RQPanicCallback =
+    Option<unsafe extern "C" fn(message: *const c_char, user_data: *mut c_void)>;
+
+struct Callback {
+    f: RQPanicCallback,
+    user_data: usize,
+}
+
+static CALLBACK: Mutex<Callback> = Mutex::new(Callback {
+    f: None,
+    user_data: 0,
+});
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<(CString, RQErrorInfo)>> = const { RefCell::new(None) };
+}
+
+fn message(payload: &(dyn Any + Send)) -> CString {
+    let text = if let Some(text) = payload.downcast_ref::<&str>() {
+        text
+    } else if let Some(text) = payload.downcast_ref::<String>() {
+        text.as_str()
+    } else {
+        "panic with a non-string payload"
+    };
+    CString::new(text.replace('\0', "")).unwrap_or_default()
+}
+
+/// Record a caught panic and forward it to the registered callback.
+pub(crate) fn report(payload: Box<dyn Any + Send>) {
+    let message = message(&*payload);
+    let (f, user_data) = match CALLBACK.lock() {
+        Ok(callback) => (callback.f, callback.user_data),
+        Err(_) => (None, 0),
+    };
+    if let Some(f) = f {
+        unsafe { f(message.as_ptr(), user_data as *mut c_void) };
+    }
+    let info = RQErrorInfo::new(RQStatus::Failed);
+    let _ = LAST_ERROR.try_with(|last| *last.borrow_mut() = Some((message, info)));
+}
+
+/// Make `message`, with its detail, the calling thread's last error.
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+pub(crate) fn set_last_error(message: &'static str, info: RQErrorInfo) {
+    let message = CString::new(message).unwrap_or_default();
+    let _ = LAST_ERROR.try_with(|last| *last.borrow_mut() = Some((message, info)));
+}
+
+/// Register `callback` (or clear it with `NULL`) to be told about every panic
+/// caught at the FFI boundary.  Replaces any earlier registration.
+///
+/// # Safety
+/// `callback` must stay callable, and `user_data` valid for it, until it is
+/// replaced.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_set_panic_callback(
+    callback: RQPanicCallback,
+    user_data: *mut c_void,
+) {
+    let mut registered = CALLBACK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    registered.f = callback;
+    registered.user_data = user_data as usize;
+}
+
+/// The message of the last panic caught, or the reason for the last argument
+/// rejected, on the calling thread; `NULL` if there was none since the last
+/// [`raptorq_clear_last_error`].  The string is owned by the library and
+/// valid until the next error or clear on this thread; copy it out.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_last_error() -> *const c_char {
+    LAST_ERROR
+        .try_with(|last| {
+            last.borrow()
+                .as_ref()
+                .map_or(ptr::null(), |(m, _)| m.as_ptr())
+        })
+        .unwrap_or(ptr::null())
+}
+
+/// Copy the detail of the calling thread's last error (see
+/// [`raptorq_last_error`]) to `info_out`: the status reported and, for a
+/// rejected frame, the offending symbol, the expected and actual lengths or
+/// the offset where parsing failed.  Returns `false`, leaving `info_out`
+/// untouched, if there was no error or `info_out` is `NULL`.
+///
+/// # Safety
+/// `info_out` must be `NULL` or valid for writing an [`RQErrorInfo`].
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_last_error_info(info_out: *mut RQErrorInfo) -> bool {
+    let info = LAST_ERROR
+        .try_with(|last| last.borrow().as_ref().map(|(_, info)| *info))
+        .ok()
+        .flatten();
+    match (info, info_out.is_null()) {
+        (Some(info), false) => {
+            *info_out = info;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Forget the calling thread's last error.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_clear_last_error() {
+    let _ = LAST_ERROR.try_with(|last| *last.borrow_mut() = None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn keeps_the_message() {
+        raptorq_clear_last_error();
+        assert!(raptorq_last_error().is_null());
+        report(Box::new(String::from("index out of bounds")));
+        let last = unsafe { CStr::from_ptr(raptorq_last_error()) };
+        assert_eq!(last.to_str(), Ok("index out of bounds"));
+        report(Box::new(7u32));
+        let last = unsafe { CStr::from_ptr(raptorq_last_error()) };
+        assert_eq!(last.to_str(), Ok("panic with a non-string payload"));
+        raptorq_clear_last_error();
+        assert!(raptorq_last_error().is_null());
+    }
+}
@@ -0,0 +1,113 @@
+//! Pool of decoding contexts reused across consecutive transfers.
+//!
+//! A scanning session that receives many payloads back to back checks a
+//! context out per transfer and returns it afterwards; the context's block
+//! arenas survive the round trip, so the next transfer does not have to
+//! allocate (and fragment) them again.
+
+use core::ptr;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use raptorq::ObjectTransmissionInformation;
+
+use crate::context::RQContext;
+use crate::{check_ctx, check_transfer, invalid_argument, try_catch_unwind};
+
+pub struct RQPool {
+    idle: Vec<RQContext>,
+    max_idle: usize,
+}
+
+/// Create a pool keeping at most `max_idle` returned contexts around.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_pool_new(max_idle: u32) -> *mut RQPool {
+    try_catch_unwind(|| {
+        Box::into_raw(Box::new(RQPool {
+            idle: Vec::with_capacity(max_idle as usize),
+            max_idle: max_idle as usize,
+        }))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Check out a context for a transfer of `transfer_length` bytes, like
+/// `raptorq_ctx_new`, reusing an idle one when available.  Give it back with
+/// [`raptorq_pool_return`] (or destroy it with `raptorq_ctx_free`).
+///
+/// # Safety
+/// `pool` must be `NULL` or a live pool.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_pool_checkout(
+    pool: *mut RQPool,
+    transfer_length: u64,
+    max_payload_size: u16,
+) -> *mut RQContext {
+    if pool.is_null() {
+        invalid_argument("pool is NULL");
+        return ptr::null_mut();
+    }
+    if check_transfer(transfer_length, max_payload_size).is_err() {
+        return ptr::null_mut();
+    }
+    let pool = &mut *pool;
+    try_catch_unwind(|| {
+        let oti = ObjectTransmissionInformation::with_defaults(transfer_length, max_payload_size);
+        let mut ctx = match pool.idle.pop() {
+            Some(idle) => idle.recycle(oti),
+            None => RQContext::new(oti),
+        };
+        ctx.pooled = true;
+        Box::into_raw(Box::new(ctx))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Return a context to the pool.  It is freed instead if the pool already
+/// holds `max_idle` contexts.  A context that was retained is only given
+/// back once its last reference is returned (or released).
+///
+/// # Safety
+/// `pool` must be `NULL` or a live pool; `ctx` must be `NULL` or a live
+/// context, which must not be used afterwards through this reference.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_pool_return(pool: *mut RQPool, ctx: *mut RQContext) {
+    if ctx.is_null() || check_ctx(ctx).is_err() || !(*ctx).release() {
+        return;
+    }
+    let ctx = RQContext::take_raw(ctx);
+    if pool.is_null() {
+        return;
+    }
+    let pool = &mut *pool;
+    if pool.idle.len() < pool.max_idle {
+        pool.idle.push(ctx);
+    }
+}
+
+/// Number of idle contexts currently held by the pool.
+///
+/// # Safety
+/// `pool` must be `NULL` or a live pool.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_pool_idle_count(pool: *const RQPool) -> u32 {
+    if pool.is_null() {
+        invalid_argument("pool is NULL");
+        return 0;
+    }
+    (*pool).idle.len() as u32
+}
+
+/// Destroy the pool and every idle context in it.  Contexts still checked out
+/// stay valid and must be freed with `raptorq_ctx_free`.
+///
+/// # Safety
+/// `pool` must be `NULL` or a live pool; it must not be used afterwards.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_pool_free(pool: *mut RQPool) {
+    if pool.is_null() {
+        return;
+    }
+    drop(Box::from_raw(pool));
+}
@@ -0,0 +1,173 @@
+//! Built‑in compatibility presets for the QR profiles used across the wallet
+//! ecosystem.
+//!
+//! A profile pins every parameter the two sides of a transfer must agree on
+//! but that is not carried in the packets themselves: the maximum payload per
+//! frame (and therefore the symbol size), the per‑frame prefix and the
+//! envelope the packet is wrapped in.
+
+/// Identifier accepted by `raptorq_ctx_new_with_profile`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RQProfile {
+    /// Parity Signer / Polkadot Vault multipart frames: a 4‑byte big endian
+    /// header `0x80000000 | transfer_length` in front of every packet.
+    VaultClassic = 0,
+    /// Nova Wallet: bare packets, preceded by an optional handshake frame.
+    NovaCurrent = 1,
+    /// BC‑UR transport: every packet wrapped in a CBOR byte string, as left
+    /// over once the app has stripped the `ur:` text and bytewords layer.
+    BcUr = 2,
+}
+
+impl RQProfile {
+    pub(crate) fn from_id(id: u32) -> Option<RQProfile> {
+        match id {
+            0 => Some(RQProfile::VaultClassic),
+            1 => Some(RQProfile::NovaCurrent),
+            2 => Some(RQProfile::BcUr),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn max_payload_size(self) -> u16 {
+        match self {
+            RQProfile::VaultClassic | RQProfile::NovaCurrent => 1072,
+            RQProfile::BcUr => 200,
+        }
+    }
+
+    pub(crate) fn prefix(self) -> FramePrefix {
+        match self {
+            RQProfile::VaultClassic => FramePrefix::VaultLength,
+            RQProfile::NovaCurrent | RQProfile::BcUr => FramePrefix::None,
+        }
+    }
+
+    pub(crate) fn envelope(self) -> Envelope {
+        match self {
+            RQProfile::VaultClassic | RQProfile::NovaCurrent => Envelope::None,
+            RQProfile::BcUr => Envelope::CborBytes,
+        }
+    }
+}
+
+/// Header preceding the encoding packet inside a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FramePrefix {
+    None,
+    /// `u32` big endian with the top bit set; the rest is the transfer length.
+    VaultLength,
+}
+
+/// Container the prefixed packet is carried in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Envelope {
+    None,
+    /// A single CBOR major type 2 (byte string) item.
+    CborBytes,
+}
+
+impl FramePrefix {
+    pub fn id(self) -> u8 {
+        match self {
+            FramePrefix::None => 0,
+            FramePrefix::VaultLength => 1,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<FramePrefix> {
+        match id {
+            0 => Some(FramePrefix::None),
+            1 => Some(FramePrefix::VaultLength),
+            _ => None,
+        }
+    }
+
+    /// Strip the prefix, checking it against the expected transfer length.
+    pub fn strip(self, frame: &[u8], transfer_length: u64) -> Option<&[u8]> {
+        match self {
+            FramePrefix::None => Some(frame),
+            FramePrefix::VaultLength => {
+                if frame.len() < 4 || frame[0] & 0x80 == 0 {
+                    return None;
+                }
+                let header = u32::from_be_bytes([frame[0], frame[1], frame[2], frame[3]]);
+                if u64::from(header & 0x7fff_ffff) != transfer_length {
+                    return None;
+                }
+                Some(&frame[4..])
+            }
+        }
+    }
+}
+
+impl Envelope {
+    pub fn id(self) -> u8 {
+        match self {
+            Envelope::None => 0,
+            Envelope::CborBytes => 1,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Envelope> {
+        match id {
+            0 => Some(Envelope::None),
+            1 => Some(Envelope::CborBytes),
+            _ => None,
+        }
+    }
+
+    /// Unwrap the envelope; `None` if `frame` is not a well formed one.
+    pub fn open(self, frame: &[u8]) -> Option<&[u8]> {
+        match self {
+            Envelope::None => Some(frame),
+            Envelope::CborBytes => {
+                let (&head, rest) = frame.split_first()?;
+                if head >> 5 != 2 {
+                    return None;
+                }
+                let (len, rest) = match head & 0x1f {
+                    n @ 0..=23 => (usize::from(n), rest),
+                    24 => (usize::from(*rest.first()?), rest.get(1..)?),
+                    25 => {
+                        let b = rest.get(..2)?;
+                        (usize::from(u16::from_be_bytes([b[0], b[1]])), &rest[2..])
+                    }
+                    _ => return None,
+                };
+                if rest.len() != len {
+                    return None;
+                }
+                Some(rest)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vault_prefix() {
+        let frame = [0x80, 0x00, 0x01, 0x00, 0xaa];
+        assert_eq!(
+            FramePrefix::VaultLength.strip(&frame, 256),
+            Some(&[0xaa][..])
+        );
+        assert_eq!(FramePrefix::VaultLength.strip(&frame, 255), None);
+        assert_eq!(FramePrefix::VaultLength.strip(&frame[1..], 256), None);
+    }
+
+    #[test]
+    fn cbor_envelope() {
+        assert_eq!(Envelope::CborBytes.open(&[0x42, 1, 2]), Some(&[1, 2][..]));
+        assert_eq!(
+            Envelope::CborBytes.open(&[0x58, 2, 1, 2]),
+            Some(&[1, 2][..])
+        );
+        assert_eq!(Envelope::CborBytes.open(&[0x43, 1, 2]), None);
+        assert_eq!(Envelope::CborBytes.open(&[0x62, 1, 2]), None);
+    }
+}
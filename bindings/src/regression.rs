@@ -0,0 +1,221 @@
+//! Embedded regression vectors: a snapshot of this library's own output.
+//!
+//! Each vector describes a deterministic input object together with the
+//! encoder parameters used to produce it.  The expected digest is the FNV‑1a
+//! hash of the serialized OTI followed by every serialized packet, recorded
+//! from a desktop build of this crate.  These are not the RFC 6330 test
+//! vectors (the RFC publishes none for whole objects), so passing says that a
+//! build encodes bit‑for‑bit like the one the digests came from, whichever
+//! GF(256) kernels its CPU selects, and decodes its own output after heavy
+//! packet loss; it does not prove conformance to the RFC.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use raptorq::{Decoder, EncoderBuilder, EncodingPacket};
+
+use crate::status::RQStatus;
+
+/// Outcome of the first failing vector, if any.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RQRegressionFailure {
+    /// Every vector passed.
+    None = 0,
+    /// The encoded packet stream differs from the recorded digest.
+    EncodeMismatch = 1,
+    /// The decoder failed to recover the object or recovered wrong bytes.
+    DecodeMismatch = 2,
+    /// The underlying implementation panicked while running the vector.
+    Panic = 3,
+}
+
+/// Summary returned by `raptorq_run_regression_vectors`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RQRegressionReport {
+    /// Number of embedded vectors.
+    pub total: u32,
+    /// Number of vectors that passed both the encode and the decode check.
+    pub passed: u32,
+    /// Index of the first failing vector, or `-1` when all passed.
+    pub first_failed_vector: i32,
+    /// Why the first failing vector failed.
+    pub first_failure: RQRegressionFailure,
+}
+
+pub(crate) struct Vector {
+    pub name: &'static [u8],
+    length: usize,
+    max_packet_size: u16,
+    decoder_memory: u64,
+    repair_packets_per_block: u32,
+    seed: u32,
+    digest: u64,
+}
+
+pub(crate) const VECTORS: &[Vector] = &[
+    Vector {
+        name: b"tiny-single-symbol\0",
+        length: 15,
+        max_packet_size: 64,
+        decoder_memory: 10 * 1024 * 1024,
+        repair_packets_per_block: 2,
+        seed: 0x0000_0001,
+        digest: 0xf6ca_c900_14da_059f,
+    },
+    Vector {
+        name: b"unaligned-small-symbols\0",
+        length: 1_000,
+        max_packet_size: 32,
+        decoder_memory: 10 * 1024 * 1024,
+        repair_packets_per_block: 8,
+        seed: 0x00c0_ffee,
+        digest: 0x40a1_ce6f_6564_d0b1,
+    },
+    Vector {
+        name: b"vault-frame-size\0",
+        length: 10_000,
+        max_packet_size: 1_072,
+        decoder_memory: 10 * 1024 * 1024,
+        repair_packets_per_block: 4,
+        seed: 0xdead_beef,
+        digest: 0x1c99_d3bb_ce27_e139,
+    },
+    Vector {
+        name: b"multi-block-sub-blocked\0",
+        length: 50_000,
+        max_packet_size: 256,
+        decoder_memory: 8 * 1024,
+        repair_packets_per_block: 6,
+        seed: 0x1234_5678,
+        digest: 0xecb0_dc7b_0738_4f9a,
+    },
+    Vector {
+        name: b"large-mtu\0",
+        length: 100_000,
+        max_packet_size: 1_400,
+        decoder_memory: 10 * 1024 * 1024,
+        repair_packets_per_block: 12,
+        seed: 0x0bad_f00d,
+        digest: 0x84cf_2496_7893_86b3,
+    },
+];
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for b in bytes {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Deterministic xorshift32 byte stream so vectors need no embedded payloads.
+fn generate(seed: u32, length: usize) -> Vec<u8> {
+    let mut state = seed.max(1);
+    (0..length)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+impl Vector {
+    pub(crate) fn run(&self) -> RQRegressionFailure {
+        let data = generate(self.seed, self.length);
+        let mut builder = EncoderBuilder::new();
+        builder.set_max_packet_size(self.max_packet_size);
+        builder.set_decoder_memory_requirement(self.decoder_memory);
+        let encoder = builder.build(&data);
+        let oti = encoder.get_config();
+
+        let mut digest = fnv1a(FNV_OFFSET, &oti.serialize());
+        for packet in encoder.get_encoded_packets(self.repair_packets_per_block) {
+            digest = fnv1a(digest, &packet.serialize());
+        }
+        if digest != self.digest {
+            return RQRegressionFailure::EncodeMismatch;
+        }
+
+        // Drop every other source symbol so the repair path has to do real
+        // work instead of hitting the systematic shortcut.
+        let mut lossy: Vec<EncodingPacket> = vec![];
+        for block in encoder.get_block_encoders() {
+            let source = block.source_packets();
+            let count = source.len() as u32;
+            lossy.extend(source.into_iter().skip(1).step_by(2));
+            lossy.extend(block.repair_packets(0, count));
+        }
+
+        let mut decoder = Decoder::new(oti);
+        for packet in lossy {
+            if let Some(out) = decoder.decode(packet) {
+                return if out == data {
+                    RQRegressionFailure::None
+                } else {
+                    RQRegressionFailure::DecodeMismatch
+                };
+            }
+        }
+        RQRegressionFailure::DecodeMismatch
+    }
+}
+
+/// Round trip behind `raptorq_self_test`: an object encoded into frames,
+/// its first source frames lost so the repair symbols have to be solved for
+/// (on the GF(256) kernels the device selects), then decoded from the
+/// handshake through a context like a scan.  `Err` names the step that
+/// went wrong.
+pub(crate) fn self_test() -> Result<(), &'static str> {
+    let data = generate(0x5e1f_7e57, 16 * 1024);
+    let enc = unsafe { crate::raptorq_enc_new(data.as_ptr(), data.len() as u64, 512, 16) };
+    if enc.is_null() {
+        return Err("self-test: cannot encode");
+    }
+    let handshake = unsafe { crate::raptorq_enc_handshake_frame_buffer(enc, 0) };
+    let frames = unsafe { (*enc).frames.clone() };
+    unsafe { crate::raptorq_enc_free(enc) };
+    let ctx = unsafe { crate::raptorq_ctx_from_handshake(handshake.ptr, handshake.len) };
+    unsafe { crate::raptorq_buffer_free(handshake) };
+    if ctx.is_null() {
+        return Err("self-test: handshake rejected");
+    }
+    let mut status = RQStatus::Ok;
+    for frame in frames.iter().skip(8) {
+        status = unsafe {
+            crate::raptorq_ctx_push_frame_status(ctx, frame.as_ptr(), frame.len() as u64)
+        };
+        if status != RQStatus::Ok {
+            break;
+        }
+    }
+    let result = unsafe { crate::raptorq_ctx_take_result_buffer(ctx) };
+    let recovered = !result.ptr.is_null()
+        && unsafe { core::slice::from_raw_parts(result.ptr, result.len as usize) } == &data[..];
+    unsafe {
+        crate::raptorq_buffer_free(result);
+        crate::raptorq_ctx_free(ctx);
+    }
+    match (status, recovered) {
+        (RQStatus::Complete, true) => Ok(()),
+        (RQStatus::Complete, false) => Err("self-test: recovered wrong bytes"),
+        _ => Err("self-test: decode did not complete"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vectors_match_snapshot() {
+        for vector in VECTORS {
+            assert_eq!(vector.run(), RQRegressionFailure::None);
+        }
+    }
+}
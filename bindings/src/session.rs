@@ -0,0 +1,220 @@
+//! Scan sessions: a context plus the throughput of the frames pushed into
+//! it, for "12 new frames/s" style feedback in the scanner UI.
+//!
+//! A frame counts as unique when it gave the decoder at least one symbol it
+//! did not have; the animation loop repeating, or the camera holding still
+//! on one code, shows up as frames without unique ones.
+
+use core::ptr;
+use core::time::Duration;
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+
+use crate::context::RQContext;
+use crate::decoder::{push_timed, with_ctx};
+use crate::stats::Stopwatch;
+use crate::status::RQStatus;
+use crate::{check_ctx, invalid_argument, slice_from_raw, try_catch_unwind};
+
+/// Span the per-second rates of [`RQScanStats`] are taken over.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Snapshot returned by `raptorq_session_stats`.  Without a clock (no
+/// `std`, or in the browser) `runtime_ms` and both rates stay `0`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RQScanStats {
+    /// Frames pushed through the session, whatever their status.
+    pub frames: u64,
+    /// Frames that gave the decoder a symbol it did not have.
+    pub unique_frames: u64,
+    /// Wall-clock time since the session was created.
+    pub runtime_ms: u64,
+    /// Frames pushed over the last second, or over the runtime while it is
+    /// shorter.
+    pub frames_per_second: f32,
+    /// Unique frames over the same span.
+    pub unique_frames_per_second: f32,
+}
+
+/// Opaque handle from [`raptorq_session_new`].
+pub struct RQScanSession {
+    ctx: *mut RQContext, // a reference of its own, see `raptorq_ctx_retain`
+    watch: Stopwatch,
+    throughput: Throughput,
+}
+
+#[derive(Default)]
+struct Throughput {
+    frames: u64,
+    unique_frames: u64,
+    recent: VecDeque<(Duration, bool)>, // pushes within `RATE_WINDOW`, oldest first
+}
+
+impl Throughput {
+    fn record(&mut self, at: Duration, unique: bool) {
+        self.frames += 1;
+        self.unique_frames += u64::from(unique);
+        while matches!(self.recent.front(), Some((t, _)) if at.saturating_sub(*t) >= RATE_WINDOW) {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((at, unique));
+    }
+
+    fn snapshot(&self, now: Duration) -> RQScanStats {
+        let (frames, unique) = self
+            .recent
+            .iter()
+            .filter(|(t, _)| now.saturating_sub(*t) < RATE_WINDOW)
+            .fold((0u32, 0u32), |(n, u), (_, unique)| {
+                (n + 1, u + u32::from(*unique))
+            });
+        let span = now.min(RATE_WINDOW).as_secs_f32();
+        let rate = |count: u32| if span > 0.0 { count as f32 / span } else { 0.0 };
+        RQScanStats {
+            frames: self.frames,
+            unique_frames: self.unique_frames,
+            runtime_ms: u64::try_from(now.as_millis()).unwrap_or(u64::MAX),
+            frames_per_second: rate(frames),
+            unique_frames_per_second: rate(unique),
+        }
+    }
+}
+
+/// Start a scan session over `ctx`, with its clock starting now.  The
+/// session takes a reference of its own to the context (see
+/// `raptorq_ctx_retain`), so the app may free its handle independently;
+/// the context is destroyed with the last reference.  `NULL` for a `NULL`
+/// context.
+///
+/// Push frames through [`raptorq_session_push`] for them to be counted;
+/// everything else (`raptorq_ctx_is_complete`, taking the result, ...) goes
+/// to the context directly.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_session_new(ctx: *mut RQContext) -> *mut RQScanSession {
+    if check_ctx(ctx).is_err() {
+        return ptr::null_mut();
+    }
+    try_catch_unwind(|| {
+        (*ctx).retain();
+        Box::into_raw(Box::new(RQScanSession {
+            ctx,
+            watch: Stopwatch::start(),
+            throughput: Throughput::default(),
+        }))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Push a frame like `raptorq_ctx_push_frame_status` and count it.  Frames
+/// the context could not be entered for ([`RQStatus::Busy`] and invalid
+/// arguments) are not counted.
+///
+/// # Safety
+/// `session` must be `NULL` or a live session, and `payload_ptr` must point
+/// to `payload_len` readable bytes.  Calls on one session must not overlap.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_session_push(
+    session: *mut RQScanSession,
+    payload_ptr: *const u8,
+    payload_len: u64,
+) -> RQStatus {
+    if session.is_null() {
+        return invalid_argument("session is NULL");
+    }
+    let session = &mut *session;
+    let payload = match slice_from_raw(payload_ptr, payload_len) {
+        Ok(payload) => payload,
+        Err(status) => return status,
+    };
+    let pushed = with_ctx(session.ctx, |ctx| {
+        let before = ctx.new_symbols;
+        let status = push_timed(ctx, payload);
+        (status, ctx.new_symbols != before)
+    });
+    match pushed {
+        Ok((status, unique)) => {
+            session.throughput.record(session.watch.elapsed(), unique);
+            status
+        }
+        Err(status) => status,
+    }
+}
+
+/// Frame counts, rates and runtime of the session so far.  All zero for a
+/// `NULL` session.
+///
+/// # Safety
+/// `session` must be `NULL` or a live session.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_session_stats(session: *const RQScanSession) -> RQScanStats {
+    if session.is_null() {
+        invalid_argument("session is NULL");
+        return RQScanStats::default();
+    }
+    let session = &*session;
+    session.throughput.snapshot(session.watch.elapsed())
+}
+
+/// The session's context, for the calls other than pushes.  Borrowed: it
+/// stays valid while the session does, and is not to be freed through this
+/// pointer.
+///
+/// # Safety
+/// `session` must be `NULL` or a live session.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_session_ctx(session: *const RQScanSession) -> *mut RQContext {
+    if session.is_null() {
+        invalid_argument("session is NULL");
+        return ptr::null_mut();
+    }
+    (*session).ctx
+}
+
+/// End the session and give back its reference to the context.
+///
+/// # Safety
+/// `session` must be `NULL` or a live session; it must not be used
+/// afterwards.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_session_free(session: *mut RQScanSession) {
+    if session.is_null() {
+        return;
+    }
+    let session = Box::from_raw(session);
+    crate::raptorq_ctx_release(session.ctx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_over_the_last_second() {
+        let mut throughput = Throughput::default();
+        assert_eq!(throughput.snapshot(Duration::ZERO), RQScanStats::default());
+        // Ten frames over half a second, every other one new.
+        for i in 0..10u64 {
+            throughput.record(Duration::from_millis(i * 50), i % 2 == 0);
+        }
+        let stats = throughput.snapshot(Duration::from_millis(500));
+        assert_eq!((stats.frames, stats.unique_frames), (10, 5));
+        assert_eq!(stats.runtime_ms, 500);
+        assert_eq!(stats.frames_per_second, 20.0);
+        assert_eq!(stats.unique_frames_per_second, 10.0);
+        // A second later only the frames since count.
+        for i in 0..3u64 {
+            throughput.record(Duration::from_millis(1_200 + i * 100), false);
+        }
+        let stats = throughput.snapshot(Duration::from_millis(1_500));
+        assert_eq!((stats.frames, stats.unique_frames), (13, 5));
+        assert_eq!(stats.frames_per_second, 3.0);
+        assert_eq!(stats.unique_frames_per_second, 0.0);
+        let stats = throughput.snapshot(Duration::from_secs(5));
+        assert_eq!(stats.frames_per_second, 0.0);
+    }
+}
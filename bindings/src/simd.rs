@@ -0,0 +1,76 @@
+//! Reporting which GF(256) acceleration the underlying `raptorq` crate uses.
+//!
+//! `raptorq` selects its octet kernels at runtime (NEON on arm64, AVX2 or
+//! SSSE3 on x86) and falls back to scalar code otherwise.  The detection here
+//! makes the same checks as its dispatch in `octets.rs` so integrators can
+//! confirm on device which path a multi‑megabyte decode is actually running
+//! on.  Its kernel for the binary rows of the matrix has an AVX2 version only,
+//! which also needs BMI1, so `Avx2` means both and every kernel runs on AVX2.
+//! A CPU with AVX2 but not BMI1 reports `Ssse3` although its other kernels
+//! use AVX2.
+
+use core::ffi::c_char;
+
+/// GF(256) kernel family in use.  On x86 the binary‑row kernel is vectorized
+/// for `Avx2` only, and scalar code otherwise.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RQSimdBackend {
+    Scalar = 0,
+    Neon = 1,
+    Ssse3 = 2,
+    Avx2 = 3,
+}
+
+pub(crate) fn detect() -> RQSimdBackend {
+    // Without `std`, `raptorq` has no runtime detection and stays scalar.
+    #[cfg(all(target_arch = "aarch64", feature = "std"))]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            return RQSimdBackend::Neon;
+        }
+    }
+    #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "std"))]
+    {
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("bmi1") {
+            return RQSimdBackend::Avx2;
+        }
+        if is_x86_feature_detected!("ssse3") {
+            return RQSimdBackend::Ssse3;
+        }
+    }
+    RQSimdBackend::Scalar
+}
+
+/// GF(256) acceleration active on this device.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_simd_backend() -> RQSimdBackend {
+    detect()
+}
+
+/// Static, NUL‑terminated name of the active backend (`"neon"`, `"avx2"`,
+/// `"ssse3"` or `"scalar"`), for logs.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_simd_backend_name() -> *const c_char {
+    let name: &'static [u8] = match detect() {
+        RQSimdBackend::Scalar => b"scalar\0",
+        RQSimdBackend::Neon => b"neon\0",
+        RQSimdBackend::Ssse3 => b"ssse3\0",
+        RQSimdBackend::Avx2 => b"avx2\0",
+    };
+    name.as_ptr() as *const c_char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backend_matches_target() {
+        let backend = raptorq_simd_backend();
+        if cfg!(target_arch = "aarch64") {
+            assert_eq!(backend, RQSimdBackend::Neon);
+        }
+        assert!(!raptorq_simd_backend_name().is_null());
+    }
+}
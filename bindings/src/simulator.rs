@@ -0,0 +1,280 @@
+//! Lossy camera simulator for QA builds (`simulator` feature): replays an
+//! encoder's animation loop the way a camera would see it, with frames
+//! dropped, duplicated and reordered at configurable rates and patterns.
+//!
+//! Every choice comes from a seeded generator, so a failing scan replays
+//! frame for frame from its seed and the scanning pipeline can be exercised
+//! without a second device showing the codes.
+
+use core::ptr;
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::encoder::RQEncoder;
+use crate::memory::RQBuffer;
+use crate::{into_raw_buffer, invalid_argument, try_catch_unwind};
+
+/// How the simulated camera mangles the loop.  All zero delivers every
+/// frame once, in order.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RQLossConfig {
+    /// Seed of every random choice; the same seed and settings give the
+    /// same frames.
+    pub seed: u64,
+    /// Chance, per mille, that a frame starts a drop; below `1000`.
+    pub drop_per_mille: u16,
+    /// Frames lost in a row once a drop starts, like a hand passing over
+    /// the camera; `0` and `1` lose single frames.
+    pub burst_len: u32,
+    /// Drop every `drop_every`th frame on top of the random drops, a fixed
+    /// pattern for reproducing a report; `0` for none, never `1`.
+    pub drop_every: u32,
+    /// Chance, per mille, that a delivered frame is delivered twice, like a
+    /// code held still for two camera frames; at most `1000`.
+    pub duplicate_per_mille: u16,
+    /// Shuffle the delivered frames within consecutive windows of this many
+    /// frames of the loop; `0` and `1` keep the order.
+    pub reorder_window: u32,
+}
+
+/// Counts returned by `raptorq_sim_counters`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RQLossCounters {
+    /// Frames of the loop shown so far, over all passes.
+    pub shown: u64,
+    /// Of those, frames the camera missed.
+    pub dropped: u64,
+    /// Extra copies delivered.
+    pub duplicated: u64,
+}
+
+/// Opaque handle from [`raptorq_sim_new`].
+pub struct RQLossSimulator {
+    frames: Vec<Vec<u8>>, // the encoder's loop
+    config: RQLossConfig,
+    rng: SplitMix64,
+    cursor: usize,            // next frame of the loop to show
+    burst_left: u32,          // frames still to drop in the current burst
+    queue: VecDeque<Vec<u8>>, // delivered but not yet handed out
+    counters: RQLossCounters,
+}
+
+/// SplitMix64: small, fast and good enough for test choices.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// `true` with a chance of `per_mille` in a thousand.
+    fn chance(&mut self, per_mille: u16) -> bool {
+        per_mille > 0 && self.next() % 1000 < u64::from(per_mille)
+    }
+
+    /// Uniform in `0..n`, `n > 0`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+impl RQLossSimulator {
+    fn new(frames: Vec<Vec<u8>>, config: RQLossConfig) -> RQLossSimulator {
+        RQLossSimulator {
+            frames,
+            config,
+            rng: SplitMix64(config.seed),
+            cursor: 0,
+            burst_left: 0,
+            queue: VecDeque::new(),
+            counters: RQLossCounters::default(),
+        }
+    }
+
+    /// Whether the camera misses the frame shown now.
+    fn drops_next(&mut self) -> bool {
+        self.counters.shown += 1;
+        let every = u64::from(self.config.drop_every);
+        if every != 0 && self.counters.shown.is_multiple_of(every) {
+            return true;
+        }
+        if self.burst_left > 0 {
+            self.burst_left -= 1;
+            return true;
+        }
+        if self.rng.chance(self.config.drop_per_mille) {
+            self.burst_left = self.config.burst_len.saturating_sub(1);
+            return true;
+        }
+        false
+    }
+
+    /// Show the next window of the loop and queue what the camera got.
+    fn refill(&mut self) {
+        let window = self.config.reorder_window.max(1) as usize;
+        let mut delivered = Vec::with_capacity(window);
+        for _ in 0..window {
+            let index = self.cursor;
+            self.cursor = (self.cursor + 1) % self.frames.len();
+            if self.drops_next() {
+                self.counters.dropped += 1;
+                continue;
+            }
+            let frame = self.frames[index].clone();
+            if self.rng.chance(self.config.duplicate_per_mille) {
+                self.counters.duplicated += 1;
+                delivered.push(frame.clone());
+            }
+            delivered.push(frame);
+        }
+        // Fisher–Yates; a window of one keeps the loop's order.
+        for i in (1..delivered.len()).rev() {
+            let j = self.rng.below(i + 1);
+            delivered.swap(i, j);
+        }
+        self.queue.extend(delivered);
+    }
+
+    fn next_frame(&mut self) -> Vec<u8> {
+        // Terminates: drops are never certain (see `raptorq_sim_new`).
+        while self.queue.is_empty() {
+            self.refill();
+        }
+        self.queue.pop_front().unwrap_or_default()
+    }
+}
+
+/// Start replaying the frames of `enc` through a simulated camera.  The
+/// frames are copied, so the encoder may be freed afterwards.  `NULL` for a
+/// `NULL` encoder or settings that would drop every frame
+/// (`drop_per_mille` of `1000` or more, `drop_every` of `1`) or exceed a
+/// certainty (`duplicate_per_mille` over `1000`).
+///
+/// # Safety
+/// `enc` must be `NULL` or a live encoder.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_sim_new(
+    enc: *const RQEncoder,
+    config: RQLossConfig,
+) -> *mut RQLossSimulator {
+    if enc.is_null() {
+        invalid_argument("encoder is NULL");
+        return ptr::null_mut();
+    }
+    if config.drop_per_mille >= 1000 || config.drop_every == 1 {
+        invalid_argument("loss settings drop every frame");
+        return ptr::null_mut();
+    }
+    if config.duplicate_per_mille > 1000 {
+        invalid_argument("duplicate_per_mille is over 1000");
+        return ptr::null_mut();
+    }
+    let frames = (*enc).frames.clone();
+    try_catch_unwind(|| Box::into_raw(Box::new(RQLossSimulator::new(frames, config))))
+        .unwrap_or(ptr::null_mut())
+}
+
+/// The next frame the simulated camera delivers, looping over the
+/// animation forever.  Release it with [`crate::raptorq_buffer_free`];
+/// a `NULL` buffer for a `NULL` simulator.
+///
+/// # Safety
+/// `sim` must be `NULL` or a live simulator.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_sim_next_frame(sim: *mut RQLossSimulator) -> RQBuffer {
+    if sim.is_null() {
+        invalid_argument("simulator is NULL");
+        return RQBuffer::from_raw(ptr::null_mut(), 0);
+    }
+    let sim = &mut *sim;
+    match try_catch_unwind(|| sim.next_frame()) {
+        Some(frame) => {
+            let mut len = 0;
+            RQBuffer::from_raw(into_raw_buffer(frame, &mut len), len)
+        }
+        None => RQBuffer::from_raw(ptr::null_mut(), 0),
+    }
+}
+
+/// What the simulated camera did so far, to check a test run against.
+/// All zero for a `NULL` simulator.
+///
+/// # Safety
+/// `sim` must be `NULL` or a live simulator.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_sim_counters(sim: *const RQLossSimulator) -> RQLossCounters {
+    if sim.is_null() {
+        invalid_argument("simulator is NULL");
+        return RQLossCounters::default();
+    }
+    (*sim).counters
+}
+
+/// Destroy the simulator.
+///
+/// # Safety
+/// `sim` must be `NULL` or a live simulator; it must not be used afterwards.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_sim_free(sim: *mut RQLossSimulator) {
+    if sim.is_null() {
+        return;
+    }
+    drop(Box::from_raw(sim));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames() -> Vec<Vec<u8>> {
+        (0..10u8).map(|i| vec![i]).collect()
+    }
+
+    fn take(sim: &mut RQLossSimulator, n: usize) -> Vec<u8> {
+        (0..n).map(|_| sim.next_frame()[0]).collect()
+    }
+
+    #[test]
+    fn lossless_replay() {
+        let mut sim = RQLossSimulator::new(frames(), RQLossConfig::default());
+        assert_eq!(take(&mut sim, 12), [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1]);
+        assert_eq!(sim.counters.dropped + sim.counters.duplicated, 0);
+    }
+
+    #[test]
+    fn patterns() {
+        let config = RQLossConfig {
+            drop_every: 3,
+            ..RQLossConfig::default()
+        };
+        let mut sim = RQLossSimulator::new(frames(), config);
+        assert_eq!(take(&mut sim, 6), [0, 1, 3, 4, 6, 7]);
+
+        let config = RQLossConfig {
+            seed: 7,
+            drop_per_mille: 200,
+            burst_len: 3,
+            duplicate_per_mille: 100,
+            reorder_window: 4,
+            ..RQLossConfig::default()
+        };
+        let mut a = RQLossSimulator::new(frames(), config);
+        let mut b = RQLossSimulator::new(frames(), config);
+        let run = take(&mut a, 200);
+        assert_eq!(run, take(&mut b, 200));
+        assert_eq!(a.counters, b.counters);
+        let c = a.counters;
+        assert!(c.dropped > 0 && c.duplicated > 0);
+        // Every frame shown was dropped, delivered or still queued.
+        let delivered = run.len() as u64 + a.queue.len() as u64;
+        assert_eq!(c.shown - c.dropped + c.duplicated, delivered);
+    }
+}
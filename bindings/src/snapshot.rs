@@ -0,0 +1,387 @@
+//! Snapshots of a transfer in progress that survive the app being
+//! terminated.
+//!
+//! iOS may kill a backgrounded app at any time; a large backup scanned for
+//! minutes should not have to start over.  A snapshot is a debug dump (see
+//! `crate::dump`) behind the context's options, so the restored context
+//! accepts the same frames as the original:
+//!
+//! ```text
+//! offset  size  field
+//!      0     4  magic "RQSS"
+//!      4     1  format version (1)
+//!      5     1  flags: 1 padding tolerant, 2 packed frames, 4 frame
+//!               checksums, 8 deferred finalize, 16 zeroize, 32 keep
+//!               symbols, 64 mlock result
+//!      6     1  frame prefix (0 none, 1 Vault length)
+//!      7     1  envelope (0 none, 1 CBOR byte string)
+//!      8     4  decode overhead (big endian)
+//!     12     4  Bloom filter bits per block, 0 for exact sets (big endian)
+//!     16        dump
+//! ```
+//!
+//! Restoring replays the dumped symbols, so it costs about as much as
+//! pushing them did.  Symbols the dump could not include (see
+//! `raptorq_ctx_set_keep_symbols`) have to be scanned again; the decode
+//! thread count, logging, spool files and push timings are not kept.
+//!
+//! With `std` a snapshot can also go straight to a file, written to a
+//! temporary next to it and renamed over it so a crash mid‑write leaves the
+//! previous snapshot intact:
+//!
+//! ```text
+//! [magic "RQSF"][file version: u8 = 1][snapshot][crc32 of the rest: u32 LE]
+//! ```
+
+#[cfg(feature = "std")]
+use core::ffi::c_char;
+use core::ptr;
+#[cfg(feature = "std")]
+use std::ffi::CStr;
+#[cfg(feature = "std")]
+use std::fs::{self, File};
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::blocks::Options;
+use crate::context::RQContext;
+use crate::decoder::with_ctx_ref;
+use crate::memory::RQBuffer;
+use crate::profile::{Envelope, FramePrefix};
+use crate::{check_ctx, into_raw_buffer, invalid_argument, slice_from_raw, try_catch_unwind};
+#[cfg(feature = "std")]
+use crate::{checksum, memory, RQStatus};
+
+pub(crate) const SNAPSHOT_MAGIC: [u8; 4] = *b"RQSS";
+pub(crate) const SNAPSHOT_VERSION: u8 = 1;
+const HEADER_LEN: usize = 16;
+
+#[cfg(feature = "std")]
+pub(crate) const SNAPSHOT_FILE_MAGIC: [u8; 4] = *b"RQSF";
+#[cfg(feature = "std")]
+pub(crate) const SNAPSHOT_FILE_VERSION: u8 = 1;
+
+const PADDING_TOLERANT: u8 = 1;
+const PACKED_FRAMES: u8 = 2;
+const FRAME_CHECKSUMS: u8 = 4;
+const DEFERRED: u8 = 8;
+const ZEROIZE: u8 = 16;
+const KEEP_SYMBOLS: u8 = 32;
+const MLOCK_RESULT: u8 = 64;
+
+impl RQContext {
+    /// Snapshot the transfer; `None` once it is complete.
+    pub(crate) fn serialize(&self) -> Option<Vec<u8>> {
+        self.serialize_framed(&[], 0)
+    }
+
+    /// [`RQContext::serialize`] after `head`, with room for exactly
+    /// `trailer` more bytes, like [`RQContext::dump_framed`].
+    fn serialize_framed(&self, head: &[u8], trailer: usize) -> Option<Vec<u8>> {
+        let options = self.decoder.options();
+        let flags = [
+            (self.padding_tolerant, PADDING_TOLERANT),
+            (self.packed_frames, PACKED_FRAMES),
+            (self.frame_checksums, FRAME_CHECKSUMS),
+            (options.deferred, DEFERRED),
+            (options.zeroize, ZEROIZE),
+            (options.keep_symbols, KEEP_SYMBOLS),
+            (self.mlock_result, MLOCK_RESULT),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .fold(0, |flags, (_, flag)| flags | flag);
+        let mut header = Vec::with_capacity(head.len() + HEADER_LEN);
+        header.extend_from_slice(head);
+        header.extend_from_slice(&SNAPSHOT_MAGIC);
+        header.push(SNAPSHOT_VERSION);
+        header.push(flags);
+        header.push(self.prefix.id());
+        header.push(self.envelope.id());
+        header.extend_from_slice(&options.overhead.to_be_bytes());
+        header.extend_from_slice(&options.bloom_bits.unwrap_or(0).to_be_bytes());
+        self.dump_framed(&header, trailer)
+    }
+
+    /// Rebuild a context from [`RQContext::serialize`] output; `None` if
+    /// `blob` is not a well‑formed snapshot.
+    pub(crate) fn deserialize(blob: &[u8]) -> Option<RQContext> {
+        let (header, dump) = blob.split_first_chunk::<HEADER_LEN>()?;
+        if header[..4] != SNAPSHOT_MAGIC || header[4] != SNAPSHOT_VERSION {
+            return None;
+        }
+        let flags = header[5];
+        let prefix = FramePrefix::from_id(header[6])?;
+        let envelope = Envelope::from_id(header[7])?;
+        let overhead = u32::from_be_bytes([header[8], header[9], header[10], header[11]]);
+        let bloom_bits = u32::from_be_bytes([header[12], header[13], header[14], header[15]]);
+        let options = Options {
+            overhead,
+            bloom_bits: (bloom_bits > 0).then_some(bloom_bits),
+            deferred: flags & DEFERRED != 0,
+            zeroize: flags & ZEROIZE != 0,
+            keep_symbols: flags & KEEP_SYMBOLS != 0,
+        };
+        let mut ctx = RQContext::from_dump_with(dump, |ctx| {
+            ctx.decoder.set_options(options);
+            ctx.mlock_result = flags & MLOCK_RESULT != 0;
+        })?;
+        // The dumped symbols are unwrapped already; frames pushed from now
+        // on are not.
+        ctx.padding_tolerant = flags & PADDING_TOLERANT != 0;
+        ctx.packed_frames = flags & PACKED_FRAMES != 0;
+        ctx.frame_checksums = flags & FRAME_CHECKSUMS != 0;
+        ctx.prefix = prefix;
+        ctx.envelope = envelope;
+        Some(ctx)
+    }
+}
+
+/// Snapshot `ctx` so the transfer can be resumed with
+/// [`raptorq_ctx_deserialize`] after the app is terminated, and write the
+/// snapshot's length to `len_out` (when not `NULL`).  Free it with
+/// [`crate::raptorq_free`].  Returns `NULL` for a `NULL` context or a
+/// completed transfer (take its result instead).  Call it before the decode
+/// thread starts or after it stops.
+///
+/// The snapshot contains the received part of the payload; store it
+/// accordingly (e.g. with complete file protection).
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; `len_out` must be `NULL` or valid
+/// for writes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_serialize(
+    ctx: *const RQContext,
+    len_out: *mut u64,
+) -> *mut u8 {
+    if check_ctx(ctx).is_err() {
+        return ptr::null_mut();
+    }
+    match try_catch_unwind(|| with_ctx_ref(ctx, RQContext::serialize).ok().flatten()).flatten() {
+        Some(blob) => into_raw_buffer(blob, len_out),
+        None => ptr::null_mut(),
+    }
+}
+
+/// [`raptorq_ctx_serialize`] returning an [`RQBuffer`], to be released with
+/// [`crate::raptorq_buffer_free`].
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_serialize_buffer(ctx: *const RQContext) -> RQBuffer {
+    let mut len = 0;
+    RQBuffer::from_raw(raptorq_ctx_serialize(ctx, &mut len), len)
+}
+
+/// Resume a transfer from a [`raptorq_ctx_serialize`] snapshot: the new
+/// context holds the same symbols and options and takes frames where the old
+/// one left off.  Returns `NULL` for a blob that is not a well‑formed
+/// snapshot.
+///
+/// # Safety
+/// `blob` must point to `len` readable bytes.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_deserialize(blob: *const u8, len: u64) -> *mut RQContext {
+    let blob = match slice_from_raw(blob, len) {
+        Ok(blob) => blob,
+        Err(_) => return ptr::null_mut(),
+    };
+    match try_catch_unwind(|| RQContext::deserialize(blob)) {
+        Some(Some(ctx)) => Box::into_raw(Box::new(ctx)),
+        Some(None) => {
+            invalid_argument("not a well-formed snapshot");
+            ptr::null_mut()
+        }
+        None => ptr::null_mut(),
+    }
+}
+
+/// Write `bytes` to `path` through a temporary file renamed over it.
+#[cfg(feature = "std")]
+fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let written = File::create(&tmp).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+    let result = written.and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[cfg(feature = "std")]
+unsafe fn path_arg<'a>(path: *const c_char) -> Option<&'a Path> {
+    if path.is_null() {
+        invalid_argument("path is NULL");
+        return None;
+    }
+    match CStr::from_ptr(path).to_str() {
+        Ok(path) => Some(Path::new(path)),
+        Err(_) => {
+            invalid_argument("path is not UTF-8");
+            None
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl RQContext {
+    fn save(&self, path: &Path) -> RQStatus {
+        let head = [&SNAPSHOT_FILE_MAGIC[..], &[SNAPSHOT_FILE_VERSION]].concat();
+        // Sized for the CRC up front, so appending it does not reallocate.
+        let mut file = match self.serialize_framed(&head, 4) {
+            Some(snapshot) => snapshot,
+            None => return RQStatus::Ignored,
+        };
+        file.extend_from_slice(&checksum::crc32(&file).to_le_bytes());
+        let status = match write_atomic(path, &file) {
+            Ok(()) => RQStatus::Ok,
+            Err(error) => {
+                rq_log!(in self.decoder.log(), Error, "snapshot write failed error={}", error);
+                RQStatus::Failed
+            }
+        };
+        if self.decoder.zeroizes() {
+            memory::wipe(&mut file);
+        }
+        status
+    }
+
+    fn load(path: &Path) -> Option<RQContext> {
+        let mut file = fs::read(path).ok()?;
+        let ctx = checksum::open(&file)
+            .and_then(|body| body.strip_prefix(&SNAPSHOT_FILE_MAGIC))
+            .and_then(|body| body.split_first())
+            .filter(|(version, _)| **version == SNAPSHOT_FILE_VERSION)
+            .and_then(|(_, snapshot)| RQContext::deserialize(snapshot));
+        if ctx.as_ref().is_some_and(|ctx| ctx.decoder.zeroizes()) {
+            memory::wipe(&mut file);
+        }
+        ctx
+    }
+}
+
+/// [`raptorq_ctx_serialize`] straight to the file at `path`, replaced
+/// atomically.  Returns [`RQStatus::Ignored`] for a completed transfer and
+/// [`RQStatus::Failed`] if the file could not be written.  Requires `std`.
+///
+/// # Safety
+/// `ctx` must be `NULL` or a live context; `path` must be `NULL` or a
+/// NUL‑terminated UTF‑8 string.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_save(ctx: *const RQContext, path: *const c_char) -> RQStatus {
+    if let Err(status) = check_ctx(ctx) {
+        return status;
+    }
+    let path = match path_arg(path) {
+        Some(path) => path,
+        None => return RQStatus::InvalidArgument,
+    };
+    try_catch_unwind(|| with_ctx_ref(ctx, |ctx| ctx.save(path)).unwrap_or_else(|status| status))
+        .unwrap_or(RQStatus::Failed)
+}
+
+/// [`raptorq_ctx_deserialize`] from a file written by [`raptorq_ctx_save`].
+/// Returns `NULL` if the file is missing, damaged or from an incompatible
+/// version.  Requires `std`.
+///
+/// # Safety
+/// `path` must be `NULL` or a NUL‑terminated UTF‑8 string.
+#[cfg(feature = "std")]
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub unsafe extern "C" fn raptorq_ctx_load(path: *const c_char) -> *mut RQContext {
+    let path = match path_arg(path) {
+        Some(path) => path,
+        None => return ptr::null_mut(),
+    };
+    match try_catch_unwind(|| RQContext::load(path)).flatten() {
+        Some(ctx) => Box::into_raw(Box::new(ctx)),
+        None => ptr::null_mut(),
+    }
+}
+
+#[cfg(all(test, feature = "encoder"))]
+mod tests {
+    use super::*;
+    use crate::profile::RQProfile;
+    use raptorq::{Encoder, ObjectTransmissionInformation};
+
+    #[test]
+    fn resumes_with_options() {
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i * 17 + i / 5) as u8).collect();
+        let profile = RQProfile::VaultClassic;
+        let mut ctx = RQContext::with_profile(profile, data.len() as u64);
+        ctx.padding_tolerant = true;
+        ctx.decoder.set_overhead(3);
+        ctx.decoder.set_keep_symbols(true);
+        let oti = ObjectTransmissionInformation::with_defaults(
+            data.len() as u64,
+            profile.max_payload_size(),
+        );
+        let frames: Vec<Vec<u8>> = Encoder::new(&data, oti)
+            .get_encoded_packets(2)
+            .iter()
+            .map(|p| {
+                let mut frame = (0x8000_0000 | data.len() as u32).to_be_bytes().to_vec();
+                frame.extend_from_slice(&p.serialize());
+                frame
+            })
+            .collect();
+        let (before, after) = frames.split_at(frames.len() / 2);
+        for frame in before {
+            ctx.push(frame);
+        }
+        let blob = ctx.serialize().unwrap();
+        assert_eq!(blob.capacity(), blob.len());
+        assert!(RQContext::deserialize(&blob[1..]).is_none());
+        let mut restored = RQContext::deserialize(&blob).unwrap();
+        assert_eq!(restored.decoder.options(), ctx.decoder.options());
+        assert_eq!(restored.serialize().unwrap(), blob);
+        // Still takes prefixed, padded frames.
+        for frame in after {
+            let mut padded = frame.clone();
+            padded.extend_from_slice(&[0; 3]);
+            restored.push(&padded);
+        }
+        assert_eq!(restored.take_result().unwrap(), data);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn saves_to_a_file() {
+        let data: Vec<u8> = (0..3_000u32).map(|i| (i * 29) as u8).collect();
+        let enc = Encoder::with_defaults(&data, 64);
+        let mut ctx = RQContext::new(enc.get_config());
+        let packets = enc.get_encoded_packets(0);
+        for p in &packets[..20] {
+            ctx.push(&p.serialize());
+        }
+        let path = std::env::temp_dir().join(format!("rq-snapshot-{}", std::process::id()));
+        assert_eq!(ctx.save(&path), RQStatus::Ok);
+        let mut restored = RQContext::load(&path).unwrap();
+        assert_eq!(restored.dump(), ctx.dump());
+        // A damaged file is refused.
+        let mut file = fs::read(&path).unwrap();
+        file[10] ^= 1;
+        fs::write(&path, &file).unwrap();
+        assert!(RQContext::load(&path).is_none());
+        fs::remove_file(&path).unwrap();
+        for p in &packets[20..] {
+            restored.push(&p.serialize());
+        }
+        assert_eq!(restored.save(&path), RQStatus::Ignored);
+        assert_eq!(restored.take_result().unwrap(), data);
+    }
+}
@@ -0,0 +1,114 @@
+//! Assembling the decoded object outside the heap: in a file, or (on unix)
+//! in a memory mapping (see [`crate::mapping`]).
+//!
+//! Each source block is written at its final offset as soon as it decodes
+//! and its buffer released, so the resident size of a very large transfer is
+//! bounded by the block being solved rather than by the whole object.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use crate::mapping::Mapping;
+
+enum Backing {
+    File {
+        file: File,
+        path: PathBuf,
+    },
+    #[cfg(unix)]
+    Mapped(Mapping),
+}
+
+pub(crate) struct Spool {
+    backing: Backing,
+}
+
+impl Spool {
+    /// Create (or truncate) the output file.
+    pub fn create(path: &Path) -> io::Result<Spool> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Ok(Spool {
+            backing: Backing::File {
+                file,
+                path: path.to_path_buf(),
+            },
+        })
+    }
+
+    /// Open the existing file at `path` without truncating it, read-only
+    /// unless `writable`, to fail or resume writes in tests.
+    #[cfg(test)]
+    pub fn reopen(path: &Path, writable: bool) -> io::Result<Spool> {
+        let file = OpenOptions::new().read(true).write(writable).open(path)?;
+        Ok(Spool {
+            backing: Backing::File {
+                file,
+                path: path.to_path_buf(),
+            },
+        })
+    }
+
+    /// Map `transfer_length` bytes, anonymous or shared with the file at
+    /// `path`.
+    #[cfg(unix)]
+    pub fn mapped(path: Option<&Path>, transfer_length: usize) -> io::Result<Spool> {
+        Ok(Spool {
+            backing: Backing::Mapped(Mapping::create(path, transfer_length)?),
+        })
+    }
+
+    pub fn write_at(&mut self, offset: u64, data: &[u8]) -> io::Result<()> {
+        match &mut self.backing {
+            Backing::File { file, .. } => {
+                file.seek(SeekFrom::Start(offset))?;
+                file.write_all(data)
+            }
+            #[cfg(unix)]
+            Backing::Mapped(mapping) => {
+                // The mapping ends at the transfer length; drop the padding.
+                let out = mapping.as_mut_slice();
+                let start = (offset as usize).min(out.len());
+                let end = (start + data.len()).min(out.len());
+                out[start..end].copy_from_slice(&data[..end - start]);
+                Ok(())
+            }
+        }
+    }
+
+    /// Trim the symbol padding off the last block and flush to disk.
+    pub fn finish(&mut self, transfer_length: u64) -> io::Result<()> {
+        match &mut self.backing {
+            Backing::File { file, .. } => {
+                file.set_len(transfer_length)?;
+                file.sync_all()
+            }
+            #[cfg(unix)]
+            Backing::Mapped(mapping) => mapping.flush(),
+        }
+    }
+
+    /// Fresh reader over the assembled object.
+    pub fn reader(&self) -> io::Result<Box<dyn Read + '_>> {
+        match &self.backing {
+            Backing::File { path, .. } => Ok(Box::new(File::open(path)?)),
+            #[cfg(unix)]
+            Backing::Mapped(mapping) => Ok(Box::new(mapping.as_slice())),
+        }
+    }
+
+    /// The assembled object, when it lives in a mapping.
+    pub fn mapped_bytes(&self) -> Option<&[u8]> {
+        match &self.backing {
+            Backing::File { .. } => None,
+            #[cfg(unix)]
+            Backing::Mapped(mapping) => Some(mapping.as_slice()),
+        }
+    }
+}
@@ -0,0 +1,664 @@
+//! Timing of the work done per pushed frame, for lining up with camera frame
+//! drops reported on the app side.
+
+use core::time::Duration;
+
+use crate::status::RQStatus;
+
+/// Snapshot returned by `raptorq_ctx_push_stats`.  All durations are in
+/// nanoseconds; every field is `0` before the first push.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RQPushStats {
+    /// Number of frames pushed so far, whatever their status.
+    pub pushes: u64,
+    pub min_ns: u64,
+    pub avg_ns: u64,
+    pub max_ns: u64,
+}
+
+/// Snapshot returned by `raptorq_ctx_stats`, for analytics events.  Stays
+/// available after completion and after the result was taken.  Without a
+/// clock (no `std`, or in the browser) the times stay `0`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RQStats {
+    /// Frames pushed so far, whatever their status.
+    pub frames: u64,
+    /// Frames that brought at least one symbol the decoder did not have.
+    pub unique_frames: u64,
+    /// Frames with nothing new: symbols already received, and repeated
+    /// handshakes or frames after completion (`RQ_STATUS_IGNORED`).
+    pub duplicate_frames: u64,
+    /// Frames rejected as `RQ_STATUS_MALFORMED`.
+    pub malformed_frames: u64,
+    /// Bytes of every frame pushed.
+    pub bytes: u64,
+    /// Solves run over all blocks, failed ones included.
+    pub solve_attempts: u32,
+    /// Time spent in those solves, added up over the blocks (blocks solved
+    /// on parallel threads overlap).
+    pub solve_ns: u64,
+    /// Wall-clock time since the context was created, less the time spent
+    /// paused (`raptorq_ctx_pause`).
+    pub elapsed_ms: u64,
+}
+
+/// The malformed frames of [`RQStats`] by what was wrong with them, from
+/// `raptorq_ctx_frame_quality`.  Truncated frames and checksum failures
+/// point at the camera misreading codes (a steadier hand, more light);
+/// frames of the wrong length at a sender emitting something this context
+/// was not set up for.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RQFrameQuality {
+    /// Frames shorter than a packet (or than the checksum trailer).
+    pub truncated_frames: u64,
+    /// Frames longer than a packet, other than by tolerated zero padding.
+    pub wrong_length_frames: u64,
+    /// Frames whose CRC trailer did not match, with frame checksums on.
+    pub checksum_failures: u64,
+    /// The other malformed frames: broken envelopes, prefixes or packing,
+    /// and symbols outside the transfer.
+    pub other_malformed_frames: u64,
+}
+
+/// One source block of a transfer, from `raptorq_ctx_block_timing`: what
+/// its solve cost, to spot the block a slow transfer spent its time on.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RQBlockTiming {
+    /// Source symbols in the block.
+    pub symbols: u32,
+    /// Distinct symbols received for the block, source and repair; past
+    /// `symbols`, the overhead the block needed.
+    pub symbols_received: u32,
+    /// Solves run on the block, failed ones included; `0` when every
+    /// source symbol arrived and none was needed.
+    pub solve_attempts: u32,
+    /// Time spent in those solves.
+    pub solve_ns: u64,
+    /// The block is decoded.
+    pub decoded: bool,
+}
+
+/// How economically a transfer completed, from `raptorq_ctx_efficiency`,
+/// to compare sender configurations with field data.  Counts stop at the
+/// frame that completed the object.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RQEfficiency {
+    /// Frames pushed up to completion, whatever their status.
+    pub frames: u64,
+    /// The fewest frames the object could have taken: its `K` source
+    /// symbols over all blocks, one per frame unless frames are packed.
+    pub minimum_frames: u32,
+    /// `frames` over `minimum_frames`; `1` for a flawless scan.
+    pub overhead_ratio: f32,
+    /// Frames that brought nothing new, as in `RQStats`.
+    pub duplicate_frames: u64,
+    /// `duplicate_frames` as a percentage of `frames`.
+    pub duplicate_percent: f32,
+}
+
+/// Why a frame was rejected as malformed, for [`RQFrameQuality`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Defect {
+    Truncated,
+    WrongLength,
+    Checksum,
+    Other,
+}
+
+impl Defect {
+    /// [`Defect::Truncated`] or [`Defect::WrongLength`] for a packet of
+    /// `len` bytes where `expected` were due.
+    pub fn of_length(len: usize, expected: usize) -> Defect {
+        if len < expected {
+            Defect::Truncated
+        } else {
+            Defect::WrongLength
+        }
+    }
+}
+
+/// Limits for `raptorq_ctx_is_stalled`; a field left `0` is not checked.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RQStallWindow {
+    /// Pushes in a row that brought no new symbol.
+    pub pushes: u32,
+    /// Milliseconds without a new symbol, counted from the context's
+    /// creation until the first one arrives; time paused with
+    /// `raptorq_ctx_pause` does not count.
+    pub ms: u32,
+}
+
+/// Clock for [`PushTimer`]; without `std`, or in the browser, there is none
+/// and every push measures zero.
+#[derive(Clone, Copy)]
+pub(crate) struct Stopwatch {
+    #[cfg(all(feature = "std", not(no_clock)))]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    pub fn start() -> Stopwatch {
+        Stopwatch {
+            #[cfg(all(feature = "std", not(no_clock)))]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        #[cfg(all(feature = "std", not(no_clock)))]
+        return self.start.elapsed();
+        #[cfg(not(all(feature = "std", not(no_clock))))]
+        Duration::ZERO
+    }
+}
+
+/// Scanning time of a context: wall-clock time since its creation, less
+/// the time spent paused (`raptorq_ctx_pause`), for the clocks that should
+/// not run while the app is in the background.
+#[derive(Clone, Copy)]
+pub(crate) struct ScanClock {
+    origin: Stopwatch,
+    paused: Duration,            // over the pauses that ended
+    paused_at: Option<Duration>, // start of the current pause, since `origin`
+}
+
+impl Default for ScanClock {
+    fn default() -> ScanClock {
+        ScanClock {
+            origin: Stopwatch::start(),
+            paused: Duration::ZERO,
+            paused_at: None,
+        }
+    }
+}
+
+impl ScanClock {
+    /// Scanning time so far; stands still while paused.
+    pub fn now(&self) -> Duration {
+        let at = self.paused_at.unwrap_or_else(|| self.origin.elapsed());
+        at.saturating_sub(self.paused)
+    }
+
+    /// Wall-clock time since creation, pauses included.
+    pub fn since_creation(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    /// Time spent paused so far, the current pause included.
+    pub fn paused(&self) -> Duration {
+        match self.paused_at {
+            Some(at) => self.paused + self.origin.elapsed().saturating_sub(at),
+            None => self.paused,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(self.origin.elapsed());
+        }
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = self.paused();
+        self.paused_at = None;
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct PushTimer {
+    pushes: u64,
+    total_ns: u64,
+    min_ns: u64,
+    max_ns: u64,
+    recent_ns: u64, // moving average weighted 1/8 towards the latest push
+}
+
+impl PushTimer {
+    pub fn record(&mut self, elapsed: Duration) {
+        let ns = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.min_ns = if self.pushes == 0 {
+            ns
+        } else {
+            self.min_ns.min(ns)
+        };
+        self.max_ns = self.max_ns.max(ns);
+        self.total_ns = self.total_ns.saturating_add(ns);
+        self.recent_ns = if self.pushes == 0 {
+            ns
+        } else {
+            self.recent_ns - self.recent_ns / 8 + ns / 8
+        };
+        self.pushes += 1;
+    }
+
+    /// Minimum interval between pushes the decoder can keep up with: the
+    /// recent cost of a push, or the worst one seen when `solve_imminent`
+    /// (solves dwarf the cost of buffering a symbol).
+    pub fn pacing_hint(&self, solve_imminent: bool) -> Duration {
+        let ns = if solve_imminent {
+            self.recent_ns.max(self.max_ns)
+        } else {
+            self.recent_ns
+        };
+        Duration::from_nanos(ns)
+    }
+
+    pub fn snapshot(&self) -> RQPushStats {
+        RQPushStats {
+            pushes: self.pushes,
+            min_ns: self.min_ns,
+            avg_ns: self.total_ns.checked_div(self.pushes).unwrap_or(0),
+            max_ns: self.max_ns,
+        }
+    }
+}
+
+/// Solve attempts and the time they took.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct SolveTally {
+    pub attempts: u32,
+    pub ns: u64,
+}
+
+impl SolveTally {
+    pub fn record(&mut self, elapsed: Duration) {
+        self.attempts = self.attempts.saturating_add(1);
+        self.ns = self
+            .ns
+            .saturating_add(u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX));
+    }
+
+    pub fn merge(self, other: SolveTally) -> SolveTally {
+        SolveTally {
+            attempts: self.attempts.saturating_add(other.attempts),
+            ns: self.ns.saturating_add(other.ns),
+        }
+    }
+}
+
+/// What became of the frames pushed into a context, for [`RQStats`].
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FrameTally {
+    frames: u64,
+    unique: u64,
+    duplicates: u64,
+    malformed: u64,
+    quality: RQFrameQuality,
+    bytes: u64,
+}
+
+impl FrameTally {
+    /// Count a frame of `len` bytes, `fresh` when it brought at least one
+    /// new symbol; `defect` says what was wrong with a malformed one.
+    pub fn record(&mut self, status: RQStatus, len: usize, fresh: bool, defect: Option<Defect>) {
+        self.frames += 1;
+        self.bytes = self.bytes.saturating_add(len as u64);
+        if fresh {
+            self.unique += 1;
+        } else if matches!(
+            status,
+            RQStatus::Ok | RQStatus::PaddingStripped | RQStatus::Ignored
+        ) {
+            self.duplicates += 1;
+        } else if status == RQStatus::Malformed {
+            self.malformed += 1;
+            let count = match defect.unwrap_or(Defect::Other) {
+                Defect::Truncated => &mut self.quality.truncated_frames,
+                Defect::WrongLength => &mut self.quality.wrong_length_frames,
+                Defect::Checksum => &mut self.quality.checksum_failures,
+                Defect::Other => &mut self.quality.other_malformed_frames,
+            };
+            *count += 1;
+        }
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    pub fn quality(&self) -> RQFrameQuality {
+        self.quality
+    }
+
+    /// The counts against a transfer of `minimum_frames`.
+    pub fn efficiency(&self, minimum_frames: u32) -> RQEfficiency {
+        let ratio = |count: u64, of: u64| {
+            if of == 0 {
+                0.0
+            } else {
+                count as f32 / of as f32
+            }
+        };
+        RQEfficiency {
+            frames: self.frames,
+            minimum_frames,
+            overhead_ratio: ratio(self.frames, minimum_frames.into()),
+            duplicate_frames: self.duplicates,
+            duplicate_percent: ratio(self.duplicates, self.frames) * 100.0,
+        }
+    }
+
+    /// The counts, with `elapsed` as the scanning time.
+    pub fn snapshot(&self, solves: SolveTally, elapsed: Duration) -> RQStats {
+        RQStats {
+            frames: self.frames,
+            unique_frames: self.unique,
+            duplicate_frames: self.duplicates,
+            malformed_frames: self.malformed,
+            bytes: self.bytes,
+            solve_attempts: solves.attempts,
+            solve_ns: solves.ns,
+            elapsed_ms: u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+        }
+    }
+}
+
+/// Recent pace of new symbols, for `raptorq_ctx_eta_ms`.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct SymbolRate {
+    last: Option<Duration>, // scanning time of the last push that brought symbols
+    ns_per_symbol: u64,     // moving average weighted 1/4 towards the latest
+}
+
+impl SymbolRate {
+    /// Count a push that brought `new` symbols.  The wait for the first
+    /// one is not a pace and only starts the clock.  `now` is the
+    /// [`ScanClock`] time.
+    pub fn record(&mut self, new: u64, now: Duration) {
+        if new == 0 {
+            return;
+        }
+        if let Some(last) = self.last {
+            self.observe(new, now.saturating_sub(last));
+        }
+        self.last = Some(now);
+    }
+
+    fn observe(&mut self, new: u64, interval: Duration) {
+        let ns = u64::try_from(interval.as_nanos()).unwrap_or(u64::MAX) / new;
+        self.ns_per_symbol = if self.ns_per_symbol == 0 {
+            ns
+        } else {
+            self.ns_per_symbol - self.ns_per_symbol / 4 + ns / 4
+        };
+    }
+
+    /// Time to collect `missing` more symbols at the recent pace; `None`
+    /// before a pace was measured.
+    pub fn eta(&self, missing: u32) -> Option<Duration> {
+        if self.ns_per_symbol == 0 {
+            return None;
+        }
+        Some(Duration::from_nanos(
+            self.ns_per_symbol.saturating_mul(missing.into()),
+        ))
+    }
+}
+
+/// Time constant of [`NoveltyRate`], in seconds.
+const NOVELTY_WINDOW_S: f32 = 2.0;
+
+/// New symbols per second, smoothed, for `raptorq_ctx_novelty_rate`: every
+/// new symbol adds `1 / NOVELTY_WINDOW_S`, and the sum decays by
+/// `w / (w + t)` over `t` seconds, which over the short steps between frames
+/// follows `exp(-t / w)` without needing `std` for it.  Bursts of duplicates
+/// only let it decay.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct NoveltyRate {
+    last: Duration, // scanning time of the last push; zero before the first
+    per_second: f32,
+}
+
+impl NoveltyRate {
+    /// Count a push at `now` that brought `new` symbols.  Without a clock
+    /// `now` stays zero and so does the rate.
+    pub fn record(&mut self, new: u64, now: Duration) {
+        if now.is_zero() {
+            return;
+        }
+        self.per_second = self.at(now) + new as f32 / NOVELTY_WINDOW_S;
+        self.last = now;
+    }
+
+    /// The rate at `now`, decayed since the last push.
+    pub fn at(&self, now: Duration) -> f32 {
+        let idle = now.saturating_sub(self.last).as_secs_f32();
+        self.per_second * NOVELTY_WINDOW_S / (NOVELTY_WINDOW_S + idle)
+    }
+}
+
+/// How long a context has gone without a new symbol, in pushes and in
+/// [`ScanClock`] time.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct StallTracker {
+    stale_pushes: u32,
+    last_new_symbol: Duration, // scanning time; zero before the first
+}
+
+impl StallTracker {
+    /// Count a push at `now`, `fresh` when it brought at least one new
+    /// symbol.
+    pub fn record(&mut self, fresh: bool, now: Duration) {
+        if fresh {
+            self.stale_pushes = 0;
+            self.last_new_symbol = now;
+        } else {
+            self.stale_pushes = self.stale_pushes.saturating_add(1);
+        }
+    }
+
+    pub fn is_stalled(&self, window: RQStallWindow, now: Duration) -> bool {
+        self.exceeds(window, now.saturating_sub(self.last_new_symbol))
+    }
+
+    fn exceeds(&self, window: RQStallWindow, elapsed: Duration) -> bool {
+        (window.pushes != 0 && self.stale_pushes >= window.pushes)
+            || (window.ms != 0 && elapsed >= Duration::from_millis(window.ms.into()))
+    }
+}
+
+/// Frames [`RecentFrames`] bases its advice on: about two seconds of
+/// camera frames, so advice follows a sender that switched densities.
+const RECENT_FRAMES: usize = 64;
+
+/// Frames needed in the window before there is any advice to give.
+const MIN_ADVICE_FRAMES: usize = 16;
+
+/// Whether the sender should change its QR density, from
+/// `raptorq_ctx_density_advice`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RQDensityAdvice {
+    /// Keep the current codes: they read well and bring new symbols, or
+    /// there are too few frames to tell yet.
+    Keep = 0,
+    /// Switch to smaller, denser codes (more bytes per code): the camera
+    /// reads them cleanly but mostly sees codes it already has.
+    Denser = 1,
+    /// Switch to larger, sparser codes: the camera misreads too many.
+    Sparser = 2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FrameKind {
+    Fresh,
+    Duplicate,
+    /// Truncated, or failing its checksum: the camera misread it.
+    Misread,
+    /// Rejected for another reason, which a density change would not fix.
+    Other,
+}
+
+/// What became of the last [`RECENT_FRAMES`] frames, for
+/// [`RQDensityAdvice`].
+#[derive(Clone, Copy)]
+pub(crate) struct RecentFrames {
+    kinds: [FrameKind; RECENT_FRAMES], // a ring, `next` being the oldest once full
+    next: usize,
+    len: usize,
+}
+
+impl Default for RecentFrames {
+    fn default() -> RecentFrames {
+        RecentFrames {
+            kinds: [FrameKind::Fresh; RECENT_FRAMES],
+            next: 0,
+            len: 0,
+        }
+    }
+}
+
+impl RecentFrames {
+    pub fn record(&mut self, status: RQStatus, fresh: bool, defect: Option<Defect>) {
+        let kind = if fresh {
+            FrameKind::Fresh
+        } else if matches!(defect, Some(Defect::Truncated | Defect::Checksum)) {
+            FrameKind::Misread
+        } else if matches!(
+            status,
+            RQStatus::Ok | RQStatus::PaddingStripped | RQStatus::Ignored
+        ) {
+            FrameKind::Duplicate
+        } else {
+            FrameKind::Other
+        };
+        self.kinds[self.next] = kind;
+        self.next = (self.next + 1) % RECENT_FRAMES;
+        self.len = (self.len + 1).min(RECENT_FRAMES);
+    }
+
+    /// Sparser once one frame in eight is misread; denser once half the
+    /// frames are duplicates while hardly any (one in fifty) is misread.
+    pub fn advice(&self) -> RQDensityAdvice {
+        if self.len < MIN_ADVICE_FRAMES {
+            return RQDensityAdvice::Keep;
+        }
+        let count = |kind| {
+            self.kinds[..self.len]
+                .iter()
+                .filter(|k| **k == kind)
+                .count()
+        };
+        let (misread, duplicate) = (count(FrameKind::Misread), count(FrameKind::Duplicate));
+        if misread * 8 >= self.len {
+            RQDensityAdvice::Sparser
+        } else if duplicate * 2 >= self.len && misread * 50 <= self.len {
+            RQDensityAdvice::Denser
+        } else {
+            RQDensityAdvice::Keep
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_avg_max() {
+        let mut timer = PushTimer::default();
+        assert_eq!(timer.snapshot(), RQPushStats::default());
+        for ns in [300, 100, 200] {
+            timer.record(Duration::from_nanos(ns));
+        }
+        assert_eq!(
+            timer.snapshot(),
+            RQPushStats {
+                pushes: 3,
+                min_ns: 100,
+                avg_ns: 200,
+                max_ns: 300,
+            }
+        );
+        assert_eq!(timer.pacing_hint(false), Duration::from_nanos(266));
+        assert_eq!(timer.pacing_hint(true), Duration::from_nanos(300));
+    }
+
+    #[test]
+    fn stall_windows() {
+        let mut stall = StallTracker::default();
+        let window = RQStallWindow { pushes: 3, ms: 0 };
+        for _ in 0..2 {
+            stall.record(false, Duration::ZERO);
+        }
+        assert!(!stall.exceeds(window, Duration::ZERO));
+        stall.record(false, Duration::ZERO);
+        assert!(stall.exceeds(window, Duration::ZERO));
+        stall.record(true, Duration::ZERO);
+        assert!(!stall.exceeds(window, Duration::ZERO));
+        let window = RQStallWindow { pushes: 0, ms: 500 };
+        assert!(!stall.exceeds(window, Duration::from_millis(499)));
+        assert!(stall.exceeds(window, Duration::from_millis(500)));
+        assert!(!stall.exceeds(RQStallWindow::default(), Duration::MAX));
+    }
+
+    #[test]
+    fn symbol_pace() {
+        let mut rate = SymbolRate::default();
+        assert_eq!(rate.eta(10), None);
+        rate.observe(2, Duration::from_millis(200));
+        assert_eq!(rate.eta(10), Some(Duration::from_secs(1)));
+        rate.observe(1, Duration::from_millis(500));
+        assert_eq!(rate.eta(10), Some(Duration::from_millis(2_000)));
+        assert_eq!(rate.eta(0), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn novelty_rate() {
+        let mut rate = NoveltyRate::default();
+        let frame = Duration::from_millis(33);
+        let mut now = Duration::ZERO;
+        rate.record(1, now);
+        assert_eq!(rate.at(now), 0.0); // no clock
+                                       // A new symbol per frame for 20 s, at 30 frames per second.
+        for _ in 0..600 {
+            now += frame;
+            rate.record(1, now);
+        }
+        let steady = rate.at(now);
+        assert!((28.0..32.0).contains(&steady), "{steady}");
+        // A second of duplicates eases it down, by about exp(-1/2).
+        let mut previous = steady;
+        for _ in 0..30 {
+            now += frame;
+            rate.record(0, now);
+            let current = rate.at(now);
+            assert!(current < previous && current > previous * 0.97);
+            previous = current;
+        }
+        assert!((16.0..20.0).contains(&previous), "{previous}");
+        assert!(rate.at(now + Duration::from_secs(60)) < 1.0);
+    }
+
+    #[test]
+    fn density_advice() {
+        let mut recent = RecentFrames::default();
+        let duplicate = |recent: &mut RecentFrames| recent.record(RQStatus::Ok, false, None);
+        for _ in 0..15 {
+            duplicate(&mut recent);
+        }
+        assert_eq!(recent.advice(), RQDensityAdvice::Keep);
+        duplicate(&mut recent);
+        assert_eq!(recent.advice(), RQDensityAdvice::Denser);
+        // Frames of the wrong length are the sender's problem, not density.
+        for _ in 0..RECENT_FRAMES {
+            recent.record(RQStatus::Malformed, false, Some(Defect::WrongLength));
+        }
+        assert_eq!(recent.advice(), RQDensityAdvice::Keep);
+        for i in 0..RECENT_FRAMES {
+            let defect = (i % 8 == 0).then_some(Defect::Checksum);
+            recent.record(RQStatus::Malformed, defect.is_none(), defect);
+        }
+        assert_eq!(recent.advice(), RQDensityAdvice::Sparser);
+    }
+}
@@ -0,0 +1,211 @@
+//! Status codes shared by the C API.
+
+use core::ffi::c_char;
+
+/// Outcome of a call into the library.
+///
+/// Values are stable; new ones are only ever appended.  Each has a
+/// description in [`raptorq_status_message`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RQStatus {
+    /// The frame was accepted; more are needed.
+    Ok = 0,
+    /// The frame finished decoding the whole object.
+    Complete = 1,
+    /// The frame was accepted after trailing zero padding was stripped.
+    PaddingStripped = 2,
+    /// The frame was not an encoding packet for this transfer (a repeated
+    /// handshake frame, or anything pushed after completion) and was skipped.
+    Ignored = 3,
+    /// The frame does not match the expected prefix, envelope or size.
+    Malformed = 4,
+    /// The recovered object does not match the handshake digest.
+    DigestMismatch = 5,
+    /// An internal panic or I/O error (see `raptorq_last_error`).
+    Failed = 6,
+    /// A length passed in, or the object being assembled in memory, does
+    /// not fit the address space of this (32‑bit) target.
+    LengthOverflow = 7,
+    /// Deferred finalization: every block has enough symbols, drive the
+    /// solve with `raptorq_ctx_finalize_step`.
+    ReadyToFinalize = 8,
+    /// A `raptorq_handle_*` call got a handle that was already freed or
+    /// never issued.
+    InvalidHandle = 9,
+    /// A `NULL` pointer where one is required, a `NULL` buffer with a
+    /// non‑zero length or a parameter out of range; `raptorq_last_error`
+    /// says which.
+    InvalidArgument = 10,
+    /// The context is in use by a call on another thread; calls on a
+    /// context not created with `raptorq_ctx_new_threadsafe` must not
+    /// overlap.  Nothing was done.
+    Busy = 11,
+    /// `raptorq_ctx_poll`: the decode thread has not finished a frame since
+    /// the previous poll.
+    Pending = 12,
+    /// The context was cancelled through `raptorq_cancel`; nothing more is
+    /// decoded.
+    Cancelled = 13,
+}
+
+impl RQStatus {
+    /// Inverse of `status as u8`, for statuses kept in an atomic or
+    /// passed through another language as an integer.
+    pub fn from_repr(value: u8) -> Option<RQStatus> {
+        use RQStatus::*;
+        [
+            Ok,
+            Complete,
+            PaddingStripped,
+            Ignored,
+            Malformed,
+            DigestMismatch,
+            Failed,
+            LengthOverflow,
+            ReadyToFinalize,
+            InvalidHandle,
+            InvalidArgument,
+            Busy,
+            Pending,
+            Cancelled,
+        ]
+        .into_iter()
+        .find(|status| *status as u8 == value)
+    }
+}
+
+/// [`RQErrorInfo::fields`] bit: `sbn` and `esi` name the offending symbol.
+pub const RQ_ERROR_HAS_SYMBOL: u32 = 1;
+/// [`RQErrorInfo::fields`] bit: `expected_len` and `actual_len` are set.
+pub const RQ_ERROR_HAS_LENGTHS: u32 = 2;
+/// [`RQErrorInfo::fields`] bit: `offset` is set.
+pub const RQ_ERROR_HAS_OFFSET: u32 = 4;
+
+/// Detail of the calling thread's last error, from
+/// `raptorq_last_error_info`.  Only the fields named in `fields` are
+/// meaningful; the others are zero.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RQErrorInfo {
+    /// The status the failing call reported.
+    pub status: RQStatus,
+    /// `RQ_ERROR_HAS_*` bits.
+    pub fields: u32,
+    /// Source block number of the offending symbol.
+    pub sbn: u32,
+    /// Encoding symbol id of the offending symbol.
+    pub esi: u32,
+    /// Length the call expected, e.g. of an encoding packet.
+    pub expected_len: u64,
+    /// Length it got.
+    pub actual_len: u64,
+    /// Byte offset into the frame where parsing failed.
+    pub offset: u64,
+}
+
+impl RQErrorInfo {
+    #[cfg(any(feature = "std", feature = "decoder"))]
+    pub(crate) fn new(status: RQStatus) -> RQErrorInfo {
+        RQErrorInfo {
+            status,
+            fields: 0,
+            sbn: 0,
+            esi: 0,
+            expected_len: 0,
+            actual_len: 0,
+            offset: 0,
+        }
+    }
+
+    #[cfg(feature = "decoder")]
+    pub(crate) fn symbol(mut self, sbn: u8, esi: u32) -> RQErrorInfo {
+        self.fields |= RQ_ERROR_HAS_SYMBOL;
+        self.sbn = u32::from(sbn);
+        self.esi = esi;
+        self
+    }
+
+    #[cfg(feature = "decoder")]
+    pub(crate) fn lengths(mut self, expected: usize, actual: usize) -> RQErrorInfo {
+        self.fields |= RQ_ERROR_HAS_LENGTHS;
+        self.expected_len = expected as u64;
+        self.actual_len = actual as u64;
+        self
+    }
+
+    #[cfg(feature = "decoder")]
+    pub(crate) fn offset(mut self, offset: usize) -> RQErrorInfo {
+        self.fields |= RQ_ERROR_HAS_OFFSET;
+        self.offset = offset as u64;
+        self
+    }
+}
+
+/// A short, stable English description of the [`RQStatus`] value `status`,
+/// e.g. for an error alert or a log line; "unknown status" for a value that
+/// is not one, such as a status added by a newer library.  The string is
+/// static and must not be freed.
+#[cfg_attr(export_unprefixed, no_mangle)]
+pub extern "C" fn raptorq_status_message(status: u32) -> *const c_char {
+    let message: &'static str = match u8::try_from(status).ok().and_then(RQStatus::from_repr) {
+        None => "unknown status\0",
+        Some(RQStatus::Ok) => "frame accepted, more frames needed\0",
+        Some(RQStatus::Complete) => "transfer complete\0",
+        Some(RQStatus::PaddingStripped) => "frame accepted after stripping padding\0",
+        Some(RQStatus::Ignored) => "frame ignored\0",
+        Some(RQStatus::Malformed) => "malformed frame\0",
+        Some(RQStatus::DigestMismatch) => "recovered data does not match the handshake digest\0",
+        Some(RQStatus::Failed) => "internal failure\0",
+        Some(RQStatus::LengthOverflow) => "length too large for this device\0",
+        Some(RQStatus::ReadyToFinalize) => "ready to finalize\0",
+        Some(RQStatus::InvalidHandle) => "invalid handle\0",
+        Some(RQStatus::InvalidArgument) => "invalid argument\0",
+        Some(RQStatus::Busy) => "context in use by another thread\0",
+        Some(RQStatus::Pending) => "no new frames decoded\0",
+        Some(RQStatus::Cancelled) => "cancelled\0",
+    };
+    message.as_ptr() as *const c_char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn every_status_has_a_message() {
+        use RQStatus::*;
+        let messages: Vec<&CStr> = [
+            Ok,
+            Complete,
+            PaddingStripped,
+            Ignored,
+            Malformed,
+            DigestMismatch,
+            Failed,
+            LengthOverflow,
+            ReadyToFinalize,
+            InvalidHandle,
+            InvalidArgument,
+            Busy,
+            Pending,
+            Cancelled,
+        ]
+        .into_iter()
+        .map(|status| unsafe { CStr::from_ptr(raptorq_status_message(status as u32)) })
+        .collect();
+        for (i, message) in messages.iter().enumerate() {
+            assert!(!message.is_empty());
+            assert!(!messages[..i].contains(message));
+        }
+    }
+
+    #[test]
+    fn unknown_status_has_a_message() {
+        for status in [RQStatus::Cancelled as u32 + 1, 256, u32::MAX] {
+            let message = unsafe { CStr::from_ptr(raptorq_status_message(status)) };
+            assert_eq!(message.to_str(), Ok("unknown status"));
+        }
+    }
+}
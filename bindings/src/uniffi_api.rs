@@ -0,0 +1,266 @@
+//! Typed interface for uniffi-generated Swift and Kotlin bindings, declared
+//! in `raptorq.udl`.  A thin layer over the `raptorq_ctx_*` functions, so
+//! both surfaces behave the same.
+//!
+//! Pushes never run the final solve: contexts defer it, and the async
+//! [`FrameDecoder::finish`] runs it on a thread of its own, so a caller on
+//! the main actor cannot block the UI with it.
+
+use std::ffi::CStr;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::ptr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context as TaskContext, Poll, Waker};
+use std::thread;
+
+use crate::{
+    raptorq_abi_version, raptorq_buffer_free, raptorq_cancel, raptorq_cancel_token_free,
+    raptorq_ctx_cancel_token, raptorq_ctx_finalize_step, raptorq_ctx_free,
+    raptorq_ctx_from_handshake, raptorq_ctx_from_oti, raptorq_ctx_is_complete, raptorq_ctx_new,
+    raptorq_ctx_push_frame_status, raptorq_ctx_set_deferred_finalize,
+    raptorq_ctx_take_result_buffer, raptorq_ctx_total_frames, raptorq_status_message,
+    RQCancelToken, RQContext, RQStatus,
+};
+
+fn abi_version() -> u32 {
+    raptorq_abi_version()
+}
+
+/// Solve time per lock, so pushes and queries get in between slices.
+const FINISH_SLICE_MS: u32 = 50;
+
+/// The failing [`RQStatus`] values, as a typed error, plus finishing too
+/// early.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// [`FrameDecoder::finish`] before every block had enough symbols, or
+    /// after the result was taken.
+    Incomplete,
+    Malformed,
+    DigestMismatch,
+    Failed,
+    LengthOverflow,
+    InvalidArgument,
+    Busy,
+    Cancelled,
+}
+
+impl DecodeError {
+    fn from_status(status: RQStatus) -> DecodeError {
+        match status {
+            RQStatus::Malformed => DecodeError::Malformed,
+            RQStatus::DigestMismatch => DecodeError::DigestMismatch,
+            RQStatus::LengthOverflow => DecodeError::LengthOverflow,
+            RQStatus::InvalidArgument => DecodeError::InvalidArgument,
+            RQStatus::Busy => DecodeError::Busy,
+            RQStatus::Cancelled => DecodeError::Cancelled,
+            _ => DecodeError::Failed,
+        }
+    }
+
+    fn status(self) -> Option<RQStatus> {
+        match self {
+            DecodeError::Incomplete => None,
+            DecodeError::Malformed => Some(RQStatus::Malformed),
+            DecodeError::DigestMismatch => Some(RQStatus::DigestMismatch),
+            DecodeError::Failed => Some(RQStatus::Failed),
+            DecodeError::LengthOverflow => Some(RQStatus::LengthOverflow),
+            DecodeError::InvalidArgument => Some(RQStatus::InvalidArgument),
+            DecodeError::Busy => Some(RQStatus::Busy),
+            DecodeError::Cancelled => Some(RQStatus::Cancelled),
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.status() {
+            Some(status) => {
+                let message = unsafe { CStr::from_ptr(raptorq_status_message(status as u32)) };
+                f.write_str(&message.to_string_lossy())
+            }
+            None => f.write_str("more frames needed"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// What happened to a frame that was not rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushResult {
+    Accepted,
+    /// Every block has enough symbols; [`FrameDecoder::finish`] recovers the
+    /// object.
+    ReadyToFinish,
+    Complete,
+    Ignored,
+}
+
+/// An owned context; uniffi hands decoders to any thread, so it is only
+/// touched under [`FrameDecoder`]'s lock.
+struct Context(*mut RQContext);
+
+unsafe impl Send for Context {}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        unsafe { raptorq_ctx_free(self.0) };
+    }
+}
+
+/// A token of the context, usable without its lock.
+struct CancelToken(*const RQCancelToken);
+
+unsafe impl Send for CancelToken {}
+unsafe impl Sync for CancelToken {}
+
+impl Drop for CancelToken {
+    fn drop(&mut self) {
+        unsafe { raptorq_cancel_token_free(self.0) };
+    }
+}
+
+/// One decoding context, freed when the foreign object is released.
+pub struct FrameDecoder {
+    ctx: Mutex<Context>,
+    cancel: CancelToken,
+}
+
+impl FrameDecoder {
+    pub fn new(transfer_length: u64, max_payload_size: u16) -> Result<FrameDecoder, DecodeError> {
+        FrameDecoder::adopt(raptorq_ctx_new(transfer_length, max_payload_size))
+    }
+
+    pub fn from_oti(oti: Vec<u8>) -> Result<FrameDecoder, DecodeError> {
+        if oti.len() != 12 {
+            return Err(DecodeError::InvalidArgument);
+        }
+        FrameDecoder::adopt(unsafe { raptorq_ctx_from_oti(oti.as_ptr()) })
+    }
+
+    pub fn from_handshake(frame: Vec<u8>) -> Result<FrameDecoder, DecodeError> {
+        FrameDecoder::adopt(unsafe {
+            raptorq_ctx_from_handshake(frame.as_ptr(), frame.len() as u64)
+        })
+    }
+
+    fn adopt(ctx: *mut RQContext) -> Result<FrameDecoder, DecodeError> {
+        if ctx.is_null() {
+            return Err(DecodeError::InvalidArgument);
+        }
+        unsafe { raptorq_ctx_set_deferred_finalize(ctx, true) };
+        Ok(FrameDecoder {
+            cancel: CancelToken(unsafe { raptorq_ctx_cancel_token(ctx) }),
+            ctx: Mutex::new(Context(ctx)),
+        })
+    }
+
+    fn with_ctx<R>(&self, f: impl FnOnce(*mut RQContext) -> R) -> R {
+        f(self.ctx.lock().unwrap_or_else(PoisonError::into_inner).0)
+    }
+
+    pub fn push(&self, frame: Vec<u8>) -> Result<PushResult, DecodeError> {
+        let status = self.with_ctx(|ctx| unsafe {
+            raptorq_ctx_push_frame_status(ctx, frame.as_ptr(), frame.len() as u64)
+        });
+        match status {
+            RQStatus::Ok | RQStatus::PaddingStripped => Ok(PushResult::Accepted),
+            RQStatus::ReadyToFinalize => Ok(PushResult::ReadyToFinish),
+            RQStatus::Complete => Ok(PushResult::Complete),
+            RQStatus::Ignored => Ok(PushResult::Ignored),
+            status => Err(DecodeError::from_status(status)),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.with_ctx(|ctx| unsafe { raptorq_ctx_is_complete(ctx) })
+    }
+
+    pub fn total_frames(&self) -> u32 {
+        self.with_ctx(|ctx| unsafe { raptorq_ctx_total_frames(ctx) })
+    }
+
+    /// The recovered object, once; foreign bytes are always copied, so the
+    /// library's buffer is released right away.
+    pub fn take_result(&self) -> Option<Vec<u8>> {
+        let buf = self.with_ctx(|ctx| unsafe { raptorq_ctx_take_result_buffer(ctx) });
+        if buf.ptr.is_null() {
+            return None;
+        }
+        let result = unsafe { std::slice::from_raw_parts(buf.ptr, buf.len as usize) }.to_vec();
+        unsafe { raptorq_buffer_free(buf) };
+        Some(result)
+    }
+
+    /// Stop a [`FrameDecoder::finish`] in progress after the block it is
+    /// solving, failing it with [`DecodeError::Cancelled`], and turn away
+    /// every later push.  Does not wait for the decoder's lock.
+    pub fn cancel(&self) {
+        unsafe { raptorq_cancel(self.cancel.0) };
+    }
+
+    /// Run the final solve on a new thread and take the result, without
+    /// blocking the awaiting task.
+    pub async fn finish(self: Arc<Self>) -> Result<Vec<u8>, DecodeError> {
+        let finish = Finish::default();
+        let state = Arc::clone(&finish.0);
+        let spawned = thread::Builder::new()
+            .name("raptorq-finish".into())
+            .spawn(move || {
+                let result = self.finish_blocking();
+                let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+                state.result = Some(result);
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            });
+        if spawned.is_err() {
+            return Err(DecodeError::Failed);
+        }
+        finish.await
+    }
+
+    fn finish_blocking(&self) -> Result<Vec<u8>, DecodeError> {
+        loop {
+            let status = self.with_ctx(|ctx| unsafe {
+                raptorq_ctx_finalize_step(ctx, FINISH_SLICE_MS, ptr::null_mut())
+            });
+            match status {
+                RQStatus::Ok => continue,
+                RQStatus::Complete => return self.take_result().ok_or(DecodeError::Incomplete),
+                RQStatus::Ignored => return Err(DecodeError::Incomplete),
+                status => return Err(DecodeError::from_status(status)),
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct FinishState {
+    result: Option<Result<Vec<u8>, DecodeError>>,
+    waker: Option<Waker>,
+}
+
+/// Resolves once the thread spawned by [`FrameDecoder::finish`] is done.
+#[derive(Default)]
+struct Finish(Arc<Mutex<FinishState>>);
+
+impl Future for Finish {
+    type Output = Result<Vec<u8>, DecodeError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let mut state = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+uniffi::include_scaffolding!("raptorq");
@@ -0,0 +1,131 @@
+//! `wasm-bindgen` interface for the browser extension.  A thin layer over
+//! the `raptorq_ctx_*` functions, like `uniffi_api.rs`, so the extension
+//! reads exactly the frames the apps read.
+//!
+//! The browser has no threads for the library to use: the final solve runs
+//! inside the push that completes the transfer, so call [`FrameDecoder::push`]
+//! from a worker rather than the page's main thread.
+
+use std::ffi::CStr;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    raptorq_abi_version, raptorq_buffer_free, raptorq_clear_last_error, raptorq_ctx_free,
+    raptorq_ctx_from_handshake, raptorq_ctx_from_oti, raptorq_ctx_is_complete, raptorq_ctx_new,
+    raptorq_ctx_push_frame_status, raptorq_ctx_take_result_buffer, raptorq_ctx_total_frames,
+    raptorq_last_error, raptorq_status_message, RQContext, RQStatus,
+};
+
+/// Version of the C API this module was built from.
+#[wasm_bindgen(js_name = abiVersion)]
+pub fn abi_version() -> u32 {
+    raptorq_abi_version()
+}
+
+/// What happened to a frame that was not rejected.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushResult {
+    Accepted,
+    Complete,
+    Ignored,
+}
+
+/// An `Error` carrying the status message, and the reason the library
+/// recorded for the failing call where there is one.
+fn error(status: RQStatus) -> JsError {
+    let message = unsafe { CStr::from_ptr(raptorq_status_message(status as u32)) };
+    let reason = raptorq_last_error();
+    if reason.is_null() {
+        return JsError::new(&message.to_string_lossy());
+    }
+    let reason = unsafe { CStr::from_ptr(reason) };
+    JsError::new(&format!(
+        "{}: {}",
+        message.to_string_lossy(),
+        reason.to_string_lossy()
+    ))
+}
+
+/// One decoding context, freed by `free()` on the JavaScript object.
+#[wasm_bindgen]
+pub struct FrameDecoder {
+    ctx: *mut RQContext,
+}
+
+impl Drop for FrameDecoder {
+    fn drop(&mut self) {
+        unsafe { raptorq_ctx_free(self.ctx) };
+    }
+}
+
+#[wasm_bindgen]
+impl FrameDecoder {
+    /// `transferLength` is a `bigint`, as in the OTI.
+    #[wasm_bindgen(constructor)]
+    pub fn new(transfer_length: u64, max_payload_size: u16) -> Result<FrameDecoder, JsError> {
+        raptorq_clear_last_error();
+        FrameDecoder::adopt(raptorq_ctx_new(transfer_length, max_payload_size))
+    }
+
+    #[wasm_bindgen(js_name = fromOti)]
+    pub fn from_oti(oti: &[u8]) -> Result<FrameDecoder, JsError> {
+        raptorq_clear_last_error();
+        if oti.len() != 12 {
+            return Err(error(RQStatus::InvalidArgument));
+        }
+        FrameDecoder::adopt(unsafe { raptorq_ctx_from_oti(oti.as_ptr()) })
+    }
+
+    #[wasm_bindgen(js_name = fromHandshake)]
+    pub fn from_handshake(frame: &[u8]) -> Result<FrameDecoder, JsError> {
+        raptorq_clear_last_error();
+        FrameDecoder::adopt(unsafe {
+            raptorq_ctx_from_handshake(frame.as_ptr(), frame.len() as u64)
+        })
+    }
+
+    fn adopt(ctx: *mut RQContext) -> Result<FrameDecoder, JsError> {
+        if ctx.is_null() {
+            return Err(error(RQStatus::InvalidArgument));
+        }
+        Ok(FrameDecoder { ctx })
+    }
+
+    /// Throws for a frame that is rejected or a transfer that failed.
+    pub fn push(&mut self, frame: &[u8]) -> Result<PushResult, JsError> {
+        raptorq_clear_last_error();
+        let status =
+            unsafe { raptorq_ctx_push_frame_status(self.ctx, frame.as_ptr(), frame.len() as u64) };
+        match status {
+            RQStatus::Ok | RQStatus::PaddingStripped => Ok(PushResult::Accepted),
+            RQStatus::Complete => Ok(PushResult::Complete),
+            RQStatus::Ignored => Ok(PushResult::Ignored),
+            status => Err(error(status)),
+        }
+    }
+
+    #[wasm_bindgen(getter, js_name = isComplete)]
+    pub fn is_complete(&self) -> bool {
+        unsafe { raptorq_ctx_is_complete(self.ctx) }
+    }
+
+    #[wasm_bindgen(getter, js_name = totalFrames)]
+    pub fn total_frames(&self) -> u32 {
+        unsafe { raptorq_ctx_total_frames(self.ctx) }
+    }
+
+    /// The recovered object as a `Uint8Array`, once; `undefined` before
+    /// then and after.
+    #[wasm_bindgen(js_name = takeResult)]
+    pub fn take_result(&mut self) -> Option<Vec<u8>> {
+        let buf = unsafe { raptorq_ctx_take_result_buffer(self.ctx) };
+        if buf.ptr.is_null() {
+            return None;
+        }
+        let result = unsafe { std::slice::from_raw_parts(buf.ptr, buf.len as usize) }.to_vec();
+        unsafe { raptorq_buffer_free(buf) };
+        Some(result)
+    }
+}
@@ -0,0 +1,452 @@
+//! Library-owned decode thread fed from the camera thread.
+//!
+//! The camera callback only copies the frame into a preallocated slot of a
+//! single-producer single-consumer ring and wakes the worker, so it never
+//! allocates, takes a lock or waits on a decode in progress.  While the worker runs the
+//! real decoding state sits in [`Shared`], locked by the worker for each
+//! frame it decodes; queries on the context the app holds read it there
+//! (see [`RQContext::with_state`]), while that context itself is a stand-in
+//! until the worker is stopped and the state moved back.
+//!
+//! The enqueue calls reach the ring through the context's [`Feed`], not the
+//! [`Worker`]: a poll on another thread may stop the worker and swap the
+//! state back at any time, and waits for an enqueue that got hold of the
+//! ring just before to finish its copy.
+
+use std::cell::UnsafeCell;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::thread::{self, JoinHandle, Thread};
+
+use crate::context::RQContext;
+use crate::stats::Stopwatch;
+use crate::status::RQStatus;
+use crate::{invalid_argument, try_catch_hot};
+
+/// Queue used when [`RQContext::ensure_worker`] starts the thread.
+const DEFAULT_QUEUE_LEN: usize = 32;
+
+/// Room left in each slot beyond a bare encoding packet, for the profile's
+/// envelope, prefix and CRC trailer.
+const FRAME_SLACK: usize = 64;
+
+/// Fixed-capacity SPSC ring of frame buffers allocated up front.
+struct Ring {
+    slots: Box<[UnsafeCell<Vec<u8>>]>,
+    slot_len: usize,
+    head: AtomicUsize, // next slot the consumer reads
+    tail: AtomicUsize, // next slot the producer writes
+}
+
+// Each slot is touched by one side at a time, handed over through
+// `head`/`tail` with release/acquire ordering.
+unsafe impl Sync for Ring {}
+
+impl Ring {
+    fn new(slots: usize, slot_len: usize) -> Ring {
+        Ring {
+            slots: (0..slots)
+                .map(|_| UnsafeCell::new(Vec::with_capacity(slot_len)))
+                .collect(),
+            slot_len,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Producer side: copy `frame` into the next free slot.  `false` when
+    /// the ring is full or the frame is larger than a slot.
+    fn push(&self, frame: &[u8]) -> bool {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        if tail.wrapping_sub(head) == self.slots.len() || frame.len() > self.slot_len {
+            return false;
+        }
+        let slot = unsafe { &mut *self.slots[tail % self.slots.len()].get() };
+        slot.clear();
+        slot.extend_from_slice(frame); // within the reserved capacity
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    /// Consumer side: run `f` on the oldest queued frame, then free its slot.
+    fn pop_with<R>(&self, f: impl FnOnce(&[u8]) -> R) -> Option<R> {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        let slot = unsafe { &*self.slots[head % self.slots.len()].get() };
+        let result = f(slot);
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        Some(result)
+    }
+}
+
+struct Shared {
+    ring: Ring,
+    status: AtomicU8, // latest `RQStatus`, see `Shared::record`
+    fresh: AtomicU8,  // status not yet collected by `Worker::poll`
+    stop: AtomicBool,
+    state: Mutex<Option<RQContext>>, // the decoding state, until taken back
+    thread: OnceLock<Thread>,        // to wake, once spawned
+}
+
+impl Shared {
+    /// Queue a copy of `frame` and wake the worker; `false` when the ring
+    /// is full, the frame too large or the worker stopping.
+    fn enqueue(&self, frame: &[u8]) -> bool {
+        if self.stop.load(Ordering::Acquire) || !self.ring.push(frame) {
+            return false;
+        }
+        if let Some(thread) = self.thread.get() {
+            thread.unpark();
+        }
+        true
+    }
+
+    /// Publish the status of a decoded frame.  A completed (or rejected or
+    /// cancelled) object stays reported as such while later frames come
+    /// back ignored.
+    fn record(&self, status: RQStatus) {
+        let _ = self
+            .status
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+                let settled = current == RQStatus::Complete as u8
+                    || current == RQStatus::DigestMismatch as u8
+                    || current == RQStatus::Cancelled as u8;
+                (!settled).then_some(status as u8)
+            });
+        // Until polled, an outcome the app must act on is not hidden by the
+        // frames decoded after it.
+        let _ = self
+            .fresh
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+                (!is_outcome(current)).then_some(status as u8)
+            });
+    }
+}
+
+/// Whether the status `value` ends the decode thread's part of the
+/// transfer: the object is complete, failed its digest, awaits the solve or
+/// was cancelled.
+fn is_outcome(value: u8) -> bool {
+    value == RQStatus::Complete as u8
+        || value == RQStatus::DigestMismatch as u8
+        || value == RQStatus::ReadyToFinalize as u8
+        || value == RQStatus::Cancelled as u8
+}
+
+/// The producer side of a context's worker, in its own allocation like
+/// the context's in-use count so the enqueue calls never hold a reference
+/// to the context itself.  The worker's [`Shared`] is published through an
+/// atomic pointer, and callers announce themselves in `readers` before
+/// loading it: an enqueue is a few atomic operations and the ring copy,
+/// with no lock to contend for.  Only [`Feed::detach`] waits, for the
+/// calls already past the load.
+#[derive(Default)]
+pub(crate) struct Feed {
+    shared: AtomicPtr<Shared>, // from `Arc::into_raw`, or null
+    readers: AtomicUsize,      // calls between the load and their last use
+}
+
+impl Feed {
+    /// Run `f` on the published [`Shared`]; `None` when no worker runs.
+    fn with_shared<R>(&self, f: impl FnOnce(&Shared) -> R) -> Option<R> {
+        // Sequentially consistent, so that `detach` either sees this
+        // reader or the reader sees null.
+        self.readers.fetch_add(1, Ordering::SeqCst);
+        let shared = self.shared.load(Ordering::SeqCst);
+        // The pointer stays valid until `readers` drops back.
+        let result = unsafe { shared.as_ref() }.map(f);
+        self.readers.fetch_sub(1, Ordering::Release);
+        result
+    }
+
+    fn attach(&self, shared: Arc<Shared>) {
+        let old = self
+            .shared
+            .swap(Arc::into_raw(shared).cast_mut(), Ordering::SeqCst);
+        debug_assert!(old.is_null(), "feed attached twice");
+    }
+
+    /// Unpublish the worker's [`Shared`]: later calls find no worker, and
+    /// the ones that loaded it just before are waited out.
+    fn detach(&self) {
+        let shared = self.shared.swap(ptr::null_mut(), Ordering::SeqCst);
+        if shared.is_null() {
+            return;
+        }
+        while self.readers.load(Ordering::Acquire) != 0 {
+            thread::yield_now();
+        }
+        drop(unsafe { Arc::from_raw(shared) });
+    }
+
+    /// Queue a copy of `frame` for the worker, see [`Shared::enqueue`];
+    /// `None` when no worker runs.
+    pub fn enqueue(&self, frame: &[u8]) -> Option<bool> {
+        self.with_shared(|shared| shared.enqueue(frame))
+    }
+
+    /// The worker's latest status; `None` when no worker runs.
+    pub fn status(&self) -> Option<RQStatus> {
+        self.with_shared(|shared| {
+            RQStatus::from_repr(shared.status.load(Ordering::Acquire)).unwrap_or(RQStatus::Failed)
+        })
+    }
+}
+
+impl Drop for Feed {
+    fn drop(&mut self) {
+        self.detach();
+    }
+}
+
+pub(crate) struct Worker {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Worker {
+    /// Move the state in `ctx` onto a new decode thread, leaving a fresh
+    /// context behind.  `ctx` is untouched if the thread cannot be spawned.
+    fn spawn(ctx: &mut RQContext, slots: usize, slot_len: usize) -> Option<Worker> {
+        let mut idle = RQContext::new(ctx.oti);
+        ctx.hand_over(&mut idle);
+        let state = mem::replace(ctx, idle);
+        let shared = Arc::new(Shared {
+            ring: Ring::new(slots, slot_len),
+            status: AtomicU8::new(RQStatus::Ok as u8),
+            fresh: AtomicU8::new(RQStatus::Pending as u8),
+            stop: AtomicBool::new(false),
+            state: Mutex::new(Some(state)),
+            thread: OnceLock::new(),
+        });
+        let worker_shared = Arc::clone(&shared);
+        let spawned = thread::Builder::new()
+            .name("raptorq-decode".into())
+            .spawn(move || run(&worker_shared));
+        match spawned {
+            Ok(thread) => {
+                let _ = shared.thread.set(thread.thread().clone());
+                ctx.feed.attach(Arc::clone(&shared));
+                Some(Worker {
+                    shared,
+                    thread: Some(thread),
+                })
+            }
+            Err(_) => {
+                let mut state = take_state(&shared).expect("context handed to no thread");
+                ctx.hand_over(&mut state);
+                *ctx = state;
+                None
+            }
+        }
+    }
+
+    /// Status of the frames decoded since the previous call, and
+    /// [`RQStatus::Pending`] if there were none.
+    fn poll(&self) -> RQStatus {
+        let fresh = self
+            .shared
+            .fresh
+            .swap(RQStatus::Pending as u8, Ordering::AcqRel);
+        RQStatus::from_repr(fresh).unwrap_or(RQStatus::Failed)
+    }
+
+    /// Decode whatever is still queued, then join the thread and return the
+    /// state.  `None` if the worker panicked.
+    fn join(mut self) -> Option<RQContext> {
+        let thread = self.thread.take()?;
+        self.shared.stop.store(true, Ordering::Release);
+        thread.thread().unpark();
+        thread.join().ok()?;
+        take_state(&self.shared)
+    }
+
+    /// Run `f` on the decoding state, once the frame being decoded is done.
+    fn with_state<R>(&self, f: impl FnOnce(&RQContext) -> R) -> R {
+        let state = lock_state(&self.shared);
+        f(state
+            .as_ref()
+            .expect("decoding state taken from a running worker"))
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.shared.stop.store(true, Ordering::Release);
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+fn lock_state(shared: &Shared) -> MutexGuard<'_, Option<RQContext>> {
+    shared
+        .state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn take_state(shared: &Shared) -> Option<RQContext> {
+    lock_state(shared).take()
+}
+
+fn run(shared: &Shared) {
+    loop {
+        // Read before draining: frames queued ahead of the stop request are
+        // visible by then and still get decoded.
+        let stopping = shared.stop.load(Ordering::Acquire);
+        while let Some(status) = shared.ring.pop_with(|frame| {
+            let mut state = lock_state(shared);
+            let ctx = state
+                .as_mut()
+                .expect("decoding state taken from a running worker");
+            let watch = Stopwatch::start();
+            let status = try_catch_hot(|| ctx.push(frame)).unwrap_or(RQStatus::Failed);
+            ctx.push_timer.record(watch.elapsed());
+            status
+        }) {
+            shared.record(status);
+        }
+        if stopping {
+            break;
+        }
+        // An `unpark` between the drain above and here leaves a token, so
+        // no wakeup is lost.
+        thread::park();
+    }
+}
+
+impl RQContext {
+    /// Pass what belongs to the pointer the app holds rather than to the
+    /// decoding state (its references, lock, in‑use count and cancel
+    /// tokens) on to `to`.
+    fn hand_over(&mut self, to: &mut RQContext) {
+        to.decoder.set_cancel(self.decoder.cancel().clone());
+        to.refs
+            .store(self.refs.load(Ordering::Relaxed), Ordering::Relaxed);
+        to.lock = self.lock.take();
+        mem::swap(&mut to.entered, &mut self.entered);
+        mem::swap(&mut to.feed, &mut self.feed);
+    }
+
+    /// Move the decoding state onto a new worker thread with a queue of
+    /// `slots` frames of up to `slot_len` bytes.
+    pub(crate) fn start_worker(&mut self, slots: usize, slot_len: usize) -> bool {
+        if self.worker.is_some() {
+            return false;
+        }
+        if self.isolate_safe {
+            invalid_argument("isolate-safe contexts have no decode thread");
+            return false;
+        }
+        self.worker = Worker::spawn(self, slots, slot_len);
+        self.worker.is_some()
+    }
+
+    /// Start a worker with the default queue unless one runs, sizing its
+    /// slots for frames of `frame_len` bytes or a whole packet if larger.
+    /// Not once no more frames are needed, when a late frame must not move
+    /// the state away from the result.
+    pub(crate) fn ensure_worker(&mut self, frame_len: usize) -> bool {
+        if self.is_complete() || self.taken || self.digest_mismatch {
+            return false;
+        }
+        self.worker.is_some()
+            || self.start_worker(
+                DEFAULT_QUEUE_LEN,
+                frame_len.max(self.packet_len()) + FRAME_SLACK,
+            )
+    }
+
+    /// [`Worker::poll`], moving the decoding state back once it reports an
+    /// outcome so that the result can be taken or the solve driven.
+    /// [`RQStatus::Failed`] when no worker runs or it failed.
+    pub(crate) fn poll_worker(&mut self) -> RQStatus {
+        let status = match &self.worker {
+            Some(worker) => worker.poll(),
+            None => return RQStatus::Failed,
+        };
+        if is_outcome(status as u8) && !self.stop_worker() {
+            return RQStatus::Failed;
+        }
+        status
+    }
+
+    /// Run `f` on the decoding state: the worker's while one runs, this
+    /// context otherwise.
+    pub(crate) fn with_state<R>(&self, f: impl FnOnce(&RQContext) -> R) -> R {
+        match &self.worker {
+            Some(worker) => worker.with_state(f),
+            None => f(self),
+        }
+    }
+
+    /// Drain the queue, join the worker and take the decoding state back.
+    /// The feed is detached first: later enqueues find no worker, and one
+    /// that got hold of the ring just before has finished its copy by the
+    /// time the queue is drained.
+    pub(crate) fn stop_worker(&mut self) -> bool {
+        self.feed.detach();
+        let worker = match self.worker.take() {
+            Some(worker) => worker,
+            None => return false,
+        };
+        match worker.join() {
+            Some(mut ctx) => {
+                self.hand_over(&mut ctx);
+                *self = ctx;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_wraps_and_rejects_when_full() {
+        let ring = Ring::new(2, 4);
+        assert!(ring.pop_with(|_| ()).is_none());
+        assert!(!ring.push(b"12345"));
+        for round in 0u8..3 {
+            assert!(ring.push(&[round]));
+            assert!(ring.push(&[round, round]));
+            assert!(!ring.push(b"x"));
+            assert_eq!(ring.pop_with(|f| f.to_vec()), Some(vec![round]));
+            assert_eq!(ring.pop_with(|f| f.len()), Some(2));
+        }
+        assert!(ring.pop_with(|_| ()).is_none());
+    }
+
+    #[test]
+    fn detached_feed_finds_no_worker() {
+        let feed = Feed::default();
+        assert_eq!(feed.enqueue(b"frame"), None);
+        let shared = Arc::new(Shared {
+            ring: Ring::new(1, 8),
+            status: AtomicU8::new(RQStatus::Ok as u8),
+            fresh: AtomicU8::new(RQStatus::Pending as u8),
+            stop: AtomicBool::new(false),
+            state: Mutex::new(None),
+            thread: OnceLock::new(),
+        });
+        feed.attach(Arc::clone(&shared));
+        assert_eq!(feed.enqueue(b"frame"), Some(true));
+        assert_eq!(feed.enqueue(b"frame"), Some(false));
+        assert_eq!(feed.status(), Some(RQStatus::Ok));
+        feed.detach();
+        assert_eq!(feed.enqueue(b"frame"), None);
+        assert_eq!(feed.status(), None);
+        // The feed let go of its reference.
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
+}